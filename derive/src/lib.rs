@@ -24,12 +24,18 @@ use quote::{quote, quote_spanned};
 use syn::{parse_macro_input, ItemFn};
 
 /// An Attribute MAcro
+///
+/// Its arguments are the title of the validation, optionally followed by the IDs
+/// of the requirements it verifies (e.g., `#[valid(Zone Temperature, requirement = "REQ-1")]`)
 #[proc_macro_attribute]
-pub fn valid(title: TokenStream, item: TokenStream) -> TokenStream {
+pub fn valid(args: TokenStream, item: TokenStream) -> TokenStream {
     let item2 = item.clone();
     let item_ast = parse_macro_input!(item2 as ItemFn);
     let item = proc_macro2::TokenStream::from(item);
-    let title = title.to_string().replace("\"", "");
+    let (title, requirements) = match parse_args(proc_macro2::TokenStream::from(args)) {
+        Ok(args) => args,
+        Err(e) => return e.to_compile_error().into(),
+    };
     let v = &item_ast.attrs;
     let docs = get_docs(v);
     let span = proc_macro2::Span::call_site();
@@ -72,11 +78,13 @@ pub fn valid(title: TokenStream, item: TokenStream) -> TokenStream {
 
             let t : fn() -> #wrapper_result_type = #function_name;
 
-            let wrapper = validate::ValidatorWrapper{
-                title: #title .to_string(),
-                description: #docs.into(),
-                val: t
-            };
+            let mut wrapper = validate::ValidatorWrapper::new(
+                #title .to_string(),
+                #docs.into(),
+                t
+            );
+            wrapper.requirements = vec![#(#requirements.to_string()),*];
+            wrapper.location = Some(validate::SourceLocation::new(file!(), line!()));
 
             #ret_statement
         }
@@ -85,6 +93,34 @@ pub fn valid(title: TokenStream, item: TokenStream) -> TokenStream {
 }
 
 
+/// Splits the arguments of the attribute into the title and the IDs of
+/// the requirements (i.e., the `requirement = "..."` arguments), failing if
+/// a requirement is not given as a string literal
+fn parse_args(args: proc_macro2::TokenStream) -> syn::Result<(String, Vec<String>)> {
+    let mut segments: Vec<Vec<TokenTree>> = vec![Vec::new()];
+    for tok in args {
+        match &tok {
+            TokenTree::Punct(p) if p.as_char() == ',' => segments.push(Vec::new()),
+            _ => segments.last_mut().unwrap().push(tok),
+        }
+    }
+
+    let mut title = Vec::new();
+    let mut requirements = Vec::new();
+    for segment in segments {
+        match segment.as_slice() {
+            [TokenTree::Ident(name), TokenTree::Punct(eq), value @ ..]
+                if name == "requirement" && eq.as_char() == '=' =>
+            {
+                let value: proc_macro2::TokenStream = value.iter().cloned().collect();
+                requirements.push(syn::parse2::<syn::LitStr>(value)?.value())
+            }
+            _ => title.push(segment.into_iter().collect::<proc_macro2::TokenStream>().to_string()),
+        }
+    }
+    Ok((title.join(", ").replace("\"", ""), requirements))
+}
+
 /// Get the output type of a function based on its return type
 fn get_output_type(ret: &syn::ReturnType) -> proc_macro2::TokenStream {
     match ret {
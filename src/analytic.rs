@@ -0,0 +1,209 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::rc::Rc;
+
+use crate::{
+    Artifact, ChartData, Indicator, ReportContext, RunInput, SeriesValidator, Thresholds, Validate,
+    ValidationResult,
+};
+
+/// Compares a series against an analytic solution (e.g., the exact solution
+/// of transient conduction in a semi-infinite solid, or a benchmark built
+/// on Bessel functions), given as a function of x that is evaluated at the
+/// x-values of the found series. The solution does not need to be
+/// tabulated beforehand, and its formula is written in the report.
+///
+/// The comparison itself—its thresholds, labels and so on—is configured
+/// through the `series` field, whose `expected` values are replaced by
+/// those of the solution.
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::{AnalyticValidator, Validate};
+///
+/// // A steady wall, conducting between 20 C and 10 C
+/// let x = vec![0., 0.05, 0.1, 0.15, 0.2];
+/// let found = vec![20., 17.6, 15.1, 12.4, 10.];
+/// let mut v = AnalyticValidator::new(|x| 20. - 50. * x, x, found);
+/// v.formula = Some("T(x) = T_in - (T_in - T_out) x / L");
+/// v.series.allowed_max_error = Some(0.5);
+/// assert!(v.validate().is_ok());
+/// ```
+#[derive(Clone)]
+pub struct AnalyticValidator {
+    /// The formula of the analytic solution, written in the report
+    pub formula: Option<&'static str>,
+
+    /// The x-values at which the found values were produced (e.g., the
+    /// position of each node of a mesh, or the time of each step)
+    pub x: Vec<f64>,
+
+    /// The analytic solution, as a function of x
+    pub solution: Rc<dyn Fn(f64) -> f64>,
+
+    /// The comparison between the solution and the found values, which
+    /// holds the found values and the thresholds
+    pub series: SeriesValidator<f64>,
+}
+
+impl AnalyticValidator {
+    /// Creates a validator that compares `found`, produced at the
+    /// x-values `x`, against the analytic `solution`
+    pub fn new<F: Fn(f64) -> f64 + 'static>(solution: F, x: Vec<f64>, found: Vec<f64>) -> Self {
+        Self {
+            formula: None,
+            x,
+            solution: Rc::new(solution),
+            series: SeriesValidator {
+                expected_legend: Some("Analytic solution".to_string()),
+                found,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// The comparison, with the solution evaluated at each x-value
+    fn comparison(&self) -> Result<SeriesValidator<f64>, String> {
+        if self.x.len() != self.series.found.len() {
+            return Err(format!(
+                "There are {} x-values for {} found values",
+                self.x.len(),
+                self.series.found.len()
+            ));
+        }
+        let expected: Vec<f64> = self.x.iter().map(|x| (self.solution)(*x)).collect();
+        if let Some(i) = expected.iter().position(|v| !v.is_finite()) {
+            return Err(format!(
+                "The analytic solution is not defined at x = {} (it evaluates to {})",
+                self.x[i], expected[i]
+            ));
+        }
+        let mut series = self.series.clone();
+        series.expected = expected;
+        Ok(series)
+    }
+}
+
+impl Validate for AnalyticValidator {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        let series = match self.comparison() {
+            Ok(s) => s,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+        let mut intro = String::new();
+        if let Some(formula) = self.formula {
+            intro = format!("{}: `{}`\n\n", ctx.tr("Analytic solution"), formula);
+        }
+        if let Ok((first, last)) = crate::stats::try_min_max(&self.x) {
+            intro += &format!(
+                "{} {} x-values, {} {:.4} {} {:.4}\n",
+                ctx.tr("Evaluated at"),
+                self.x.len(),
+                ctx.tr("from"),
+                first,
+                ctx.tr("to"),
+                last
+            );
+        }
+        match series.validate_in(ctx) {
+            ValidationResult::Ok(txt) => ValidationResult::Ok(intro + &txt),
+            ValidationResult::Err(txt, e) => ValidationResult::Err(intro + &txt, e),
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn artifacts(&self) -> Vec<Artifact> {
+        self.series.artifacts()
+    }
+
+    fn chart_data(&self) -> Option<ChartData> {
+        self.comparison().ok()?.chart_data()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        self.comparison().map(|s| s.indicators()).unwrap_or_default()
+    }
+
+    fn inputs(&self) -> Vec<RunInput> {
+        self.series.inputs()
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.series.covers()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.series.requirements()
+    }
+
+    fn defines_thresholds(&self, ctx: &ReportContext) -> bool {
+        self.series.defines_thresholds(ctx)
+    }
+
+    fn suggested_thresholds(&self, margin: f64) -> Option<Thresholds> {
+        self.comparison().ok()?.suggested_thresholds(margin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analytic() {
+        // Exponential decay, found with an explicit Euler scheme
+        let dt = 0.1;
+        let x: Vec<f64> = (0..20).map(|i| i as f64 * dt).collect();
+        let found: Vec<f64> = (0..20).map(|i| (1.0 - dt).powi(i)).collect();
+        let mut v = AnalyticValidator::new(|t: f64| (-t).exp(), x, found);
+        v.formula = Some("y(t) = exp(-t)");
+        v.series.allowed_max_error = Some(0.05);
+        match v.validate() {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.starts_with("Analytic solution: `y(t) = exp(-t)`"));
+                assert!(txt.contains("Evaluated at 20 x-values, from 0.0000 to 1.9000"));
+            }
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        v.series.allowed_max_error = Some(0.001);
+        assert!(v.validate().is_err());
+
+        // The solution must be defined everywhere
+        let v = AnalyticValidator::new(|x: f64| 1. / x, vec![0., 1.], vec![1., 1.]);
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains("not defined at x = 0")),
+        }
+
+        let v = AnalyticValidator::new(|x| x, vec![0., 1.], vec![0.]);
+        assert!(v.validate().is_err());
+    }
+}
@@ -0,0 +1,170 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::ReportContext;
+use std::path::Path;
+
+/// A file that is attached to a validation (e.g., a snapshot of the input
+/// data, a configuration dump or a log excerpt) so that the validation can be
+/// reproduced from the report alone.
+///
+/// Artifacts are copied into the assets directory of the report and linked
+/// from the section of the validation that registered them.
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::{Artifact, SeriesValidator, Validator};
+///
+/// let mut validator = Validator::new("Validate with inputs", "report.html");
+/// let v = SeriesValidator {
+///     expected: vec![1., 2., 3.],
+///     found: vec![1., 2., 3.],
+///     artifacts: vec![Artifact::new("Input data", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test_data/data.csv"))],
+///     ..SeriesValidator::default()
+/// };
+/// validator.push(Box::new(v));
+/// validator.validate().unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    /// The text of the link in the report
+    pub label: String,
+
+    /// The path to the file to attach
+    pub path: String,
+}
+
+impl Artifact {
+    /// Creates a new `Artifact`
+    pub fn new<T: Into<String>, P: Into<String>>(label: T, path: P) -> Self {
+        Self {
+            label: label.into(),
+            path: path.into(),
+        }
+    }
+
+    /// Copies the artifact into the assets directory of the report, prefixing
+    /// its name with `prefix` so it is easy to tell which validation it belongs to.
+    ///
+    /// Returns the link to the copied file.
+    pub(crate) fn copy_into(&self, ctx: &ReportContext, prefix: usize) -> Result<String, String> {
+        let source = Path::new(&self.path);
+        let name = match source.file_name() {
+            Some(name) => format!("{}_{}", prefix, name.to_string_lossy()),
+            None => return Err(format!("Artifact '{}' is not a file", self.path)),
+        };
+
+        let (path, link) = ctx.asset_path(&name)?;
+        if let Err(e) = std::fs::copy(source, path) {
+            return Err(format!("Cannot copy artifact '{}': {}", self.path, e));
+        }
+        Ok(link)
+    }
+}
+
+/// The data behind a chart, as `(x, expected, found)` triples
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChartData {
+    /// The values in the x axis
+    pub x: Vec<f64>,
+
+    /// The expected values
+    pub expected: Vec<f64>,
+
+    /// The found values
+    pub found: Vec<f64>,
+}
+
+impl ChartData {
+    /// Writes the data as a CSV file with columns `x`, `expected` and `found`
+    /// into the assets directory of the report, prefixing its name with `prefix`.
+    ///
+    /// Returns the link to the written file.
+    #[cfg(feature = "csv")]
+    pub(crate) fn write_into(&self, ctx: &ReportContext, prefix: usize) -> Result<String, String> {
+        let (path, link) = ctx.asset_path(&format!("{}_chart_data.csv", prefix))?;
+        let write = || -> Result<(), csv::Error> {
+            let mut wtr = csv::Writer::from_path(&path)?;
+            wtr.write_record(["x", "expected", "found"])?;
+            for ((x, exp), found) in self.x.iter().zip(&self.expected).zip(&self.found) {
+                wtr.write_record(&[x.to_string(), exp.to_string(), found.to_string()])?;
+            }
+            wtr.flush()?;
+            Ok(())
+        };
+        match write() {
+            Ok(()) => Ok(link),
+            Err(e) => Err(format!("Cannot write chart data into '{}': {}", path.display(), e)),
+        }
+    }
+
+    /// The data is written as CSV, so it cannot be exported without the `csv` feature
+    #[cfg(not(feature = "csv"))]
+    pub(crate) fn write_into(&self, ctx: &ReportContext, prefix: usize) -> Result<String, String> {
+        let (path, _) = ctx.asset_path(&format!("{}_chart_data.csv", prefix))?;
+        Err(format!("Cannot write chart data into '{}' without the `csv` feature", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(dir: &str) -> ReportContext {
+        let dir = std::env::temp_dir().join(dir);
+        ReportContext {
+            assets_dir: dir.join("assets"),
+            report_dir: dir,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_copy_into() {
+        let ctx = context("validate_test_copy_into");
+        let a = Artifact::new("Data", "./tests/test_data/data.csv");
+        let link = a.copy_into(&ctx, 3).unwrap();
+        assert_eq!(link, "assets/3_data.csv");
+        assert!(ctx.assets_dir.join("3_data.csv").exists());
+
+        // The same file, registered twice
+        assert_eq!(a.copy_into(&ctx, 3).unwrap(), "assets/3_data_2.csv");
+
+        let a = Artifact::new("Data", "./tests/test_data/nope.csv");
+        assert!(a.copy_into(&ctx, 3).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "csv")]
+    fn test_chart_data_write_into() {
+        let ctx = context("validate_test_chart_data");
+        let data = ChartData {
+            x: vec![0., 1.],
+            expected: vec![1., 2.],
+            found: vec![1.5, 2.5],
+        };
+        let link = data.write_into(&ctx, 2).unwrap();
+        assert_eq!(link, "assets/2_chart_data.csv");
+        let content = std::fs::read_to_string(ctx.assets_dir.join("2_chart_data.csv")).unwrap();
+        assert_eq!(content, "x,expected,found\n0,1,1.5\n1,2,2.5\n");
+    }
+}
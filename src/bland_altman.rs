@@ -0,0 +1,313 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::numberish::Numberish;
+use crate::stats::{count_as_f64, mean};
+use crate::{Indicator, ReportContext, Validate, ValidationResult};
+#[cfg(feature = "charts")]
+use crate::plot::{self, Series};
+
+/// The number of standard deviations of the differences spanned by the
+/// limits of agreement, on each side of the bias (i.e., 95% of the
+/// differences fall within them, if they are normally distributed)
+const LIMITS_Z: f64 = 1.96;
+
+/// The bias and limits of agreement of a Bland-Altman analysis
+struct Agreement {
+    /// The mean of the differences
+    bias: f64,
+
+    /// The bias minus 1.96 standard deviations of the differences
+    lower: f64,
+
+    /// The bias plus 1.96 standard deviations of the differences
+    upper: f64,
+}
+
+/// Compares a model against measurements of the same quantity through a
+/// Bland-Altman analysis, plotting the difference between each pair of
+/// values against their mean. The average difference (i.e., the bias) and
+/// the limits of agreement—within which 95% of the differences are expected
+/// to fall—are checked against the allowed values.
+///
+/// Unlike a [`crate::ScatterValidator`], this shows whether the errors
+/// depend on the magnitude of the values, and how large they are, in the
+/// units of the data.
+///
+/// # Example
+///
+/// ```
+/// use validate::{BlandAltmanValidator, Validate};
+///
+/// let v = BlandAltmanValidator {
+///     expected: vec![20.1, 21.3, 22.8, 24.0, 25.2, 23.1],
+///     found: vec![20.3, 21.2, 23.1, 24.1, 25.6, 23.2],
+///     allowed_bias: Some(0.2),
+///     allowed_limits_of_agreement: Some(0.6),
+///     ..Default::default()
+/// };
+/// assert!(v.validate().is_ok());
+/// ```
+#[derive(Default, Clone)]
+pub struct BlandAltmanValidator<T: Numberish> {
+    /// The expected values (e.g., measurements)
+    pub expected: Vec<T>,
+
+    /// The values found (e.g., by a model), paired with the expected ones
+    pub found: Vec<T>,
+
+    /// The maximum allowed absolute value of the bias (i.e., the mean
+    /// difference between found and expected values)
+    pub allowed_bias: Option<f64>,
+
+    /// The maximum allowed absolute value of both limits of agreement
+    pub allowed_limits_of_agreement: Option<f64>,
+
+    /// The units of the data
+    pub units: Option<&'static str>,
+
+    /// The label of the data, used in the axes of the chart
+    pub label: Option<&'static str>,
+
+    /// the title of the chart
+    pub chart_title: Option<&'static str>,
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"zone air temperature"`), listed in the coverage of the report
+    pub covers: Vec<&'static str>,
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"`), mapped to it in the report
+    pub requirements: Vec<&'static str>,
+}
+
+impl<T: Numberish> BlandAltmanValidator<T> {
+    /// The mean and the difference (found minus expected) of each pair
+    fn pairs(&self) -> Result<Vec<(f64, f64)>, String> {
+        if self.expected.len() != self.found.len() {
+            return Err(format!(
+                "There are {} expected values for {} found values",
+                self.expected.len(),
+                self.found.len()
+            ));
+        }
+        if self.expected.len() < 2 {
+            return Err("A Bland-Altman analysis needs at least two pairs of values".to_string());
+        }
+        let ret: Vec<(f64, f64)> = self
+            .expected
+            .iter()
+            .zip(self.found.iter())
+            .map(|(e, f)| {
+                let (e, f): (f64, f64) = ((*e).into(), (*f).into());
+                ((e + f) / 2., f - e)
+            })
+            .collect();
+        if ret.iter().any(|(m, d)| !m.is_finite() || !d.is_finite()) {
+            return Err("Trying to run a Bland-Altman analysis with non-finite values".to_string());
+        }
+        Ok(ret)
+    }
+
+    /// The bias and limits of agreement of the differences
+    fn agreement(pairs: &[(f64, f64)]) -> Agreement {
+        let differences: Vec<f64> = pairs.iter().map(|(_, d)| *d).collect();
+        let bias = mean(&differences);
+        let squares: f64 = differences.iter().map(|d| (d - bias).powi(2)).sum();
+        let sd = (squares / count_as_f64(differences.len() - 1)).sqrt();
+        Agreement {
+            bias,
+            lower: bias - LIMITS_Z * sd,
+            upper: bias + LIMITS_Z * sd,
+        }
+    }
+}
+
+impl<T: Numberish + 'static> Validate for BlandAltmanValidator<T> {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        let pairs = match self.pairs() {
+            Ok(p) => p,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+        let agreement = Self::agreement(&pairs);
+        let units = ctx.unit_system();
+        let data_units = self.units.unwrap_or("");
+        let show_diff = |v: f64| units.convert_difference(v, data_units);
+
+        let file_msg = format!(
+            "\n * {}: {}\n * {}: {:.4}\n * {}: {:.4} {} {:.4}",
+            ctx.tr("Number of pairs"),
+            pairs.len(),
+            ctx.tr("Bias (mean difference)"),
+            show_diff(agreement.bias),
+            ctx.tr("Limits of agreement"),
+            show_diff(agreement.lower),
+            ctx.tr("to"),
+            show_diff(agreement.upper)
+        );
+
+        let mut nchecks = 0;
+        let mut err_msg = String::new();
+        if let Some(allowed) = self.allowed_bias {
+            nchecks += 1;
+            if agreement.bias.abs() > allowed {
+                err_msg = format!(
+                    "{}\n * The bias is {:.4}, which is beyond the allowed {:.4}",
+                    err_msg,
+                    show_diff(agreement.bias),
+                    show_diff(allowed)
+                );
+            }
+        }
+        if let Some(allowed) = self.allowed_limits_of_agreement {
+            nchecks += 1;
+            let worst = agreement.lower.abs().max(agreement.upper.abs());
+            if worst > allowed {
+                err_msg = format!(
+                    "{}\n * The limits of agreement are {:.4} and {:.4}, which reach beyond the allowed {:.4}",
+                    err_msg,
+                    show_diff(agreement.lower),
+                    show_diff(agreement.upper),
+                    show_diff(allowed)
+                );
+            }
+        }
+
+        #[cfg(feature = "charts")]
+        let chart = {
+            let show = |v: f64| units.convert(v, data_units);
+            let (first, last) = pairs
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (m, _)| (lo.min(*m), hi.max(*m)));
+            let level = |name: &str, y: f64| Series::line(ctx.tr(name), [[show(first), show_diff(y)], [show(last), show_diff(y)]]);
+            let series = vec![
+                Series::scatter(ctx.tr("Differences"), pairs.iter().map(|(m, d)| [show(*m), show_diff(*d)])),
+                level("Bias", agreement.bias),
+                level("Lower limit of agreement", agreement.lower),
+                level("Upper limit of agreement", agreement.upper),
+            ];
+            let label = ctx.tr(self.label.unwrap_or("Value"));
+            let (mut x_label, mut y_label) = (
+                format!("{} ({})", label, ctx.tr("mean of the pair")),
+                format!("{} ({})", label, ctx.tr("found minus expected")),
+            );
+            if let Some(u) = self.units {
+                x_label = format!("{} [{}]", x_label, units.units(u));
+                y_label = format!("{} [{}]", y_label, units.units(u));
+            }
+            plot::chart(self.chart_title.unwrap_or(""), &x_label, &y_label, series, ctx).to_string()
+        };
+        #[cfg(not(feature = "charts"))]
+        let chart = ctx.chart_theme().omitted();
+
+        let show_err = if nchecks == 0 {
+            ctx.tr("No checks performed...")
+        } else if err_msg.is_empty() {
+            ctx.tr("No errors found")
+        } else {
+            &err_msg
+        };
+        let file = format!(
+            "{}\n\n#### {}:\n {}\n#### {}:\n\n{}",
+            file_msg,
+            ctx.tr("Errors"),
+            show_err,
+            ctx.tr("Data"),
+            chart
+        );
+        if err_msg.is_empty() {
+            ValidationResult::Ok(file)
+        } else {
+            ValidationResult::Err(file, err_msg)
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.covers.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.requirements.iter().map(|r| r.to_string()).collect()
+    }
+
+    fn defines_thresholds(&self, _ctx: &ReportContext) -> bool {
+        self.allowed_bias.is_some() || self.allowed_limits_of_agreement.is_some()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        match self.pairs() {
+            Ok(pairs) => {
+                let agreement = Self::agreement(&pairs);
+                vec![
+                    Indicator::new("Bias", agreement.bias),
+                    Indicator::new("Lower limit of agreement", agreement.lower),
+                    Indicator::new("Upper limit of agreement", agreement.upper),
+                ]
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bland_altman() {
+        // Differences of 1 and 3: bias of 2, standard deviation of sqrt(4/3)
+        let mut v = BlandAltmanValidator {
+            expected: vec![10., 20., 30., 40.],
+            found: vec![11., 23., 31., 43.],
+            allowed_bias: Some(2.5),
+            ..Default::default()
+        };
+        let indicators = v.indicators();
+        assert_eq!(indicators[0].value, 2.);
+        assert!((indicators[2].value - (2. + 1.96 * (4f64 / 3.).sqrt())).abs() < 1e-9);
+        match v.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("Bias (mean difference): 2.0000")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        v.allowed_bias = Some(1.);
+        v.allowed_limits_of_agreement = Some(4.);
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => {
+                assert!(e.contains("The bias is 2.0000, which is beyond the allowed 1.0000"));
+                assert!(e.contains("The limits of agreement are"));
+            }
+        }
+
+        v.found.pop();
+        assert!(v.validate().is_err());
+        assert!(v.indicators().is_empty());
+    }
+}
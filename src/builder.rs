@@ -0,0 +1,130 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use std::path::PathBuf;
+
+use crate::Validator;
+
+/// Configures a [`Validator`] step by step, returning an error—instead of
+/// panicking—if the configuration is not valid, so validations can be set
+/// up programmatically (e.g., by a test harness reading its settings).
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::ValidatorBuilder;
+///
+/// let validator = ValidatorBuilder::default()
+///     .title("Nightly")
+///     .output_html("report.html")
+///     .output_json("results.json")
+///     .max_errors(10)
+///     .requirement("REQ-THERM-012")
+///     .build()
+///     .unwrap();
+/// assert!(validator.is_empty());
+///
+/// // A report is required, and must be writable
+/// assert!(ValidatorBuilder::default().build().is_err());
+/// assert!(ValidatorBuilder::default()
+///     .output_html("missing_directory/report.html")
+///     .build()
+///     .is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ValidatorBuilder<'a> {
+    title: Option<String>,
+    html: Option<&'a str>,
+    json: Option<PathBuf>,
+    fail_fast: bool,
+    max_errors: Option<usize>,
+    requirements: Vec<String>,
+}
+
+impl<'a> ValidatorBuilder<'a> {
+    /// Sets the title of the report (`"Validation report"` by default)
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the file into which the HTML report is written (see
+    /// [`Validator::new`]). It is required.
+    pub fn output_html(mut self, file: &'a str) -> Self {
+        self.html = Some(file);
+        self
+    }
+
+    /// Sets a file into which the results are written as JSON (see
+    /// [`Validator::set_json_file`])
+    pub fn output_json<P: Into<PathBuf>>(mut self, file: P) -> Self {
+        self.json = Some(file.into());
+        self
+    }
+
+    /// Sets whether to stop running validations after the first one fails
+    /// (see [`Validator::set_fail_fast`])
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Sets the number of failed validations after which the remaining
+    /// ones are not run (see [`Validator::set_max_errors`])
+    pub fn max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = Some(max_errors);
+        self
+    }
+
+    /// Adds the ID of a requirement that the validations are expected to
+    /// verify (see [`Validator::set_requirements`]). It can be called once
+    /// per requirement.
+    pub fn requirement<S: Into<String>>(mut self, id: S) -> Self {
+        self.requirements.push(id.into());
+        self
+    }
+
+    /// Creates the [`Validator`], failing if no HTML report was given or
+    /// if it cannot be written
+    pub fn build(self) -> Result<Validator<'a>, String> {
+        let html = self
+            .html
+            .ok_or_else(|| "A file for the HTML report is required (see ValidatorBuilder::output_html)".to_string())?;
+        if self.max_errors == Some(0) {
+            return Err("The maximum number of errors must be at least 1".to_string());
+        }
+        let mut validator = Validator::try_new(self.title.as_deref().unwrap_or("Validation report"), html)?;
+        if let Some(json) = self.json {
+            validator.set_json_file(json);
+        }
+        if let Some(max_errors) = self.max_errors {
+            validator.set_max_errors(max_errors);
+        }
+        if self.fail_fast {
+            validator.set_fail_fast(true);
+        }
+        if !self.requirements.is_empty() {
+            validator.set_requirements(self.requirements);
+        }
+        Ok(validator)
+    }
+}
@@ -0,0 +1,239 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::report::{indicator_map, input_map, write_atomically, Section};
+use crate::{Indicator, RunInput};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+
+/// The version of SARIF that is written
+const SARIF_VERSION: &str = "2.1.0";
+
+/// The schema of the SARIF files
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A stable identifier of a failure (FNV-1a of the title of its validation)
+fn fingerprint(section: &Section) -> String {
+    format!("{:016x}", crate::report::fnv1a64(section.title.as_bytes()))
+}
+
+/// The description of a failure: the title of the validation and its error
+fn description(section: &Section) -> String {
+    let error = section.error.as_deref().unwrap_or_default().trim();
+    format!("{}: {}", section.title, error.replace('\n', " "))
+}
+
+/// Writes the failed validations as a GitLab Code Quality report, so they
+/// appear in the merge request widget. Failures are located where their
+/// validation is defined or, if that is unknown, at the top of `fallback`.
+pub(crate) fn write_code_quality(path: &Path, sections: &[Section], fallback: &str) -> Result<(), String> {
+    let issues: Vec<Value> = sections
+        .iter()
+        .filter(|s| s.failed())
+        .map(|s| {
+            let (file, line) = s
+                .location
+                .as_ref()
+                .map_or((fallback, 1), |l| (l.file.as_str(), l.line));
+            json!({
+                "description": description(s),
+                "check_name": s.anchor,
+                "fingerprint": fingerprint(s),
+                "severity": "major",
+                "location": {
+                    "path": file,
+                    "lines": { "begin": line },
+                },
+            })
+        })
+        .collect();
+    write_atomically(path, Value::Array(issues).to_string())
+}
+
+/// Writes the failed validations as a SARIF log, so they appear in code
+/// scanning tools. Failures whose location is unknown are written without one.
+pub(crate) fn write_sarif(path: &Path, sections: &[Section]) -> Result<(), String> {
+    let failed: Vec<&Section> = sections.iter().filter(|s| s.failed()).collect();
+    let rules: Vec<Value> = failed
+        .iter()
+        .map(|s| {
+            json!({
+                "id": s.anchor,
+                "name": s.title,
+                "shortDescription": { "text": s.title },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = failed
+        .iter()
+        .map(|s| {
+            let locations: Vec<Value> = s
+                .location
+                .iter()
+                .map(|l| {
+                    json!({
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": l.file },
+                            "region": { "startLine": l.line },
+                        },
+                    })
+                })
+                .collect();
+            json!({
+                "ruleId": s.anchor,
+                "level": "error",
+                "message": { "text": description(s) },
+                "locations": locations,
+                "partialFingerprints": { "validation/v1": fingerprint(s) },
+            })
+        })
+        .collect();
+    let doc = json!({
+        "version": SARIF_VERSION,
+        "$schema": SARIF_SCHEMA,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "validate",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    write_atomically(path, doc.to_string())
+}
+
+/// The results of the validations, as written by [`write_json`]
+#[derive(Serialize)]
+struct Results<'a> {
+    title: &'a str,
+    version: &'a str,
+    passed: usize,
+    failed: usize,
+    duration: Option<f64>,
+    validations: Vec<ValidationResults<'a>>,
+}
+
+/// The results of a validation, as written by [`write_json`]
+#[derive(Serialize)]
+struct ValidationResults<'a> {
+    title: &'a str,
+    anchor: &'a str,
+    passed: bool,
+    error: Option<&'a str>,
+    #[serde(serialize_with = "indicator_map::serialize")]
+    indicators: &'a [Indicator],
+    covers: &'a [String],
+    requirements: &'a [String],
+    #[serde(serialize_with = "input_map")]
+    inputs: &'a [RunInput],
+    file: Option<&'a str>,
+    line: Option<u32>,
+}
+
+/// Writes the results of the validations as a JSON document, for dashboards
+/// and other tools: whether each of them passed, its error and its indicators
+/// (e.g., `{"Root Mean Squared Error": 0.12}`). The duration of the run is
+/// written if given.
+pub(crate) fn write_json(path: &Path, title: &str, sections: &[Section], duration: Option<Duration>) -> Result<(), String> {
+    let failed = sections.iter().filter(|s| s.failed()).count();
+    let doc = Results {
+        title,
+        version: env!("CARGO_PKG_VERSION"),
+        passed: sections.len() - failed,
+        failed,
+        duration: duration.map(|d| d.as_secs_f64()),
+        validations: sections
+            .iter()
+            .map(|s| ValidationResults {
+                title: &s.title,
+                anchor: &s.anchor,
+                passed: !s.failed(),
+                error: s.error.as_deref().map(str::trim),
+                indicators: &s.indicators,
+                covers: &s.covers,
+                requirements: &s.requirements,
+                inputs: &s.inputs,
+                file: s.location.as_ref().map(|l| l.file.as_str()),
+                line: s.location.as_ref().map(|l| l.line),
+            })
+            .collect(),
+    };
+    let txt = serde_json::to_string(&doc).map_err(|e| format!("Cannot write '{}': {}", path.display(), e))?;
+    write_atomically(path, txt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SourceLocation;
+
+    #[test]
+    fn test_ci_reports() {
+        let mut failed = Section::new(0, "## Zone A\n\nBad".to_string(), Some("RMSE is \"too\" high".into()));
+        failed.location = Some(SourceLocation::new("tests/thermal.rs", 42));
+        let unknown = Section::new(1, "## Zone B\n\nBad".to_string(), Some("MBE is too high".into()));
+        let mut passed = Section::new(2, "## Zone C\n\nGood".to_string(), None);
+        passed.indicators = vec![crate::Indicator::new("Mean Bias Error", 0.5)];
+        passed.inputs = vec![crate::RunInput::tool_version("EnergyPlus", "23.2.0")];
+        let sections = [failed, unknown, passed];
+
+        let dir = std::env::temp_dir().join(format!("validate_ci_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let read = |path: &Path| -> Value { serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap() };
+
+        let path = dir.join("gl-code-quality-report.json");
+        write_code_quality(&path, &sections, "Cargo.toml").unwrap();
+        let issues = read(&path);
+        assert_eq!(issues.as_array().unwrap().len(), 2);
+        assert_eq!(issues[0]["description"], "Zone A: RMSE is \"too\" high");
+        assert_eq!(issues[0]["location"]["path"], "tests/thermal.rs");
+        assert_eq!(issues[0]["location"]["lines"]["begin"], 42);
+        assert_eq!(issues[1]["location"]["path"], "Cargo.toml");
+        assert_ne!(issues[0]["fingerprint"], issues[1]["fingerprint"]);
+
+        let path = dir.join("validate.sarif");
+        write_sarif(&path, &sections).unwrap();
+        let doc = read(&path);
+        let results = doc["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "zone-a");
+        assert_eq!(results[1]["locations"].as_array().unwrap().len(), 0);
+
+        let path = dir.join("results.json");
+        write_json(&path, "Thermal", &sections, None).unwrap();
+        let doc = read(&path);
+        assert_eq!(doc["failed"], 2);
+        assert!(doc["duration"].is_null());
+        let validations = &doc["validations"];
+        assert_eq!(validations[0]["passed"], false);
+        assert_eq!(validations[0]["error"], "RMSE is \"too\" high");
+        assert_eq!(validations[0]["line"], 42);
+        assert_eq!(validations[2]["indicators"]["Mean Bias Error"], 0.5);
+        assert_eq!(validations[2]["inputs"]["EnergyPlus version"], "23.2.0");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
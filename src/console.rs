@@ -0,0 +1,229 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::report::Section;
+use std::io::IsTerminal;
+
+/// How much is printed to the console after validating
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Nothing is printed
+    Quiet,
+
+    /// One line per validation, the error messages of the failed
+    /// ones, the totals and the path to the report
+    #[default]
+    Normal,
+
+    /// Like [`Verbosity::Normal`], but also printing the indicators
+    /// calculated by each validation
+    Verbose,
+}
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Writes the console summary of a report, similar to that of `cargo test`
+pub(crate) fn summary(sections: &[Section], report: &str, verbosity: Verbosity, color: bool) -> String {
+    let paint = |txt: &str, c: &str| {
+        if color {
+            format!("{}{}{}", c, txt, RESET)
+        } else {
+            txt.to_string()
+        }
+    };
+
+    let mut ret = format!("\nrunning {} validations\n", sections.len());
+    for s in sections {
+        match &s.error {
+            None => ret.push_str(&format!("validation {} ... {}\n", s.title, paint("ok", GREEN))),
+            Some(e) => {
+                ret.push_str(&format!("validation {} ... {}\n", s.title, paint("FAILED", RED)));
+                for line in e.lines().filter(|l| !l.trim().is_empty()) {
+                    ret.push_str(&format!("    {}\n", line.trim()));
+                }
+            }
+        }
+        if verbosity == Verbosity::Verbose {
+            for ind in &s.indicators {
+                ret.push_str(&format!("    {} = {:.4}\n", ind.name, ind.value));
+            }
+        }
+    }
+
+    let failed = sections.iter().filter(|s| s.failed()).count();
+    let result = if failed == 0 {
+        paint("ok", GREEN)
+    } else {
+        paint("FAILED", RED)
+    };
+    ret.push_str(&format!(
+        "\nvalidation result: {}. {} passed; {} failed; report written to {}\n",
+        result,
+        sections.len() - failed,
+        failed,
+        report
+    ));
+    ret
+}
+
+/// Prints the console summary of a report into stderr, with colors
+/// unless stderr is not a terminal or `NO_COLOR` is set
+pub(crate) fn print_summary(sections: &[Section], report: &str, verbosity: Verbosity) {
+    if verbosity == Verbosity::Quiet {
+        return;
+    }
+    let color = std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    eprint!("{}", summary(sections, report, verbosity, color));
+}
+
+/// Emits the result of each validation once the report has been written.
+///
+/// With the `tracing` or `log` features, results are emitted as events
+/// (errors for failed validations, info for passed ones) carrying the title
+/// of the validation, so they can be aggregated with the rest of the logs.
+/// With both features, each result is emitted once, as a `tracing` event
+/// (`tracing` can forward its events to `log`).
+#[cfg(any(feature = "tracing", feature = "log"))]
+pub(crate) fn emit(sections: &[Section]) {
+    for s in sections {
+        #[cfg(feature = "tracing")]
+        match &s.error {
+            Some(e) => tracing::error!(validation = %s.title, error = %e, "Validation failed"),
+            None => tracing::info!(validation = %s.title, "Validation passed"),
+        }
+
+        #[cfg(all(feature = "log", not(feature = "tracing")))]
+        match &s.error {
+            Some(e) => log::error!(target: "validate", "Validation '{}' failed: {}", s.title, e),
+            None => log::info!(target: "validate", "Validation '{}' passed", s.title),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Indicator;
+
+    fn sections() -> Vec<Section> {
+        vec![
+            Section::new(0, "## Zone A".into(), None),
+            Section::new(1, "## Zone B".into(), Some("\n * RMSE is too high\n".into())),
+        ]
+    }
+
+    #[test]
+    fn test_summary() {
+        let s = sections();
+        let txt = summary(&s, "report.html", Verbosity::Normal, false);
+        assert!(txt.contains("running 2 validations"));
+        assert!(txt.contains("validation Zone A ... ok\n"));
+        assert!(txt.contains("validation Zone B ... FAILED\n    * RMSE is too high\n"));
+        assert!(txt.contains("validation result: FAILED. 1 passed; 1 failed; report written to report.html"));
+        assert!(!txt.contains("R2"));
+
+        let mut s = s;
+        s[0].indicators.push(Indicator::new("R2", 0.5));
+        let txt = summary(&s, "report.html", Verbosity::Verbose, false);
+        assert!(txt.contains("validation Zone A ... ok\n    R2 = 0.5000\n"));
+
+        let txt = summary(&s[..1], "report.html", Verbosity::Normal, true);
+        assert!(txt.contains("\x1b[32mok\x1b[0m"));
+    }
+
+    /// Sections with titles that no other test uses, as the logger is global
+    #[cfg(any(feature = "tracing", feature = "log"))]
+    fn emitted_sections() -> Vec<Section> {
+        vec![
+            Section::new(0, "## Emitted A".into(), None),
+            Section::new(1, "## Emitted B".into(), Some("RMSE is too high".into())),
+        ]
+    }
+
+    #[cfg(all(feature = "log", not(feature = "tracing")))]
+    #[test]
+    fn test_emit_log() {
+        use std::sync::Mutex;
+
+        static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        struct Capture;
+        impl log::Log for Capture {
+            fn enabled(&self, _metadata: &log::Metadata) -> bool {
+                true
+            }
+            fn log(&self, record: &log::Record) {
+                let record = format!("{} {}", record.level(), record.args());
+                RECORDS.lock().unwrap_or_else(|e| e.into_inner()).push(record);
+            }
+            fn flush(&self) {}
+        }
+
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(log::LevelFilter::Info);
+        emit(&emitted_sections());
+        let records = RECORDS.lock().unwrap_or_else(|e| e.into_inner());
+        let count = |r: &str| records.iter().filter(|x| *x == r).count();
+        assert_eq!(count("INFO Validation 'Emitted A' passed"), 1);
+        assert_eq!(count("ERROR Validation 'Emitted B' failed: RMSE is too high"), 1);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_emit_tracing() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+
+        #[derive(Default)]
+        struct Capture(Mutex<Vec<String>>);
+        struct Fields(String);
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0 += &format!(" {}={:?}", field.name(), value);
+            }
+        }
+        impl tracing::Subscriber for Capture {
+            fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _span: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+            fn event(&self, event: &tracing::Event<'_>) {
+                let mut fields = Fields(event.metadata().level().to_string());
+                event.record(&mut fields);
+                self.0.lock().unwrap_or_else(|e| e.into_inner()).push(fields.0);
+            }
+            fn enter(&self, _span: &Id) {}
+            fn exit(&self, _span: &Id) {}
+        }
+
+        let capture = Arc::new(Capture::default());
+        tracing::subscriber::with_default(capture.clone(), || emit(&emitted_sections()));
+        let events = capture.0.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(events.len(), 2);
+        assert!(events[0].starts_with("INFO") && events[0].contains("validation=Emitted A"));
+        assert!(events[1].starts_with("ERROR") && events[1].contains("error=RMSE is too high"));
+    }
+}
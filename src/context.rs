@@ -0,0 +1,296 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{ChartTheme, MarkdownOptions, TextPolicy, Thresholds, UnitSystem};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// A table of translations for the text written in the reports.
+///
+/// The keys are the original (English) texts, so any text that has
+/// not been translated is written unchanged.
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::{Locale, Validator};
+///
+/// let mut locale = Locale::new();
+/// locale.insert("Passed", "Aprobado");
+/// locale.insert("Failed", "Reprobado");
+/// assert_eq!(locale.get("Passed"), "Aprobado");
+/// assert_eq!(locale.get("Validation"), "Validation");
+///
+/// let mut validator = Validator::new("Validación", "report.html");
+/// validator.set_locale(locale);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Locale {
+    strings: HashMap<String, String>,
+}
+
+impl Locale {
+    /// Creates a new, empty, `Locale`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the translation of a text
+    pub fn insert<K: Into<String>, V: Into<String>>(&mut self, txt: K, translation: V) {
+        self.strings.insert(txt.into(), translation.into());
+    }
+
+    /// Gets the translation of a text, or the text itself if
+    /// there is no translation for it
+    pub fn get<'b>(&'b self, txt: &'b str) -> &'b str {
+        match self.strings.get(txt) {
+            Some(t) => t,
+            None => txt,
+        }
+    }
+}
+
+/// The settings under which a report is written, which are handed to
+/// each validation when it is run through [`crate::Validate::validate_in`].
+///
+/// It also manages the assets directory of the report, into which
+/// validations can write auxiliary files (e.g., images or data) that are
+/// linked from the report instead of being written inline.
+///
+/// ```
+/// use validate::{ReportContext, Validate, ValidationResult};
+///
+/// struct Photo;
+/// impl Validate for Photo {
+///     fn validate(&self) -> ValidationResult {
+///         self.validate_in(&ReportContext::default())
+///     }
+///     fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+///         match ctx.write_asset("photo.txt", "not really a photo") {
+///             Ok(link) => ValidationResult::Ok(format!("[The photo]({})", link)),
+///             Err(e) => ValidationResult::Err(e.clone(), e),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ReportContext {
+    /// The translations of the texts in the report
+    pub(crate) locale: Locale,
+
+    /// The system of units in which the values are displayed
+    pub(crate) unit_system: UnitSystem,
+
+    /// The style of the charts
+    pub(crate) chart_theme: ChartTheme,
+
+    /// The thresholds inherited by validations that do not set their own
+    pub(crate) thresholds: Thresholds,
+
+    /// How the text provided by the user is written into the report
+    pub(crate) text_policy: TextPolicy,
+
+    /// The Markdown extensions enabled in the report
+    pub(crate) markdown: MarkdownOptions,
+
+    /// The directory into which the assets of the report are written
+    pub(crate) assets_dir: PathBuf,
+
+    /// The directory of the report, to which links are relative
+    pub(crate) report_dir: PathBuf,
+
+    /// The names of the assets written so far, which can be written
+    /// from several threads (see `Validator::validate_par`)
+    pub(crate) used_assets: Mutex<HashSet<String>>,
+}
+
+impl Clone for ReportContext {
+    fn clone(&self) -> Self {
+        Self {
+            locale: self.locale.clone(),
+            unit_system: self.unit_system,
+            chart_theme: self.chart_theme.clone(),
+            thresholds: self.thresholds,
+            text_policy: self.text_policy,
+            markdown: self.markdown,
+            assets_dir: self.assets_dir.clone(),
+            report_dir: self.report_dir.clone(),
+            used_assets: Mutex::new(self.used_assets().clone()),
+        }
+    }
+}
+
+impl ReportContext {
+    /// Translates a text of the report
+    pub fn tr<'b>(&'b self, txt: &'b str) -> &'b str {
+        self.locale.get(txt)
+    }
+
+    /// The system of units in which values should be displayed
+    pub fn unit_system(&self) -> UnitSystem {
+        self.unit_system
+    }
+
+    /// The style of the charts
+    pub fn chart_theme(&self) -> &ChartTheme {
+        &self.chart_theme
+    }
+
+    /// The default thresholds of the report
+    pub fn thresholds(&self) -> &Thresholds {
+        &self.thresholds
+    }
+
+    /// The Markdown extensions enabled in the report, so validations
+    /// know whether they can write, e.g., tables
+    pub fn markdown_options(&self) -> &MarkdownOptions {
+        &self.markdown
+    }
+
+    /// Prepares a text provided by the user (e.g., a title or the name of a
+    /// case) to be written into the Markdown of the report, following
+    /// its [`TextPolicy`]
+    pub fn user_text(&self, txt: &str) -> String {
+        self.text_policy.apply(txt)
+    }
+
+    /// Prepares a text provided by the user to be written into a cell of a
+    /// Markdown table, like [`ReportContext::user_text`] but also escaping
+    /// the `|` that would split the cell (unless the [`TextPolicy`] already did)
+    ///
+    /// ```
+    /// use validate::ReportContext;
+    ///
+    /// let ctx = ReportContext::default();
+    /// assert_eq!(ctx.user_cell("a | b"), "a \\| b");
+    /// ```
+    pub fn user_cell(&self, txt: &str) -> String {
+        match self.text_policy {
+            TextPolicy::PlainText => self.text_policy.apply(txt),
+            _ => self.text_policy.apply(txt).replace('|', "\\|"),
+        }
+    }
+
+    /// Reserves a file called `name` in the assets directory—which is created
+    /// if needed—and returns its path and its link relative to the report.
+    ///
+    /// Names are made unique within a report by adding a suffix (e.g.,
+    /// `chart_2.svg`), so validations need not coordinate their names.
+    pub fn asset_path(&self, name: &str) -> Result<(PathBuf, String), String> {
+        std::fs::create_dir_all(&self.assets_dir).map_err(|e| {
+            format!(
+                "Cannot create assets directory '{}': {}",
+                self.assets_dir.display(),
+                e
+            )
+        })?;
+
+        let name = self.unique_asset_name(name);
+        let link_dir = self
+            .assets_dir
+            .strip_prefix(&self.report_dir)
+            .unwrap_or(&self.assets_dir);
+        let link = link_dir.join(&name).to_string_lossy().to_string();
+        Ok((self.assets_dir.join(name), link))
+    }
+
+    /// Writes a file called `name` (see [`ReportContext::asset_path`]) into the
+    /// assets directory, returning its link relative to the report.
+    pub fn write_asset<C: AsRef<[u8]>>(&self, name: &str, contents: C) -> Result<String, String> {
+        let (path, link) = self.asset_path(name)?;
+        std::fs::write(&path, contents)
+            .map_err(|e| format!("Cannot write asset '{}': {}", path.display(), e))?;
+        Ok(link)
+    }
+
+    /// Finds a name—based on `name`—that has not been used by other assets
+    fn unique_asset_name(&self, name: &str) -> String {
+        let mut used = self.used_assets();
+        let mut candidate = name.to_string();
+        let path = Path::new(name);
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let ext = path
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        let mut i = 1;
+        while used.contains(&candidate) {
+            i += 1;
+            candidate = format!("{}_{}{}", stem, i, ext);
+        }
+        used.insert(candidate.clone());
+        candidate
+    }
+
+    /// Forgets the assets written so far, before writing a new report
+    pub(crate) fn reset_assets(&self) {
+        self.used_assets().clear()
+    }
+
+    /// The names of the assets written so far. A panic while they were
+    /// being updated leaves them consistent, so poisoning is ignored.
+    fn used_assets(&self) -> MutexGuard<'_, HashSet<String>> {
+        self.used_assets.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_names() {
+        let dir = std::env::temp_dir().join("validate_test_asset_names");
+        let ctx = ReportContext {
+            assets_dir: dir.join("report_assets"),
+            report_dir: dir.clone(),
+            ..Default::default()
+        };
+        assert_eq!(
+            ctx.write_asset("chart.svg", "<svg/>").unwrap(),
+            "report_assets/chart.svg"
+        );
+        assert_eq!(
+            ctx.write_asset("chart.svg", "<svg/>").unwrap(),
+            "report_assets/chart_2.svg"
+        );
+        assert_eq!(
+            ctx.write_asset("chart.svg", "<svg/>").unwrap(),
+            "report_assets/chart_3.svg"
+        );
+        assert_eq!(ctx.write_asset("notes", "").unwrap(), "report_assets/notes");
+        assert_eq!(
+            ctx.write_asset("notes", "").unwrap(),
+            "report_assets/notes_2"
+        );
+        assert!(dir.join("report_assets/chart_3.svg").exists());
+
+        ctx.reset_assets();
+        assert_eq!(
+            ctx.write_asset("chart.svg", "<svg/>").unwrap(),
+            "report_assets/chart.svg"
+        );
+    }
+}
@@ -0,0 +1,579 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{Artifact, ChartData, Indicator, ReportContext, RunInput, SourceLocation, Validate, ValidationResult};
+use std::fmt;
+
+/// Short names for the indicators of the validators of this crate
+const ALIASES: [(&str, &str); 6] = [
+    ("mbe", "Mean Bias Error"),
+    ("rmse", "Root Mean Squared Error"),
+    ("cv_rmse", "CV(RMSE)"),
+    ("max_error", "Maximum Absolute Error"),
+    ("r2", "R2"),
+    ("std_error", "Std Error"),
+];
+
+/// An acceptance criterion written as an expression on the indicators of a
+/// validation (see [`Validate::indicators`]), so compound rules from standards
+/// documents can be encoded—and printed in the report—verbatim.
+///
+/// Expressions combine comparisons (`<`, `<=`, `>`, `>=`, `==`, `!=`) with
+/// `&&`, `||` and `!`, and support arithmetic (`+`, `-`, `*`, `/`), absolute
+/// values (`|mbe|` or `abs(mbe)`), parentheses and percentages (`15%` is `0.15`).
+/// Indicators are referred to by their name in `snake_case` (e.g.,
+/// `mean_bias_error`) or by a short name: `mbe`, `rmse`, `cv_rmse`, `max_error`,
+/// `r2` and `std_error`.
+///
+/// # Example
+///
+/// ```
+/// use validate::{Criterion, Indicator};
+///
+/// let criterion = Criterion::parse("rmse < 0.5 && |mbe| < 0.1 || cv_rmse < 15%").unwrap();
+/// let indicators = [
+///     Indicator::new("Root Mean Squared Error", 0.7),
+///     Indicator::new("Mean Bias Error", -0.05),
+///     Indicator::new("CV(RMSE)", 0.1),
+/// ];
+/// assert!(criterion.evaluate(&indicators).unwrap());
+/// assert!(Criterion::parse("rmse < ").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Criterion {
+    /// The expression, as written by the user
+    source: String,
+
+    /// The parsed expression
+    expr: Cond,
+}
+
+/// A boolean expression
+#[derive(Debug, Clone, PartialEq)]
+enum Cond {
+    Or(Box<Cond>, Box<Cond>),
+    And(Box<Cond>, Box<Cond>),
+    Not(Box<Cond>),
+    Compare(Num, &'static str, Num),
+}
+
+/// A numeric expression
+#[derive(Debug, Clone, PartialEq)]
+enum Num {
+    Value(f64),
+    Var(String),
+    Abs(Box<Num>),
+    Neg(Box<Num>),
+    Op(Box<Num>, char, Box<Num>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(&'static str),
+}
+
+/// Splits an expression into tokens
+fn tokenize(txt: &str) -> Result<Vec<Token>, String> {
+    const OPS: [&str; 17] = [
+        "&&", "||", "<=", ">=", "==", "!=", "<", ">", "!", "+", "-", "*", "/", "(", ")", "|", "%",
+    ];
+    let chars: Vec<char> = txt.chars().collect();
+    let mut ret = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            // Digits, decimal points and exponents (e.g., `1.5e-3`)
+            let in_number = |i: usize| match chars[i] {
+                '0'..='9' | '.' | 'e' | 'E' => true,
+                '-' | '+' => matches!(chars[i - 1], 'e' | 'E'),
+                _ => false,
+            };
+            while i < chars.len() && in_number(i) {
+                i += 1;
+            }
+            let n: String = chars[start..i].iter().collect();
+            let n = n.parse().map_err(|_| format!("Invalid number '{}' in criterion '{}'", n, txt))?;
+            ret.push(Token::Number(n));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            ret.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..].iter().take(2).collect();
+            let op = OPS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or_else(|| format!("Unexpected '{}' in criterion '{}'", c, txt))?;
+            i += op.len();
+            ret.push(Token::Op(op));
+        }
+    }
+    Ok(ret)
+}
+
+/// Parses a list of tokens by recursive descent
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    source: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &str) -> String {
+        format!("Invalid criterion '{}': {}", self.source, msg)
+    }
+
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if self.peek_op() == Some(op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), String> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", op)))
+        }
+    }
+
+    fn or(&mut self) -> Result<Cond, String> {
+        let mut ret = self.and()?;
+        while self.eat("||") {
+            ret = Cond::Or(Box::new(ret), Box::new(self.and()?));
+        }
+        Ok(ret)
+    }
+
+    fn and(&mut self) -> Result<Cond, String> {
+        let mut ret = self.not()?;
+        while self.eat("&&") {
+            ret = Cond::And(Box::new(ret), Box::new(self.not()?));
+        }
+        Ok(ret)
+    }
+
+    fn not(&mut self) -> Result<Cond, String> {
+        if self.eat("!") {
+            return Ok(Cond::Not(Box::new(self.not()?)));
+        }
+        // Parentheses might group a condition or be part of a comparison
+        if self.peek_op() == Some("(") {
+            let start = self.pos;
+            self.pos += 1;
+            if let Ok(cond) = self.or() {
+                if self.eat(")") {
+                    return Ok(cond);
+                }
+            }
+            self.pos = start;
+        }
+        let left = self.sum()?;
+        let op = match self.peek_op() {
+            Some(op @ ("<" | "<=" | ">" | ">=" | "==" | "!=")) => op,
+            _ => return Err(self.error("expected a comparison")),
+        };
+        self.pos += 1;
+        Ok(Cond::Compare(left, op, self.sum()?))
+    }
+
+    fn sum(&mut self) -> Result<Num, String> {
+        let mut ret = self.product()?;
+        loop {
+            let op = match self.peek_op() {
+                Some("+") => '+',
+                Some("-") => '-',
+                _ => return Ok(ret),
+            };
+            self.pos += 1;
+            ret = Num::Op(Box::new(ret), op, Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Num, String> {
+        let mut ret = self.unary()?;
+        loop {
+            let op = match self.peek_op() {
+                Some("*") => '*',
+                Some("/") => '/',
+                _ => return Ok(ret),
+            };
+            self.pos += 1;
+            ret = Num::Op(Box::new(ret), op, Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Num, String> {
+        if self.eat("-") {
+            return Ok(Num::Neg(Box::new(self.unary()?)));
+        }
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => {
+                if self.eat("%") {
+                    Ok(Num::Value(n / 100.))
+                } else {
+                    Ok(Num::Value(n))
+                }
+            }
+            Some(Token::Ident(name)) if name == "abs" && self.peek_op() == Some("(") => {
+                self.pos += 1;
+                let ret = self.sum()?;
+                self.expect(")")?;
+                Ok(Num::Abs(Box::new(ret)))
+            }
+            Some(Token::Ident(name)) => Ok(Num::Var(name)),
+            Some(Token::Op("(")) => {
+                let ret = self.sum()?;
+                self.expect(")")?;
+                Ok(ret)
+            }
+            Some(Token::Op("|")) => {
+                let ret = self.sum()?;
+                self.expect("|")?;
+                Ok(Num::Abs(Box::new(ret)))
+            }
+            _ => Err(self.error("expected a number or an indicator")),
+        }
+    }
+}
+
+/// Builds the name by which an indicator can be referred to (e.g.,
+/// `mean_bias_error` for `Mean Bias Error`)
+fn snake_case(name: &str) -> String {
+    crate::report::slug(name).replace('-', "_")
+}
+
+impl Num {
+    fn eval(&self, indicators: &[Indicator]) -> Result<f64, String> {
+        Ok(match self {
+            Num::Value(v) => *v,
+            Num::Var(name) => {
+                let full = ALIASES
+                    .iter()
+                    .find(|(alias, _)| alias == name)
+                    .map(|(_, full)| *full);
+                let value = indicators
+                    .iter()
+                    .find(|ind| Some(ind.name.as_str()) == full || snake_case(&ind.name) == *name)
+                    .map(|ind| ind.value)
+                    .ok_or_else(|| format!("Unknown indicator '{}'", name))?;
+                // A NaN would make every comparison false, so negated ones pass
+                if !value.is_finite() {
+                    return Err(format!("Indicator '{}' is not a finite number ({})", name, value));
+                }
+                value
+            }
+            Num::Abs(x) => x.eval(indicators)?.abs(),
+            Num::Neg(x) => -x.eval(indicators)?,
+            Num::Op(a, op, b) => {
+                let (a, b) = (a.eval(indicators)?, b.eval(indicators)?);
+                match op {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    _ => a / b,
+                }
+            }
+        })
+    }
+
+    fn variables<'b>(&'b self, ret: &mut Vec<&'b str>) {
+        match self {
+            Num::Value(_) => {}
+            Num::Var(name) => {
+                if !ret.contains(&name.as_str()) {
+                    ret.push(name)
+                }
+            }
+            Num::Abs(x) | Num::Neg(x) => x.variables(ret),
+            Num::Op(a, _, b) => {
+                a.variables(ret);
+                b.variables(ret);
+            }
+        }
+    }
+}
+
+impl Cond {
+    fn eval(&self, indicators: &[Indicator]) -> Result<bool, String> {
+        Ok(match self {
+            Cond::Or(a, b) => a.eval(indicators)? || b.eval(indicators)?,
+            Cond::And(a, b) => a.eval(indicators)? && b.eval(indicators)?,
+            Cond::Not(a) => !a.eval(indicators)?,
+            Cond::Compare(a, op, b) => {
+                let (a, b) = (a.eval(indicators)?, b.eval(indicators)?);
+                match *op {
+                    "<" => a < b,
+                    "<=" => a <= b,
+                    ">" => a > b,
+                    ">=" => a >= b,
+                    "==" => a == b,
+                    _ => a != b,
+                }
+            }
+        })
+    }
+
+    fn variables<'b>(&'b self, ret: &mut Vec<&'b str>) {
+        match self {
+            Cond::Or(a, b) | Cond::And(a, b) => {
+                a.variables(ret);
+                b.variables(ret);
+            }
+            Cond::Not(a) => a.variables(ret),
+            Cond::Compare(a, _, b) => {
+                a.variables(ret);
+                b.variables(ret);
+            }
+        }
+    }
+}
+
+impl Criterion {
+    /// Parses a criterion
+    pub fn parse(txt: &str) -> Result<Self, String> {
+        let tokens = tokenize(txt)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            source: txt,
+        };
+        let expr = parser.or()?;
+        if parser.pos < tokens.len() {
+            return Err(parser.error("unexpected trailing text"));
+        }
+        Ok(Self {
+            source: txt.trim().to_string(),
+            expr,
+        })
+    }
+
+    /// Checks whether a set of indicators meets this criterion. Fails if
+    /// the criterion refers to an indicator that is not in the set, or
+    /// whose value is not finite (e.g., NaN).
+    pub fn evaluate(&self, indicators: &[Indicator]) -> Result<bool, String> {
+        self.expr.eval(indicators)
+    }
+
+    /// The names of the indicators used by this criterion, in order of appearance
+    pub fn variables(&self) -> Vec<&str> {
+        let mut ret = Vec::new();
+        self.expr.variables(&mut ret);
+        ret
+    }
+}
+
+impl fmt::Display for Criterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl std::str::FromStr for Criterion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// A validation whose acceptance is decided by a [`Criterion`] on its
+/// indicators, on top of its own checks. The criterion and the values it
+/// was evaluated with are written into the report.
+///
+/// # Example
+///
+/// ```
+/// use validate::{Criterion, CriterionValidator, SeriesValidator, Validate};
+///
+/// let v = CriterionValidator {
+///     validation: SeriesValidator {
+///         expected: vec![1., 2., 3.],
+///         found: vec![1.1, 2., 3.],
+///         ..Default::default()
+///     },
+///     criterion: Criterion::parse("rmse < 0.1 && |mbe| < 5%").unwrap(),
+/// };
+/// assert!(v.validate().is_ok());
+/// ```
+pub struct CriterionValidator<V: Validate> {
+    /// The validation whose indicators are checked
+    pub validation: V,
+
+    /// The criterion the indicators need to meet
+    pub criterion: Criterion,
+}
+
+impl<V: Validate> Validate for CriterionValidator<V> {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        let (mut txt, mut error) = match self.validation.validate_in(ctx) {
+            ValidationResult::Ok(txt) => (txt, None),
+            ValidationResult::Err(txt, e) => (txt, Some(e)),
+        };
+        let indicators = self.validation.indicators();
+        txt.push_str(&format!(
+            "\n\n#### {}:\n\n`{}`\n\n",
+            ctx.tr("Acceptance criterion"),
+            self.criterion.source.replace('`', "'")
+        ));
+        for name in self.criterion.variables() {
+            if let Ok(value) = Num::Var(name.to_string()).eval(&indicators) {
+                txt.push_str(&format!(" * `{}` = {:.4}\n", name, value));
+            }
+        }
+        let failure = match self.criterion.evaluate(&indicators) {
+            Ok(true) => None,
+            Ok(false) => Some(format!(
+                "{}: {}",
+                ctx.tr("The acceptance criterion is not met"),
+                self.criterion
+            )),
+            Err(e) => Some(format!("{}: {}", ctx.tr("Cannot evaluate the acceptance criterion"), e)),
+        };
+        match failure {
+            None => txt.push_str(&format!("\n**{}**\n", ctx.tr("The acceptance criterion is met"))),
+            Some(e) => {
+                txt.push_str(&format!("\n**{}**\n", e));
+                error = Some(match error {
+                    Some(prev) => format!("{}\n{}", prev, e),
+                    None => e,
+                });
+            }
+        }
+        match error {
+            Some(e) => ValidationResult::Err(txt, e),
+            None => ValidationResult::Ok(txt),
+        }
+    }
+
+    fn title(&self) -> Option<String> {
+        self.validation.title()
+    }
+
+    fn description(&self) -> Option<String> {
+        self.validation.description()
+    }
+
+    fn artifacts(&self) -> Vec<Artifact> {
+        self.validation.artifacts()
+    }
+
+    fn chart_data(&self) -> Option<ChartData> {
+        self.validation.chart_data()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        self.validation.indicators()
+    }
+
+    fn inputs(&self) -> Vec<RunInput> {
+        self.validation.inputs()
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.validation.covers()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.validation.requirements()
+    }
+
+    fn defines_thresholds(&self, _ctx: &ReportContext) -> bool {
+        true
+    }
+
+    fn location(&self) -> Option<SourceLocation> {
+        self.validation.location()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indicators() -> Vec<Indicator> {
+        vec![
+            Indicator::new("Mean Bias Error", -0.2),
+            Indicator::new("Root Mean Squared Error", 0.4),
+            Indicator::new("Points", 10.),
+        ]
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let check = |txt: &str| Criterion::parse(txt).unwrap().evaluate(&indicators());
+        assert_eq!(check("rmse < 0.5"), Ok(true));
+        assert_eq!(check("|mbe| < 0.1"), Ok(false));
+        assert_eq!(check("abs(mean_bias_error) <= 20%"), Ok(true));
+        // && binds tighter than ||
+        assert_eq!(check("rmse > 1 && mbe < 0 || points >= 10"), Ok(true));
+        assert_eq!(check("rmse > 1 && (mbe < 0 || points >= 10)"), Ok(false));
+        assert_eq!(check("!(rmse > 1) && (rmse + |mbe|) * 2 > 1.1"), Ok(true));
+        assert_eq!(check("-mbe > 1e-1"), Ok(true));
+        assert!(check("r2 > 0.9").unwrap_err().contains("'r2'"));
+    }
+
+    #[test]
+    fn test_evaluate_non_finite() {
+        let mut indicators = indicators();
+        indicators[1].value = f64::NAN;
+        let check = |txt: &str| Criterion::parse(txt).unwrap().evaluate(&indicators);
+        assert!(check("!(rmse > 0.5)").unwrap_err().contains("'rmse'"));
+        assert!(check("rmse < 0.5").is_err());
+        assert_eq!(check("points >= 10"), Ok(true));
+        indicators[1].value = f64::INFINITY;
+        assert!(Criterion::parse("rmse != 0").unwrap().evaluate(&indicators).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Criterion::parse("rmse").is_err());
+        assert!(Criterion::parse("rmse < 0.5 &&").is_err());
+        assert!(Criterion::parse("(rmse < 0.5").is_err());
+        assert!(Criterion::parse("rmse < 0.5 $").is_err());
+        let c = Criterion::parse(" rmse < 0.5 && |mbe| < 0.1 ").unwrap();
+        assert_eq!(c.to_string(), "rmse < 0.5 && |mbe| < 0.1");
+        assert_eq!(c.variables(), ["rmse", "mbe"]);
+    }
+}
@@ -0,0 +1,299 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::stats::{count_as_f64, mean, trend_test, TrendTest};
+use crate::{Indicator, ReportContext, Validate, ValidationResult};
+#[cfg(feature = "charts")]
+use crate::svg::describe_series;
+#[cfg(feature = "charts")]
+use poloto::prelude::*;
+
+/// The p-value below which a trend of the metric is considered real
+const SIGNIFICANCE: f64 = 0.05;
+
+/// Checks that a validation metric (e.g., the Root Mean Squared Error of the
+/// same test, run on the same rig) is stable across repeated experiments.
+/// If the model did not change but the metric trends over time, the test
+/// rig—not the model—is drifting (e.g., a sensor is ageing).
+///
+/// The report plots the metric against the date of each experiment, along
+/// with its trend (see [`crate::stats::trend_test`]).
+///
+/// # Example
+///
+/// ```
+/// use validate::{DriftValidator, Validate};
+///
+/// // The error of the model creeps up, month after month
+/// let v = DriftValidator {
+///     metric: "Root Mean Squared Error",
+///     units: Some("C"),
+///     dates: vec![0., 30., 61., 91., 122., 152.],
+///     values: vec![0.41, 0.44, 0.47, 0.52, 0.55, 0.58],
+///     allowed_drift: Some(0.0005),
+///     ..Default::default()
+/// };
+/// assert!(v.validate().is_err());
+/// ```
+#[derive(Default, Clone)]
+pub struct DriftValidator {
+    /// The name of the metric (e.g., `"Root Mean Squared Error"`)
+    pub metric: &'static str,
+
+    /// The units of the metric
+    pub units: Option<&'static str>,
+
+    /// The date of each experiment (e.g., days since the rig was commissioned)
+    pub dates: Vec<f64>,
+
+    /// The value of the metric in each experiment
+    pub values: Vec<f64>,
+
+    /// The maximum allowed change of the metric per unit of `dates`. The
+    /// validation fails if the trend is both significant and steeper than this.
+    pub allowed_drift: Option<f64>,
+
+    /// The maximum allowed standard deviation of the metric across experiments
+    pub allowed_standard_deviation: Option<f64>,
+
+    /// The units of the `dates` (e.g., `"days"`)
+    pub x_units: Option<&'static str>,
+
+    /// The label in the x axis of the chart
+    pub x_label: Option<&'static str>,
+
+    /// the title of the chart
+    pub chart_title: Option<&'static str>,
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"test rig"`), listed in the coverage of the report
+    pub covers: Vec<&'static str>,
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"`), mapped to it in the report
+    pub requirements: Vec<&'static str>,
+}
+
+impl DriftValidator {
+    /// The sample standard deviation of the metric
+    fn standard_deviation(&self) -> f64 {
+        if self.values.len() < 2 {
+            return 0.;
+        }
+        let m = mean(&self.values);
+        let squares: f64 = self.values.iter().map(|v| (v - m).powi(2)).sum();
+        (squares / count_as_f64(self.values.len() - 1)).sqrt()
+    }
+
+    /// The trend of the metric over time
+    fn trend(&self) -> Result<TrendTest, String> {
+        if self.dates.len() != self.values.len() {
+            return Err(format!(
+                "There are {} dates for {} values of the metric",
+                self.dates.len(),
+                self.values.len()
+            ));
+        }
+        trend_test(&self.dates, &self.values)
+    }
+}
+
+impl Validate for DriftValidator {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        let trend = match self.trend() {
+            Ok(t) => t,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+        let units = ctx.unit_system();
+        let y_units = self.units.unwrap_or("");
+        let show_diff = |v: f64| units.convert_difference(v, y_units);
+        let per = self.x_units.unwrap_or("unit");
+
+        let standard_deviation = self.standard_deviation();
+        let file_msg = format!(
+            "\n * {}: {}\n * {}: {:.4}\n * {}: {:.4} {} {} (p = {:.4})",
+            ctx.tr("Experiments"),
+            self.values.len(),
+            ctx.tr("Standard deviation of the metric"),
+            show_diff(standard_deviation),
+            ctx.tr("Drift of the metric"),
+            show_diff(trend.slope),
+            ctx.tr("per"),
+            per,
+            trend.p_value
+        );
+
+        let mut nchecks = 0;
+        let mut err_msg = String::new();
+        if let Some(allowed) = self.allowed_drift {
+            nchecks += 1;
+            if trend.p_value < SIGNIFICANCE && trend.slope.abs() > allowed {
+                err_msg = format!(
+                    "{}\n * {} drifts by {:.4} per {} (p = {:.4}), which is more than the allowed {:.4}",
+                    err_msg,
+                    self.metric,
+                    show_diff(trend.slope),
+                    per,
+                    trend.p_value,
+                    show_diff(allowed)
+                );
+            }
+        }
+        if let Some(allowed) = self.allowed_standard_deviation {
+            nchecks += 1;
+            if standard_deviation > allowed {
+                err_msg = format!(
+                    "{}\n * The standard deviation of {} across experiments is {:.4}, which is greater than the allowed value of {:.4}",
+                    err_msg,
+                    self.metric,
+                    show_diff(standard_deviation),
+                    show_diff(allowed)
+                );
+            }
+        }
+
+        #[cfg(feature = "charts")]
+        let chart = {
+            let show = |v: f64| units.convert(v, y_units);
+            let points = self
+                .dates
+                .iter()
+                .zip(self.values.iter())
+                .map(|(d, v)| [*d, show(*v)])
+                .buffered_plot()
+                .scatter(ctx.tr(self.metric));
+            let (first, last) = crate::stats::min_max(&self.dates);
+            let fit = [first, last]
+                .into_iter()
+                .map(|d| [d, show(trend.intersect + trend.slope * d)])
+                .buffered_plot()
+                .line(ctx.tr("Trend"));
+            let origin = poloto::build::origin();
+
+            let mut x_label: String = self.x_label.unwrap_or("Experiment date").into();
+            if let Some(x_units) = self.x_units {
+                x_label = format!("{} ({})", x_label, x_units);
+            }
+            let mut y_label: String = self.metric.into();
+            if let Some(y_units) = self.units {
+                y_label = format!("{} ({})", y_label, units.units(y_units));
+            }
+            let p = quick_fmt!(
+                self.chart_title.unwrap_or(""),
+                &x_label,
+                &y_label,
+                points,
+                fit,
+                origin
+            );
+            let alt_title = match self.chart_title {
+                Some(title) if !title.is_empty() => title.to_string(),
+                _ => format!("{} vs {}", y_label, x_label),
+            };
+            let description = [
+                describe_series(ctx.tr(self.metric), self.values.iter().map(|v| show(*v))),
+                format!("{}: {:.4} {} {} (p = {:.4})", ctx.tr("Drift of the metric"), show_diff(trend.slope), ctx.tr("per"), per, trend.p_value),
+            ]
+            .join(". ");
+            ctx.chart_theme()
+                .render(poloto::disp(|w| p.simple_theme(w)).to_string(), &alt_title, &description)
+        };
+        #[cfg(not(feature = "charts"))]
+        let chart = ctx.chart_theme().omitted();
+
+        let show_err = if nchecks == 0 {
+            ctx.tr("No checks performed...")
+        } else if err_msg.is_empty() {
+            ctx.tr("No errors found")
+        } else {
+            &err_msg
+        };
+        let file = format!(
+            "{}\n\n#### {}:\n {}\n#### {}:\n\n{}",
+            file_msg,
+            ctx.tr("Errors"),
+            show_err,
+            ctx.tr("Data"),
+            chart
+        );
+        if err_msg.is_empty() {
+            ValidationResult::Ok(file)
+        } else {
+            ValidationResult::Err(file, err_msg)
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.covers.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.requirements.iter().map(|r| r.to_string()).collect()
+    }
+
+    fn defines_thresholds(&self, _ctx: &ReportContext) -> bool {
+        self.allowed_drift.is_some() || self.allowed_standard_deviation.is_some()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        let mut ret = vec![Indicator::new("Standard deviation", self.standard_deviation())];
+        if let Ok(trend) = self.trend() {
+            ret.push(Indicator::new("Drift", trend.slope));
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drift() {
+        // A stable rig
+        let mut v = DriftValidator {
+            metric: "Mean Bias Error",
+            dates: vec![1., 2., 3., 4., 5., 6.],
+            values: vec![0.1, -0.1, 0.12, -0.08, 0.1, -0.1],
+            allowed_drift: Some(0.01),
+            allowed_standard_deviation: Some(0.2),
+            ..Default::default()
+        };
+        assert!(v.validate().is_ok());
+
+        v.allowed_standard_deviation = Some(0.05);
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains("The standard deviation of Mean Bias Error across experiments is 0.1")),
+        }
+
+        v.dates.pop();
+        assert!(v.validate().is_err());
+    }
+}
@@ -0,0 +1,405 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::{Indicator, ReportContext, Validate, ValidationResult};
+#[cfg(feature = "charts")]
+use crate::svg::describe_series;
+#[cfg(feature = "charts")]
+use poloto::prelude::*;
+
+/// An energy flow (e.g., an end use like heating or lighting, or the
+/// losses of a system), with its expected and found values, checked by an
+/// [`EnergyFlowValidator`]. A tolerance given here overrides the one of
+/// the validator.
+///
+/// ```
+/// use validate::EnergyFlow;
+///
+/// let lighting = EnergyFlow {
+///     allowed_relative_error: Some(0.05),
+///     ..EnergyFlow::new("Lighting", 210., 204.)
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnergyFlow {
+    /// The name of the flow
+    pub name: String,
+
+    /// The expected value of the flow
+    pub expected: f64,
+
+    /// The value of the flow found
+    pub found: f64,
+
+    /// The maximum allowed relative error of this flow (e.g., `0.1` for 10%)
+    pub allowed_relative_error: Option<f64>,
+}
+
+impl EnergyFlow {
+    /// Creates a new `EnergyFlow`, with the tolerance of the validator
+    pub fn new<S: Into<String>>(name: S, expected: f64, found: f64) -> Self {
+        Self {
+            name: name.into(),
+            expected,
+            found,
+            allowed_relative_error: None,
+        }
+    }
+
+    /// The error of the flow, relative to its expected value. It is
+    /// infinite if only the expected value is zero.
+    fn relative_error(&self) -> f64 {
+        let error = self.found - self.expected;
+        if error == 0. {
+            0.
+        } else {
+            error / self.expected.abs()
+        }
+    }
+}
+
+/// Compares the breakdown of the energy of a model (e.g., into heating,
+/// cooling, lighting, equipment and losses) against the expected one, as
+/// the accuracy of such a disaggregation is a common acceptance criterion.
+/// Each [`EnergyFlow`] is checked against its own tolerance—or that of the
+/// validator—and so is the total. The flows are drawn as pairs of bars.
+///
+/// # Example
+///
+/// ```
+/// use validate::{EnergyFlow, EnergyFlowValidator, Validate};
+///
+/// let v = EnergyFlowValidator {
+///     flows: vec![
+///         EnergyFlow::new("Heating", 1200., 1260.),
+///         EnergyFlow::new("Cooling", 300., 285.),
+///         EnergyFlow {
+///             allowed_relative_error: Some(0.05),
+///             ..EnergyFlow::new("Lighting", 210., 204.)
+///         },
+///     ],
+///     allowed_relative_error: Some(0.1),
+///     allowed_total_relative_error: Some(0.05),
+///     units: Some("kWh".to_string()),
+///     ..Default::default()
+/// };
+/// assert!(v.validate().is_ok());
+/// ```
+#[derive(Default, Clone)]
+pub struct EnergyFlowValidator {
+    /// The flows, in the order in which they are reported
+    pub flows: Vec<EnergyFlow>,
+
+    /// The maximum allowed relative error of the flows that do not set
+    /// their own (e.g., `0.1` for 10%)
+    pub allowed_relative_error: Option<f64>,
+
+    /// The maximum allowed relative error of the sum of the flows
+    pub allowed_total_relative_error: Option<f64>,
+
+    /// The units of the flows
+    pub units: Option<String>,
+
+    /// the title of the chart
+    pub chart_title: Option<String>,
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"end uses"`), listed in the coverage of the report
+    pub covers: Vec<String>,
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"`), mapped to it in the report
+    pub requirements: Vec<String>,
+}
+
+impl EnergyFlowValidator {
+    /// Checks the flows
+    fn check(&self) -> Result<(), String> {
+        if self.flows.is_empty() {
+            return Err("There are no energy flows to validate".to_string());
+        }
+        if let Some(f) = self.flows.iter().find(|f| !f.expected.is_finite() || !f.found.is_finite()) {
+            return Err(format!("Energy flow '{}' has non-finite values", f.name));
+        }
+        Ok(())
+    }
+
+    /// The expected and found sums of the flows
+    fn totals(&self) -> (f64, f64) {
+        self.flows
+            .iter()
+            .fold((0., 0.), |(e, f), flow| (e + flow.expected, f + flow.found))
+    }
+
+    /// The tolerance of a flow
+    fn tolerance(&self, flow: &EnergyFlow) -> Option<f64> {
+        flow.allowed_relative_error.or(self.allowed_relative_error)
+    }
+}
+
+impl Validate for EnergyFlowValidator {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        if let Err(e) = self.check() {
+            return ValidationResult::Err(e.clone(), e);
+        }
+        let units = ctx.unit_system();
+        let data_units = self.units.as_deref().unwrap_or("");
+        let show = |v: f64| units.convert_difference(v, data_units);
+        let (expected_total, found_total) = self.totals();
+        let total = EnergyFlow::new(ctx.tr("Total"), expected_total, found_total);
+        let share = |v: f64, total: f64| if total == 0. { 0. } else { 100. * v / total };
+        let percent = |v: Option<f64>| v.map(|v| format!("{:.1}%", 100. * v)).unwrap_or_else(|| "-".to_string());
+
+        // The flows, with their shares of the total
+        let unit_label = self.units.as_deref().map(|u| format!(" ({})", units.units(u))).unwrap_or_default();
+        let mut file_msg = String::from("\n");
+        let tables = ctx.markdown_options().tables;
+        let rows = self.flows.iter().chain(std::iter::once(&total)).map(|f| {
+            [
+                if tables { ctx.user_cell(&f.name) } else { ctx.user_text(&f.name) },
+                format!("{:.2}", show(f.expected)),
+                format!("{:.2}", show(f.found)),
+                format!("{:.1}%", 100. * f.relative_error()),
+                format!("{:.1}%", share(f.expected, expected_total)),
+                format!("{:.1}%", share(f.found, found_total)),
+                percent(if std::ptr::eq(f, &total) {
+                    self.allowed_total_relative_error
+                } else {
+                    self.tolerance(f)
+                }),
+            ]
+        });
+        if tables {
+            file_msg += &format!(
+                "| {} | {}{} | {}{} | {} | {} | {} | {} |\n|---|---:|---:|---:|---:|---:|---:|\n",
+                ctx.tr("Flow"),
+                ctx.tr("Expected"),
+                unit_label,
+                ctx.tr("Found"),
+                unit_label,
+                ctx.tr("Relative error"),
+                ctx.tr("Expected share"),
+                ctx.tr("Found share"),
+                ctx.tr("Tolerance")
+            );
+            for row in rows {
+                file_msg += &format!("| {} |\n", row.join(" | "));
+            }
+        } else {
+            for row in rows {
+                file_msg += &format!(" * {}: {} ({} {} {})\n", row[0], row[3], row[1], ctx.tr("expected, found"), row[2]);
+            }
+        }
+
+        let mut nchecks = 0;
+        let mut err_msg = String::new();
+        for flow in &self.flows {
+            if let Some(allowed) = self.tolerance(flow) {
+                nchecks += 1;
+                if flow.relative_error().abs() > allowed {
+                    err_msg = format!(
+                        "{}\n * The relative error of '{}' is {:.1}%, which is beyond the allowed {:.1}%",
+                        err_msg,
+                        flow.name,
+                        100. * flow.relative_error(),
+                        100. * allowed
+                    );
+                }
+            }
+        }
+        if let Some(allowed) = self.allowed_total_relative_error {
+            nchecks += 1;
+            if total.relative_error().abs() > allowed {
+                err_msg = format!(
+                    "{}\n * The relative error of the total is {:.1}%, which is beyond the allowed {:.1}%",
+                    err_msg,
+                    100. * total.relative_error(),
+                    100. * allowed
+                );
+            }
+        }
+
+        #[cfg(feature = "charts")]
+        let chart = {
+            // Each flow takes three rows, from the top: its expected bar,
+            // its found bar and a gap. Both series span all the rows, with
+            // empty bars in those of the other one.
+            let rows = |expected: bool| {
+                self.flows.iter().rev().flat_map(move |f| {
+                    let (e, found) = if expected { (show(f.expected), 0.) } else { (0., show(f.found)) };
+                    [(0., String::new()), (found, String::new()), (e, ctx.user_text(&f.name))]
+                })
+            };
+            let expected_legend = ctx.tr("Expected");
+            let found_legend = ctx.tr("Found");
+            let (expected, ticks) = poloto::build::bar::gen_bar(expected_legend, rows(true));
+            let (found, _) = poloto::build::bar::gen_bar(found_legend, rows(false));
+            let data = poloto::data(plots!(expected, found, poloto::build::markers([0.], [])));
+            let opt = poloto::render::render_opt_builder().with_tick_lines([true, false]).build();
+            let (bx, _) = poloto::ticks::bounds(&data, &opt);
+            let x_ticks = poloto::ticks::from_default(bx);
+            let mut x_label = ctx.tr("Energy").to_string();
+            if let Some(u) = self.units.as_deref() {
+                x_label = format!("{} ({})", x_label, units.units(u));
+            }
+            let chart_title = self.chart_title.as_deref().unwrap_or("");
+            let p = poloto::plot_with(
+                data,
+                opt,
+                poloto::plot_fmt(chart_title, &x_label, "", x_ticks, ticks),
+            );
+            let alt_title = if chart_title.is_empty() {
+                ctx.tr("Energy flows").to_string()
+            } else {
+                chart_title.to_string()
+            };
+            let description = [
+                describe_series(expected_legend, self.flows.iter().map(|f| show(f.expected))),
+                describe_series(found_legend, self.flows.iter().map(|f| show(f.found))),
+            ]
+            .join(". ");
+            ctx.chart_theme()
+                .render(poloto::disp(|w| p.simple_theme(w)).to_string(), &alt_title, &description)
+        };
+        #[cfg(not(feature = "charts"))]
+        let chart = ctx.chart_theme().omitted();
+
+        let show_err = if nchecks == 0 {
+            ctx.tr("No checks performed...")
+        } else if err_msg.is_empty() {
+            ctx.tr("No errors found")
+        } else {
+            &err_msg
+        };
+        let file = format!(
+            "{}\n\n#### {}:\n {}\n#### {}:\n\n{}",
+            file_msg,
+            ctx.tr("Errors"),
+            show_err,
+            ctx.tr("Data"),
+            chart
+        );
+        if err_msg.is_empty() {
+            ValidationResult::Ok(file)
+        } else {
+            ValidationResult::Err(file, err_msg)
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.covers.clone()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.requirements.clone()
+    }
+
+    fn defines_thresholds(&self, _ctx: &ReportContext) -> bool {
+        self.allowed_total_relative_error.is_some() || self.flows.iter().any(|f| self.tolerance(f).is_some())
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        if self.check().is_err() {
+            return Vec::new();
+        }
+        let (expected, found) = self.totals();
+        let mut ret = vec![Indicator::new(
+            "Total relative error",
+            EnergyFlow::new("Total", expected, found).relative_error(),
+        )];
+        ret.extend(
+            self.flows
+                .iter()
+                .map(|f| Indicator::new(format!("Relative error ({})", f.name), f.relative_error())),
+        );
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_energy_flows() {
+        let mut v = EnergyFlowValidator {
+            flows: vec![
+                EnergyFlow::new("Heating", 100., 110.),
+                EnergyFlow {
+                    allowed_relative_error: Some(0.2),
+                    ..EnergyFlow::new("Lighting", 50., 40.)
+                },
+                EnergyFlow::new("Losses", 50., 50.),
+            ],
+            allowed_relative_error: Some(0.1),
+            ..Default::default()
+        };
+        let indicators = v.indicators();
+        assert_eq!(indicators[0].value, 0.);
+        assert_eq!(indicators[2].name, "Relative error (Lighting)");
+        assert_eq!(indicators[2].value, -0.2);
+        match v.validate() {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.contains("| Heating | 100.00 | 110.00 | 10.0% | 50.0% | 55.0% | 10.0% |"));
+                assert!(txt.contains("| Total | 200.00 | 200.00 | 0.0% | 100.0% | 100.0% | - |"));
+                #[cfg(feature = "charts")]
+                assert!(txt.contains(">Lighting<"));
+            }
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        v.allowed_relative_error = Some(0.05);
+        v.allowed_total_relative_error = Some(0.01);
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => {
+                assert!(e.contains("The relative error of 'Heating' is 10.0%, which is beyond the allowed 5.0%"));
+                assert!(!e.contains("Lighting"));
+                assert!(!e.contains("total"));
+            }
+        }
+
+        // Flows that were not expected have an infinite relative error
+        v.flows.push(EnergyFlow::new("Equipment", 0., 5.));
+        assert!(v.validate().is_err());
+        v.flows.clear();
+        assert!(v.validate().is_err());
+
+        // The names of the flows are escaped only once in plain text
+        v.flows.push(EnergyFlow::new("Fans | pumps", 10., 10.));
+        let ctx = ReportContext {
+            text_policy: crate::TextPolicy::PlainText,
+            ..Default::default()
+        };
+        match v.validate_in(&ctx) {
+            ValidationResult::Ok(txt) => assert!(txt.contains("| Fans \\| pumps | 10.00 |")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+    }
+}
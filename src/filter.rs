@@ -0,0 +1,122 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The environment variable that selects the validations to run by their
+/// tags (e.g., `thermal,!slow`), overriding the filter set through
+/// [`crate::Validator::set_filter`]
+pub(crate) const TAGS_VAR: &str = "VALIDATE_TAGS";
+
+/// Selects validations by the tags they were pushed with (see
+/// [`crate::Validator::push_tagged`]), so only a subset of a suite runs
+/// (e.g., while developing a model).
+///
+/// A filter is written as a list of tags separated by commas or spaces.
+/// Validations with any of the plain tags are run, unless they have any
+/// of the tags preceded by `!`. A filter with only excluded tags runs
+/// everything else, including the validations without tags.
+///
+/// ```
+/// use validate::TagFilter;
+///
+/// let filter: TagFilter = "thermal, !slow".parse().unwrap();
+/// assert!(filter.matches(&["thermal"]));
+/// assert!(!filter.matches(&["thermal", "slow"]));
+/// assert!(!filter.matches(&["lighting"]));
+/// assert_eq!(filter.to_string(), "thermal, !slow");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl TagFilter {
+    /// Creates a filter that selects the validations with any of the
+    /// `include` tags (or any validation, if empty) and none of the
+    /// `exclude` ones
+    pub fn new(include: &[&str], exclude: &[&str]) -> Self {
+        Self {
+            include: include.iter().map(|t| t.to_string()).collect(),
+            exclude: exclude.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    /// Checks whether a validation with the given tags is selected
+    pub fn matches<S: AsRef<str>>(&self, tags: &[S]) -> bool {
+        let has = |wanted: &Vec<String>| tags.iter().any(|t| wanted.iter().any(|w| w == t.as_ref()));
+        (self.include.is_empty() || has(&self.include)) && !has(&self.exclude)
+    }
+}
+
+impl fmt::Display for TagFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tags: Vec<String> = self
+            .include
+            .iter()
+            .cloned()
+            .chain(self.exclude.iter().map(|t| format!("!{}", t)))
+            .collect();
+        write!(f, "{}", tags.join(", "))
+    }
+}
+
+impl FromStr for TagFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ret = Self::default();
+        for tag in s.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+            match tag.strip_prefix('!') {
+                Some("") => return Err(format!("Invalid filter '{}': '!' must precede a tag", s)),
+                Some(tag) => ret.exclude.push(tag.to_string()),
+                None => ret.include.push(tag.to_string()),
+            }
+        }
+        if ret.include.is_empty() && ret.exclude.is_empty() {
+            return Err(format!("Invalid filter '{}': expected something like 'thermal,!slow'", s));
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let filter: TagFilter = "!slow".parse().unwrap();
+        assert!(filter.matches::<&str>(&[]));
+        assert!(filter.matches(&["thermal"]));
+        assert!(!filter.matches(&["thermal", "slow"]));
+
+        let filter: TagFilter = "thermal lighting".parse().unwrap();
+        assert_eq!(filter, TagFilter::new(&["thermal", "lighting"], &[]));
+        assert!(filter.matches(&["lighting".to_string()]));
+        assert!(!filter.matches::<&str>(&[]));
+
+        assert!(" , ".parse::<TagFilter>().is_err());
+        assert!("thermal,!".parse::<TagFilter>().is_err());
+    }
+}
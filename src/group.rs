@@ -0,0 +1,257 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::report::{demote_headings, move_anchors, number_title, own_html, slug};
+use crate::{Indicator, ReportContext, Validate, ValidationResult};
+
+/// A group of related validations (e.g., those of one subsystem), reported
+/// together under the title of the group. Each validation in the group gets
+/// a subsection—groups can be nested—which is listed in the table of contents
+/// of the report. The group fails if any of its validations fails.
+///
+/// # Example
+///
+/// ```
+/// use validate::{SeriesValidator, Validate, ValidationGroup};
+///
+/// let mut hvac = ValidationGroup::new("HVAC");
+/// hvac.push(ValidationGroup::new("Chiller"));
+/// hvac.push(SeriesValidator {
+///     expected: vec![1., 2.],
+///     found: vec![1., 2.],
+///     ..Default::default()
+/// });
+/// assert!(hvac.validate().is_ok());
+/// ```
+pub struct ValidationGroup {
+    /// The title of the group, which is the heading of its section
+    pub title: String,
+
+    /// A description of the group, written before its validations
+    pub description: Option<String>,
+
+    /// The validations in the group
+    pub validations: Vec<Box<dyn Validate>>,
+}
+
+impl ValidationGroup {
+    /// Creates an empty group
+    pub fn new<T: Into<String>>(title: T) -> Self {
+        Self {
+            title: title.into(),
+            description: None,
+            validations: Vec::new(),
+        }
+    }
+
+    /// Adds a validation to the group
+    pub fn push<V: Validate + 'static>(&mut self, v: V) {
+        self.validations.push(Box::new(v))
+    }
+
+    /// The titles of the validations, numbered if repeated (e.g., `Zone A (2)`)
+    /// as those of a [`crate::Validator`]
+    fn titles(&self) -> Vec<String> {
+        let mut ret: Vec<String> = Vec::with_capacity(self.validations.len());
+        for (i, v) in self.validations.iter().enumerate() {
+            let title = v.title().unwrap_or_else(|| format!("Validation {}", i + 1));
+            let title = number_title(title, |t| ret.iter().any(|u| slug(u) == slug(t)));
+            ret.push(title);
+        }
+        ret
+    }
+}
+
+impl Validate for ValidationGroup {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        let mut file = String::new();
+        let mut errors = Vec::new();
+        let mut failed = 0;
+        for ((i, v), title) in self.validations.iter().enumerate().zip(self.titles()) {
+            let txt = match v.validate_in(ctx) {
+                ValidationResult::Ok(txt) => txt,
+                ValidationResult::Err(txt, e) => {
+                    // One error per line, so those of nested groups are not
+                    // nested lists
+                    failed += 1;
+                    let lines: Vec<&str> = e.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect();
+                    for line in &lines {
+                        errors.push(format!("{}: {}", title, line.strip_prefix("* ").unwrap_or(line)));
+                    }
+                    if lines.is_empty() {
+                        errors.push(title.clone());
+                    }
+                    txt
+                }
+            };
+            // The subsections are one level below the section of the group,
+            // and their anchors start with that of the group—as do those of
+            // the subsections of nested groups
+            let anchor = format!("{}--{}", slug(&self.title), slug(&title));
+            file.push_str(&format!(
+                "\n{}anchor\" id=\"{}\"></div>\n\n### {}\n\n",
+                own_html(),
+                anchor,
+                ctx.user_text(&title)
+            ));
+            if let Some(description) = v.description() {
+                file.push_str(&format!("{}\n\n", ctx.user_text(&description)));
+            }
+            let own = slug(&v.title().unwrap_or_else(|| format!("Validation {}", i + 1)));
+            file.push_str(&move_anchors(&demote_headings(&txt), &own, &anchor));
+            file.push('\n');
+        }
+        if self.validations.is_empty() {
+            file = format!("\n{}\n", ctx.tr("There are no validations in this group."));
+        }
+        let summary = format!(
+            "\n{}: {} / {}\n",
+            ctx.tr("Validations passed"),
+            self.validations.len() - failed,
+            self.validations.len()
+        );
+        file.insert_str(0, &summary);
+        if errors.is_empty() {
+            ValidationResult::Ok(file)
+        } else {
+            let err_msg: String = errors.iter().map(|e| format!("\n * {}", e)).collect();
+            ValidationResult::Err(file, err_msg)
+        }
+    }
+
+    fn title(&self) -> Option<String> {
+        Some(self.title.clone())
+    }
+
+    fn description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        // Indicators are prefixed with the validation they come from, as the
+        // same ones are usually calculated by every validation in the group
+        self.validations
+            .iter()
+            .zip(self.titles())
+            .flat_map(|(v, title)| {
+                v.indicators()
+                    .into_iter()
+                    .map(move |ind| Indicator::new(format!("{}: {}", title, ind.name), ind.value))
+            })
+            .collect()
+    }
+
+    fn covers(&self) -> Vec<String> {
+        let mut ret: Vec<String> = Vec::new();
+        for c in self.validations.iter().flat_map(|v| v.covers()) {
+            if !ret.contains(&c) {
+                ret.push(c)
+            }
+        }
+        ret
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        let mut ret: Vec<String> = Vec::new();
+        for r in self.validations.iter().flat_map(|v| v.requirements()) {
+            if !ret.contains(&r) {
+                ret.push(r)
+            }
+        }
+        ret
+    }
+
+    fn defines_thresholds(&self, ctx: &ReportContext) -> bool {
+        self.validations.iter().all(|v| v.defines_thresholds(ctx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{AlwaysFail, AlwaysPass};
+
+    #[test]
+    fn test_group() {
+        let mut inner = ValidationGroup::new("Inner");
+        inner.push(AlwaysFail);
+        let mut group = ValidationGroup::new("Outer");
+        group.push(AlwaysPass);
+        group.push(inner);
+        match group.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(txt, e) => {
+                assert!(txt.contains("Validations passed: 1 / 2"));
+                assert!(txt.contains("id=\"outer--validation-1\"></div>\n\n### Validation 1"));
+                // The subsections of the inner group are one level deeper
+                assert!(txt.contains("id=\"outer--inner\"></div>\n\n### Inner"));
+                assert!(txt.contains("id=\"outer--inner--validation-1\"></div>\n\n#### Validation 1"));
+                assert_eq!(e, "\n * Inner: Validation 1: This validation always fails");
+            }
+        }
+    }
+
+    #[test]
+    fn test_group_anchors() {
+        use crate::Validator;
+
+        // The same inner group under two parents, twice in each
+        let group = |title: &str| {
+            let mut inner = ValidationGroup::new("Inner");
+            inner.push(AlwaysPass);
+            let mut group = ValidationGroup::new(title);
+            group.push(inner);
+            let mut inner = ValidationGroup::new("Inner");
+            inner.push(AlwaysPass);
+            group.push(inner);
+            group
+        };
+        match group("A").validate() {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.contains("id=\"a--inner--validation-1\"></div>\n\n#### Validation 1"));
+                assert!(txt.contains("id=\"a--inner-2\"></div>\n\n### Inner (2)"));
+                assert!(txt.contains("id=\"a--inner-2--validation-1\"></div>\n\n#### Validation 1"));
+            }
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        let report = std::env::temp_dir().join("validate_test_group_anchors.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Groups", &report);
+        validator.push(group("A"));
+        validator.push(group("B"));
+        validator.push(group("A"));
+        validator.validate().unwrap();
+        let html = std::fs::read_to_string(&*report).unwrap();
+        let ids: Vec<&str> = html.split("anchor\" id=\"").skip(1).map(|s| s.split('"').next().unwrap()).collect();
+        assert_eq!(ids.len(), 3 * 5);
+        assert!(ids.iter().all(|id| ids.iter().filter(|other| *other == id).count() == 1), "{:?}", ids);
+        assert!(ids.contains(&"a-2--inner-2--validation-1"));
+    }
+}
@@ -0,0 +1,367 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::numberish::Numberish;
+use crate::stats::{chi_square_two_sample, count_as_f64, histogram, wasserstein_distance};
+use crate::{Indicator, ReportContext, Validate, ValidationResult};
+#[cfg(feature = "charts")]
+use crate::svg::describe_series;
+#[cfg(feature = "charts")]
+use poloto::prelude::*;
+
+/// How the difference between two distributions is measured by a
+/// [`HistogramValidator`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DistributionDistance {
+    /// The Earth Mover's (or Wasserstein-1) distance between the samples
+    /// (see [`crate::stats::wasserstein_distance`]), in the units of the
+    /// data. It does not depend on the bins.
+    #[default]
+    EarthMover,
+
+    /// The two-sample chi-square statistic between the histograms (see
+    /// [`crate::stats::chi_square_two_sample`]). It grows with the size of
+    /// the samples, so its threshold depends on them.
+    ChiSquare,
+}
+
+impl DistributionDistance {
+    /// The name of the distance, as written in the report
+    fn name(&self) -> &'static str {
+        match self {
+            DistributionDistance::EarthMover => "Earth Mover's distance",
+            DistributionDistance::ChiSquare => "Chi-square statistic",
+        }
+    }
+}
+
+/// The histograms of the expected and found datasets, with the same bins
+struct Binned {
+    /// The edges of the bins, one more than there are bins
+    edges: Vec<f64>,
+
+    /// The number of expected values in each bin
+    expected: Vec<usize>,
+
+    /// The number of found values in each bin
+    found: Vec<usize>,
+}
+
+/// Compares the distributions of two datasets whose order is meaningless
+/// (e.g., the hourly temperatures of a typical year, or the sizes of the
+/// particles in a sample), binning them into overlaid histograms. The
+/// validation fails if the distributions are further apart than allowed
+/// (see [`DistributionDistance`]).
+///
+/// # Example
+///
+/// ```
+/// use validate::{HistogramValidator, Validate};
+///
+/// let v = HistogramValidator {
+///     expected: vec![1., 2., 2., 3., 3., 3., 4., 4., 5.],
+///     found: vec![5., 4., 3., 4., 3., 2., 3., 2., 1.2],
+///     allowed_distance: Some(0.1),
+///     ..Default::default()
+/// };
+/// assert!(v.validate().is_ok());
+/// ```
+#[derive(Default, Clone)]
+pub struct HistogramValidator<T: Numberish> {
+    /// The expected values
+    pub expected: Vec<T>,
+
+    /// The values found
+    pub found: Vec<T>,
+
+    /// The number of bins of the histograms. If not given, it follows
+    /// Sturges' rule for the largest dataset.
+    pub bins: Option<usize>,
+
+    /// How the difference between the distributions is measured
+    pub distance: DistributionDistance,
+
+    /// The maximum allowed distance between the distributions
+    pub allowed_distance: Option<f64>,
+
+    /// The units of the data
+    pub units: Option<&'static str>,
+
+    /// The label of the data, in the x axis of the chart
+    pub x_label: Option<&'static str>,
+
+    /// The name of the expected data in the legend
+    pub expected_legend: Option<&'static str>,
+
+    /// The name of the found data in the legend
+    pub found_legend: Option<&'static str>,
+
+    /// the title of the chart
+    pub chart_title: Option<&'static str>,
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"zone air temperature"`), listed in the coverage of the report
+    pub covers: Vec<&'static str>,
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"`), mapped to it in the report
+    pub requirements: Vec<&'static str>,
+}
+
+impl<T: Numberish> HistogramValidator<T> {
+    /// The number of bins of the histograms
+    fn bin_count(&self) -> usize {
+        match self.bins {
+            Some(bins) => bins,
+            None => {
+                let n = self.expected.len().max(self.found.len()).max(1);
+                count_as_f64(n).log2().ceil() as usize + 1
+            }
+        }
+    }
+
+    /// The range covered by the bins, which spans both datasets
+    fn range(&self) -> Result<(f64, f64), String> {
+        if self.expected.is_empty() || self.found.is_empty() {
+            return Err("Trying to compare the distribution of an empty dataset".to_string());
+        }
+        let values = self.expected.iter().chain(self.found.iter()).map(|v| (*v).into());
+        let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+        for v in values {
+            let v: f64 = v;
+            if !v.is_finite() {
+                return Err(format!("Trying to compare distributions with a non-finite value ({})", v));
+            }
+            min = min.min(v);
+            max = max.max(v);
+        }
+        if min == max {
+            // A single value, in the middle of a bin
+            return Ok((min - 0.5, max + 0.5));
+        }
+        Ok((min, max))
+    }
+
+    /// The histograms of both datasets
+    fn histograms(&self) -> Result<Binned, String> {
+        let (min, max) = self.range()?;
+        let bins = self.bin_count();
+        let expected = histogram(&self.expected, min, max, bins)?;
+        let found = histogram(&self.found, min, max, bins)?;
+        let width = (max - min) / count_as_f64(bins);
+        let edges = (0..=bins).map(|i| min + width * count_as_f64(i)).collect();
+        Ok(Binned { edges, expected, found })
+    }
+
+    /// The distance between the distributions
+    fn distance_between(&self, binned: &Binned) -> Result<f64, String> {
+        match self.distance {
+            DistributionDistance::EarthMover => wasserstein_distance(&self.expected, &self.found),
+            DistributionDistance::ChiSquare => chi_square_two_sample(&binned.expected, &binned.found),
+        }
+    }
+}
+
+impl<T: Numberish + 'static> Validate for HistogramValidator<T> {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        let binned = match self.histograms() {
+            Ok(h) => h,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+        let distance = match self.distance_between(&binned) {
+            Ok(d) => d,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+        let units = ctx.unit_system();
+        let data_units = self.units.unwrap_or("");
+        // The chi-square statistic has no units
+        let show_distance = |v: f64| match self.distance {
+            DistributionDistance::EarthMover => units.convert_difference(v, data_units),
+            DistributionDistance::ChiSquare => v,
+        };
+        let show = |v: f64| units.convert(v, data_units);
+
+        let file_msg = format!(
+            "\n * {}: {}\n * {}: {}\n * {}: {}, {} {:.4} {} {:.4}\n * {}: {:.4}",
+            ctx.tr("Expected samples"),
+            self.expected.len(),
+            ctx.tr("Found samples"),
+            self.found.len(),
+            ctx.tr("Bins"),
+            binned.expected.len(),
+            ctx.tr("from"),
+            show(binned.edges[0]),
+            ctx.tr("to"),
+            show(binned.edges[binned.edges.len() - 1]),
+            ctx.tr(self.distance.name()),
+            show_distance(distance)
+        );
+
+        let mut nchecks = 0;
+        let mut err_msg = String::new();
+        if let Some(allowed) = self.allowed_distance {
+            nchecks += 1;
+            if distance > allowed {
+                err_msg = format!(
+                    "{}\n * The {} between the distributions is {:.4}, which is greater than the allowed value of {:.4}",
+                    err_msg,
+                    self.distance.name(),
+                    show_distance(distance),
+                    show_distance(allowed)
+                );
+            }
+        }
+
+        #[cfg(feature = "charts")]
+        let chart = {
+            let fractions = |counts: &[usize]| -> Vec<f64> {
+                let total = count_as_f64(counts.iter().sum());
+                counts.iter().map(|c| count_as_f64(*c) / total).collect()
+            };
+            let (edges, expected, found) = (&binned.edges, fractions(&binned.expected), fractions(&binned.found));
+            let expected_legend = ctx.tr(self.expected_legend.unwrap_or("Expected"));
+            let found_legend = ctx.tr(self.found_legend.unwrap_or("Found"));
+
+            // Bars for the expected histogram, and steps for the found one
+            let bars = edges
+                .iter()
+                .zip(expected.iter().chain(std::iter::once(&0.)))
+                .map(|(e, f)| [show(*e), *f])
+                .buffered_plot()
+                .histogram(expected_legend);
+            let steps = found
+                .iter()
+                .enumerate()
+                .flat_map(|(i, f)| [[show(edges[i]), *f], [show(edges[i + 1]), *f]])
+                .buffered_plot()
+                .line(found_legend);
+            let origin = poloto::build::origin();
+
+            let mut x_label: String = self.x_label.unwrap_or("Value").into();
+            if let Some(x_units) = self.units {
+                x_label = format!("{} ({})", x_label, units.units(x_units));
+            }
+            let y_label = ctx.tr("Fraction of the samples");
+            let p = quick_fmt!(self.chart_title.unwrap_or(""), &x_label, y_label, bars, steps, origin);
+            let alt_title = match self.chart_title {
+                Some(title) if !title.is_empty() => title.to_string(),
+                _ => format!("{} vs {}", y_label, x_label),
+            };
+            let description = [
+                describe_series(expected_legend, self.expected.iter().map(|v| show((*v).into()))),
+                describe_series(found_legend, self.found.iter().map(|v| show((*v).into()))),
+            ]
+            .join(". ");
+            ctx.chart_theme()
+                .render(poloto::disp(|w| p.simple_theme(w)).to_string(), &alt_title, &description)
+        };
+        #[cfg(not(feature = "charts"))]
+        let chart = ctx.chart_theme().omitted();
+
+        let show_err = if nchecks == 0 {
+            ctx.tr("No checks performed...")
+        } else if err_msg.is_empty() {
+            ctx.tr("No errors found")
+        } else {
+            &err_msg
+        };
+        let file = format!(
+            "{}\n\n#### {}:\n {}\n#### {}:\n\n{}",
+            file_msg,
+            ctx.tr("Errors"),
+            show_err,
+            ctx.tr("Data"),
+            chart
+        );
+        if err_msg.is_empty() {
+            ValidationResult::Ok(file)
+        } else {
+            ValidationResult::Err(file, err_msg)
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.covers.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.requirements.iter().map(|r| r.to_string()).collect()
+    }
+
+    fn defines_thresholds(&self, _ctx: &ReportContext) -> bool {
+        self.allowed_distance.is_some()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        match self.histograms().and_then(|binned| self.distance_between(&binned)) {
+            Ok(d) => vec![Indicator::new(self.distance.name(), d)],
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        // The same values, shuffled
+        let expected: Vec<f64> = (0..100).map(|i| ((i * 37) % 100) as f64 / 10.).collect();
+        let found: Vec<f64> = (0..100).map(|i| i as f64 / 10.).collect();
+        let mut v = HistogramValidator {
+            expected,
+            found,
+            allowed_distance: Some(0.01),
+            ..Default::default()
+        };
+        assert_eq!(v.bin_count(), 8);
+        match v.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("Earth Mover's distance: 0.0000")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        // Shifted by 1
+        v.found.iter_mut().for_each(|x| *x += 1.);
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains("The Earth Mover's distance between the distributions is 1.0000")),
+        }
+
+        v.distance = DistributionDistance::ChiSquare;
+        v.bins = Some(11);
+        v.allowed_distance = Some(5.);
+        assert!(v.validate().is_err());
+        assert_eq!(v.indicators()[0].name, "Chi-square statistic");
+
+        v.found.clear();
+        assert!(v.validate().is_err());
+        assert!(v.indicators().is_empty());
+    }
+}
@@ -0,0 +1,372 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::escape_html;
+use crate::report::{write_atomically, Section};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of unique report names handed out by this process
+static UNIQUE_REPORTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Builds a report name based on `base` that is unique to this process
+/// and to this call (e.g., `report_1234_1.html` for `report.html`), so
+/// that validators running concurrently—in different test binaries or
+/// in different threads—do not overwrite each other's reports.
+pub(crate) fn unique_report_name(base: &str) -> String {
+    let n = UNIQUE_REPORTS.fetch_add(1, Ordering::Relaxed) + 1;
+    let path = Path::new(base);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "report".to_string());
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    path.with_file_name(format!("{}_{}_{}{}", stem, std::process::id(), n, ext))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Checks whether `name` is the name of a summary written by a report
+/// whose name was made unique from a report with the given `stem`
+fn is_unique_summary_of(name: &str, stem: &str) -> bool {
+    let ids = name
+        .strip_prefix(stem)
+        .and_then(|s| s.strip_prefix('_'))
+        .and_then(|s| s.strip_suffix("_summary.csv"));
+    match ids.and_then(|s| s.split_once('_')) {
+        Some((pid, n)) => {
+            !pid.is_empty()
+                && !n.is_empty()
+                && pid.chars().all(|c| c.is_ascii_digit())
+                && n.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// Gets the path of the summary file written next to a report
+pub(crate) fn summary_file_for(report: &Path) -> PathBuf {
+    let stem = report
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "report".to_string());
+    report.with_file_name(format!("{}_summary.csv", stem))
+}
+
+/// Writes the summary of a report—i.e., the result of each of
+/// its validations—into a CSV file next to it, so it can be
+/// collected by a [`ReportIndex`].
+#[cfg(feature = "csv")]
+pub(crate) fn write_summary(report: &Path, title: &str, sections: &[Section]) -> Result<(), String> {
+    let path = summary_file_for(report);
+    let report_name = report
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let write = || -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut wtr = csv::Writer::from_writer(Vec::new());
+        wtr.write_record(["report_title", "report_file", "validation", "result"])?;
+        for s in sections {
+            let result = if s.failed() { "failed" } else { "passed" };
+            wtr.write_record([title, &report_name, &s.title, result])?;
+        }
+        Ok(wtr.into_inner()?)
+    };
+    let contents =
+        write().map_err(|e| format!("Cannot write summary into '{}': {}", path.display(), e))?;
+    write_atomically(&path, contents)
+}
+
+/// Summaries are CSV files, so they cannot be written without the `csv` feature
+#[cfg(not(feature = "csv"))]
+pub(crate) fn write_summary(report: &Path, _title: &str, _sections: &[Section]) -> Result<(), String> {
+    Err(format!(
+        "Cannot write summary into '{}' without the `csv` feature",
+        summary_file_for(report).display()
+    ))
+}
+
+/// The summary of a single report collected by a [`ReportIndex`]
+#[derive(Debug, Clone)]
+struct ReportSummary {
+    /// The title of the report
+    title: String,
+
+    /// The path to the report
+    report: PathBuf,
+
+    /// The number of validations that passed
+    passed: usize,
+
+    /// The number of validations that failed
+    failed: usize,
+}
+
+/// Collects the summaries of several independent [`crate::Validator`] runs—e.g.,
+/// one per physical domain, possibly from different test binaries or crates—and
+/// writes an HTML index that links to each of their reports, with aggregate
+/// statistics.
+///
+/// Each `Validator` needs to be told to write its summary through
+/// [`crate::Validator::set_write_summary`].
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::{ReportIndex, SeriesValidator, Validator};
+///
+/// let mut validator = Validator::new("Thermal", "report_thermal.html");
+/// validator.set_write_summary(true);
+/// validator.push(Box::new(SeriesValidator {
+///     expected: vec![1., 2.],
+///     found: vec![1., 2.],
+///     ..SeriesValidator::default()
+/// }));
+/// validator.validate().unwrap();
+///
+/// let mut index = ReportIndex::new("All validations", "index.html");
+/// index.add_report("report_thermal.html").unwrap();
+/// index.write().unwrap();
+/// ```
+pub struct ReportIndex {
+    /// The title of the index
+    title: String,
+
+    /// The file into which the index is written
+    target_file: PathBuf,
+
+    /// The collected summaries
+    reports: Vec<ReportSummary>,
+}
+
+impl ReportIndex {
+    /// Creates a new, empty, `ReportIndex` that will be written into `target_file`
+    pub fn new<T: Into<String>, P: AsRef<Path>>(title: T, target_file: P) -> Self {
+        Self {
+            title: title.into(),
+            target_file: target_file.as_ref().to_path_buf(),
+            reports: Vec::new(),
+        }
+    }
+
+    /// Collects the summary of the report written into `report`.
+    #[cfg(feature = "csv")]
+    pub fn add_report<P: AsRef<Path>>(&mut self, report: P) -> Result<(), String> {
+        let report = report.as_ref();
+        let summary = summary_file_for(report);
+        let mut rdr = csv::Reader::from_path(&summary)
+            .map_err(|e| format!("Cannot read summary '{}': {}", summary.display(), e))?;
+
+        let mut title = String::new();
+        let mut passed = 0;
+        let mut failed = 0;
+        for record in rdr.records() {
+            let record =
+                record.map_err(|e| format!("Bad summary file '{}': {}", summary.display(), e))?;
+            title = record.get(0).unwrap_or_default().to_string();
+            if record.get(3) == Some("failed") {
+                failed += 1;
+            } else {
+                passed += 1;
+            }
+        }
+        if title.is_empty() {
+            title = report.to_string_lossy().to_string();
+        }
+
+        self.reports.push(ReportSummary {
+            title,
+            report: report.to_path_buf(),
+            passed,
+            failed,
+        });
+        Ok(())
+    }
+
+    /// Collects the summary of the report written into `report`, which
+    /// fails without the `csv` feature, as summaries are CSV files.
+    #[cfg(not(feature = "csv"))]
+    pub fn add_report<P: AsRef<Path>>(&mut self, report: P) -> Result<(), String> {
+        Err(format!(
+            "Cannot read summary '{}' without the `csv` feature",
+            summary_file_for(report.as_ref()).display()
+        ))
+    }
+
+    /// Collects the summaries of all the reports whose names were made unique
+    /// from `base` (see [`crate::Validator::set_unique_report_name`]), in
+    /// alphabetical order. Returns how many reports were found.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{ReportIndex, Validator};
+    ///
+    /// let mut validator = Validator::new("Thermal", "report_unique.html");
+    /// validator.set_unique_report_name(true);
+    /// validator.validate().unwrap();
+    ///
+    /// let mut index = ReportIndex::new("All validations", "index.html");
+    /// assert!(index.add_unique_reports("report_unique.html").unwrap() > 0);
+    /// index.write().unwrap();
+    /// ```
+    pub fn add_unique_reports<P: AsRef<Path>>(&mut self, base: P) -> Result<usize, String> {
+        let base = base.as_ref();
+        let stem = base
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "report".to_string());
+        let ext = base
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        let dir = match base.parent() {
+            Some(d) if !d.as_os_str().is_empty() => d,
+            _ => Path::new("."),
+        };
+
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| format!("Cannot read directory '{}': {}", dir.display(), e))?;
+        let mut reports: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| is_unique_summary_of(name, &stem))
+            .map(|name| {
+                let report = name.trim_end_matches("_summary.csv");
+                base.with_file_name(format!("{}{}", report, ext))
+            })
+            .collect();
+        reports.sort();
+        for report in &reports {
+            self.add_report(report)?;
+        }
+        Ok(reports.len())
+    }
+
+    /// Gets the link—relative to the index—to a report
+    fn link(&self, report: &Path) -> String {
+        let index_dir = self.target_file.parent().unwrap_or_else(|| Path::new(""));
+        match report.strip_prefix(index_dir) {
+            Ok(rel) => rel.to_string_lossy().to_string(),
+            Err(_) => std::fs::canonicalize(report)
+                .unwrap_or_else(|_| report.to_path_buf())
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+
+    /// Writes the index
+    pub fn write(&self) -> Result<(), String> {
+        let passed: usize = self.reports.iter().map(|r| r.passed).sum();
+        let failed: usize = self.reports.iter().map(|r| r.failed).sum();
+        let total = passed + failed;
+        let rate = |passed: usize, total: usize| {
+            if total == 0 {
+                100.
+            } else {
+                100. * passed as f64 / total as f64
+            }
+        };
+
+        let mut html = format!("<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"UTF-8\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\"><title>{}</title></head><body><main>", escape_html(&self.title));
+        html.push_str(&format!("<h1>{}</h1>", escape_html(&self.title)));
+        html.push_str(&format!(
+            "<ul><li>Reports: {}</li><li>Validations: {}</li><li>Passed: {}</li><li>Failed: {}</li><li>Pass rate: {:.1}%</li></ul>",
+            self.reports.len(),
+            total,
+            passed,
+            failed,
+            rate(passed, total)
+        ));
+        html.push_str("<table><thead><tr><th>Report</th><th>Validations</th><th>Passed</th><th>Failed</th><th>Pass rate</th></tr></thead><tbody>");
+        for r in &self.reports {
+            html.push_str(&format!(
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}%</td></tr>",
+                escape_html(&self.link(&r.report)),
+                escape_html(&r.title),
+                r.passed + r.failed,
+                r.passed,
+                r.failed,
+                rate(r.passed, r.passed + r.failed)
+            ));
+        }
+        html.push_str("</tbody></table></main></body></html>");
+
+        write_atomically(&self.target_file, html)
+    }
+}
+
+#[cfg(all(test, feature = "csv"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index() {
+        let dir = std::env::temp_dir().join("validate_test_index");
+        std::fs::create_dir_all(dir.join("thermal")).unwrap();
+        let report = dir.join("thermal/report.html");
+        let sections = vec![
+            Section::new(0, "## A".into(), None),
+            Section::new(1, "## B".into(), Some("oops".into())),
+        ];
+        write_summary(&report, "Thermal", &sections).unwrap();
+
+        let mut index = ReportIndex::new("Everything", dir.join("index.html"));
+        index.add_report(&report).unwrap();
+        assert!(index.add_report(dir.join("nope.html")).is_err());
+        index.write().unwrap();
+
+        let html = std::fs::read_to_string(dir.join("index.html")).unwrap();
+        assert!(html.contains("<a href=\"thermal/report.html\">Thermal</a>"));
+        assert!(html.contains("<li>Failed: 1</li>"));
+        assert!(html.contains("<li>Pass rate: 50.0%</li>"));
+    }
+
+    #[test]
+    fn test_unique_reports() {
+        let dir = std::env::temp_dir().join("validate_test_unique_reports");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let base = dir.join("report.html");
+        let base = base.to_string_lossy();
+
+        let a = unique_report_name(&base);
+        let b = unique_report_name(&base);
+        assert_ne!(a, b);
+        assert!(a.ends_with(".html"));
+        for report in [&a, &b] {
+            let sections = vec![Section::new(0, "## A".into(), None)];
+            write_summary(Path::new(report), "Thermal", &sections).unwrap();
+        }
+        // Not made unique from `report.html`
+        write_summary(&dir.join("report_final.html"), "Other", &[]).unwrap();
+
+        let mut index = ReportIndex::new("Everything", dir.join("index.html"));
+        assert_eq!(index.add_unique_reports(&*base).unwrap(), 2);
+
+        assert!(is_unique_summary_of("report_12_3_summary.csv", "report"));
+        assert!(!is_unique_summary_of("report_final_summary.csv", "report"));
+        assert!(!is_unique_summary_of("report_12__summary.csv", "report"));
+        assert!(!is_unique_summary_of("other_12_3_summary.csv", "report"));
+    }
+}
@@ -0,0 +1,248 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::numberish::Numberish;
+use std::fmt;
+
+/// How values are estimated between the samples of a series
+/// (see [`resample`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Along the straight line between the neighbouring samples
+    #[default]
+    Linear,
+
+    /// The value of the closest sample (the earlier one, on ties)
+    Nearest,
+
+    /// The value of the latest sample at or before each point, as for
+    /// readings that hold until the next one (e.g., thermostat setpoints)
+    Previous,
+
+    /// Along the natural cubic spline through the samples (i.e., the smooth
+    /// curve with no curvature at either end), for smooth quantities
+    /// sampled coarsely (e.g., hourly outdoor temperatures). Unlike the
+    /// others, it can overshoot the neighbouring samples.
+    ///
+    /// ```
+    /// use validate::interp::{interpolate, Interpolation};
+    ///
+    /// let v = interpolate(&[0., 1., 2.], &[0., 1., 0.], 0.5, Interpolation::CubicSpline).unwrap();
+    /// assert_eq!(v, 0.6875);
+    /// ```
+    CubicSpline,
+}
+
+impl fmt::Display for Interpolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Interpolation::Linear => write!(f, "linear interpolation"),
+            Interpolation::Nearest => write!(f, "nearest sample"),
+            Interpolation::Previous => write!(f, "previous sample"),
+            Interpolation::CubicSpline => write!(f, "natural cubic spline"),
+        }
+    }
+}
+
+/// Checks that `y` is sampled at `x`, which must be strictly increasing
+fn check_samples<X: Numberish, Y>(x: &[X], y: &[Y]) -> Result<(), String> {
+    if x.len() != y.len() {
+        return Err(format!(
+            "Interpolating over x and y of different length. x.len() = {}, y.len = {}",
+            x.len(),
+            y.len()
+        ));
+    }
+    if x.is_empty() {
+        return Err("Trying to interpolate empty datasets".to_string());
+    }
+    if let Some(i) = x
+        .windows(2)
+        .position(|w| w[1].into().partial_cmp(&w[0].into()) != Some(std::cmp::Ordering::Greater))
+    {
+        return Err(format!(
+            "Interpolating requires strictly increasing x values, but x[{}] = {} and x[{}] = {}",
+            i,
+            x[i],
+            i + 1,
+            x[i + 1]
+        ));
+    }
+    Ok(())
+}
+
+/// The second derivatives of the natural cubic spline through the samples
+/// `y` at `x`, which are zero at both ends
+fn spline_curvatures(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let n = x.len();
+    let mut m = vec![0.; n];
+    if n < 3 {
+        return m;
+    }
+    // Solves the tridiagonal system of the inner samples (Thomas algorithm)
+    let mut diag = vec![0.; n];
+    let mut rhs = vec![0.; n];
+    for i in 1..n - 1 {
+        let (h0, h1) = (x[i] - x[i - 1], x[i + 1] - x[i]);
+        diag[i] = 2. * (h0 + h1);
+        rhs[i] = 6. * ((y[i + 1] - y[i]) / h1 - (y[i] - y[i - 1]) / h0);
+        if i > 1 {
+            let w = h0 / diag[i - 1];
+            diag[i] -= w * h0;
+            rhs[i] -= w * rhs[i - 1];
+        }
+    }
+    for i in (1..n - 1).rev() {
+        m[i] = (rhs[i] - (x[i + 1] - x[i]) * m[i + 1]) / diag[i];
+    }
+    m
+}
+
+/// Estimates the values of the series `y`—sampled at `x`—at the points
+/// `at`, which do not need to be sorted. Points outside the range of `x`
+/// are `NaN`, as extrapolating would make up data; they can then be left
+/// out of the comparisons (see [`crate::stats::NanPolicy`]).
+///
+/// This is useful for bringing the expected data (e.g., measured every 10
+/// minutes) onto the time grid of the simulation before comparing them.
+///
+/// # Example
+///
+/// ```
+/// use validate::interp::{resample, Interpolation};
+///
+/// let minutes = vec![0., 10., 20.];
+/// let temperature = vec![20., 21., 23.];
+/// let every_five = vec![0., 5., 10., 15., 20., 25.];
+///
+/// let linear = resample(&minutes, &temperature, &every_five, Interpolation::Linear).unwrap();
+/// assert_eq!(linear[..5], [20., 20.5, 21., 22., 23.]);
+/// assert!(linear[5].is_nan());
+///
+/// let held = resample(&minutes, &temperature, &every_five, Interpolation::Previous).unwrap();
+/// assert_eq!(held[..5], [20., 20., 21., 21., 23.]);
+/// ```
+///
+/// # Errors
+///
+/// * if `x` and `y` have different lengths or are empty
+/// * if `x` is not strictly increasing
+pub fn resample<X: Numberish, Y: Numberish>(
+    x: &[X],
+    y: &[Y],
+    at: &[f64],
+    method: Interpolation,
+) -> Result<Vec<f64>, String> {
+    check_samples(x, y)?;
+    let x: Vec<f64> = x.iter().map(|v| (*v).into()).collect();
+    let y: Vec<f64> = y.iter().map(|v| (*v).into()).collect();
+    let (first, last) = (x[0], x[x.len() - 1]);
+    let curvatures = match method {
+        Interpolation::CubicSpline => spline_curvatures(&x, &y),
+        _ => Vec::new(),
+    };
+    Ok(at
+        .iter()
+        .map(|t| {
+            let t = *t;
+            if t.is_nan() || t < first || t > last {
+                return f64::NAN;
+            }
+            // The first sample after t (or the last one, at the end)
+            let i = x.partition_point(|v| *v <= t).min(x.len() - 1);
+            if x[i] == t || i == 0 {
+                return y[i];
+            }
+            let (x0, x1, y0, y1) = (x[i - 1], x[i], y[i - 1], y[i]);
+            match method {
+                Interpolation::Linear => y0 + (y1 - y0) * (t - x0) / (x1 - x0),
+                Interpolation::Nearest if t - x0 <= x1 - t => y0,
+                Interpolation::Nearest => y1,
+                Interpolation::Previous => y0,
+                Interpolation::CubicSpline => {
+                    let h = x1 - x0;
+                    let (a, b) = ((x1 - t) / h, (t - x0) / h);
+                    let (m0, m1) = (curvatures[i - 1], curvatures[i]);
+                    a * y0 + b * y1 + ((a * a * a - a) * m0 + (b * b * b - b) * m1) * h * h / 6.
+                }
+            }
+        })
+        .collect())
+}
+
+/// Estimates the value of the series `y`—sampled at `x`—at a single point
+/// (see [`resample`]).
+///
+/// # Example
+///
+/// ```
+/// use validate::interp::{interpolate, Interpolation};
+///
+/// let v = interpolate(&[0., 2.], &[1., 5.], 0.5, Interpolation::Linear).unwrap();
+/// assert_eq!(v, 2.);
+/// ```
+///
+/// # Errors
+///
+/// * if `x` and `y` have different lengths or are empty
+/// * if `x` is not strictly increasing
+pub fn interpolate<X: Numberish, Y: Numberish>(x: &[X], y: &[Y], at: f64, method: Interpolation) -> Result<f64, String> {
+    Ok(resample(x, y, &[at], method)?[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample_edges() {
+        let x = vec![0., 1., 2.];
+        let y = vec![0., 10., 20.];
+        let at = vec![2., 0.5, 1.5, -0.1, 1.];
+        let nearest = resample(&x, &y, &at, Interpolation::Nearest).unwrap();
+        assert_eq!(nearest[..3], [20., 0., 10.]);
+        assert!(nearest[3].is_nan());
+        assert_eq!(nearest[4], 10.);
+        assert_eq!(resample(&[1.], &[3.], &[1., 2.], Interpolation::Linear).unwrap()[0], 3.);
+        assert!(resample(&[0., 0.], &[1., 2.], &[0.], Interpolation::Linear).is_err());
+        assert!(resample(&[0., 1.], &[1.], &[0.], Interpolation::Linear).is_err());
+    }
+
+    #[test]
+    fn test_cubic_spline() {
+        // Goes through the samples, unevenly spaced
+        let x = vec![0., 1., 3., 4., 7.];
+        let y = vec![1., 3., 2., 5., 4.];
+        let spline = resample(&x, &y, &x, Interpolation::CubicSpline).unwrap();
+        assert_eq!(spline, y);
+
+        // Is smooth: the slopes at both sides of an inner sample match
+        let dx = 1e-6;
+        let around = resample(&x, &y, &[3. - dx, 3., 3. + dx], Interpolation::CubicSpline).unwrap();
+        let (left, right) = ((around[1] - around[0]) / dx, (around[2] - around[1]) / dx);
+        assert!((left - right).abs() < 1e-4, "{} vs {}", left, right);
+
+        // Keeps straight lines straight, and two samples are joined linearly
+        let line = resample(&x, &[0., 2., 6., 8., 14.], &[0.5, 2., 5.5], Interpolation::CubicSpline).unwrap();
+        assert!(line.iter().zip([1., 4., 11.]).all(|(a, b)| (a - b).abs() < 1e-12));
+        assert_eq!(resample(&[0., 2.], &[1., 5.], &[0.5], Interpolation::CubicSpline).unwrap(), [2.]);
+        assert!(resample(&x, &y, &[7.5], Interpolation::CubicSpline).unwrap()[0].is_nan());
+    }
+}
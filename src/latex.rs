@@ -0,0 +1,123 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::report::Section;
+use std::path::Path;
+
+/// Escapes the characters that have a special meaning in LaTeX
+fn escape(txt: &str) -> String {
+    let mut ret = String::with_capacity(txt.len());
+    for c in txt.chars() {
+        match c {
+            '\\' => ret.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                ret.push('\\');
+                ret.push(c);
+            }
+            '~' => ret.push_str("\\textasciitilde{}"),
+            '^' => ret.push_str("\\textasciicircum{}"),
+            _ => ret.push(c),
+        }
+    }
+    ret
+}
+
+/// Finds the first SVG chart in a text
+fn find_svg(txt: &str) -> Option<&str> {
+    let start = txt.find("<svg")?;
+    let end = txt[start..].find("</svg>")? + start + "</svg>".len();
+    Some(&txt[start..end])
+}
+
+/// Writes a LaTeX fragment for a section—a `booktabs` table with its
+/// indicators and, if the section has a chart, a figure that includes
+/// it through the `svg` package.
+fn fragment(section: &Section, figure: Option<&str>) -> String {
+    let title = escape(&section.title);
+    let result = if section.failed() { "Failed" } else { "Passed" };
+
+    let mut ret = format!("% {}\n", title);
+    ret.push_str("\\begin{table}[htbp]\n  \\centering\n");
+    ret.push_str(&format!("  \\caption{{{}}}\n", title));
+    ret.push_str("  \\begin{tabular}{lr}\n    \\toprule\n");
+    ret.push_str("    Indicator & Value \\\\\n    \\midrule\n");
+    for ind in &section.indicators {
+        ret.push_str(&format!("    {} & {:.4} \\\\\n", escape(&ind.name), ind.value));
+    }
+    ret.push_str(&format!("    Result & {} \\\\\n", result));
+    ret.push_str("    \\bottomrule\n  \\end{tabular}\n\\end{table}\n");
+
+    if let Some(figure) = figure {
+        ret.push_str("\\begin{figure}[htbp]\n  \\centering\n");
+        ret.push_str(&format!("  \\includesvg[width=\\linewidth]{{{}}}\n", figure));
+        ret.push_str(&format!("  \\caption{{{}}}\n", title));
+        ret.push_str("\\end{figure}\n");
+    }
+    ret
+}
+
+/// Writes each section as a LaTeX fragment (`validation_1.tex`,
+/// `validation_2.tex`...) into `dir`, together with the SVG charts
+/// they include.
+pub(crate) fn write_fragments(dir: &Path, sections: &[Section]) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Cannot create LaTeX directory '{}': {}", dir.display(), e))?;
+
+    for (i, s) in sections.iter().enumerate() {
+        let name = format!("validation_{}", i + 1);
+        let figure = match find_svg(&s.body) {
+            Some(svg) => {
+                let path = dir.join(format!("{}.svg", name));
+                std::fs::write(&path, svg)
+                    .map_err(|e| format!("Cannot write '{}': {}", path.display(), e))?;
+                Some(name.as_str())
+            }
+            None => None,
+        };
+        let path = dir.join(format!("{}.tex", name));
+        std::fs::write(&path, fragment(s, figure))
+            .map_err(|e| format!("Cannot write '{}': {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Indicator;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("R2 & 50% of x_1"), "R2 \\& 50\\% of x\\_1");
+    }
+
+    #[test]
+    fn test_fragment() {
+        let mut s = Section::new(0, "## Zone_1\n\n<svg a>chart</svg>".into(), None);
+        s.indicators = vec![Indicator::new("RMSE", 0.5)];
+        assert_eq!(find_svg(&s.body), Some("<svg a>chart</svg>"));
+
+        let tex = fragment(&s, Some("validation_1"));
+        assert!(tex.contains("\\caption{Zone\\_1}"));
+        assert!(tex.contains("    RMSE & 0.5000 \\\\\n"));
+        assert!(tex.contains("    Result & Passed \\\\\n"));
+        assert!(tex.contains("\\includesvg[width=\\linewidth]{validation_1}"));
+    }
+}
@@ -19,6 +19,7 @@ SOFTWARE.
 */
 
 #![deny(missing_docs)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 //! This crate was developed with the intent of helping you to validate scientific
 //! tools; for exmple, for comparing the results of the temperature calculated
@@ -32,6 +33,7 @@ SOFTWARE.
 //! # Example
 //!
 //! ```
+//! # std::env::set_current_dir(std::env::temp_dir()).unwrap();
 //! use validate::{valid, Validator, Validate, ValidationResult};
 //!
 //!
@@ -59,7 +61,7 @@ SOFTWARE.
 //!             err = format!("{}\n{} and {} aren't equal", err, self.expected, self.found);
 //!             ret = format!("{}\n\n# {}\n\n * Failed... {} and {} aren't equal",ret, self.title, self.expected, self.found );
 //!         };
-//!         if err.len()>0{
+//!         if !err.is_empty(){
 //!             ValidationResult::Err(err, ret)
 //!         }else{
 //!             ValidationResult::Ok(ret)
@@ -80,36 +82,149 @@ SOFTWARE.
 //!     Box::new(v) // We need a Box.
 //! }
 //!
-//! // Write a test
-//! #[test]
-//! fn test_custon_validator(){
-//!     let mut validator = Validator::new("Test Validation", "report.html");
-//!     validator.push(check_if_equal());
-//!     validator.validate().unwrap()
-//! }
+//! // Run the validations and write the report
+//! let mut validator = Validator::new("Test Validation", "report.html");
+//! validator.push(check_if_equal());
+//! validator.validate().unwrap()
 //!
 //! ```
 
+extern crate alloc;
+
+#[cfg(feature = "csv")]
 use numberish::Numberish;
-use pulldown_cmark::{html, Options, Parser};
+#[cfg(feature = "std")]
+use report::{Section, TitleFilter};
+#[cfg(feature = "std")]
 use std::fs;
-use std::{fs::File, io::Write};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "csv")]
+use std::fs::File;
 
+#[cfg(feature = "macros")]
 pub use derive::valid;
 
 /// A wrapper that contains an object that implements [`Validate`]
+#[cfg(feature = "std")]
 mod validator_wrapper;
+#[cfg(feature = "std")]
 pub use validator_wrapper::ValidatorWrapper;
 
 /// A trait defining some numerical-ish trait.
+#[cfg(feature = "core")]
 mod numberish;
 
+/// Utilities for assembling the sections of a report
+#[cfg(feature = "std")]
+mod report;
+#[cfg(feature = "std")]
+pub use report::{
+    Compression, DuplicateTitles, Indicator, MarkdownOptions, MatrixMetric, ReportFormat, ReportOrder, RunInput,
+    SourceLocation,
+};
+
+/// Files attached to validations and copied into the report's assets
+#[cfg(feature = "std")]
+mod artifact;
+#[cfg(feature = "std")]
+pub use artifact::{Artifact, ChartData};
+
+/// Conversions between systems of units for displaying values
+#[cfg(feature = "std")]
+mod units;
+#[cfg(feature = "std")]
+pub use units::UnitSystem;
+
+/// The settings handed to the validations when writing a report
+#[cfg(feature = "std")]
+mod context;
+#[cfg(feature = "std")]
+pub use context::{Locale, ReportContext};
+
+/// Re-running the validations on a schedule
+#[cfg(feature = "std")]
+mod soak;
+#[cfg(feature = "std")]
+pub use soak::{Hysteresis, SoakSchedule};
+
+/// Configures a [`Validator`] step by step
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+pub use builder::ValidatorBuilder;
+
+/// Default thresholds shared by the validations in a report
+#[cfg(feature = "std")]
+mod thresholds;
+#[cfg(feature = "std")]
+pub use thresholds::Thresholds;
+#[cfg(feature = "toml")]
+pub use thresholds::ThresholdProfiles;
+
+/// An index collecting the summaries of several reports
+#[cfg(feature = "std")]
+mod index;
+#[cfg(feature = "std")]
+pub use index::ReportIndex;
+
+/// Printing reports into PDF
+#[cfg(feature = "pdf")]
+mod pdf;
+
+/// Emitting the results of the validations to the console or logs
+#[cfg(feature = "std")]
+mod console;
+#[cfg(feature = "std")]
+pub use console::Verbosity;
+
+/// Exporting validations as LaTeX fragments
+#[cfg(feature = "std")]
+mod latex;
+
+/// Escaping of the text provided by the user
+#[cfg(feature = "std")]
+mod sanitize;
+#[cfg(feature = "std")]
+pub use sanitize::{escape_html, escape_markdown, TextPolicy};
+
+/// Acceptance criteria written as expressions on the indicators
+#[cfg(feature = "std")]
+mod criterion;
+#[cfg(feature = "std")]
+pub use criterion::{Criterion, CriterionValidator};
+
+/// Reporting failed validations to CI services
+#[cfg(feature = "std")]
+mod ci;
+
+/// Splitting a suite of validations across several runners
+#[cfg(feature = "std")]
+mod shard;
+#[cfg(feature = "std")]
+pub use shard::Shard;
+
+/// Selecting validations by their tags
+#[cfg(feature = "std")]
+mod filter;
+#[cfg(feature = "std")]
+pub use filter::TagFilter;
+
+/// Styling and optimization of the SVG charts embedded in the reports
+#[cfg(feature = "std")]
+mod svg;
+#[cfg(feature = "std")]
+pub use svg::ChartTheme;
+
 /// A Validator that plots two time series and calculates—if required—the
 /// Root Mean Squared Error and Mean Bias Error between them.
 ///
 /// # Example
 ///
 /// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
 /// use validate::{Validator, SeriesValidator};
 ///
 /// let expected = vec![1., 2., 3.];
@@ -118,9 +233,9 @@ mod numberish;
 /// let mut validator = Validator::new("Validate Time series", "report.html");    
 /// // Note that we are not defining a maximum allowed error
 /// let v = validate::SeriesValidator {
-///     x_label: Some("time step"),
-///     y_label: Some("Zone Temperature"),
-///     y_units: Some("C"),
+///     x_label: Some("time step".into()),
+///     y_label: Some("Zone Temperature".into()),
+///     y_units: Some("C".into()),
 ///     expected,
 ///     found,
 ///     ..SeriesValidator::default()
@@ -132,8 +247,10 @@ mod numberish;
 /// // an error and the unwrap woul
 /// validator.validate().unwrap();
 /// ```
+#[cfg(feature = "std")]
 mod time_series;
-pub use time_series::SeriesValidator;
+#[cfg(feature = "std")]
+pub use time_series::{SeriesAlignment, SeriesMetrics, SeriesValidator, Uncertainty};
 
 /// A Validator that creates a scatter plot from two datasets, indicating
 /// the R-value and the linear equation fitting
@@ -141,6 +258,7 @@ pub use time_series::SeriesValidator;
 /// # Example
 ///
 /// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
 /// use validate::{Validator, ScatterValidator};
 ///
 /// let expected = vec![1., 2., 3.];
@@ -149,7 +267,7 @@ pub use time_series::SeriesValidator;
 /// let mut validator = Validator::new("Validate Scatter", "report.html");    
 /// // Note that we are not defining a maximum allowed error
 /// let v = validate::ScatterValidator {
-///     units: Some("C"),
+///     units: Some("C".into()),
 ///     expected,
 ///     found,
 ///     ..ScatterValidator::default()
@@ -161,21 +279,117 @@ pub use time_series::SeriesValidator;
 /// // an error and the unwrap woul
 /// validator.validate().unwrap();
 /// ```
+#[cfg(feature = "std")]
 mod scatter;
-pub use scatter::ScatterValidator;
+#[cfg(feature = "std")]
+pub use scatter::{ScatterMetrics, ScatterValidator};
+
+/// A Validator that checks that measured data is physically plausible
+/// before it is used as the reference of other validations
+#[cfg(feature = "std")]
+mod plausibility;
+#[cfg(feature = "std")]
+pub use plausibility::{PlausibilityValidator, Quantity};
+
+/// A Validator that checks that a metric is stable across repeated
+/// experiments, to detect drifting test rigs
+#[cfg(feature = "std")]
+mod drift;
+#[cfg(feature = "std")]
+pub use drift::DriftValidator;
+
+/// A Validator that compares the distributions of two datasets through
+/// their histograms
+#[cfg(feature = "std")]
+mod histogram;
+#[cfg(feature = "std")]
+pub use histogram::{DistributionDistance, HistogramValidator};
+
+/// A Validator that compares the distributions of two datasets through
+/// their quantiles, on a Q-Q plot
+#[cfg(feature = "std")]
+mod qq;
+#[cfg(feature = "std")]
+pub use qq::QQValidator;
+
+/// A Validator that compares a model against measurements through
+/// their differences, on a Bland-Altman plot
+#[cfg(feature = "std")]
+mod bland_altman;
+#[cfg(feature = "std")]
+pub use bland_altman::BlandAltmanValidator;
+
+/// A Validator for georeferenced data, with the errors drawn on a map
+#[cfg(feature = "std")]
+mod spatial;
+#[cfg(feature = "std")]
+pub use spatial::{Site, SpatialValidator};
+
+/// A Validator that compares the states of a zone on a psychrometric chart
+#[cfg(feature = "std")]
+mod psychrometric;
+#[cfg(feature = "std")]
+pub use psychrometric::{ComfortRegion, PsychrometricValidator};
+
+/// A Validator that compares the breakdown of energy into flows (e.g., end uses)
+#[cfg(feature = "std")]
+mod energy_flow;
+#[cfg(feature = "std")]
+pub use energy_flow::{EnergyFlow, EnergyFlowValidator};
+
+/// A Validator that compares a series against an analytic solution
+#[cfg(feature = "std")]
+mod analytic;
+#[cfg(feature = "std")]
+pub use analytic::AnalyticValidator;
+
+/// A group of validations, reported as subsections of a single section
+#[cfg(feature = "std")]
+mod group;
+#[cfg(feature = "std")]
+pub use group::ValidationGroup;
+
+/// A Validator that rolls the results of zones or components up into
+/// those of the building or system they belong to
+#[cfg(feature = "std")]
+mod rollup;
+#[cfg(feature = "std")]
+pub use rollup::{RollupNode, RollupValidator};
 
 /// Asserts whether two numbers are close enough
 /// by comparing the first argument with the second, and
 /// the threshold being the third.
 ///
+/// Absolute and relative tolerances can be combined, in which case
+/// the numbers are close if either of them is met (see
+/// [`stats::within_tolerance`]).
+///
 /// # Examples
 /// ```
 /// use validate::assert_close;
 /// assert_close!(1., 1.01, 0.1);
 /// assert_close!(1., 1.0000001); // This assumes a threshold of 1e-6
+/// assert_close!(0., 1e-9, abs = 1e-6, rel = 0.01);
+/// assert_close!(1000., 1005., abs = 1e-6, rel = 0.01);
 /// ```
 #[macro_export]
 macro_rules! assert_close {
+    ($left:expr, $right:expr, abs = $abs_tol:expr, rel = $rel_tol:expr ) => {
+        match ($left, $right, $abs_tol, $rel_tol) {
+            (left_val, right_val, abs_tol, rel_tol) => {
+                if !$crate::stats::within_tolerance(left_val as f64, right_val as f64, abs_tol, rel_tol) {
+                    panic!(
+                        "{} and {} are not close enough (allowed difference was {} or {} relative... found {})",
+                        left_val,
+                        right_val,
+                        abs_tol,
+                        rel_tol,
+                        (left_val as f64 - right_val as f64).abs()
+                    );
+                }
+            }
+        }
+    };
     ($left:expr, $right:expr, $allowed_diff: expr ) => {
         match ($left, $right, $allowed_diff) {
             (left_val, right_val, allowed_diff) => {
@@ -207,14 +421,35 @@ macro_rules! assert_close {
 
 /// Asserts whether two numbers are close enough
 ///
+/// Absolute and relative tolerances can be combined, in which case the
+/// numbers need to be farther apart than both of them (see
+/// [`stats::within_tolerance`]).
+///
 /// # Examples
 /// ```
 /// use validate::assert_not_close;
 /// assert_not_close!(1., 10., 0.2);
 /// assert_not_close!(1., 10.); // This assumes a threshold of 1e-6
+/// assert_not_close!(1000., 1020., abs = 1e-6, rel = 0.01);
 /// ```
 #[macro_export]
 macro_rules! assert_not_close {
+    ($left:expr, $right:expr, abs = $abs_tol:expr, rel = $rel_tol:expr ) => {
+        match ($left, $right, $abs_tol, $rel_tol) {
+            (left_val, right_val, abs_tol, rel_tol) => {
+                if $crate::stats::within_tolerance(left_val as f64, right_val as f64, abs_tol, rel_tol) {
+                    panic!(
+                        "{} and {} are too close (minimum difference was {} or {} relative... found {})",
+                        left_val,
+                        right_val,
+                        abs_tol,
+                        rel_tol,
+                        (left_val as f64 - right_val as f64).abs()
+                    );
+                }
+            }
+        }
+    };
     ($left:expr, $right:expr, $allowed_diff: expr ) => {
         match ($left, $right, $allowed_diff) {
             (left_val, right_val, allowed_diff) => {
@@ -246,11 +481,13 @@ macro_rules! assert_not_close {
 
 /// The type that represents the output of a valid
 /// validation function
+#[cfg(feature = "std")]
 pub use crate::validator_wrapper::ValidFunc;
 
 /// Implements a validation error, where
 /// `Ok` returns just the text to write in the report,
 /// but `Err` returns not only that but also an error message
+#[cfg(feature = "std")]
 pub enum ValidationResult {
     /// Returns an error, containing
     /// something to write in the report (1st param) and also an error message
@@ -261,12 +498,14 @@ pub enum ValidationResult {
     Ok(String),
 }
 
+#[cfg(feature = "std")]
 impl std::convert::From<String> for ValidationResult {
     fn from(value: String) -> Self {
         Self::Err(value.clone(), value.clone())
     }
 }
 
+#[cfg(feature = "std")]
 impl ValidationResult {
     /// Panics if this `ValidationResult` is of type `Err`.
     ///
@@ -307,162 +546,2503 @@ impl ValidationResult {
 /// This structure holds a number of validations to be ran, runs them,
 /// and writes the results into an HTML report. It has a title, which is used
 /// as a Header in its report.
+#[cfg(feature = "std")]
 pub struct Validator<'a> {
     /// The title of this section
-    title: &'a str,
+    title: String,
 
     /// The validations to run
     validations: Vec<Box<dyn Validate>>,
 
+    /// The titles the validations got when pushed: the one they declare,
+    /// numbered if it was already used (see [`DuplicateTitles`])
+    titles: Vec<Option<String>>,
+
     /// The file in which the report will be written
-    target_file: &'a str,
+    target_file: String,
+
+    /// The file requested when creating the `Validator`, on which
+    /// `target_file` is based
+    base_file: &'a str,
+
+    /// The order in which the validations are reported
+    order: ReportOrder,
+
+    /// Write the data behind each chart into a CSV file
+    export_chart_data: bool,
+
+    /// The file in which a report containing only the failed validations
+    /// will be written
+    failures_file: Option<&'a str>,
+
+    /// The settings handed to the validations
+    context: ReportContext,
+
+    /// Write a summary of the results next to the report, so
+    /// it can be collected by a [`ReportIndex`]
+    write_summary: bool,
+
+    /// The directory into which LaTeX fragments are written
+    latex_dir: Option<PathBuf>,
+
+    /// Reduce the size of the charts in the report
+    optimize_svg: bool,
+
+    /// How the report is compressed
+    compression: Compression,
+
+    /// The format in which the report is written
+    format: ReportFormat,
+
+    /// Write the same report for the same results, byte by byte
+    deterministic: bool,
+
+    /// The name of the threshold profile in use, if any
+    threshold_profile: Option<String>,
+
+    /// How much is printed to the console after validating
+    verbosity: Verbosity,
+
+    /// Fail validations that do not define any acceptance threshold
+    strict: bool,
+
+    /// The time after which the remaining validations are not run
+    time_budget: Option<Duration>,
+
+    /// The number of failed validations after which the remaining
+    /// ones are not run
+    max_errors: Option<usize>,
+
+    /// Write each section into the report as soon as it is ready
+    streaming: bool,
+
+    /// What to do when several validations have the same title
+    duplicate_titles: DuplicateTitles,
+
+    /// Called before running each validation
+    before_each: Option<Box<BeforeHook<'a>>>,
+
+    /// Called after running each validation
+    after_each: Option<Box<AfterHook<'a>>>,
+
+    /// The part of the suite to run, if it is split across several runners
+    shard: Option<Shard>,
+
+    /// The tags of the validations that have any, with their index
+    tags: Vec<(usize, Vec<String>)>,
+
+    /// The tags of the validations to run, if not all of them
+    filter: Option<TagFilter>,
+
+    /// The number of consecutive runs on which a validation needs to fail
+    /// before it is reported as failed, and where those runs are recorded
+    hysteresis: Option<Hysteresis>,
+
+    /// The columns of the run matrix written into the report, if any
+    matrix: Vec<MatrixMetric>,
+
+    /// The quantities that the validations are expected to cover
+    coverage: Vec<String>,
+
+    /// The IDs of the requirements that the validations are expected to verify
+    requirements: Vec<String>,
+
+    /// The file into which a GitLab Code Quality report is written
+    code_quality_file: Option<PathBuf>,
+
+    /// The file into which a SARIF log is written
+    sarif_file: Option<PathBuf>,
+
+    /// The file into which the results are written as JSON
+    json_file: Option<PathBuf>,
+
+    /// The file into which suggested thresholds are written, and the
+    /// margin they leave over the current results
+    threshold_suggestions: Option<(PathBuf, f64)>,
+
+    /// The validations that can run on any thread, with their index
+    #[cfg(feature = "rayon")]
+    concurrent: Vec<(usize, std::sync::Arc<dyn Validate + Send + Sync>)>,
 }
 
+/// A function called with the index and the title of a validation before running it
+#[cfg(feature = "std")]
+type BeforeHook<'a> = dyn Fn(usize, &str) + 'a;
+
+/// A function called with the title and the result of a validation after running it
+#[cfg(feature = "std")]
+type AfterHook<'a> = dyn Fn(&str, Result<(), &str>) + 'a;
+
+#[cfg(feature = "std")]
 impl<'a> Validator<'a> {
     /// Creates a new `Validator` that will write a report on `target_file` and put the
-    /// supporting data on `report_data_dir`
-    pub fn new(title: &'a str, target_file: &'a str) -> Self {
-        // Let's check that we can write into this file
-        if let std::io::Result::Err(_e) = fs::File::create(target_file) {
-            panic!(
+    /// supporting data (e.g., [`Artifact`]s) on a directory next to it. This
+    /// directory is named after the report (e.g., `report_assets` for `report.html`)
+    ///
+    /// # Panics
+    ///
+    /// * If `target_file` cannot be written (see [`Validator::try_new`])
+    pub fn new<S: Into<String>>(title: S, target_file: &'a str) -> Self {
+        Self::try_new(title, target_file).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new `Validator`, like [`Validator::new`], but returning an
+    /// error instead of panicking if `target_file` cannot be written (see
+    /// also [`ValidatorBuilder`])
+    pub fn try_new<S: Into<String>>(title: S, target_file: &'a str) -> Result<Self, String> {
+        // Let's check that we can write into this file... without truncating
+        // it, as another process might be writing it
+        if let std::io::Result::Err(_e) = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(target_file)
+        {
+            return Err(format!(
                 "Cannot write to file '{}'... check that such directory exists.",
                 target_file
-            )
+            ));
         }
 
-        Self {
-            title,
-            target_file,
+        Ok(Self {
+            title: title.into(),
+            target_file: target_file.to_string(),
+            base_file: target_file,
             validations: Vec::new(),
+            titles: Vec::new(),
+            order: ReportOrder::default(),
+            export_chart_data: false,
+            failures_file: None,
+            context: ReportContext {
+                assets_dir: Self::default_assets_dir(target_file),
+                report_dir: Path::new(target_file)
+                    .parent()
+                    .unwrap_or_else(|| Path::new(""))
+                    .to_path_buf(),
+                ..Default::default()
+            },
+            write_summary: false,
+            latex_dir: None,
+            optimize_svg: false,
+            compression: Compression::default(),
+            format: ReportFormat::default(),
+            deterministic: false,
+            threshold_profile: None,
+            verbosity: Verbosity::default(),
+            strict: false,
+            time_budget: None,
+            max_errors: None,
+            streaming: false,
+            duplicate_titles: DuplicateTitles::default(),
+            before_each: None,
+            after_each: None,
+            shard: None,
+            coverage: Vec::new(),
+            requirements: Vec::new(),
+            code_quality_file: None,
+            sarif_file: None,
+            json_file: None,
+            threshold_suggestions: None,
+            tags: Vec::new(),
+            filter: None,
+            hysteresis: None,
+            matrix: Vec::new(),
+            #[cfg(feature = "rayon")]
+            concurrent: Vec::new(),
+        })
+    }
+
+    /// Gets the directory in which the assets of a report written
+    /// into `target_file` are stored by default
+    fn default_assets_dir(target_file: &str) -> PathBuf {
+        let path = Path::new(target_file);
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "report".to_string());
+        path.with_file_name(format!("{}_assets", stem))
+    }
+
+    /// Sets the directory in which the assets of the report (e.g., [`Artifact`]s)
+    /// are written. This directory is created when needed.
+    ///
+    /// Links are written relative to the report file, so it is advisable to put this
+    /// directory next to it.
+    pub fn set_assets_dir<P: AsRef<Path>>(&mut self, dir: P) {
+        self.context.assets_dir = dir.as_ref().to_path_buf()
+    }
+
+    /// Copies the artifacts registered by a validation into the assets
+    /// directory, returning the Markdown that links to them.
+    fn write_artifacts(&self, index: usize, artifacts: &[Artifact]) -> Result<String, String> {
+        if artifacts.is_empty() {
+            return Ok(String::new());
+        }
+
+        let mut ret = format!("\n\n#### {}:\n\n", self.context.tr("Artifacts"));
+        for a in artifacts {
+            let link = a.copy_into(&self.context, index + 1)?;
+            ret.push_str(&format!(" * [{}]({})\n", self.context.user_text(&a.label), link));
         }
+        Ok(ret)
     }
 
-    /// Adds a new validation to the `Validator`
-    pub fn push(&mut self, v: Box<dyn Validate>) {
-        self.validations.push(v)
+    /// Writes the data behind the chart of a validation into a CSV file
+    /// in the assets directory, returning the Markdown that links to it.
+    fn write_chart_data(&self, index: usize, data: Option<ChartData>) -> Result<String, String> {
+        let data = match data {
+            Some(d) if self.export_chart_data => d,
+            _ => return Ok(String::new()),
+        };
+        let link = data.write_into(&self.context, index + 1)?;
+        Ok(format!(
+            "\n\n[{}]({})\n",
+            self.context.tr("Download the data of this chart"),
+            link
+        ))
     }
 
-    /// Runs the validations, writes the report and fails the task if necessary
-    pub fn validate(&self) -> Result<(), String> {
-        let mut errors = Vec::new();
+    /// Sets a file into which a second, compact report containing only the
+    /// failed validations will be written. The full report is still written
+    /// into the main target file.
+    ///
+    /// This report links to the same assets as the main report, so it is
+    /// advisable to write it into the same directory.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::Validator;
+    ///
+    /// let mut validator = Validator::new("Full report", "report.html");
+    /// validator.set_failures_file("report_failures.html");
+    /// ```
+    pub fn set_failures_file(&mut self, failures_file: &'a str) {
+        self.failures_file = Some(failures_file)
+    }
 
-        // Solve
-        let txt: Vec<String> = self
-            .validations
-            .iter()
-            .map(|v| {
-                // md.write_all(b"\n\n").unwrap();
-                match v.validate() {
-                    ValidationResult::Err(txt, e) => {
-                        errors.push(e);
-                        txt
-                    }
-                    ValidationResult::Ok(txt) => txt,
-                }
-            })
-            .collect();
-        let txt = format!("# {}\n\n{}", self.title, txt.join("\n"));
+    /// Sets whether the report is written into a file whose name is unique
+    /// to this `Validator` (e.g., `report_1234_1.html` for `report.html`, where
+    /// `1234` is the ID of the process), so that suites running concurrently—such
+    /// as the test binaries and threads of `cargo test`—do not overwrite each other's
+    /// reports. Defaults to `false`.
+    ///
+    /// A summary is always written next to uniquely named reports, so they can
+    /// be gathered afterwards with [`ReportIndex::add_unique_reports`].
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::Validator;
+    ///
+    /// let mut validator = Validator::new("Thermal", "report.html");
+    /// validator.set_unique_report_name(true);
+    /// assert_ne!(validator.report_path(), "report.html");
+    /// ```
+    pub fn set_unique_report_name(&mut self, unique: bool) {
+        let target_file = if unique {
+            index::unique_report_name(self.base_file)
+        } else {
+            self.base_file.to_string()
+        };
+        // Assets follow the report, unless they were put somewhere else
+        if self.context.assets_dir == Self::default_assets_dir(&self.target_file) {
+            self.context.assets_dir = Self::default_assets_dir(&target_file);
+        }
+        self.target_file = target_file;
+        self.write_summary |= unique;
+    }
 
-        // Write
-        // Set up options and parser.
-        let options = Options::empty();
-        // options.insert(Options::ENABLE_STRIKETHROUGH);
-        let parser = Parser::new_ext(&txt, options);
+    /// The path of the file into which the report is written
+    pub fn report_path(&self) -> &str {
+        &self.target_file
+    }
 
-        // Write to String buffer.
-        let mut html_output = String::new();
-        html::push_html(&mut html_output, parser);
+    /// Sets whether a summary of the results is written next to the report
+    /// (e.g., `report_summary.csv` for `report.html`), so that it can be
+    /// collected into a [`ReportIndex`]. Defaults to `false`.
+    pub fn set_write_summary(&mut self, write_summary: bool) {
+        self.write_summary = write_summary
+    }
 
-        let mut output = fs::File::create(self.target_file).unwrap();
+    /// Sets a directory into which each validation is also written as a
+    /// LaTeX fragment (`validation_1.tex`, `validation_2.tex`, etc., following the
+    /// order of the report), so it can be `\input` into a manuscript.
+    ///
+    /// Each fragment contains a table with the indicators of the validation—which
+    /// requires the `booktabs` package—and a figure including its chart, which
+    /// is written next to it as an SVG file and requires the `svg` package.
+    pub fn set_latex_dir<P: AsRef<Path>>(&mut self, dir: P) {
+        self.latex_dir = Some(dir.as_ref().to_path_buf())
+    }
 
-        // Open HTML
-        output.write_all(format!("<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"UTF-8\"><meta http-equiv=\"X-UA-Compatible\" content=\"IE=edge\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\"><title>{}</title></head><body>", self.title).as_bytes()).unwrap();
+    /// Sets a file (e.g., `gl-code-quality-report.json`) into which the failed
+    /// validations are written as a GitLab Code Quality report, so they show up
+    /// in the merge request widget. Each failure points at the function marked
+    /// with [`valid`] that defines it (see [`Validate::location`]) or, if that is
+    /// unknown, at the top of `Cargo.toml`.
+    pub fn set_code_quality_file<P: AsRef<Path>>(&mut self, file: P) {
+        self.code_quality_file = Some(file.as_ref().to_path_buf())
+    }
 
-        output.write_all(html_output.as_bytes()).unwrap();
-        // Close html
-        output.write_all(b"</body></html>").unwrap();
+    /// Sets a file (e.g., `validate.sarif`) into which the failed validations
+    /// are written as a SARIF log, so they show up in code scanning tools.
+    /// Each failure points at the function marked with [`valid`] that defines
+    /// it (see [`Validate::location`]), if known.
+    pub fn set_sarif_file<P: AsRef<Path>>(&mut self, file: P) {
+        self.sarif_file = Some(file.as_ref().to_path_buf())
+    }
 
-        // Return
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            for e in errors {
-                eprintln!("{}", e);
-            }
-            Err("Some validations failed...".to_string())
-        }
+    /// Sets a file (e.g., `results.json`) into which the results are written as
+    /// JSON, in addition to the report, so they can be read by dashboards. It
+    /// holds the title of each validation, whether it passed, its error, its
+    /// indicators (see [`Validate::indicators`]) and its inputs—including the
+    /// hashes of input files, to tell whether the reference data changed
+    /// between runs (see [`RunInput::file_hash`]):
+    ///
+    /// ```json
+    /// {"title": "Thermal", "version": "0.1.0", "passed": 1, "failed": 0, "duration": 0.2,
+    ///  "validations": [{"title": "Zone A", "anchor": "zone-a", "passed": true, "error": null,
+    ///    "indicators": {"Root Mean Squared Error": 0.12}, "covers": [], "requirements": [],
+    ///    "inputs": {"data/zone_a.csv": "fnv1a64:9c3ab1e0d4f2a7b5"},
+    ///    "file": "tests/thermal.rs", "line": 12}]}
+    /// ```
+    pub fn set_json_file<P: AsRef<Path>>(&mut self, file: P) {
+        self.json_file = Some(file.as_ref().to_path_buf())
     }
-}
 
-/// The main trait of this crate. All validator modules need
-/// to comply with this trait.
-pub trait Validate {
-    /// Runs a validation procedure, returning an error message if
-    /// the validation failed.
+    /// Runs in exploratory mode: the validations that define no thresholds
+    /// (see [`Validate::defines_thresholds`]) still compute and report their
+    /// metrics, and the thresholds they would meet with a `margin` (e.g.,
+    /// `1.2` allows errors 20% larger than the current ones) are written into
+    /// `file` as a `[suggested]` profile (see [`Validate::suggested_thresholds`]).
+    /// When several validations suggest thresholds, the loosest are kept.
     ///
-    /// Whether it fails or not,
-    /// it should write the results of the validations into `file`
-    /// so a full report is written.
-    fn validate(&self) -> ValidationResult;
-}
+    /// This bootstraps the acceptance criteria of a newly instrumented test
+    /// rig: the file can be reviewed and loaded as a threshold profile.
+    ///
+    /// ```no_run
+    /// use validate::Validator;
+    ///
+    /// let mut validator = Validator::new("New rig", "report.html");
+    /// validator.set_threshold_suggestions("thresholds.toml", 1.2);
+    /// // ... push validations without thresholds
+    /// validator.validate().unwrap();
+    /// ```
+    pub fn set_threshold_suggestions<P: AsRef<Path>>(&mut self, file: P, margin: f64) {
+        self.threshold_suggestions = Some((file.as_ref().to_path_buf(), margin))
+    }
 
-/// Reads a number of columns from a CSV, transforms them into f64
-pub fn from_csv<T: Numberish>(path: &str, cols: &[usize]) -> Vec<Vec<T>> {
-    let reader = File::open(path).unwrap();
-    let mut rdr = csv::Reader::from_reader(reader);
+    /// Sets whether the SVG charts in the report are optimized—i.e., their
+    /// coordinates rounded to a tenth of a pixel, overlapping points removed and
+    /// repeated styles dropped—which can greatly reduce the size of reports with
+    /// long time series without visibly changing them. Defaults to `false`.
+    pub fn set_optimize_svg(&mut self, optimize: bool) {
+        self.optimize_svg = optimize
+    }
 
-    let mut ret: Vec<Vec<T>> = Vec::with_capacity(cols.len());
-    for _ in cols {
-        ret.push(Vec::new());
+    /// Sets how the report (and the failures report, if any) is compressed.
+    ///
+    /// Note that a compressed report is written into a file with an extra
+    /// extension (e.g., `report.html.gz`), so features that read the report back,
+    /// such as the PDF export, will not find it.
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression
     }
 
-    for record in rdr.records() {
-        let data = record.unwrap();
-        // dbg!(&data);
+    /// Sets the format in which the report (and the failures report, if any)
+    /// is written. Defaults to [`ReportFormat::Html`].
+    ///
+    /// Note that features that read the report back, such as the PDF
+    /// export, need it to be written as HTML.
+    pub fn set_report_format(&mut self, format: ReportFormat) {
+        self.format = format
+    }
 
-        for (i, col) in cols.iter().enumerate() {
-            // dbg!(i,col);
-            // dbg!(data.get(*col));
-            match data.get(*col) {
-                Some(v) => {
-                    // dbg!(v.to_string());
-                    let v = v.trim();
-                    let v: T = v.parse::<f32>().unwrap().into();
-                    ret[i].push(v);
-                }
-                None => continue, //panic!("Index out of bounds: {}", *col)
+    /// Makes the report depend only on the results of the validations, so
+    /// two runs on the same data write byte-identical reports that can be
+    /// diffed or content-addressed: the duration of the run is not written,
+    /// the IDs in the charts are numbered in order and numbers rounded
+    /// to zero from below (e.g., `-0.0000`) are written as zeros.
+    /// Defaults to `false`.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic
+    }
+
+    /// Sets the thresholds inherited by the validations that do not
+    /// set their own (see [`Thresholds`])
+    pub fn set_default_thresholds(&mut self, thresholds: Thresholds) {
+        self.context.thresholds = thresholds
+    }
+
+    /// Uses the thresholds of a named profile as the default thresholds
+    /// of the report, and names the profile in the report's header.
+    ///
+    /// The `VALIDATE_PROFILE` environment variable, if set, takes precedence
+    /// over `name`, so the strictness can be changed without changing the code.
+    #[cfg(feature = "toml")]
+    pub fn set_threshold_profile(&mut self, profiles: &ThresholdProfiles, name: &str) -> Result<(), String> {
+        let name = std::env::var("VALIDATE_PROFILE").unwrap_or_else(|_| name.to_string());
+        match profiles.get(&name) {
+            Some(thresholds) => {
+                self.context.thresholds = *thresholds;
+                self.threshold_profile = Some(name);
+                Ok(())
             }
+            None => Err(format!("There is no threshold profile called '{}'", name)),
         }
     }
 
-    ret
-}
+    /// In strict mode, the validations that do not define any acceptance
+    /// threshold (e.g., a chart with no limits on its errors) fail instead
+    /// of silently passing.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{SeriesValidator, Validator};
+    ///
+    /// let mut validator = Validator::new("Strict report", "report.html");
+    /// validator.set_strict(true);
+    /// validator.push(Box::new(SeriesValidator {
+    ///     expected: vec![1., 2., 3.],
+    ///     found: vec![1., 2., 3.],
+    ///     ..Default::default()
+    /// }));
+    /// assert!(validator.validate().is_err());
+    /// ```
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict
+    }
 
-/// Module with some useful functions for calculating
-/// indicators for validation (e.g., Mean Squared Error)
-pub mod stats;
+    /// Sets a time budget for the whole suite. Validations that would start
+    /// after the budget has been exhausted are not run, and are reported as
+    /// failed instead. The budget and the actual duration are recorded in
+    /// the report.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use std::time::Duration;
+    /// use validate::Validator;
+    ///
+    /// let mut validator = Validator::new("Nightly", "report.html");
+    /// validator.set_time_budget(Duration::from_secs(30 * 60));
+    /// ```
+    pub fn set_time_budget(&mut self, budget: Duration) {
+        self.time_budget = Some(budget)
+    }
 
-#[cfg(test)]
-mod tests {
-    use crate::from_csv;
+    /// Stops running validations after `max_errors` of them failed. The
+    /// remaining validations are reported as failed, without being run.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::Validator;
+    ///
+    /// let mut validator = Validator::new("Smoke", "report.html");
+    /// validator.set_max_errors(5);
+    /// ```
+    pub fn set_max_errors(&mut self, max_errors: usize) {
+        self.max_errors = Some(max_errors)
+    }
 
-    #[test]
-    fn test_from_csv() {
-        let data = from_csv::<f64>("./tests/test_data/data.csv", &vec![0, 1, 2, 3]);
-        for c in 0..4 {
-            let d = &data[c];
-            assert_eq!(d.len(), 3);
-            for (i, found) in d.iter().enumerate() {
-                let exp = 10 * i + c;
-                assert_close!(exp as f64, *found);
-            }
+    /// Sets whether to stop running validations after the first one
+    /// fails (see [`Validator::set_max_errors`])
+    pub fn set_fail_fast(&mut self, fail_fast: bool) {
+        self.max_errors = if fail_fast { Some(1) } else { None }
+    }
+
+    /// Sets a function to call before running each validation, receiving
+    /// the index of the validation (i.e., the order in which it was pushed)
+    /// and its title, as listed by [`Validator::titles`].
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::Validator;
+    ///
+    /// let mut validator = Validator::new("Hooks", "report.html");
+    /// validator.set_before_each(|i, title| println!("Running validation {}: {}", i + 1, title));
+    /// ```
+    pub fn set_before_each<F: Fn(usize, &str) + 'a>(&mut self, hook: F) {
+        self.before_each = Some(Box::new(hook))
+    }
+
+    /// Sets a function to call after running each validation, receiving its
+    /// title and result (i.e., `Err` with the error message if it failed).
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::Validator;
+    ///
+    /// let mut validator = Validator::new("Hooks", "report.html");
+    /// validator.set_after_each(|title, result| {
+    ///     if let Err(e) = result {
+    ///         println!("'{}' failed: {}", title, e)
+    ///     }
+    /// });
+    /// ```
+    pub fn set_after_each<F: Fn(&str, Result<(), &str>) + 'a>(&mut self, hook: F) {
+        self.after_each = Some(Box::new(hook))
+    }
+
+    /// In streaming mode, each section is appended to the report as soon
+    /// as its validation finishes, so a crash halfway through a long suite
+    /// still leaves a partial report. The complete report—including the
+    /// summary and the requested order—replaces it at the end.
+    pub fn set_streaming(&mut self, streaming: bool) {
+        self.streaming = streaming
+    }
+
+    /// Runs only one shard of the suite (see [`Shard`]), so a long suite can
+    /// be split across several CI runners. The `VALIDATE_SHARD` environment
+    /// variable (e.g., `VALIDATE_SHARD=2/8`) overrides this setting.
+    ///
+    /// Every runner builds the same suite and runs its own shard, writing a
+    /// report with its validations and their results into a JSON file next
+    /// to it (e.g., `report_shard_2_of_8.json`). These files are then merged
+    /// into a single report through [`Validator::merge_shards`].
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{Shard, Validator};
+    ///
+    /// let mut validator = Validator::new("Nightly", "report.html");
+    /// validator.set_shard(Shard::new(2, 8).unwrap());
+    /// ```
+    pub fn set_shard(&mut self, shard: Shard) {
+        self.shard = Some(shard)
+    }
+
+    /// Runs only the validations selected by a [`TagFilter`], based on the
+    /// tags they were pushed with (see [`Validator::push_tagged`]). The
+    /// report is written with those validations only, and mentions the
+    /// filter. The `VALIDATE_TAGS` environment variable (e.g.,
+    /// `VALIDATE_TAGS=thermal,!slow`) overrides this setting.
+    pub fn set_filter(&mut self, filter: TagFilter) {
+        self.filter = Some(filter)
+    }
+
+    /// Adds a run matrix to the report: a table with a row per validation
+    /// and a column per metric (i.e., per [`Indicator`]), each cell colored
+    /// by whether its value passes, warns or fails
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{MatrixMetric, Validator};
+    ///
+    /// let mut validator = Validator::new("Steering committee", "report.html");
+    /// validator.set_matrix(vec![
+    ///     MatrixMetric {
+    ///         warning: Some(0.5),
+    ///         limit: Some(1.0),
+    ///         ..MatrixMetric::new("Root Mean Squared Error")
+    ///     },
+    ///     MatrixMetric {
+    ///         limit: Some(0.25),
+    ///         ..MatrixMetric::new("Mean Bias Error")
+    ///     },
+    /// ]);
+    /// ```
+    pub fn set_matrix(&mut self, metrics: Vec<MatrixMetric>) {
+        self.matrix = metrics
+    }
+
+    /// Reports a failed validation as failed only if it also failed on the
+    /// previous runs recorded in the history file, so a single noisy night
+    /// does not fail the task (see [`Hysteresis`]). Until then, the failure
+    /// is tolerated: the validation passes, but the report keeps its error.
+    ///
+    /// The results of each run—before tolerating any failure—are appended
+    /// to the history file. Validations are matched across runs by title.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{Hysteresis, Validator};
+    ///
+    /// let mut validator = Validator::new("Nightly", "report.html");
+    /// validator.set_hysteresis(Hysteresis {
+    ///     history_file: "nightly.jsonl".into(),
+    ///     consecutive_failures: 3,
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn set_hysteresis(&mut self, hysteresis: Hysteresis) -> Result<(), String> {
+        if hysteresis.consecutive_failures == 0 {
+            return Err("The number of consecutive failures must be at least 1".to_string());
         }
+        self.hysteresis = Some(hysteresis);
+        Ok(())
     }
 
-    #[test]
-    fn test_assert_close_correct() {
-        assert_close!(1., 2., 2.);
-        assert_not_close!(1., 21., 1.);
+    /// Declares the physical quantities or subsystems that the validations
+    /// are expected to cover (see [`Validate::covers`]). The report then
+    /// includes a coverage section listing the validations of each quantity,
+    /// highlighting those that are not validated at all.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{SeriesValidator, Validator};
+    ///
+    /// let mut validator = Validator::new("Thermal model", "report.html");
+    /// validator.set_coverage(["zone air temperature", "surface conduction"]);
+    /// validator.push(SeriesValidator {
+    ///     expected: vec![1., 2., 3.],
+    ///     found: vec![1., 2., 3.],
+    ///     covers: vec!["zone air temperature".to_string()],
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn set_coverage<I, S>(&mut self, quantities: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.coverage = quantities.into_iter().map(Into::into).collect()
+    }
+
+    /// Declares the IDs of the requirements that the validations are expected
+    /// to verify (see [`Validate::requirements`]). The traceability section of
+    /// the report then highlights those that no validation verifies.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{SeriesValidator, Validator};
+    ///
+    /// let mut validator = Validator::new("Thermal model", "report.html");
+    /// validator.set_requirements(["REQ-THERM-012", "REQ-THERM-013"]);
+    /// validator.push(SeriesValidator {
+    ///     expected: vec![1., 2., 3.],
+    ///     found: vec![1., 2., 3.],
+    ///     requirements: vec!["REQ-THERM-012".to_string()],
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn set_requirements<I, S>(&mut self, requirements: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.requirements = requirements.into_iter().map(Into::into).collect()
+    }
+
+    /// Sets how much is printed to the console after validating
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{Validator, Verbosity};
+    ///
+    /// let mut validator = Validator::new("Quiet report", "report.html");
+    /// validator.set_verbosity(Verbosity::Quiet);
+    /// ```
+    pub fn set_verbosity(&mut self, verbosity: Verbosity) {
+        self.verbosity = verbosity
+    }
+
+    /// Sets how the text provided by the user—titles, descriptions, labels
+    /// of artifacts and inputs, etc.—is written into the report. By default,
+    /// HTML is escaped but Markdown is rendered (see [`TextPolicy`]).
+    pub fn set_text_policy(&mut self, policy: TextPolicy) {
+        self.context.text_policy = policy
+    }
+
+    /// Sets the Markdown extensions (e.g., tables or footnotes) used when
+    /// transforming the report into HTML, and whether raw HTML is let through
+    pub fn set_markdown_options(&mut self, options: MarkdownOptions) {
+        self.context.markdown = options
+    }
+
+    /// Sets the style of the charts in the report
+    pub fn set_chart_theme(&mut self, theme: ChartTheme) {
+        self.context.chart_theme = theme
+    }
+
+    /// Sets the translations of the texts written in the report
+    pub fn set_locale(&mut self, locale: Locale) {
+        self.context.locale = locale
+    }
+
+    /// Sets the system of units in which the values are displayed in the
+    /// report. This does not affect the calculations, which are always
+    /// performed in the units of the data (which should be SI).
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{Validator, UnitSystem};
+    ///
+    /// let mut validator = Validator::new("Report in IP", "report.html");
+    /// validator.set_unit_system(UnitSystem::IP);
+    /// ```
+    pub fn set_unit_system(&mut self, unit_system: UnitSystem) {
+        self.context.unit_system = unit_system
+    }
+
+    /// Sets whether the data behind the charts of the validations (i.e., the
+    /// `x`, `expected` and `found` values) is written into CSV files in the
+    /// assets directory and linked under each chart. Defaults to `false`.
+    pub fn set_export_chart_data(&mut self, export: bool) {
+        self.export_chart_data = export
+    }
+
+    /// Sets what to do when several validations have the same title. Titles
+    /// need to be unique, as they identify the validations—e.g., through the
+    /// `VALIDATE_FILTER` environment variable—and their anchors in the report.
+    /// Repeated titles are numbered when the validations are pushed (see
+    /// [`Validator::titles`]), whatever the policy.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{DuplicateTitles, Validator};
+    ///
+    /// let mut validator = Validator::new("Unique titles", "report.html");
+    /// validator.set_duplicate_titles(DuplicateTitles::Fail);
+    /// ```
+    pub fn set_duplicate_titles(&mut self, policy: DuplicateTitles) {
+        self.duplicate_titles = policy
+    }
+
+    /// Sets the order in which the validations are written into the report.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{Validator, ReportOrder};
+    ///
+    /// let mut validator = Validator::new("Sorted report", "report.html");
+    /// validator.set_order(ReportOrder::FailuresFirst);
+    /// ```
+    pub fn set_order(&mut self, order: ReportOrder) {
+        self.order = order
+    }
+
+    /// Adds a new validation to the `Validator`. Validations can be
+    /// pushed as they are or boxed.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{SeriesValidator, Validator};
+    ///
+    /// let mut validator = Validator::new("Push", "report.html");
+    /// validator.push(SeriesValidator::<f64>::default());
+    /// validator.push(Box::new(SeriesValidator::<f64>::default()));
+    /// ```
+    pub fn push<V: Validate + 'static>(&mut self, v: V) {
+        self.push_boxed(Box::new(v))
+    }
+
+    /// Adds a boxed validation, numbering its title if a validation
+    /// pushed before already used it
+    fn push_boxed(&mut self, v: Box<dyn Validate>) {
+        let title = v.title().map(|title| {
+            report::number_title(title, |t| self.titles.iter().flatten().any(|u| report::slug(u) == report::slug(t)))
+        });
+        self.titles.push(title);
+        self.validations.push(v)
+    }
+
+    /// Adds a validation with some tags (e.g., the subsystem it checks, or
+    /// `slow`), so it can be selected—or left out—by a [`TagFilter`]
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{SeriesValidator, TagFilter, Validator};
+    ///
+    /// let mut validator = Validator::new("Tagged", "report.html");
+    /// let v = SeriesValidator {
+    ///     expected: vec![1., 2., 3.],
+    ///     found: vec![1., 2., 3.],
+    ///     ..Default::default()
+    /// };
+    /// validator.push_tagged(v.clone(), &["thermal"]);
+    /// validator.push_tagged(v, &["thermal", "slow"]);
+    /// validator.set_filter(TagFilter::new(&["thermal"], &["slow"]));
+    /// validator.validate().unwrap();
+    /// ```
+    pub fn push_tagged<V: Validate + 'static>(&mut self, v: V, tags: &[&str]) {
+        let tags = tags.iter().map(|t| t.to_string()).collect();
+        self.tags.push((self.validations.len(), tags));
+        self.push(v)
+    }
+
+    /// Adds a validation that can run on any thread, so it runs
+    /// concurrently with others when validating with [`Validator::validate_par`].
+    /// It runs like any other validation when validating with
+    /// [`Validator::validate`].
+    #[cfg(feature = "rayon")]
+    pub fn push_concurrent<V: Validate + Send + Sync + 'static>(&mut self, v: V) {
+        let v = std::sync::Arc::new(v);
+        self.concurrent.push((self.validations.len(), v.clone()));
+        self.push_boxed(Box::new(v))
+    }
+
+    /// Adds several validations to the `Validator` (e.g., one per case of a
+    /// suite generated programmatically), in order.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{SeriesValidator, Validate, Validator};
+    ///
+    /// let mut validator = Validator::new("Zones", "report.html");
+    /// validator.push_all((0..3).map(|zone| {
+    ///     Box::new(SeriesValidator {
+    ///         expected: vec![zone as f64; 10],
+    ///         found: vec![zone as f64; 10],
+    ///         ..Default::default()
+    ///     }) as Box<dyn Validate>
+    /// }));
+    /// assert_eq!(validator.len(), 3);
+    ///
+    /// // Validators can also be merged (see Validator::merge)
+    /// let mut other = Validator::new("More zones", "report.html");
+    /// other.merge(validator);
+    /// assert_eq!(other.len(), 3);
+    /// ```
+    pub fn push_all<I: IntoIterator<Item = Box<dyn Validate>>>(&mut self, validations: I) {
+        for v in validations {
+            self.push_boxed(v)
+        }
+    }
+
+    /// Moves the validations of another `Validator` into this one, in
+    /// order, keeping their tags and—with the `rayon` feature—whether they
+    /// can run concurrently. The settings of `other` (e.g., its hooks or
+    /// its filter) are not merged.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{SeriesValidator, TagFilter, Validator};
+    ///
+    /// let v = SeriesValidator {
+    ///     expected: vec![1., 2., 3.],
+    ///     found: vec![1., 2., 3.],
+    ///     ..Default::default()
+    /// };
+    /// let mut slow = Validator::new("Slow zones", "report.html");
+    /// slow.push_tagged(v.clone(), &["slow"]);
+    /// let mut validator = Validator::new("Zones", "report.html");
+    /// validator.push(v);
+    /// validator.merge(slow);
+    /// assert_eq!(validator.len(), 2);
+    /// validator.set_filter(TagFilter::new(&[], &["slow"]));
+    /// validator.validate().unwrap();
+    /// ```
+    pub fn merge(&mut self, other: Validator) {
+        let offset = self.validations.len();
+        self.tags
+            .extend(other.tags.into_iter().map(|(i, tags)| (i + offset, tags)));
+        #[cfg(feature = "rayon")]
+        self.concurrent
+            .extend(other.concurrent.into_iter().map(|(i, v)| (i + offset, v)));
+        self.push_all(other.validations)
+    }
+
+    /// The number of validations pushed into this `Validator`
+    pub fn len(&self) -> usize {
+        self.validations.len()
+    }
+
+    /// Checks whether no validations have been pushed into this `Validator`
+    pub fn is_empty(&self) -> bool {
+        self.validations.is_empty()
+    }
+
+    /// Iterates over the validations pushed into this `Validator`, in
+    /// order, so they can be inspected (e.g., through [`Validate::title`]) or
+    /// downcast to their concrete types (through [`Validate::as_any`]).
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{SeriesValidator, Validator};
+    ///
+    /// let mut validator = Validator::new("Inspect", "report.html");
+    /// validator.push(Box::new(SeriesValidator {
+    ///     expected: vec![1., 2.],
+    ///     found: vec![1., 2.],
+    ///     ..Default::default()
+    /// }));
+    ///
+    /// let series = validator
+    ///     .validations()
+    ///     .find_map(|v| v.as_any()?.downcast_ref::<SeriesValidator<f64>>())
+    ///     .unwrap();
+    /// assert_eq!(series.expected.len(), 2);
+    /// ```
+    pub fn validations(&self) -> impl Iterator<Item = &dyn Validate> {
+        self.validations.iter().map(|v| v.as_ref())
+    }
+
+    /// The titles of the validations, in order (e.g., for listing what a suite
+    /// checks). Repeated titles are numbered (e.g., `Zone A (2)`), as in the
+    /// report. Validations that do not declare their title (see [`Validate::title`])
+    /// get the generic title used in the report until they are run.
+    pub fn titles(&self) -> Vec<String> {
+        (0..self.validations.len()).map(|i| self.title_of(i)).collect()
+    }
+
+    /// The title of the validation at position `i`: the one it got when
+    /// pushed or, until it runs, the generic one used in the report
+    fn title_of(&self, i: usize) -> String {
+        self.titles[i].clone().unwrap_or_else(|| format!("Validation {}", i + 1))
+    }
+
+    /// Gives the section of the validation at position `i` the title that
+    /// validation got when pushed, failing it if the title it declares was
+    /// already used and the policy does not allow it (see [`DuplicateTitles`])
+    fn set_title(&self, i: usize, v: &dyn Validate, section: &mut Section) {
+        let (Some(title), Some(declared)) = (&self.titles[i], v.title()) else {
+            return;
+        };
+        section.rename(title.clone(), &self.context);
+        if *title != declared && self.duplicate_titles == DuplicateTitles::Fail {
+            let e = format!("{}: '{}'", self.context.tr("Duplicate title"), declared);
+            section.body.push_str(&format!("\n\n**{}**\n", e));
+            section.error = Some(match section.error.take() {
+                Some(prev) => format!("{}\n{}", prev, e),
+                None => e,
+            });
+        }
+    }
+
+    /// Writes an HTML report with a title, a summary and the given sections
+    /// into `target_file`. The coverage of the validations, and the requirements
+    /// they are expected to verify, are reported if the report is `complete`—i.e.,
+    /// it has all the sections (see [`Validator::set_coverage`] and
+    /// [`Validator::set_requirements`]).
+    fn write_report(
+        &self,
+        target_file: &str,
+        title: &str,
+        sections: &[&Section],
+        complete: bool,
+        duration: Duration,
+    ) -> Result<(), String> {
+        let body: Vec<String> = sections.iter().map(|s| s.body_with_anchor()).collect();
+        let title = self.context.user_text(title);
+        let mut header = match &self.threshold_profile {
+            Some(name) => format!(
+                "# {}\n\n{}: **{}**",
+                title,
+                self.context.tr("Threshold profile"),
+                self.context.user_text(name)
+            ),
+            None => format!("# {}", title),
+        };
+        if let Ok(Some(filter)) = self.filter() {
+            header += &format!(
+                "\n\n{}: **{}**",
+                self.context.tr("Filtered by tags"),
+                self.context.user_text(&filter.to_string())
+            );
+        }
+        let txt = if sections.is_empty() {
+            format!(
+                "{}\n\n{}",
+                header,
+                self.context.tr("There are no validations to report.")
+            )
+        } else {
+            let (coverage, requirements) = if complete {
+                (report::coverage_table(sections, &self.coverage, &self.context), self.requirements.as_slice())
+            } else {
+                (String::new(), &[][..])
+            };
+            format!(
+                "{}\n\n{}{}\n\n{}\n\n{}\n\n{}\n\n{}",
+                header,
+                report::table_of_contents(sections, &self.context),
+                report::summary_table(sections, &self.context),
+                report::run_matrix(sections, &self.matrix, &self.context),
+                coverage,
+                report::traceability_table(sections, requirements, &self.context),
+                body.join("\n")
+            )
+        };
+        let duration = if self.deterministic { None } else { Some(duration) };
+        let environment = report::environment(&self.context, duration, self.time_budget);
+
+        let mut contents = match self.format {
+            ReportFormat::Html => {
+                // Open HTML
+                let mut html = report::html_head(&sanitize::unescape(&title));
+                html.push_str(&report::markdown_to_html(&txt, &self.context.markdown));
+                // Close html
+                html.push_str(&report::html_tail(&report::markdown_to_html(
+                    &environment,
+                    &self.context.markdown,
+                )));
+                html
+            }
+            ReportFormat::Markdown => report::publish(&format!("{}\n\n{}", txt, environment)),
+        };
+
+        if self.optimize_svg {
+            contents = svg::optimize_svgs(&contents);
+        }
+        if self.deterministic {
+            contents = report::without_negative_zeros(&svg::stable_ids(&contents));
+        }
+        if self.format == ReportFormat::Markdown {
+            contents = report::charts_as_files(&contents, &self.context)?;
+        }
+
+        match self.compression {
+            Compression::None => report::write_atomically(target_file, contents),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                let target_file = format!("{}.gz", target_file);
+                let compress = || -> std::io::Result<Vec<u8>> {
+                    use std::io::Write;
+                    let mut encoder =
+                        flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+                    encoder.write_all(contents.as_bytes())?;
+                    encoder.finish()
+                };
+                let contents = compress()
+                    .map_err(|e| format!("Cannot write report into '{}': {}", target_file, e))?;
+                report::write_atomically(&target_file, contents)
+            }
+            #[cfg(not(feature = "gzip"))]
+            Compression::Gzip => Err(format!(
+                "Cannot write report into '{}.gz': compressing reports requires the `gzip` feature",
+                target_file
+            )),
+        }
+    }
+
+    /// Prints the report into a PDF file, by means of a headless
+    /// Chromium browser (see the `VALIDATE_CHROMIUM` environment variable).
+    ///
+    /// This exports the report as written by the last call to [`Validator::validate`],
+    /// so it needs to be called afterwards.
+    ///
+    /// ```no_run
+    /// use validate::Validator;
+    ///
+    /// let validator = Validator::new("Certification", "report.html");
+    /// // Even if some validations fail, we want the PDF
+    /// let result = validator.validate();
+    /// validator.export_pdf("report.pdf").unwrap();
+    /// result.unwrap();
+    /// ```
+    #[cfg(feature = "pdf")]
+    pub fn export_pdf<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        pdf::html_to_pdf(Path::new(&self.target_file), path.as_ref())
+    }
+
+    /// Writes the heading and description of a validation that declares
+    /// its title (see [`Validate::title`])
+    fn heading(&self, v: &dyn Validate) -> String {
+        let title = match v.title() {
+            Some(t) => t,
+            None => return String::new(),
+        };
+        let mut ret = format!("## {}\n\n", self.context.user_text(&title));
+        if let Some(description) = v.description() {
+            ret.push_str(&format!("{}\n\n", self.context.user_text(&description)));
+        }
+        ret
+    }
+
+    /// Runs a single validation, producing its section of the report
+    ///
+    /// The `result` of the validation is given if it was already run (see
+    /// [`Validator::validate_par`]), in which case it is not skipped.
+    fn run_validation(
+        &self,
+        i: usize,
+        v: &dyn Validate,
+        start: Instant,
+        failures: usize,
+        result: Option<ValidationResult>,
+    ) -> Section {
+        let skip = if result.is_some() {
+            None
+        } else if self.time_budget.is_some_and(|budget| start.elapsed() > budget) {
+            Some("Not run (time budget exceeded)")
+        } else if self.max_errors.is_some_and(|max| failures >= max) {
+            Some("Not run (too many validations failed)")
+        } else {
+            None
+        };
+        if let Some(reason) = skip {
+            let e = self.context.tr(reason).to_string();
+            let mut section = Section::new(i, format!("{}**{}**", self.heading(v), e), Some(e));
+            self.set_title(i, v, &mut section);
+            return section;
+        }
+        let result = result.unwrap_or_else(|| {
+            if let Some(before_each) = &self.before_each {
+                before_each(i, &self.title_of(i));
+            }
+            v.validate_in(&self.context)
+        });
+        let (mut txt, mut error) = match result {
+            ValidationResult::Err(txt, e) => (txt, Some(e)),
+            ValidationResult::Ok(txt) => (txt, None),
+        };
+        txt.insert_str(0, &self.heading(v));
+        if self.strict && !v.defines_thresholds(&self.context) {
+            let e = self
+                .context
+                .tr("No acceptance thresholds are defined, which is not allowed in strict mode")
+                .to_string();
+            txt.push_str(&format!("\n\n**{}**\n", e));
+            error = Some(match error {
+                Some(prev) => format!("{}\n{}", prev, e),
+                None => e,
+            });
+        }
+        txt.push_str(&report::inputs_list(&v.inputs(), &self.context));
+        let assets = self.write_chart_data(i, v.chart_data()).and_then(|mut links| {
+            links.push_str(&self.write_artifacts(i, &v.artifacts())?);
+            Ok(links)
+        });
+        match assets {
+            Ok(links) => txt.push_str(&links),
+            Err(e) => {
+                let e = match error {
+                    Some(prev) => format!("{}\n{}", prev, e),
+                    None => e,
+                };
+                error = Some(e)
+            }
+        }
+        let mut section = Section::new(i, txt, error);
+        self.set_title(i, v, &mut section);
+        section.indicators = v.indicators();
+        section.covers = v.covers();
+        section.requirements = v.requirements();
+        section.location = v.location();
+        section.inputs = v.inputs();
+        section.add_rerun_hint(&self.title_of(i), &self.context);
+        if let Some(after_each) = &self.after_each {
+            after_each(&section.title, section.error.as_deref().map_or(Ok(()), Err));
+        }
+        section
+    }
+
+    /// Runs the validations, writes the report and fails the task if necessary
+    ///
+    /// If the `VALIDATE_FILTER` environment variable is set, only the
+    /// validations whose title contains its value are run and reported. If
+    /// the `VALIDATE_TITLE` one is, only the validation with exactly that
+    /// title—its declared one or, otherwise, `Validation N`, with `N` its
+    /// position—is. The report and error message of each failed validation
+    /// include the command that re-runs just that validation. If the `VALIDATE_SHARD` environment
+    /// variable is set, only the validations of that shard are run (see
+    /// [`Validator::set_shard`]), and if the `VALIDATE_TAGS` one is, only
+    /// those selected by its tags (see [`Validator::set_filter`]).
+    pub fn validate(&self) -> Result<(), String> {
+        self.context.reset_assets();
+        self.run_and_report(Instant::now(), Vec::new())
+    }
+
+    /// Runs the validations like [`Validator::validate`], but those pushed
+    /// with [`Validator::push_concurrent`] run concurrently (on the thread
+    /// pool of [rayon](https://docs.rs/rayon)) before the rest. The report
+    /// is still assembled in the order in which the validations were pushed.
+    ///
+    /// Only the validations pushed with [`Validator::push_concurrent`] run
+    /// concurrently, as the rest (e.g., those pushed with [`Validator::push`])
+    /// are not known to be `Send` and `Sync`; they run one after the other
+    /// once the concurrent ones are done. Validations written as functions
+    /// (e.g., with `#[valid]`) can run concurrently when wrapped in a
+    /// [`ValidatorWrapper`], which is `Send` and `Sync`.
+    ///
+    /// The hooks set with [`Validator::set_before_each`] are called for the
+    /// concurrent validations one after the other before they start, as they
+    /// may not be shared across threads; those set with
+    /// [`Validator::set_after_each`] are called as the report is assembled.
+    /// Concurrent validations are not started once the time budget is
+    /// exceeded or the maximum number of errors is reached; the latter
+    /// then applies to the rest in the order of the report.
+    ///
+    /// ```
+    /// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    /// use validate::{SeriesValidator, Validate, Validator, ValidatorWrapper};
+    ///
+    /// fn flat_series() -> Result<Box<dyn Validate>, String> {
+    ///     Ok(Box::new(SeriesValidator {
+    ///         expected: vec![1., 1., 1.],
+    ///         found: vec![1., 1., 1.],
+    ///         ..Default::default()
+    ///     }))
+    /// }
+    ///
+    /// let mut validator = Validator::new("Parallel", "report.html");
+    /// for i in 0..8 {
+    ///     validator.push_concurrent(SeriesValidator {
+    ///         expected: vec![1., 2., 3.],
+    ///         found: vec![1., 2., 3. + i as f64 * 1e-3],
+    ///         ..Default::default()
+    ///     });
+    /// }
+    /// validator.push_concurrent(ValidatorWrapper::new(
+    ///     "Flat series".to_string(),
+    ///     "A series that does not change".to_string(),
+    ///     flat_series,
+    /// ));
+    /// validator.validate_par().unwrap();
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn validate_par(&self) -> Result<(), String> {
+        use rayon::prelude::*;
+
+        self.context.reset_assets();
+        let start = Instant::now();
+        let shard = self.shard()?;
+        let filter = self.filter()?;
+        let title = TitleFilter::from_env();
+        let selected: Vec<&(usize, std::sync::Arc<dyn Validate + Send + Sync>)> = self
+            .concurrent
+            .iter()
+            .filter(|(i, _)| self.selects(*i, shard, filter.as_ref(), title.as_ref()))
+            .collect();
+        let (budget, max_errors) = (self.time_budget, self.max_errors);
+        let stops = move |failures: usize| {
+            budget.is_some_and(|budget| start.elapsed() > budget) || max_errors.is_some_and(|max| failures >= max)
+        };
+        let selected = if stops(0) { Vec::new() } else { selected };
+        if let Some(before_each) = &self.before_each {
+            for (i, _) in &selected {
+                before_each(*i, &self.title_of(*i));
+            }
+        }
+        let context = &self.context;
+        let failures = std::sync::atomic::AtomicUsize::new(0);
+        let done: Vec<(usize, ValidationResult)> = selected
+            .into_par_iter()
+            .filter_map(|(i, v)| {
+                // The ones that are not run are reported as such afterwards
+                if stops(failures.load(std::sync::atomic::Ordering::SeqCst)) {
+                    return None;
+                }
+                let result = v.validate_in(context);
+                if !result.is_ok() {
+                    failures.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                }
+                Some((*i, result))
+            })
+            .collect();
+        let mut results: Vec<Option<ValidationResult>> = Vec::with_capacity(self.validations.len());
+        results.resize_with(self.validations.len(), || None);
+        for (i, result) in done {
+            results[i] = Some(result);
+        }
+        self.run_and_report(start, results)
+    }
+
+    /// The part of the suite to run: the one given by the `VALIDATE_SHARD`
+    /// environment variable or, if that is not set, by [`Validator::set_shard`]
+    fn shard(&self) -> Result<Option<Shard>, String> {
+        match std::env::var(shard::SHARD_VAR) {
+            Ok(shard) => Ok(Some(shard.parse::<Shard>()?)),
+            Err(_) => Ok(self.shard),
+        }
+    }
+
+    /// The tags of the validations to run: those given by the
+    /// `VALIDATE_TAGS` environment variable or, if that is not set, by
+    /// [`Validator::set_filter`]
+    fn filter(&self) -> Result<Option<TagFilter>, String> {
+        match std::env::var(filter::TAGS_VAR) {
+            Ok(filter) => Ok(Some(filter.parse::<TagFilter>()?)),
+            Err(_) => Ok(self.filter.clone()),
+        }
+    }
+
+    /// Checks whether the validation at position `i` is run, given the
+    /// shard, the filter and the filter of its title (see [`Validator::validate`]).
+    /// Validations that do not declare their title are run when their title
+    /// must contain some text, as it is only known afterwards.
+    fn selects(&self, i: usize, shard: Option<Shard>, filter: Option<&TagFilter>, title: Option<&TitleFilter>) -> bool {
+        let tags = self
+            .tags
+            .iter()
+            .find(|(j, _)| *j == i)
+            .map(|(_, tags)| tags.as_slice())
+            .unwrap_or_default();
+        shard.is_none_or(|shard| shard.includes(i))
+            && filter.is_none_or(|filter| filter.matches(tags))
+            && title.is_none_or(|title| match title {
+                TitleFilter::Exact(_) => title.matches(&self.title_of(i)),
+                TitleFilter::Contains(_) => self.titles[i].as_ref().is_none_or(|t| title.matches(t)),
+            })
+    }
+
+    /// Runs the validations that were not run yet—the results of those that
+    /// were are given at their position in `results`—and writes the report
+    fn run_and_report(&self, start: Instant, results: Vec<Option<ValidationResult>>) -> Result<(), String> {
+        let sections = self.run_and_write(start, results)?;
+        self.conclude(&sections)
+    }
+
+    /// Runs the selected validations that were not run yet—the results of
+    /// those that were are given at their position in `results`—and writes
+    /// the report and the other outputs, returning the sections as reported
+    fn run_and_write(&self, start: Instant, mut results: Vec<Option<ValidationResult>>) -> Result<Vec<Section>, String> {
+        // Solve
+        let mut partial = if self.streaming {
+            Some(report::PartialReport::create(&self.target_file, &self.title, &self.context)?)
+        } else {
+            None
+        };
+        let shard = self.shard()?;
+        let filter = self.filter()?;
+        let title = TitleFilter::from_env();
+        let mut sections: Vec<Section> = Vec::with_capacity(self.validations.len());
+        let mut suggesting: Vec<(String, &dyn Validate)> = Vec::new();
+        for (i, v) in self.validations.iter().enumerate() {
+            if !self.selects(i, shard, filter.as_ref(), title.as_ref()) {
+                continue;
+            }
+            let failures = sections.iter().filter(|s| s.failed()).count();
+            let result = results.get_mut(i).and_then(Option::take);
+            let section = self.run_validation(i, v.as_ref(), start, failures, result);
+            if !v.defines_thresholds(&self.context) {
+                suggesting.push((section.title.clone(), v.as_ref()));
+            }
+            if let Some(partial) = partial.as_mut() {
+                partial.append(&section)?;
+            }
+            sections.push(section);
+        }
+        let duration = start.elapsed();
+        if let Some(hysteresis) = &self.hysteresis {
+            self.tolerate_failures(hysteresis, &mut sections)?;
+        }
+
+        // Write
+        let sections = self.write_outputs(sections, duration)?;
+        if let Some((file, margin)) = &self.threshold_suggestions {
+            self.write_threshold_suggestions(file, *margin, &suggesting)?;
+        }
+        if partial.is_some() && !matches!(self.compression, Compression::None) {
+            // The compressed report was written elsewhere
+            fs::remove_file(&self.target_file)
+                .map_err(|e| format!("Cannot remove partial report '{}': {}", self.target_file, e))?;
+        }
+        if let Some(shard) = shard {
+            let path = shard::results_file_for(Path::new(&self.target_file), shard);
+            shard::write_results(&path, shard, duration, &sections)?;
+        }
+        Ok(sections)
+    }
+
+    /// Merges the results written by the shards of a suite (see
+    /// [`Validator::set_shard`]) into a single report, written with the
+    /// settings of this `Validator` (which does not need any validation).
+    /// Fails if the results of some shard are missing, or if some of the
+    /// merged validations failed.
+    ///
+    /// The sections of the shards link to their assets relative to the shard
+    /// reports, so the merged report needs to be written next to them.
+    ///
+    /// ```no_run
+    /// use validate::Validator;
+    ///
+    /// let validator = Validator::new("Nightly", "report.html");
+    /// let shards: Vec<String> = (1..=8)
+    ///     .map(|i| format!("report_shard_{}_of_8.json", i))
+    ///     .collect();
+    /// validator.merge_shards(&shards).unwrap();
+    /// ```
+    pub fn merge_shards<P: AsRef<Path>>(&self, files: &[P]) -> Result<(), String> {
+        let (sections, duration) = shard::collect(files)?;
+        let sections = self.write_outputs(sections, duration)?;
+        self.conclude(&sections)
+    }
+
+    /// Runs the validations over and over (e.g., on a hardware-in-the-loop
+    /// rig that runs for days), in checkpoints that start every
+    /// `schedule.interval`, until `stop` is set or the maximum number of
+    /// checkpoints is reached. At each checkpoint the report—and the other
+    /// requested outputs—are written again, and the results are appended
+    /// to the history file of the schedule, if any. Returns the number of
+    /// checkpoints run.
+    ///
+    /// Each checkpoint runs the validations selected—and post-processes their
+    /// results—like [`Validator::validate`] does (e.g., shards, filters and
+    /// hysteresis apply). Failed validations do not stop soaking; they are
+    /// recorded in the report and in the history. The validations built by functions
+    /// marked with [`valid`] are built again at each checkpoint, so they
+    /// can read the latest data.
+    ///
+    /// ```no_run
+    /// use std::sync::atomic::AtomicBool;
+    /// use std::time::Duration;
+    /// use validate::{SoakSchedule, Validator};
+    ///
+    /// let validator = Validator::new("Rig", "report.html");
+    /// let schedule = SoakSchedule {
+    ///     interval: Duration::from_secs(600),
+    ///     history_file: Some("history.jsonl".into()),
+    ///     ..Default::default()
+    /// };
+    /// // Set from another thread (e.g., a Ctrl-C handler) to stop
+    /// let stop = AtomicBool::new(false);
+    /// validator.soak(&schedule, &stop).unwrap();
+    /// ```
+    pub fn soak(&self, schedule: &SoakSchedule, stop: &std::sync::atomic::AtomicBool) -> Result<usize, String> {
+        use std::sync::atomic::Ordering;
+
+        let mut checkpoints = 0;
+        loop {
+            let start = Instant::now();
+            self.context.reset_assets();
+            let sections = self.run_and_write(start, Vec::new())?;
+            checkpoints += 1;
+            if let Some(file) = &schedule.history_file {
+                soak::append_history(file, checkpoints, &sections)?;
+            }
+            console::print_summary(&sections, &self.target_file, self.verbosity);
+
+            if stop.load(Ordering::Relaxed) || schedule.max_checkpoints.is_some_and(|max| checkpoints >= max) {
+                return Ok(checkpoints);
+            }
+            soak::wait_until(start + schedule.interval, stop);
+            if stop.load(Ordering::Relaxed) {
+                return Ok(checkpoints);
+            }
+        }
+    }
+
+    /// Records the results of this run in the history file of `hysteresis`,
+    /// and then tolerates the failures of the validations that have not
+    /// failed on enough consecutive runs yet
+    fn tolerate_failures(&self, hysteresis: &Hysteresis, sections: &mut [Section]) -> Result<(), String> {
+        let history = soak::read_history(&hysteresis.history_file)?;
+        soak::append_history(&hysteresis.history_file, history.len() + 1, sections)?;
+        for section in sections.iter_mut() {
+            let streak = soak::failure_streak(&history, &section.title) + 1;
+            if streak >= hysteresis.consecutive_failures {
+                continue;
+            }
+            if let Some(error) = section.error.take() {
+                section.body += &format!(
+                    "\n\n**{}** ({} {} {} {}): {}\n",
+                    self.context.tr("Tolerated failure"),
+                    streak,
+                    self.context.tr("of"),
+                    hysteresis.consecutive_failures,
+                    self.context.tr("consecutive failures"),
+                    self.context.user_text(&error)
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the report—and the other requested outputs—with the sections
+    /// produced by the validations, returning them as reported
+    fn write_outputs(&self, mut sections: Vec<Section>, duration: Duration) -> Result<Vec<Section>, String> {
+        report::check_titles(&mut sections, self.duplicate_titles, &self.context);
+        // The validations selected by their exact title were known before running
+        if let Some(filter @ TitleFilter::Contains(_)) = TitleFilter::from_env() {
+            sections.retain(|s| filter.matches(&s.title));
+        }
+        report::sort_sections(&mut sections, self.order);
+
+        let all: Vec<&Section> = sections.iter().collect();
+        self.write_report(&self.target_file, &self.title, &all, true, duration)?;
+        if let Some(failures_file) = self.failures_file {
+            let failed: Vec<&Section> = sections.iter().filter(|s| s.failed()).collect();
+            let title = format!("{} ({})", self.title, self.context.tr("failures"));
+            self.write_report(failures_file, &title, &failed, false, duration)?;
+        }
+        if self.write_summary {
+            index::write_summary(Path::new(&self.target_file), &self.title, &sections)?;
+        }
+        if let Some(latex_dir) = &self.latex_dir {
+            latex::write_fragments(latex_dir, &sections)?;
+        }
+        if let Some(file) = &self.code_quality_file {
+            ci::write_code_quality(file, &sections, "Cargo.toml")?;
+        }
+        if let Some(file) = &self.sarif_file {
+            ci::write_sarif(file, &sections)?;
+        }
+        if let Some(file) = &self.json_file {
+            let duration = if self.deterministic { None } else { Some(duration) };
+            ci::write_json(file, &self.title, &sections, duration)?;
+        }
+        Ok(sections)
+    }
+
+    /// Writes the loosest thresholds suggested by `validations` as a TOML
+    /// profile (see [`Validator::set_threshold_suggestions`])
+    fn write_threshold_suggestions(
+        &self,
+        file: &Path,
+        margin: f64,
+        validations: &[(String, &dyn Validate)],
+    ) -> Result<(), String> {
+        let mut suggested: Option<Thresholds> = None;
+        let mut txt = format!(
+            "# Thresholds met by the validations of '{}' with a margin of {}\n",
+            self.title, margin
+        );
+        for (title, v) in validations {
+            if let Some(t) = v.suggested_thresholds(margin) {
+                txt += &format!("#  - {}\n", title);
+                suggested = Some(suggested.map_or(t, |s| s.loosest(t)));
+            }
+        }
+        match suggested {
+            Some(t) => txt += &t.to_toml("suggested"),
+            None => txt += "# (no validation without thresholds suggested any)\n",
+        }
+        fs::write(file, txt)
+            .map_err(|e| format!("Cannot write suggested thresholds to '{}': {}", file.display(), e))
+    }
+
+    /// Emits the results of the validations and fails if some of them failed
+    fn conclude(&self, sections: &[Section]) -> Result<(), String> {
+        #[cfg(any(feature = "tracing", feature = "log"))]
+        console::emit(sections);
+        console::print_summary(sections, &self.target_file, self.verbosity);
+        if sections.iter().any(|s| s.failed()) {
+            Err("Some validations failed...".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Extend<Box<dyn Validate>> for Validator<'a> {
+    fn extend<I: IntoIterator<Item = Box<dyn Validate>>>(&mut self, iter: I) {
+        self.push_all(iter)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> IntoIterator for Validator<'a> {
+    type Item = Box<dyn Validate>;
+    type IntoIter = std::vec::IntoIter<Box<dyn Validate>>;
+
+    /// Takes the validations out of the `Validator`, so they can be
+    /// moved into another one. Their tags and whether they can run
+    /// concurrently are lost; use [`Validator::merge`] to keep them.
+    fn into_iter(self) -> Self::IntoIter {
+        self.validations.into_iter()
+    }
+}
+
+/// The main trait of this crate. All validator modules need
+/// to comply with this trait.
+#[cfg(feature = "std")]
+pub trait Validate {
+    /// Runs a validation procedure, returning an error message if
+    /// the validation failed.
+    ///
+    /// Whether it fails or not,
+    /// it should write the results of the validations into `file`
+    /// so a full report is written.
+    fn validate(&self) -> ValidationResult;
+
+    /// The title of this validation. If given, the [`Validator`] writes it
+    /// as the heading of the section of this validation—so it should not be
+    /// written by [`Validate::validate`]—and uses it in the summary.
+    ///
+    /// By default, the title is the first heading written by [`Validate::validate`].
+    fn title(&self) -> Option<String> {
+        None
+    }
+
+    /// A description of what this validation checks, written under
+    /// its title (see [`Validate::title`]). It can contain Markdown.
+    fn description(&self) -> Option<String> {
+        None
+    }
+
+    /// This validation as [`std::any::Any`], so it can be downcast to its
+    /// concrete type once pushed into a [`Validator`] (see [`Validator::validations`]).
+    /// Implement it as `Some(self)` to allow this.
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        None
+    }
+
+    /// Runs the validation procedure under the settings of the report being
+    /// written (e.g., its language and system of units). This is what the
+    /// [`Validator`] calls.
+    ///
+    /// By default, this ignores the context and calls [`Validate::validate`].
+    fn validate_in(&self, _ctx: &ReportContext) -> ValidationResult {
+        self.validate()
+    }
+
+    /// The files that should be attached to the report of this
+    /// validation (e.g., snapshots of the input data). They are
+    /// queried after [`Validate::validate`] has been called.
+    fn artifacts(&self) -> Vec<Artifact> {
+        Vec::new()
+    }
+
+    /// The data plotted in the chart of this validation, if any. It
+    /// is queried after [`Validate::validate`] has been called.
+    fn chart_data(&self) -> Option<ChartData> {
+        None
+    }
+
+    /// The indicators calculated by this validation (e.g., the
+    /// Mean Bias Error), which are shown in the summary of the report.
+    /// They are queried after [`Validate::validate`] has been called.
+    fn indicators(&self) -> Vec<Indicator> {
+        Vec::new()
+    }
+
+    /// The settings under which this validation was run (e.g., random
+    /// seeds or solver tolerances), which are recorded in its section of
+    /// the report. They are queried after [`Validate::validate`] has been called.
+    fn inputs(&self) -> Vec<RunInput> {
+        Vec::new()
+    }
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"zone air temperature"` or `"surface conduction"`), which are
+    /// listed in the coverage section of the report (see [`Validator::set_coverage`]).
+    fn covers(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"` or the number of a clause of a standard), which are
+    /// mapped to the validations that verify them in the report.
+    fn requirements(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Checks whether this validation has any acceptance threshold, either
+    /// its own or inherited from the report (see [`Validator::set_strict`]).
+    ///
+    /// By default, validations are assumed to check something.
+    fn defines_thresholds(&self, _ctx: &ReportContext) -> bool {
+        true
+    }
+
+    /// Suggests the acceptance thresholds that this validation currently
+    /// meets with a `margin` (e.g., `1.2` allows errors 20% larger than the
+    /// current ones), to bootstrap the criteria of a newly instrumented
+    /// test rig (see [`Validator::set_threshold_suggestions`]).
+    ///
+    /// By default, validations suggest nothing.
+    fn suggested_thresholds(&self, _margin: f64) -> Option<Thresholds> {
+        None
+    }
+
+    /// Where this validation is defined in the source code, which failures
+    /// are mapped to in the reports for CI services (see
+    /// [`Validator::set_sarif_file`]). [`valid`] records it automatically.
+    fn location(&self) -> Option<SourceLocation> {
+        None
+    }
+}
+
+/// Boxed validations are validations too, so they can be pushed
+/// into a [`Validator`] either boxed or not
+#[cfg(feature = "std")]
+impl<V: Validate + ?Sized> Validate for Box<V> {
+    fn validate(&self) -> ValidationResult {
+        self.as_ref().validate()
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        self.as_ref().validate_in(ctx)
+    }
+
+    fn title(&self) -> Option<String> {
+        self.as_ref().title()
+    }
+
+    fn description(&self) -> Option<String> {
+        self.as_ref().description()
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        self.as_ref().as_any()
+    }
+
+    fn artifacts(&self) -> Vec<Artifact> {
+        self.as_ref().artifacts()
+    }
+
+    fn chart_data(&self) -> Option<ChartData> {
+        self.as_ref().chart_data()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        self.as_ref().indicators()
+    }
+
+    fn inputs(&self) -> Vec<RunInput> {
+        self.as_ref().inputs()
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.as_ref().covers()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.as_ref().requirements()
+    }
+
+    fn defines_thresholds(&self, ctx: &ReportContext) -> bool {
+        self.as_ref().defines_thresholds(ctx)
+    }
+
+    fn suggested_thresholds(&self, margin: f64) -> Option<Thresholds> {
+        self.as_ref().suggested_thresholds(margin)
+    }
+
+    fn location(&self) -> Option<SourceLocation> {
+        self.as_ref().location()
+    }
+}
+
+/// Shared validations are validations too (see [`Validator::push_concurrent`])
+#[cfg(feature = "std")]
+impl<V: Validate + ?Sized> Validate for std::sync::Arc<V> {
+    fn validate(&self) -> ValidationResult {
+        self.as_ref().validate()
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        self.as_ref().validate_in(ctx)
+    }
+
+    fn title(&self) -> Option<String> {
+        self.as_ref().title()
+    }
+
+    fn description(&self) -> Option<String> {
+        self.as_ref().description()
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        self.as_ref().as_any()
+    }
+
+    fn artifacts(&self) -> Vec<Artifact> {
+        self.as_ref().artifacts()
+    }
+
+    fn chart_data(&self) -> Option<ChartData> {
+        self.as_ref().chart_data()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        self.as_ref().indicators()
+    }
+
+    fn inputs(&self) -> Vec<RunInput> {
+        self.as_ref().inputs()
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.as_ref().covers()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.as_ref().requirements()
+    }
+
+    fn defines_thresholds(&self, ctx: &ReportContext) -> bool {
+        self.as_ref().defines_thresholds(ctx)
+    }
+
+    fn suggested_thresholds(&self, margin: f64) -> Option<Thresholds> {
+        self.as_ref().suggested_thresholds(margin)
+    }
+
+    fn location(&self) -> Option<SourceLocation> {
+        self.as_ref().location()
+    }
+}
+
+/// Reads a number of columns from a CSV, transforms them into f64
+#[cfg(feature = "csv")]
+pub fn from_csv<T: Numberish>(path: &str, cols: &[usize]) -> Vec<Vec<T>> {
+    let reader = File::open(path).unwrap();
+    let mut rdr = csv::Reader::from_reader(reader);
+
+    let mut ret: Vec<Vec<T>> = Vec::with_capacity(cols.len());
+    for _ in cols {
+        ret.push(Vec::new());
+    }
+
+    for record in rdr.records() {
+        let data = record.unwrap();
+        // dbg!(&data);
+
+        for (i, col) in cols.iter().enumerate() {
+            // dbg!(i,col);
+            // dbg!(data.get(*col));
+            match data.get(*col) {
+                Some(v) => {
+                    // dbg!(v.to_string());
+                    let v = v.trim();
+                    let v: T = v.parse::<f32>().unwrap().into();
+                    ret[i].push(v);
+                }
+                None => continue, //panic!("Index out of bounds: {}", *col)
+            }
+        }
+    }
+
+    ret
+}
+
+/// Module with some useful functions for calculating
+/// indicators for validation (e.g., Mean Squared Error)
+///
+/// Depending on `validate` with `default-features = false, features = ["core"]`
+/// keeps only the error metrics of this module, which then work in `no_std`
+/// environments with an allocator (e.g., firmware), giving the same results
+/// as in the reports.
+#[cfg(feature = "core")]
+pub mod stats;
+
+/// Filters for smoothing series before comparing them
+#[cfg(feature = "std")]
+pub mod smoothing;
+
+/// Interpolation for resampling series onto a different grid
+#[cfg(feature = "std")]
+pub mod interp;
+
+/// Normalization of series for the weather they were produced under
+#[cfg(feature = "std")]
+pub mod normalize;
+
+/// Charts for custom validators, drawn like those of the validators of this crate
+#[cfg(feature = "charts")]
+pub mod plot;
+
+/// The text of custom validators, built from blocks of Markdown
+#[cfg(feature = "std")]
+pub mod markdown;
+
+/// Utilities for testing validators and the suites that run them
+#[cfg(feature = "std")]
+pub mod testing;
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_from_csv() {
+        use crate::from_csv;
+
+        let data = from_csv::<f64>("./tests/test_data/data.csv", &[0, 1, 2, 3]);
+        for (c, d) in data.iter().enumerate() {
+            assert_eq!(d.len(), 3);
+            for (i, found) in d.iter().enumerate() {
+                let exp = 10 * i + c;
+                assert_close!(exp as f64, *found);
+            }
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_report() {
+        use crate::{Compression, Validator};
+
+        let report = std::env::temp_dir().join("validate_test_gzip.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Compressed", &report);
+        validator.set_compression(Compression::Gzip);
+        validator.set_optimize_svg(true);
+        validator.validate().unwrap();
+        assert!(std::path::Path::new(&format!("{}.gz", report)).exists());
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_gzip_without_feature() {
+        use crate::{Compression, Validator};
+
+        let report = std::env::temp_dir().join("validate_test_no_gzip.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Compressed", &report);
+        validator.set_compression(Compression::Gzip);
+        let e = validator.validate().unwrap_err();
+        assert!(e.contains("requires the `gzip` feature"), "{}", e);
+    }
+
+    #[cfg(feature = "charts")]
+    #[test]
+    fn test_markdown_report() {
+        use crate::{ReportFormat, SeriesValidator, Validator};
+
+        let dir = std::env::temp_dir().join("validate_test_markdown");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("report.md");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Markdown", &report);
+        validator.set_report_format(ReportFormat::Markdown);
+        validator.push(Box::new(SeriesValidator {
+            expected: vec![1., 2.],
+            found: vec![1., 2.],
+            chart_title: Some("Temperatures".into()),
+            ..Default::default()
+        }));
+        validator.validate().unwrap();
+
+        let md = std::fs::read_to_string(report.as_ref()).unwrap();
+        assert!(md.starts_with("# Markdown"));
+        assert!(!md.contains("<svg"));
+        assert!(md.contains("![Temperatures](report_assets/chart_1.svg)"));
+        let svg = std::fs::read_to_string(dir.join("report_assets/chart_1.svg")).unwrap();
+        assert!(svg.starts_with("<svg"));
+    }
+
+    #[test]
+    fn test_deterministic_report() {
+        use crate::{SeriesValidator, Validator};
+
+        let report = std::env::temp_dir().join("validate_test_deterministic.html");
+        let report = report.to_string_lossy();
+        let run = || {
+            let mut validator = Validator::new("Deterministic", &report);
+            validator.set_deterministic(true);
+            validator.push(Box::new(SeriesValidator {
+                expected: vec![0.1, 0.2, 0.3],
+                found: vec![0.1, 0.2, 0.3 - 1e-12],
+                ..Default::default()
+            }));
+            validator.validate().unwrap();
+            std::fs::read_to_string(report.as_ref()).unwrap()
+        };
+        let first = run();
+        assert_eq!(first, run());
+        assert!(!first.contains("Duration"));
+        assert!(first.contains("Mean Bias Error: 0.0000"));
+    }
+
+    #[test]
+    fn test_threshold_suggestions() {
+        use crate::{ScatterValidator, SeriesValidator, Validator};
+
+        let dir = std::env::temp_dir();
+        let report = dir.join("validate_test_suggestions.html");
+        let report = report.to_string_lossy();
+        let suggestions = dir.join("validate_test_suggestions.toml");
+        let mut validator = Validator::new("Suggestions", &report);
+        validator.set_threshold_suggestions(&suggestions, 1.2);
+        validator.push(Box::new(SeriesValidator {
+            expected: vec![0., 5., 10.],
+            found: vec![0., 6., 10.],
+            ..Default::default()
+        }));
+        // Validations with thresholds do not suggest any
+        validator.push(Box::new(ScatterValidator {
+            expected: vec![0., 5., 10.],
+            found: vec![0., 5., 10.],
+            allowed_r2: Some(0.9),
+            ..Default::default()
+        }));
+        validator.validate().unwrap();
+
+        let txt = std::fs::read_to_string(&suggestions).unwrap();
+        assert_eq!(txt.matches("#  - ").count(), 1);
+        assert!(txt.contains("[suggested]\n"));
+        // An error of 1 in a range of 10, with a margin of 20%
+        assert!(txt.contains("max_error_fraction = 0.12\n"));
+        assert!(!txt.contains("r2"));
+    }
+
+    #[test]
+    fn test_run_inputs() {
+        use crate::{RunInput, SeriesValidator, Validator};
+
+        let dir = std::env::temp_dir();
+        let report = dir.join("validate_test_inputs.html");
+        let report = report.to_string_lossy();
+        let data = dir.join("validate_test_inputs.csv");
+        std::fs::write(&data, "a").unwrap();
+        let hashed = RunInput::file_hash(&data).unwrap();
+        // The FNV-1a hash of "a"
+        assert_eq!(hashed.value, "fnv1a64:af63dc4c8601ec8c");
+        assert!(RunInput::file_hash(dir.join("validate_test_missing.csv")).is_err());
+
+        let mut validator = Validator::new("Inputs", &report);
+        validator.push(Box::new(SeriesValidator {
+            expected: vec![1., 2.],
+            found: vec![1., 2.],
+            inputs: vec![RunInput::new("seed", 42), RunInput::new("solver", "RK4"), hashed],
+            ..Default::default()
+        }));
+        validator.validate().unwrap();
+
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(html.contains("<li>seed: <code>42</code></li>"));
+        assert!(html.contains("<li>solver: <code>RK4</code></li>"));
+        assert!(html.contains("<code>fnv1a64:af63dc4c8601ec8c</code>"));
+        assert!(html.contains(std::env::consts::ARCH));
+    }
+
+    #[test]
+    fn test_time_budget() {
+        use crate::{SeriesValidator, Validator};
+
+        let report = std::env::temp_dir().join("validate_test_budget.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Budget", &report);
+        validator.set_time_budget(std::time::Duration::ZERO);
+        validator.push(Box::new(SeriesValidator {
+            expected: vec![1., 2.],
+            found: vec![1., 2.],
+            ..Default::default()
+        }));
+        assert!(validator.validate().is_err());
+
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(html.contains("<strong>Not run (time budget exceeded)</strong>"));
+        assert!(html.contains("<li>Time budget: <code>0.00 s</code></li>"));
+    }
+
+    #[test]
+    fn test_soak() {
+        use crate::testing::{AlwaysFail, AlwaysPass};
+        use crate::{SoakSchedule, TagFilter, Validator, Verbosity};
+        use std::sync::atomic::AtomicBool;
+
+        let dir = std::env::temp_dir();
+        let report = dir.join("validate_test_soak.html");
+        let report = report.to_string_lossy();
+        let history = dir.join(format!("validate_test_soak_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&history);
+        let mut validator = Validator::new("Soak", &report);
+        validator.set_verbosity(Verbosity::Quiet);
+        validator.push(AlwaysPass);
+        validator.push_tagged(AlwaysFail, &["flaky"]);
+        let schedule = SoakSchedule {
+            interval: std::time::Duration::ZERO,
+            max_checkpoints: Some(3),
+            history_file: Some(history.clone()),
+        };
+        // Failures do not stop soaking
+        assert_eq!(validator.soak(&schedule, &AtomicBool::new(false)), Ok(3));
+        let txt = std::fs::read_to_string(&history).unwrap();
+        assert_eq!(txt.lines().count(), 3);
+        assert!(txt.lines().last().unwrap().starts_with("{\"checkpoint\":3,"));
+        assert!(std::fs::read_to_string(&*report).unwrap().contains("This validation always fails"));
+
+        // A stopped soak runs a last checkpoint
+        assert_eq!(validator.soak(&schedule, &AtomicBool::new(true)), Ok(1));
+
+        // Validations are selected as when validating
+        validator.set_filter(TagFilter::new(&[], &["flaky"]));
+        assert_eq!(validator.soak(&schedule, &AtomicBool::new(true)), Ok(1));
+        assert!(!std::fs::read_to_string(&*report).unwrap().contains("This validation always fails"));
+        std::fs::remove_file(&history).unwrap();
+    }
+
+    #[test]
+    fn test_max_errors() {
+        use crate::testing::{AlwaysFail, AlwaysPass};
+        use crate::ValidatorBuilder;
+        use std::cell::Cell;
+
+        let report = std::env::temp_dir().join("validate_test_max_errors.html");
+        let report = report.to_string_lossy();
+        let run = Cell::new(0);
+        let mut validator = ValidatorBuilder::default()
+            .title("Max errors")
+            .output_html(&report)
+            .max_errors(2)
+            .build()
+            .unwrap();
+        validator.set_before_each(|_, _| run.set(run.get() + 1));
+        validator.push(AlwaysFail);
+        validator.push(AlwaysPass);
+        validator.push(AlwaysFail);
+        validator.push(AlwaysPass);
+        assert!(validator.validate().is_err());
+        assert_eq!(run.get(), 3);
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(html.contains("<strong>Not run (too many validations failed)</strong>"));
+
+        let mut validator = ValidatorBuilder::default().output_html(&report).fail_fast(true).build().unwrap();
+        validator.push(AlwaysFail);
+        validator.push(AlwaysPass);
+        assert!(validator.validate().is_err());
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(html.contains("Validation report"));
+        assert!(html.contains("<strong>Not run (too many validations failed)</strong>"));
+
+        assert!(ValidatorBuilder::default().output_html(&report).max_errors(0).build().is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_validate_par() {
+        use crate::testing::{AlwaysFail, FailsWith};
+        use crate::{Validate, ValidationResult, Validator};
+        use std::cell::Cell;
+        use std::time::Duration;
+
+        /// Passes after a while, so the first ones finish last
+        struct Slow(u64);
+        impl Validate for Slow {
+            fn validate(&self) -> ValidationResult {
+                std::thread::sleep(Duration::from_millis(60 - 20 * self.0));
+                ValidationResult::Ok(format!("Slow validation number {}", self.0))
+            }
+        }
+
+        let report = std::env::temp_dir().join("validate_test_validate_par.html");
+        let report = report.to_string_lossy();
+        let run = Cell::new(0);
+        let reported = Cell::new(0);
+        let mut validator = Validator::new("Parallel", &report);
+        validator.set_before_each(|_, _| run.set(run.get() + 1));
+        validator.set_after_each(|_, _| reported.set(reported.get() + 1));
+        validator.push_concurrent(Slow(0));
+        validator.push(FailsWith("Sequential failure".to_string()));
+        validator.push_concurrent(Slow(1));
+        validator.push_concurrent(Slow(2));
+        validator.push_concurrent(AlwaysFail);
+        assert!(validator.validate_par().is_err());
+        assert_eq!(run.get(), 5);
+        assert_eq!(reported.get(), 5);
+        let html = std::fs::read_to_string(&*report).unwrap();
+        let positions: Vec<usize> = [
+            "Slow validation number 0",
+            "Sequential failure",
+            "Slow validation number 1",
+            "Slow validation number 2",
+            "This validation always fails",
+        ]
+        .iter()
+        .map(|txt| html.find(txt).unwrap())
+        .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+        // Concurrent validations also run sequentially
+        assert!(validator.validate().is_err());
+        assert_eq!(run.get(), 10);
+        assert_eq!(reported.get(), 10);
+
+        // Nor are they started out of time, or with no errors allowed
+        validator.set_time_budget(Duration::ZERO);
+        assert!(validator.validate_par().is_err());
+        assert_eq!(run.get(), 10);
+        assert!(!std::fs::read_to_string(&*report).unwrap().contains("Slow validation number"));
+        validator.time_budget = None;
+        validator.set_max_errors(0);
+        assert!(validator.validate_par().is_err());
+        assert_eq!(run.get(), 10);
+        assert!(!std::fs::read_to_string(&*report).unwrap().contains("Slow validation number"));
+        validator.max_errors = None;
+
+        // Merged validations keep running concurrently
+        let mut merged = Validator::new("Merged", &report);
+        merged.push(FailsWith("Sequential failure".to_string()));
+        merged.merge(validator);
+        assert_eq!(merged.concurrent.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![1, 3, 4, 5]);
+        assert!(merged.validate_par().is_err());
+    }
+
+    #[test]
+    fn test_tag_filter() {
+        use crate::testing::{AlwaysFail, AlwaysPass, FailsWith};
+        use crate::{TagFilter, Validator};
+        use std::cell::RefCell;
+
+        let report = std::env::temp_dir().join("validate_test_tag_filter.html");
+        let report = report.to_string_lossy();
+        let run = RefCell::new(Vec::new());
+        let mut validator = Validator::new("Filtered", &report);
+        validator.set_before_each(|i, _| run.borrow_mut().push(i));
+        validator.push_tagged(AlwaysPass, &["thermal"]);
+        validator.push_tagged(AlwaysFail, &["thermal", "slow"]);
+        validator.push(FailsWith("Untagged".to_string()));
+        validator.push_tagged(AlwaysPass, &["lighting"]);
+        validator.set_filter("thermal,!slow".parse().unwrap());
+        assert!(validator.validate().is_ok());
+        assert_eq!(*run.borrow(), vec![0]);
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(html.contains("Filtered by tags: <strong>thermal, !slow</strong>"));
+        assert!(!html.contains("Untagged"));
+
+        run.borrow_mut().clear();
+        validator.set_filter(TagFilter::new(&[], &["thermal"]));
+        assert!(validator.validate().is_err());
+        assert_eq!(*run.borrow(), vec![2, 3]);
+
+        // The tags are kept when merging
+        run.borrow_mut().clear();
+        let mut merged = Validator::new("Merged", &report);
+        merged.set_before_each(|i, _| run.borrow_mut().push(i));
+        merged.push(AlwaysPass);
+        merged.merge(validator);
+        merged.set_filter(TagFilter::new(&["thermal"], &["slow"]));
+        assert!(merged.validate().is_ok());
+        assert_eq!(*run.borrow(), vec![1]);
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        use crate::testing::{AlwaysFail, AlwaysPass};
+        use crate::{Hysteresis, Validator};
+
+        let dir = std::env::temp_dir();
+        let report = dir.join("validate_test_hysteresis.html");
+        let report = report.to_string_lossy();
+        let history = dir.join(format!("validate_test_hysteresis_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&history);
+        let mut validator = Validator::new("Nightly", &report);
+        validator.push(AlwaysPass);
+        validator.push(AlwaysFail);
+        assert!(validator
+            .set_hysteresis(Hysteresis {
+                history_file: history.clone(),
+                consecutive_failures: 0,
+            })
+            .is_err());
+        validator
+            .set_hysteresis(Hysteresis {
+                history_file: history.clone(),
+                consecutive_failures: 2,
+            })
+            .unwrap();
+
+        // The first failure is tolerated, but not the second one
+        assert!(validator.validate().is_ok());
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(html.contains("<strong>Tolerated failure</strong> (1 of 2 consecutive failures)"));
+        assert!(validator.validate().is_err());
+        let txt = std::fs::read_to_string(&history).unwrap();
+        assert_eq!(txt.lines().count(), 2);
+        assert!(txt.lines().all(|l| l.contains("\"failed\":1")));
+        std::fs::remove_file(&history).unwrap();
+    }
+
+    #[test]
+    fn test_hooks() {
+        use crate::{SeriesValidator, Validator};
+        use std::cell::RefCell;
+
+        let log = RefCell::new(Vec::new());
+        let report = std::env::temp_dir().join("validate_test_hooks.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Hooks", &report);
+        validator.set_before_each(|i, title| log.borrow_mut().push(format!("before {} {}", i, title)));
+        validator.set_after_each(|title, result| {
+            log.borrow_mut().push(format!("after {} {}", title, result.is_ok()))
+        });
+        for found in [1., 3.] {
+            validator.push(Box::new(SeriesValidator {
+                expected: vec![1., 2.],
+                found: vec![found, 2.],
+                allowed_max_error: Some(1.),
+                ..Default::default()
+            }));
+        }
+        assert!(validator.validate().is_err());
+        drop(validator);
+
+        assert_eq!(
+            log.into_inner(),
+            vec![
+                "before 0 Validation 1",
+                "after Validation 1 true",
+                "before 1 Validation 2",
+                "after Validation 2 false"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_streaming() {
+        use crate::{SeriesValidator, Validator};
+
+        let path = std::env::temp_dir().join("validate_test_streaming.html");
+        let report = path.to_string_lossy();
+        let mut validator = Validator::new("Streaming", &report);
+        validator.set_streaming(true);
+        // The first section is in the report before the second validation runs
+        validator.set_before_each(|i, _| {
+            if i == 1 {
+                let html = std::fs::read_to_string(&path).unwrap();
+                assert!(html.contains("Partial report"));
+                assert!(html.contains("Mean Bias Error"));
+            }
+        });
+        for _ in 0..2 {
+            validator.push(Box::new(SeriesValidator {
+                expected: vec![1., 2.],
+                found: vec![1., 2.],
+                ..Default::default()
+            }));
+        }
+        validator.validate().unwrap();
+
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert!(!html.contains("Partial report"));
+        assert!(html.contains("validate-summary"));
+    }
+
+    #[test]
+    fn test_shards() {
+        use crate::{Shard, Validate, ValidationResult, Validator};
+
+        struct Case(usize);
+        impl Validate for Case {
+            fn validate(&self) -> ValidationResult {
+                let txt = format!("### Case {}", self.0);
+                if self.0 == 3 {
+                    ValidationResult::Err(txt, "Case 3 failed".into())
+                } else {
+                    ValidationResult::Ok(txt)
+                }
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("validate_test_shards_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("report.html");
+        let report = path.to_string_lossy();
+        let build = |shard: Shard| {
+            let mut validator = Validator::new("Sharded", &report);
+            validator.set_shard(shard);
+            validator.extend((0..5).map(|i| Box::new(Case(i)) as Box<dyn Validate>));
+            validator
+        };
+        assert!(build(Shard::new(1, 2).unwrap()).validate().is_ok());
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert!(html.contains("Case 2") && !html.contains("Case 3"));
+        assert!(build(Shard::new(2, 2).unwrap()).validate().is_err());
+
+        let merged = Validator::new("Sharded", &report);
+        let shards = [dir.join("report_shard_2_of_2.json"), dir.join("report_shard_1_of_2.json")];
+        assert!(merged.merge_shards(&shards).is_err());
+        let html = std::fs::read_to_string(&path).unwrap();
+        let positions: Vec<usize> = (0..5).map(|i| html.find(&format!("<h3>Case {}", i)).unwrap()).collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        assert!(merged.merge_shards(&shards[..1]).unwrap_err().contains("1/2"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_title_and_description() {
+        use crate::{Validate, ValidationResult, Validator};
+
+        struct Named;
+        impl Validate for Named {
+            fn validate(&self) -> ValidationResult {
+                ValidationResult::Ok("All good".into())
+            }
+            fn title(&self) -> Option<String> {
+                Some("Zone temperature".into())
+            }
+            fn description(&self) -> Option<String> {
+                Some("Checks the *air* temperature".into())
+            }
+        }
+
+        let report = std::env::temp_dir().join("validate_test_title.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Titles", &report);
+        validator.push(Box::new(Named));
+        validator.validate().unwrap();
+
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(html.contains("<h2>Zone temperature</h2>\n<p>Checks the <em>air</em> temperature</p>\n<p>All good</p>"));
+        assert!(html.contains("<td>1</td><td><a href=\"#zone-temperature\">Zone temperature</a></td>"));
+        assert!(html.contains("<div class=\"validate_anchor\" id=\"zone-temperature\"></div>"));
+    }
+
+    #[test]
+    fn test_inspect_validations() {
+        use crate::{ScatterValidator, SeriesValidator, ValidatorWrapper, Validator};
+
+        let report = std::env::temp_dir().join("validate_test_inspect.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Inspect", &report);
+        assert!(validator.is_empty());
+        validator.push(Box::new(SeriesValidator::<f64>::default()));
+        validator.push(Box::new(ValidatorWrapper::new(
+            "Wrapped".into(),
+            String::new(),
+            || Ok(Box::new(ScatterValidator::<f64>::default())),
+        )));
+        assert_eq!(validator.len(), 2);
+        assert_eq!(validator.titles(), vec!["Validation 1", "Wrapped"]);
+
+        let n_series = validator
+            .validations()
+            .filter_map(|v| v.as_any())
+            .filter(|v| v.is::<SeriesValidator<f64>>())
+            .count();
+        assert_eq!(n_series, 1);
+    }
+
+    #[test]
+    fn test_duplicate_titles_at_push() {
+        use crate::{DuplicateTitles, SeriesValidator, Validate, Validator, ValidatorWrapper};
+
+        fn zone() -> Result<Box<dyn Validate>, String> {
+            Ok(Box::new(SeriesValidator {
+                expected: vec![1., 2.],
+                found: vec![1., 2.],
+                ..Default::default()
+            }))
+        }
+
+        let report = std::env::temp_dir().join("validate_test_duplicate_push.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Zones", &report);
+        validator.push(ValidatorWrapper::new("Zone A".into(), String::new(), zone));
+        validator.push(ValidatorWrapper::new("Zone A".into(), String::new(), zone));
+        validator.push_all([Box::new(ValidatorWrapper::new("Zone A (2)".into(), String::new(), zone)) as Box<dyn Validate>]);
+        assert_eq!(validator.titles(), ["Zone A", "Zone A (2)", "Zone A (2) (2)"]);
+
+        validator.validate().unwrap();
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(html.contains("<h2>Zone A (2)</h2>"));
+        assert!(html.contains("<div class=\"validate_anchor\" id=\"zone-a-2-2\"></div>"));
+
+        validator.set_duplicate_titles(DuplicateTitles::Fail);
+        assert!(validator.validate().is_err());
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(html.contains("Duplicate title: 'Zone A'") && html.contains("Duplicate title: 'Zone A (2)'"));
+        assert!(html.contains("<h2>Zone A (2)</h2>"));
+    }
+
+    #[test]
+    fn test_user_text_is_escaped() {
+        use crate::{RunInput, SeriesValidator, TextPolicy, Validator};
+
+        let report = std::env::temp_dir().join("validate_test_escaped.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Cases <script>alert(1)</script>", &report);
+        validator.push(Box::new(SeriesValidator {
+            expected: vec![1., 2.],
+            found: vec![1., 2.],
+            inputs: vec![RunInput::new("<b>seed</b> *n*", 42)],
+            ..Default::default()
+        }));
+        validator.validate().unwrap();
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(!html.contains("<script>alert"));
+        assert!(html.contains("<title>Cases &lt;script&gt;alert(1)&lt;/script&gt;</title>"));
+        assert!(html.contains("<li>&lt;b&gt;seed&lt;/b&gt; <em>n</em>: <code>42</code></li>"));
+
+        validator.set_text_policy(TextPolicy::PlainText);
+        validator.validate().unwrap();
+        let html = std::fs::read_to_string(&*report).unwrap();
+        assert!(html.contains("<li>&lt;b&gt;seed&lt;/b&gt; *n*: <code>42</code></li>"));
+    }
+
+    #[test]
+    fn test_assert_close_correct() {
+        assert_close!(1., 2., 2.);
+        assert_not_close!(1., 21., 1.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_close_relative_fail() {
+        assert_close!(0., 1e-3, abs = 1e-6, rel = 0.5);
     }
 
     #[test]
@@ -0,0 +1,195 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+//! Building the text of custom validators (see [`crate::Validate`]) from
+//! blocks—headings, paragraphs, lists, tables of metrics and figures—instead
+//! of formatting Markdown by hand, so their texts are styled like those of
+//! the validators of this crate, translated, and escaped following the
+//! [`crate::TextPolicy`] of the report.
+//!
+//! The builder is [`markdown::Section`](Section) rather than `report::Section`,
+//! as the `report` module of this crate is private: it is where the sections
+//! of the report itself are assembled from the texts of the validations.
+//!
+//! # Example
+//!
+//! ```
+//! use validate::markdown::Section;
+//! use validate::{Indicator, ReportContext, Validate, ValidationResult};
+//!
+//! struct PeakLoad {
+//!     expected: f64,
+//!     found: f64,
+//! }
+//!
+//! impl Validate for PeakLoad {
+//!     fn validate(&self) -> ValidationResult {
+//!         self.validate_in(&ReportContext::default())
+//!     }
+//!
+//!     fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+//!         let error = (self.found - self.expected) / self.expected;
+//!         let txt = Section::new()
+//!             .paragraph("Peak cooling load of the <Zone 1> model")
+//!             .metrics(&[Indicator::new("Relative error", error)])
+//!             .heading("Errors")
+//!             .bullets(["No errors found"])
+//!             .render(ctx);
+//!         assert!(txt.contains("&lt;Zone 1&gt;"));
+//!         ValidationResult::Ok(txt)
+//!     }
+//! }
+//!
+//! assert!(PeakLoad { expected: 10., found: 10.2 }.validate().is_ok());
+//! ```
+
+use crate::{Indicator, ReportContext};
+
+/// A block of a [`Section`]
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    Bullets(Vec<String>),
+    Metrics(Vec<Indicator>),
+    Figure(String, Option<String>),
+}
+
+/// The text of a validation, built block by block and written as Markdown
+/// by [`Section::render`]. The texts given are translated and escaped when
+/// rendering; the figures are written unchanged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Section {
+    blocks: Vec<Block>,
+}
+
+impl Section {
+    /// Creates an empty `Section`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a heading, at the level of those of the validators of this
+    /// crate (e.g., `Errors`)
+    pub fn heading<S: Into<String>>(mut self, txt: S) -> Self {
+        self.blocks.push(Block::Heading(txt.into()));
+        self
+    }
+
+    /// Adds a paragraph
+    pub fn paragraph<S: Into<String>>(mut self, txt: S) -> Self {
+        self.blocks.push(Block::Paragraph(txt.into()));
+        self
+    }
+
+    /// Adds a bullet list
+    pub fn bullets<I: IntoIterator<Item = S>, S: Into<String>>(mut self, items: I) -> Self {
+        self.blocks.push(Block::Bullets(items.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Adds a table with the name and value of each metric. It is written
+    /// as a list if the report does not render tables (see
+    /// [`crate::MarkdownOptions`]).
+    pub fn metrics(mut self, metrics: &[Indicator]) -> Self {
+        self.blocks.push(Block::Metrics(metrics.to_vec()));
+        self
+    }
+
+    /// Adds a figure (e.g., a chart drawn with [`crate::plot`]), with an
+    /// optional caption below it
+    pub fn figure<F: std::fmt::Display>(mut self, figure: F, caption: Option<&str>) -> Self {
+        self.blocks.push(Block::Figure(figure.to_string(), caption.map(String::from)));
+        self
+    }
+
+    /// Writes the section as Markdown, with the settings of the report
+    pub fn render(&self, ctx: &ReportContext) -> String {
+        let text = |txt: &str| ctx.user_text(ctx.tr(txt));
+        let blocks: Vec<String> = self
+            .blocks
+            .iter()
+            .map(|block| match block {
+                Block::Heading(txt) => format!("#### {}:\n", text(txt)),
+                Block::Paragraph(txt) => format!("{}\n", text(txt)),
+                Block::Bullets(items) => items.iter().map(|i| format!(" * {}\n", text(i))).collect(),
+                Block::Metrics(metrics) if ctx.markdown_options().tables => {
+                    let mut ret = format!("| {} | {} |\n|---|---:|\n", ctx.tr("Metric"), ctx.tr("Value"));
+                    for m in metrics {
+                        ret.push_str(&format!("| {} | {:.4} |\n", ctx.user_cell(ctx.tr(&m.name)), m.value));
+                    }
+                    ret
+                }
+                Block::Metrics(metrics) => metrics
+                    .iter()
+                    .map(|m| format!(" * {}: {:.4}\n", text(&m.name), m.value))
+                    .collect(),
+                Block::Figure(figure, caption) => match caption {
+                    Some(caption) => format!("{}\n\n*{}*\n", figure.trim(), text(caption)),
+                    None => format!("{}\n", figure.trim()),
+                },
+            })
+            .collect();
+        blocks.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MarkdownOptions, TextPolicy};
+
+    #[test]
+    fn test_section() {
+        let section = Section::new()
+            .heading("Errors")
+            .bullets(["RMSE is *too* high", "a | b"])
+            .metrics(&[Indicator::new("a | b", 0.5)])
+            .figure("<svg></svg>", Some("The <data>"));
+        let ctx = ReportContext::default();
+        assert_eq!(
+            section.render(&ctx),
+            "#### Errors:\n\n * RMSE is *too* high\n * a | b\n\n| Metric | Value |\n|---|---:|\n| a \\| b | 0.5000 |\n\n<svg></svg>\n\n*The &lt;data&gt;*\n"
+        );
+
+        // With the settings of a report
+        let ctx = ReportContext {
+            text_policy: TextPolicy::PlainText,
+            markdown: MarkdownOptions {
+                tables: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let txt = section.render(&ctx);
+        assert!(txt.contains(" * RMSE is \\*too\\* high\n"));
+        assert!(txt.contains(" * a \\| b: 0.5000\n"));
+
+        // Plain text in tables is escaped only once
+        let ctx = ReportContext {
+            text_policy: TextPolicy::PlainText,
+            ..Default::default()
+        };
+        let txt = section.render(&ctx);
+        assert!(txt.contains("| a \\| b | 0.5000 |\n"));
+        assert!(!txt.contains("\\\\|"));
+    }
+}
@@ -0,0 +1,220 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::numberish::Numberish;
+use std::fmt;
+
+/// The kind of degree-days, with their base temperature (in C)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DegreeDays {
+    /// Heating degree-days: how far the outdoor temperature
+    /// falls below the base temperature
+    Heating(f64),
+
+    /// Cooling degree-days: how far the outdoor temperature
+    /// rises above the base temperature
+    Cooling(f64),
+}
+
+impl fmt::Display for DegreeDays {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DegreeDays::Heating(base) => write!(f, "heating degree-days (base {} C)", base),
+            DegreeDays::Cooling(base) => write!(f, "cooling degree-days (base {} C)", base),
+        }
+    }
+}
+
+/// Adds up the degree-days of a series of outdoor temperatures (in C),
+/// sampled `samples_per_day` times a day (e.g., `24.` for hourly data).
+///
+/// # The math
+/// ```math
+/// HDD = \frac{1}{s}\sum_i \max(T_{base} - T_i, 0) \qquad CDD = \frac{1}{s}\sum_i \max(T_i - T_{base}, 0)
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::normalize::{degree_days, DegreeDays};
+///
+/// // Two days of daily means
+/// assert_eq!(degree_days(&[10., 20.], DegreeDays::Heating(18.), 1.), 8.);
+/// assert_eq!(degree_days(&[10., 20.], DegreeDays::Cooling(18.), 1.), 2.);
+/// ```
+pub fn degree_days<T: Numberish>(temperature: &[T], kind: DegreeDays, samples_per_day: f64) -> f64 {
+    let sum: f64 = temperature
+        .iter()
+        .map(|t| {
+            let t: f64 = (*t).into();
+            match kind {
+                DegreeDays::Heating(base) => (base - t).max(0.),
+                DegreeDays::Cooling(base) => (t - base).max(0.),
+            }
+        })
+        .sum();
+    sum / samples_per_day
+}
+
+/// A normalization of the expected values for the weather of the found ones,
+/// so that a model run (or a building measured) in one climate can be fairly
+/// compared against data from another. The expected values are scaled by the
+/// ratio of the degree-days—or of the incident radiation—of the weather of
+/// the found values to that of the expected ones.
+///
+/// # Example
+///
+/// ```
+/// use validate::normalize::{DegreeDays, Normalization};
+///
+/// // The expected data come from a colder winter
+/// let n = Normalization::DegreeDays {
+///     kind: DegreeDays::Heating(18.),
+///     samples_per_day: 1.,
+///     expected_temperature: vec![0., 2., 4.],
+///     found_temperature: vec![6., 8., 10.],
+/// };
+/// assert_eq!(n.totals(3).unwrap(), (48., 30.));
+/// assert_eq!(n.scale(3).unwrap(), 0.625);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Normalization {
+    /// By the degree-days of the outdoor temperatures (in C) under which
+    /// each series was produced, for loads driven by the outdoor temperature
+    DegreeDays {
+        /// The kind of degree-days
+        kind: DegreeDays,
+        /// The number of samples in a day (e.g., `24.` for hourly data)
+        samples_per_day: f64,
+        /// The outdoor temperature at each sample of the expected series
+        expected_temperature: Vec<f64>,
+        /// The outdoor temperature at each sample of the found series
+        found_temperature: Vec<f64>,
+    },
+
+    /// By the solar radiation incident on the relevant surfaces while each
+    /// series was produced, for quantities driven by the sun (e.g., the
+    /// production of a photovoltaic array)
+    Radiation {
+        /// The incident radiation at each sample of the expected series
+        expected_radiation: Vec<f64>,
+        /// The incident radiation at each sample of the found series
+        found_radiation: Vec<f64>,
+    },
+}
+
+impl Normalization {
+    /// The total degree-days—or radiation—of the weather of the expected and
+    /// of the found series, which must have `n` samples.
+    ///
+    /// # Errors
+    ///
+    /// * if the auxiliary series do not have `n` samples
+    /// * if the number of samples per day is not positive
+    pub fn totals(&self, n: usize) -> Result<(f64, f64), String> {
+        let (expected, found) = match self {
+            Normalization::DegreeDays {
+                expected_temperature,
+                found_temperature,
+                ..
+            } => (expected_temperature, found_temperature),
+            Normalization::Radiation {
+                expected_radiation,
+                found_radiation,
+            } => (expected_radiation, found_radiation),
+        };
+        if expected.len() != n || found.len() != n {
+            return Err(format!(
+                "Normalizing series of {} samples requires weather data of the same length, but there are {} samples for the expected series and {} for the found one",
+                n,
+                expected.len(),
+                found.len()
+            ));
+        }
+        match self {
+            Normalization::DegreeDays {
+                kind, samples_per_day, ..
+            } => {
+                if !(samples_per_day.is_finite() && *samples_per_day > 0.) {
+                    return Err(format!(
+                        "Invalid number of samples per day {}: it must be positive",
+                        samples_per_day
+                    ));
+                }
+                Ok((
+                    degree_days(expected, *kind, *samples_per_day),
+                    degree_days(found, *kind, *samples_per_day),
+                ))
+            }
+            Normalization::Radiation { .. } => Ok((expected.iter().sum(), found.iter().sum())),
+        }
+    }
+
+    /// The factor by which the expected values are scaled (see [`Normalization::totals`])
+    ///
+    /// # Errors
+    ///
+    /// * if the totals cannot be calculated
+    /// * if the total of the weather of the expected values is zero
+    pub fn scale(&self, n: usize) -> Result<f64, String> {
+        let (expected, found) = self.totals(n)?;
+        if expected == 0. || !expected.is_finite() {
+            return Err(format!(
+                "Cannot normalize by the {}, as there are none in the weather of the expected values",
+                self
+            ));
+        }
+        Ok(found / expected)
+    }
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Normalization::DegreeDays { kind, .. } => write!(f, "{}", kind),
+            Normalization::Radiation { .. } => write!(f, "incident radiation"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization_errors() {
+        let n = Normalization::Radiation {
+            expected_radiation: vec![0., 0.],
+            found_radiation: vec![100., 200.],
+        };
+        assert!(n.totals(3).is_err());
+        assert_eq!(n.totals(2).unwrap(), (0., 300.));
+        assert!(n.scale(2).is_err());
+
+        let hourly = Normalization::DegreeDays {
+            kind: DegreeDays::Cooling(24.),
+            samples_per_day: 0.,
+            expected_temperature: vec![30.],
+            found_temperature: vec![27.],
+        };
+        assert!(hourly.scale(1).is_err());
+    }
+}
@@ -197,18 +197,18 @@ pub trait Numberish:
     + Clone
     + PartialEq
     + Sized
-    + std::fmt::Display
-    + std::fmt::Debug
-    + std::ops::Add<Output = Self>
-    + std::ops::Sub<Output = Self>
-    + std::ops::AddAssign
-    + std::ops::SubAssign
-    + std::ops::Mul<Self, Output = Self>
-    + std::ops::Mul<Output = Self>
-    + std::ops::MulAssign
-    + std::ops::Div<Self, Output = Self>
-    + std::ops::Div<Output = Self>
-    + std::ops::DivAssign
+    + core::fmt::Display
+    + core::fmt::Debug
+    + core::ops::Add<Output = Self>
+    + core::ops::Sub<Output = Self>
+    + core::ops::AddAssign
+    + core::ops::SubAssign
+    + core::ops::Mul<Self, Output = Self>
+    + core::ops::Mul<Output = Self>
+    + core::ops::MulAssign
+    + core::ops::Div<Self, Output = Self>
+    + core::ops::Div<Output = Self>
+    + core::ops::DivAssign
     + Sync
     + Send
     + core::fmt::Debug
@@ -226,18 +226,18 @@ impl<
             + Copy
             + PartialEq
             + Sized
-            + std::fmt::Display
-            + std::fmt::Debug
-            + std::ops::Add<Output = Self>
-            + std::ops::Sub<Output = Self>
-            + std::ops::AddAssign
-            + std::ops::SubAssign
-            + std::ops::Mul<Self, Output = Self>
-            + std::ops::Mul<Output = Self>
-            + std::ops::MulAssign
-            + std::ops::Div<Self, Output = Self>
-            + std::ops::Div<Output = Self>
-            + std::ops::DivAssign
+            + core::fmt::Display
+            + core::fmt::Debug
+            + core::ops::Add<Output = Self>
+            + core::ops::Sub<Output = Self>
+            + core::ops::AddAssign
+            + core::ops::SubAssign
+            + core::ops::Mul<Self, Output = Self>
+            + core::ops::Mul<Output = Self>
+            + core::ops::MulAssign
+            + core::ops::Div<Self, Output = Self>
+            + core::ops::Div<Output = Self>
+            + core::ops::DivAssign
             + Sync
             + Send
             + core::fmt::Debug
@@ -0,0 +1,74 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::path::Path;
+use std::process::Command;
+
+/// The browsers that are tried, in order, when the `VALIDATE_CHROMIUM`
+/// environment variable is not set
+const BROWSERS: [&str; 4] = [
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+];
+
+/// Prints an HTML file into a PDF by means of a headless Chromium (or Chrome).
+///
+/// The browser executable can be set through the `VALIDATE_CHROMIUM`
+/// environment variable; otherwise, the common names of the executable are
+/// searched in the `PATH`.
+pub(crate) fn html_to_pdf(html: &Path, pdf: &Path) -> Result<(), String> {
+    let html = std::fs::canonicalize(html)
+        .map_err(|e| format!("Cannot find report '{}': {}", html.display(), e))?;
+
+    let browsers: Vec<String> = match std::env::var("VALIDATE_CHROMIUM") {
+        Ok(b) => vec![b],
+        Err(_) => BROWSERS.iter().map(|b| b.to_string()).collect(),
+    };
+
+    for browser in &browsers {
+        let output = Command::new(browser)
+            .arg("--headless")
+            .arg("--disable-gpu")
+            .arg("--no-pdf-header-footer")
+            .arg(format!("--print-to-pdf={}", pdf.display()))
+            .arg(format!("file://{}", html.display()))
+            .output();
+
+        match output {
+            Ok(o) if o.status.success() => return Ok(()),
+            Ok(o) => {
+                return Err(format!(
+                    "'{}' failed to print the report into a PDF: {}",
+                    browser,
+                    String::from_utf8_lossy(&o.stderr)
+                ))
+            }
+            // Not found... try the next one
+            Err(_) => continue,
+        }
+    }
+
+    Err(format!(
+        "Could not find a Chromium browser to print the PDF (tried {}). Set the VALIDATE_CHROMIUM environment variable",
+        browsers.join(", ")
+    ))
+}
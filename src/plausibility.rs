@@ -0,0 +1,411 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::numberish::Numberish;
+use crate::stats::count_as_f64;
+use crate::{Indicator, ReportContext, Validate, ValidationResult};
+#[cfg(feature = "charts")]
+use crate::svg::describe_series;
+#[cfg(feature = "charts")]
+use poloto::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// The number of implausible values that are listed in the report
+const MAX_LISTED_VALUES: usize = 10;
+
+/// A physical quantity whose measurements can be checked for plausibility
+/// (see [`PlausibilityValidator`]). The built-in ranges are in SI units and
+/// are deliberately wide: they catch broken sensors, wrong units and
+/// missing-value codes (e.g., `-999`), not unusual weather.
+///
+/// # Example
+///
+/// ```
+/// use validate::Quantity;
+///
+/// let q: Quantity = "relative humidity".parse().unwrap();
+/// assert_eq!(q, Quantity::RelativeHumidity);
+/// assert_eq!(q.range(), (0., 100.));
+/// assert_eq!(q.units(), "%");
+/// assert!("flux capacitance".parse::<Quantity>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Quantity {
+    /// Dry-bulb air temperature, in C (between the lowest and highest
+    /// temperatures ever recorded on Earth)
+    #[default]
+    AirTemperature,
+
+    /// Relative humidity, in %
+    RelativeHumidity,
+
+    /// Solar irradiance on any surface, in W/m2 (a little below zero, as
+    /// thermopile pyranometers read at night, up to well above the solar
+    /// constant, as reflections can add up)
+    SolarIrradiance,
+
+    /// Wind speed, in m/s (up to the highest gust ever recorded)
+    WindSpeed,
+
+    /// Any other quantity, with its own range
+    Custom {
+        /// The name of the quantity
+        name: &'static str,
+        /// The units of the quantity
+        units: &'static str,
+        /// The lowest plausible value
+        min: f64,
+        /// The highest plausible value
+        max: f64,
+    },
+}
+
+impl Quantity {
+    /// The lowest and highest plausible values of this quantity
+    pub fn range(&self) -> (f64, f64) {
+        match self {
+            Quantity::AirTemperature => (-90., 60.),
+            Quantity::RelativeHumidity => (0., 100.),
+            Quantity::SolarIrradiance => (-10., 1500.),
+            Quantity::WindSpeed => (0., 115.),
+            Quantity::Custom { min, max, .. } => (*min, *max),
+        }
+    }
+
+    /// The (SI) units in which the values of this quantity are expected
+    pub fn units(&self) -> &'static str {
+        match self {
+            Quantity::AirTemperature => "C",
+            Quantity::RelativeHumidity => "%",
+            Quantity::SolarIrradiance => "W/m2",
+            Quantity::WindSpeed => "m/s",
+            Quantity::Custom { units, .. } => units,
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Quantity::AirTemperature => write!(f, "Air temperature"),
+            Quantity::RelativeHumidity => write!(f, "Relative humidity"),
+            Quantity::SolarIrradiance => write!(f, "Solar irradiance"),
+            Quantity::WindSpeed => write!(f, "Wind speed"),
+            Quantity::Custom { name, .. } => write!(f, "{}", name),
+        }
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = String;
+
+    /// Selects one of the built-in quantities by its name (ignoring case,
+    /// and with spaces, dashes or underscores between words)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace(['_', '-'], " ");
+        match name.as_str() {
+            "air temperature" | "dry bulb temperature" | "temperature" => Ok(Quantity::AirTemperature),
+            "relative humidity" | "rh" => Ok(Quantity::RelativeHumidity),
+            "solar irradiance" | "solar radiation" | "irradiance" => Ok(Quantity::SolarIrradiance),
+            "wind speed" => Ok(Quantity::WindSpeed),
+            _ => Err(format!(
+                "Unknown quantity '{}': expected air temperature, relative humidity, solar irradiance or wind speed",
+                s
+            )),
+        }
+    }
+}
+
+/// Checks that measured data—which is about to be used as the truth
+/// in other validations—is physically plausible, flagging values outside
+/// the range of its [`Quantity`] (and values that are not numbers).
+///
+/// # Example
+///
+/// ```
+/// use validate::{PlausibilityValidator, Quantity, Validate};
+///
+/// let v = PlausibilityValidator {
+///     quantity: "wind speed".parse().unwrap(),
+///     values: vec![2.1, 3.4, -999., 4.0],
+///     ..Default::default()
+/// };
+/// assert!(v.validate().is_err());
+///
+/// // Allowing a few glitches
+/// let v = PlausibilityValidator {
+///     allowed_implausible_fraction: Some(0.3),
+///     ..v
+/// };
+/// assert!(v.validate().is_ok());
+/// ```
+#[derive(Default, Clone)]
+pub struct PlausibilityValidator<T: Numberish> {
+    /// The quantity that was measured
+    pub quantity: Quantity,
+
+    /// The measured values, in the (SI) units of the `quantity`
+    pub values: Vec<T>,
+
+    /// The lowest and highest plausible values, if different from the
+    /// ones of the `quantity` (e.g., a narrower range for a climate chamber)
+    pub range: Option<(f64, f64)>,
+
+    /// The maximum allowed fraction of implausible values (e.g., `0.01`
+    /// for 1%). By default, none is allowed.
+    pub allowed_implausible_fraction: Option<f64>,
+
+    /// The label in the x axis of the chart
+    pub x_label: Option<&'static str>,
+
+    /// the title of the chart
+    pub chart_title: Option<&'static str>,
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"weather file"`), listed in the coverage of the report
+    pub covers: Vec<&'static str>,
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"`), mapped to it in the report
+    pub requirements: Vec<&'static str>,
+}
+
+impl<T: Numberish> PlausibilityValidator<T> {
+    /// The lowest and highest plausible values
+    fn bounds(&self) -> (f64, f64) {
+        self.range.unwrap_or_else(|| self.quantity.range())
+    }
+
+    /// The indices of the implausible values
+    fn implausible(&self) -> Vec<usize> {
+        let (min, max) = self.bounds();
+        self.values
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| {
+                let v: f64 = (**v).into();
+                !(v.is_finite() && v >= min && v <= max)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+impl<T: Numberish + 'static> Validate for PlausibilityValidator<T> {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        if self.values.is_empty() {
+            let err = "There are no values to check".to_string();
+            return ValidationResult::Err(err.clone(), err);
+        }
+        let (min, max) = self.bounds();
+        if min > max || min.is_nan() || max.is_nan() {
+            let err = format!("Invalid plausible range: from {} to {}", min, max);
+            return ValidationResult::Err(err.clone(), err);
+        }
+
+        // Values are checked in the units of the data, but
+        // displayed in the unit system of the report
+        let units = ctx.unit_system();
+        let si_units = self.quantity.units();
+        let show = |v: f64| units.convert(v, si_units);
+
+        let implausible = self.implausible();
+        let fraction = count_as_f64(implausible.len()) / count_as_f64(self.values.len());
+        let mut file_msg = format!(
+            "\n * {}: {}\n * {}: {:.4} {} {:.4} {}\n * {}: {} ({:.2}%)",
+            ctx.tr("Quantity"),
+            ctx.tr(&self.quantity.to_string()),
+            ctx.tr("Plausible range"),
+            show(min),
+            ctx.tr("to"),
+            show(max),
+            units.units(si_units),
+            ctx.tr("Implausible values"),
+            implausible.len(),
+            fraction * 100.
+        );
+        for i in implausible.iter().take(MAX_LISTED_VALUES) {
+            file_msg = format!(
+                "{}\n     * {} {}: {:.4}",
+                file_msg,
+                ctx.tr("Index"),
+                i,
+                show(self.values[*i].into())
+            );
+        }
+        if implausible.len() > MAX_LISTED_VALUES {
+            file_msg = format!("{}\n     * ...", file_msg);
+        }
+
+        let allowed = self.allowed_implausible_fraction.unwrap_or(0.);
+        let err_msg = if fraction > allowed {
+            format!(
+                " * {:.2}% of the values are outside the plausible range of the {}, but only {:.2}% are allowed",
+                fraction * 100.,
+                self.quantity.to_string().to_lowercase(),
+                allowed * 100.
+            )
+        } else {
+            String::new()
+        };
+
+        #[cfg(feature = "charts")]
+        let chart = {
+            let quantity = self.quantity.to_string();
+            let n = count_as_f64(self.values.len() - 1);
+            let values = self
+                .values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i, (*v).into()))
+                .filter(|(_, v): &(usize, f64)| v.is_finite())
+                .map(|(i, v)| [count_as_f64(i), show(v)])
+                .buffered_plot()
+                .line(ctx.tr(&quantity));
+            let lower = [[0., show(min)], [n, show(min)]]
+                .into_iter()
+                .buffered_plot()
+                .line(ctx.tr("Minimum plausible value"));
+            let upper = [[0., show(max)], [n, show(max)]]
+                .into_iter()
+                .buffered_plot()
+                .line(ctx.tr("Maximum plausible value"));
+            let flagged_legend = if implausible.is_empty() { "" } else { ctx.tr("Implausible values") };
+            let flagged = implausible
+                .iter()
+                .map(|i| (*i, self.values[*i].into()))
+                .filter(|(_, v): &(usize, f64)| v.is_finite())
+                .map(|(i, v)| [count_as_f64(i), show(v)])
+                .buffered_plot()
+                .scatter(flagged_legend);
+            let origin = poloto::build::origin();
+
+            let y_label = format!("{} ({})", ctx.tr(&quantity), units.units(si_units));
+            let p = quick_fmt!(
+                self.chart_title.unwrap_or(""),
+                self.x_label.unwrap_or("x"),
+                &y_label,
+                values,
+                lower,
+                upper,
+                flagged,
+                origin
+            );
+            let alt_title = match self.chart_title {
+                Some(title) if !title.is_empty() => title.to_string(),
+                _ => y_label.clone(),
+            };
+            let description = [
+                describe_series(ctx.tr(&quantity), self.values.iter().map(|v| show((*v).into()))),
+                format!("{}: {:.4} {} {:.4}", ctx.tr("Plausible range"), show(min), ctx.tr("to"), show(max)),
+                format!("{}: {}", ctx.tr("Implausible values"), implausible.len()),
+            ]
+            .join(". ");
+            ctx.chart_theme()
+                .render(poloto::disp(|w| p.simple_theme(w)).to_string(), &alt_title, &description)
+        };
+        #[cfg(not(feature = "charts"))]
+        let chart = ctx.chart_theme().omitted();
+
+        let show_err = if err_msg.is_empty() {
+            ctx.tr("No errors found")
+        } else {
+            &err_msg
+        };
+        let file = format!(
+            "{}\n\n#### {}:\n {}\n#### {}:\n\n{}",
+            file_msg,
+            ctx.tr("Errors"),
+            show_err,
+            ctx.tr("Data"),
+            chart
+        );
+        if err_msg.is_empty() {
+            ValidationResult::Ok(file)
+        } else {
+            ValidationResult::Err(file, err_msg)
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.covers.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.requirements.iter().map(|r| r.to_string()).collect()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        if self.values.is_empty() {
+            return Vec::new();
+        }
+        vec![Indicator::new(
+            "Implausible values",
+            count_as_f64(self.implausible().len()) / count_as_f64(self.values.len()),
+        )]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plausibility() {
+        assert_eq!("Air_Temperature".parse::<Quantity>().unwrap(), Quantity::AirTemperature);
+        let mut v = PlausibilityValidator {
+            quantity: Quantity::RelativeHumidity,
+            values: vec![45., 50., f64::NAN, 101., 60.],
+            ..Default::default()
+        };
+        assert_eq!(v.implausible(), vec![2, 3]);
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(txt, e) => {
+                assert!(txt.contains("Implausible values: 2 (40.00%)"));
+                assert!(e.contains("40.00% of the values are outside the plausible range of the relative humidity"));
+            }
+        }
+
+        // A custom range
+        v.values = vec![18., 22., 30.];
+        v.quantity = Quantity::Custom {
+            name: "Chamber temperature",
+            units: "C",
+            min: 15.,
+            max: 25.,
+        };
+        assert!(v.validate().is_err());
+        v.range = Some((15., 35.));
+        assert!(v.validate().is_ok());
+        v.values.clear();
+        assert!(v.validate().is_err());
+    }
+}
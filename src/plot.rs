@@ -0,0 +1,194 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+//! Charts drawn like those of the validators of this crate—with the
+//! theme of the report and an alternative text for screen readers—so
+//! custom validators (see [`crate::Validate`]) can embed them in their
+//! reports without depending on [poloto](https://docs.rs/poloto).
+//!
+//! # Example
+//!
+//! ```
+//! use validate::plot::{self, Series};
+//! use validate::{ReportContext, Validate, ValidationResult};
+//!
+//! struct Decay {
+//!     found: Vec<f64>,
+//! }
+//!
+//! impl Validate for Decay {
+//!     fn validate(&self) -> ValidationResult {
+//!         self.validate_in(&ReportContext::default())
+//!     }
+//!
+//!     fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+//!         let found = self.found.iter().enumerate().map(|(i, y)| [i as f64, *y]);
+//!         let expected = (0..self.found.len()).map(|i| [i as f64, (-(i as f64)).exp()]);
+//!         let chart = plot::chart(
+//!             "Decay",
+//!             "Step",
+//!             "Value",
+//!             vec![Series::line("Expected", expected), Series::scatter("Found", found)],
+//!             ctx,
+//!         );
+//!         ValidationResult::Ok(format!("## Decay\n\n{}", chart))
+//!     }
+//! }
+//!
+//! let v = Decay { found: vec![1., 0.37, 0.13, 0.05] };
+//! assert!(v.validate().is_ok());
+//! ```
+
+use crate::svg::describe_series;
+use crate::ReportContext;
+use poloto::prelude::*;
+use std::fmt;
+
+/// How a [`Series`] is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Line,
+    Scatter,
+    Histogram,
+}
+
+/// A named set of points drawn in a chart (see [`chart`])
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    name: String,
+    points: Vec<[f64; 2]>,
+    kind: Kind,
+}
+
+impl Series {
+    fn new<S: Into<String>, I: IntoIterator<Item = [f64; 2]>>(name: S, points: I, kind: Kind) -> Self {
+        Self {
+            name: name.into(),
+            points: points.into_iter().collect(),
+            kind,
+        }
+    }
+
+    /// Points joined by a line, in the given order
+    pub fn line<S: Into<String>, I: IntoIterator<Item = [f64; 2]>>(name: S, points: I) -> Self {
+        Self::new(name, points, Kind::Line)
+    }
+
+    /// Points drawn as markers
+    pub fn scatter<S: Into<String>, I: IntoIterator<Item = [f64; 2]>>(name: S, points: I) -> Self {
+        Self::new(name, points, Kind::Scatter)
+    }
+
+    /// Bars spanning from the x of each point to that of the next one, with
+    /// the height of the former. The last point only closes the last bar.
+    pub fn histogram<S: Into<String>, I: IntoIterator<Item = [f64; 2]>>(name: S, points: I) -> Self {
+        Self::new(name, points, Kind::Histogram)
+    }
+}
+
+/// A chart, ready to be written into the text of a validation. Displaying
+/// it gives the SVG, wrapped so that it is written verbatim into the report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Svg(String);
+
+impl Svg {
+    /// The chart, as written into the report
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Svg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Svg> for String {
+    fn from(svg: Svg) -> String {
+        svg.0
+    }
+}
+
+/// Draws a chart with the given series, with the theme of the report. Its
+/// alternative text is the title (or, if empty, the labels of the axes)
+/// and a description of the y-values of each series.
+pub fn chart(title: &str, x_label: &str, y_label: &str, series: Vec<Series>, ctx: &ReportContext) -> Svg {
+    let description = series
+        .iter()
+        .map(|s| describe_series(&s.name, s.points.iter().map(|p| p[1])))
+        .collect::<Vec<String>>()
+        .join(". ");
+    let plots: Vec<_> = series
+        .into_iter()
+        .map(|s| {
+            let plot = s.points.into_iter().buffered_plot();
+            match s.kind {
+                Kind::Line => plot.line(s.name),
+                Kind::Scatter => plot.scatter(s.name),
+                Kind::Histogram => plot.histogram(s.name),
+            }
+        })
+        .collect();
+    let plots = poloto::build::plots_dyn(plots);
+    let p = quick_fmt!(title, x_label, y_label, plots, poloto::build::origin());
+    let alt_title = if title.is_empty() {
+        format!("{} vs {}", y_label, x_label)
+    } else {
+        title.to_string()
+    };
+    Svg(ctx
+        .chart_theme()
+        .render(poloto::disp(|w| p.simple_theme(w)).to_string(), &alt_title, &description))
+}
+
+/// Draws a chart with a line per series, given as its name and points
+pub fn line_chart(title: &str, x_label: &str, y_label: &str, series: &[(&str, &[[f64; 2]])], ctx: &ReportContext) -> Svg {
+    let series = series.iter().map(|(name, points)| Series::line(*name, points.iter().copied())).collect();
+    chart(title, x_label, y_label, series, ctx)
+}
+
+/// Draws a chart with the markers of each series, given as its name and points
+pub fn scatter(title: &str, x_label: &str, y_label: &str, series: &[(&str, &[[f64; 2]])], ctx: &ReportContext) -> Svg {
+    let series = series.iter().map(|(name, points)| Series::scatter(*name, points.iter().copied())).collect();
+    chart(title, x_label, y_label, series, ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chart() {
+        let ctx = ReportContext::default();
+        let points = [[0., 1.], [1., 3.], [2., 2.]];
+        let svg = line_chart("", "Time", "Temperature", &[("Found", &points)], &ctx);
+        let svg = svg.as_str();
+        assert!(svg.contains("validate_chart\"><svg"));
+        assert!(svg.contains("<title>Temperature vs Time</title>"));
+        assert!(svg.contains("Found: 3 values from 1.0000 to 3.0000"));
+
+        let svg = scatter("Fit", "x", "y", &[("a", &points), ("b", &points[1..])], &ctx);
+        assert!(svg.as_str().contains("<title>Fit</title>"));
+        let bars = chart("Bars", "x", "y", vec![Series::histogram("Counts", points)], &ctx);
+        assert!(String::from(bars).contains("<rect"));
+    }
+}
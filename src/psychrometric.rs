@@ -0,0 +1,466 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::numberish::Numberish;
+use crate::stats::count_as_f64;
+use crate::{Indicator, ReportContext, Validate, ValidationResult};
+#[cfg(feature = "charts")]
+use crate::plot::{self, Series};
+
+/// The standard atmospheric pressure at sea level, in Pa
+const STANDARD_PRESSURE: f64 = 101_325.;
+
+/// The ratio between the molar masses of water vapour and dry air
+const MOLAR_MASS_RATIO: f64 = 0.621_945;
+
+/// The saturation pressure of water vapour over liquid water, in Pa, at a
+/// dry-bulb temperature in °C (i.e., the Magnus formula, with the
+/// coefficients of Alduchov and Eskridge, 1996)
+fn saturation_pressure(dry_bulb: f64) -> f64 {
+    610.94 * (17.625 * dry_bulb / (dry_bulb + 243.04)).exp()
+}
+
+/// The humidity ratio, in kg of water per kg of dry air, of air at a
+/// dry-bulb temperature in °C and a relative humidity in %
+#[cfg(any(feature = "charts", test))]
+fn humidity_ratio(dry_bulb: f64, relative_humidity: f64, pressure: f64) -> f64 {
+    let vapour_pressure = relative_humidity / 100. * saturation_pressure(dry_bulb);
+    MOLAR_MASS_RATIO * vapour_pressure / (pressure - vapour_pressure)
+}
+
+/// The relative humidity, in %, of air at a dry-bulb temperature in °C
+/// and a humidity ratio in kg/kg
+fn relative_humidity(dry_bulb: f64, humidity_ratio: f64, pressure: f64) -> f64 {
+    let vapour_pressure = humidity_ratio * pressure / (MOLAR_MASS_RATIO + humidity_ratio);
+    100. * vapour_pressure / saturation_pressure(dry_bulb)
+}
+
+/// A region of the psychrometric chart (e.g., a comfort zone), bounded by
+/// dry-bulb temperatures, relative humidities and a humidity ratio. The
+/// default is a simplified summer comfort zone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComfortRegion {
+    /// The lowest dry-bulb temperature, in °C
+    pub min_dry_bulb: f64,
+
+    /// The highest dry-bulb temperature, in °C
+    pub max_dry_bulb: f64,
+
+    /// The lowest relative humidity, in %
+    pub min_relative_humidity: f64,
+
+    /// The highest relative humidity, in %
+    pub max_relative_humidity: f64,
+
+    /// The highest humidity ratio, in kg/kg, if any
+    pub max_humidity_ratio: Option<f64>,
+}
+
+impl Default for ComfortRegion {
+    fn default() -> Self {
+        Self {
+            min_dry_bulb: 20.,
+            max_dry_bulb: 26.,
+            min_relative_humidity: 30.,
+            max_relative_humidity: 60.,
+            max_humidity_ratio: Some(0.012),
+        }
+    }
+}
+
+impl ComfortRegion {
+    /// Checks whether a state (i.e., a dry-bulb temperature in °C and a
+    /// humidity ratio in kg/kg) falls within the region
+    pub fn contains(&self, dry_bulb: f64, humidity_ratio: f64, pressure: f64) -> bool {
+        let rh = relative_humidity(dry_bulb, humidity_ratio, pressure);
+        (self.min_dry_bulb..=self.max_dry_bulb).contains(&dry_bulb)
+            && (self.min_relative_humidity..=self.max_relative_humidity).contains(&rh)
+            && self.max_humidity_ratio.is_none_or(|max| humidity_ratio <= max)
+    }
+
+    /// The outline of the region, as (dry-bulb, humidity ratio) points
+    #[cfg(feature = "charts")]
+    fn outline(&self, pressure: f64) -> Vec<[f64; 2]> {
+        let steps = 20;
+        let at = |i: usize| self.min_dry_bulb + (self.max_dry_bulb - self.min_dry_bulb) * count_as_f64(i) / count_as_f64(steps);
+        let upper = |t: f64| {
+            let w = humidity_ratio(t, self.max_relative_humidity, pressure);
+            self.max_humidity_ratio.map_or(w, |max| w.min(max))
+        };
+        let mut ret: Vec<[f64; 2]> = (0..=steps)
+            .map(|i| [at(i), humidity_ratio(at(i), self.min_relative_humidity, pressure)])
+            .collect();
+        ret.extend((0..=steps).rev().map(|i| [at(i), upper(at(i))]));
+        ret.push(ret[0]);
+        ret
+    }
+}
+
+/// Validates the states of a zone (i.e., its dry-bulb temperature and
+/// humidity ratio over time) against a [`ComfortRegion`], comparing the
+/// fraction of time that the expected and found states spend within it,
+/// and how often both agree on being in or out of it. Both trajectories
+/// are drawn on a psychrometric chart, over the lines of constant
+/// relative humidity and the outline of the region.
+///
+/// Dry-bulb temperatures are in °C and humidity ratios in kg of water per
+/// kg of dry air; the latter are charted in g/kg.
+///
+/// # Example
+///
+/// ```
+/// use validate::{PsychrometricValidator, Validate};
+///
+/// let v = PsychrometricValidator {
+///     expected_dry_bulb: vec![21.0, 22.5, 24.0, 25.5, 27.0],
+///     expected_humidity_ratio: vec![0.008, 0.0085, 0.009, 0.0095, 0.010],
+///     found_dry_bulb: vec![21.2, 22.4, 24.3, 25.8, 27.1],
+///     found_humidity_ratio: vec![0.0081, 0.0084, 0.0092, 0.0094, 0.0102],
+///     min_agreement: Some(0.9),
+///     ..Default::default()
+/// };
+/// assert!(v.validate().is_ok());
+/// ```
+#[derive(Default, Clone)]
+pub struct PsychrometricValidator<T: Numberish> {
+    /// The expected dry-bulb temperatures, in °C
+    pub expected_dry_bulb: Vec<T>,
+
+    /// The expected humidity ratios, in kg/kg
+    pub expected_humidity_ratio: Vec<T>,
+
+    /// The dry-bulb temperatures found, in °C
+    pub found_dry_bulb: Vec<T>,
+
+    /// The humidity ratios found, in kg/kg
+    pub found_humidity_ratio: Vec<T>,
+
+    /// The region in which the states are counted
+    pub region: ComfortRegion,
+
+    /// The atmospheric pressure, in Pa. Defaults to that of the standard
+    /// atmosphere at sea level.
+    pub pressure: Option<f64>,
+
+    /// The maximum allowed difference between the fractions of time that
+    /// the found and the expected states spend within the region (e.g.,
+    /// `0.05` for five percentage points)
+    pub allowed_fraction_difference: Option<f64>,
+
+    /// The minimum fraction of time in which the found and the expected
+    /// states agree on being within or outside the region
+    pub min_agreement: Option<f64>,
+
+    /// the title of the chart
+    pub chart_title: Option<&'static str>,
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"zone humidity"`), listed in the coverage of the report
+    pub covers: Vec<&'static str>,
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"`), mapped to it in the report
+    pub requirements: Vec<&'static str>,
+}
+
+/// The expected and found states, with how long they spend in the region
+struct Occupancy {
+    /// The expected (dry-bulb, humidity ratio) states
+    expected: Vec<[f64; 2]>,
+
+    /// The found (dry-bulb, humidity ratio) states
+    found: Vec<[f64; 2]>,
+
+    /// The fraction of time that the expected states are within the region
+    expected_fraction: f64,
+
+    /// The fraction of time that the found states are within the region
+    found_fraction: f64,
+
+    /// The fraction of time that both agree on being in or out of the region
+    agreement: f64,
+}
+
+impl<T: Numberish> PsychrometricValidator<T> {
+    /// The pressure, in Pa
+    fn pressure(&self) -> f64 {
+        self.pressure.unwrap_or(STANDARD_PRESSURE)
+    }
+
+    /// Checks the states, and counts the time they spend in the region
+    fn occupancy(&self) -> Result<Occupancy, String> {
+        let n = self.expected_dry_bulb.len();
+        if n == 0 {
+            return Err("There are no states to validate".to_string());
+        }
+        if self.expected_humidity_ratio.len() != n || self.found_dry_bulb.len() != n || self.found_humidity_ratio.len() != n {
+            return Err(format!(
+                "Expected dry-bulb temperatures and humidity ratios, and found dry-bulb temperatures and humidity ratios have different lengths ({}, {}, {} and {})",
+                n,
+                self.expected_humidity_ratio.len(),
+                self.found_dry_bulb.len(),
+                self.found_humidity_ratio.len()
+            ));
+        }
+        let states = |dry_bulb: &[T], w: &[T]| -> Vec<[f64; 2]> {
+            dry_bulb.iter().zip(w.iter()).map(|(t, w)| [(*t).into(), (*w).into()]).collect()
+        };
+        let expected = states(&self.expected_dry_bulb, &self.expected_humidity_ratio);
+        let found = states(&self.found_dry_bulb, &self.found_humidity_ratio);
+        if expected.iter().chain(found.iter()).any(|[t, w]| !t.is_finite() || !w.is_finite() || *w < 0.) {
+            return Err("Trying to validate non-finite or negative psychrometric states".to_string());
+        }
+        let pressure = self.pressure();
+        let inside = |s: &[f64; 2]| self.region.contains(s[0], s[1], pressure);
+        let (mut in_expected, mut in_found, mut agree) = (0, 0, 0);
+        for (e, f) in expected.iter().zip(found.iter()) {
+            let (e, f) = (inside(e), inside(f));
+            in_expected += usize::from(e);
+            in_found += usize::from(f);
+            agree += usize::from(e == f);
+        }
+        let n = count_as_f64(n);
+        Ok(Occupancy {
+            expected_fraction: count_as_f64(in_expected) / n,
+            found_fraction: count_as_f64(in_found) / n,
+            agreement: count_as_f64(agree) / n,
+            expected,
+            found,
+        })
+    }
+}
+
+impl<T: Numberish + 'static> Validate for PsychrometricValidator<T> {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        let occupancy = match self.occupancy() {
+            Ok(o) => o,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+        let units = ctx.unit_system();
+        let show_t = |v: f64| units.convert(v, "C");
+
+        let mut file_msg = format!(
+            "\n * {}: {}\n * {}: {:.1} {} {:.1} {}, {:.0}% {} {:.0}% {}",
+            ctx.tr("Number of states"),
+            occupancy.expected.len(),
+            ctx.tr("Region"),
+            show_t(self.region.min_dry_bulb),
+            ctx.tr("to"),
+            show_t(self.region.max_dry_bulb),
+            units.units("C"),
+            self.region.min_relative_humidity,
+            ctx.tr("to"),
+            self.region.max_relative_humidity,
+            ctx.tr("relative humidity")
+        );
+        if let Some(max) = self.region.max_humidity_ratio {
+            file_msg += &format!(", {} {:.1} g/kg", ctx.tr("humidity ratio up to"), max * 1000.);
+        }
+        file_msg += &format!(
+            "\n * {}: {:.1}%\n * {}: {:.1}%\n * {}: {:.1}%",
+            ctx.tr("Time within the region (expected)"),
+            occupancy.expected_fraction * 100.,
+            ctx.tr("Time within the region (found)"),
+            occupancy.found_fraction * 100.,
+            ctx.tr("Agreement on being within the region"),
+            occupancy.agreement * 100.
+        );
+        let n = count_as_f64(occupancy.expected.len());
+        let (dt, dw) = occupancy
+            .expected
+            .iter()
+            .zip(occupancy.found.iter())
+            .fold((0., 0.), |(dt, dw), (e, f)| (dt + f[0] - e[0], dw + f[1] - e[1]));
+        file_msg += &format!(
+            "\n * {}: {:.2} {}\n * {}: {:.2} g/kg",
+            ctx.tr("Mean dry-bulb temperature difference"),
+            units.convert_difference(dt / n, "C"),
+            units.units("C"),
+            ctx.tr("Mean humidity ratio difference"),
+            dw / n * 1000.
+        );
+
+        let mut nchecks = 0;
+        let mut err_msg = String::new();
+        if let Some(allowed) = self.allowed_fraction_difference {
+            nchecks += 1;
+            let difference = (occupancy.found_fraction - occupancy.expected_fraction).abs();
+            if difference > allowed {
+                err_msg = format!(
+                    "{}\n * The found states are within the region {:.1}% of the time, and the expected ones {:.1}%, which differ by more than the allowed {:.1} percentage points",
+                    err_msg,
+                    occupancy.found_fraction * 100.,
+                    occupancy.expected_fraction * 100.,
+                    allowed * 100.
+                );
+            }
+        }
+        if let Some(min) = self.min_agreement {
+            nchecks += 1;
+            if occupancy.agreement < min {
+                err_msg = format!(
+                    "{}\n * The found and expected states agree on being within the region {:.1}% of the time, which is less than the required {:.1}%",
+                    err_msg,
+                    occupancy.agreement * 100.,
+                    min * 100.
+                );
+            }
+        }
+
+        #[cfg(feature = "charts")]
+        let chart = {
+            let pressure = self.pressure();
+            let point = |[t, w]: [f64; 2]| [show_t(t), w * 1000.];
+            // The background spans the states and the region, with some margin
+            let (lo, hi) = occupancy
+                .expected
+                .iter()
+                .chain(occupancy.found.iter())
+                .fold((self.region.min_dry_bulb, self.region.max_dry_bulb), |(lo, hi), [t, _]| {
+                    (lo.min(*t), hi.max(*t))
+                });
+            let (lo, hi) = ((lo - 2.).floor(), (hi + 2.).ceil());
+            let steps = 40;
+            let at = |i: usize| lo + (hi - lo) * count_as_f64(i) / count_as_f64(steps);
+            let mut series: Vec<Series> = [100., 80., 60., 40., 20.]
+                .iter()
+                .map(|rh| {
+                    Series::line(
+                        format!("{} {}%", ctx.tr("RH"), rh),
+                        (0..=steps).map(|i| point([at(i), humidity_ratio(at(i), *rh, pressure)])),
+                    )
+                })
+                .collect();
+            series.push(Series::line(ctx.tr("Region"), self.region.outline(pressure).into_iter().map(point)));
+            series.push(Series::line(ctx.tr("Expected"), occupancy.expected.iter().copied().map(point)));
+            series.push(Series::line(ctx.tr("Found"), occupancy.found.iter().copied().map(point)));
+            let x_label = format!("{} [{}]", ctx.tr("Dry-bulb temperature"), units.units("C"));
+            let y_label = format!("{} [g/kg]", ctx.tr("Humidity ratio"));
+            plot::chart(self.chart_title.unwrap_or(""), &x_label, &y_label, series, ctx).to_string()
+        };
+        #[cfg(not(feature = "charts"))]
+        let chart = ctx.chart_theme().omitted();
+
+        let show_err = if nchecks == 0 {
+            ctx.tr("No checks performed...")
+        } else if err_msg.is_empty() {
+            ctx.tr("No errors found")
+        } else {
+            &err_msg
+        };
+        let file = format!(
+            "{}\n\n#### {}:\n {}\n#### {}:\n\n{}",
+            file_msg,
+            ctx.tr("Errors"),
+            show_err,
+            ctx.tr("Data"),
+            chart
+        );
+        if err_msg.is_empty() {
+            ValidationResult::Ok(file)
+        } else {
+            ValidationResult::Err(file, err_msg)
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.covers.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.requirements.iter().map(|r| r.to_string()).collect()
+    }
+
+    fn defines_thresholds(&self, _ctx: &ReportContext) -> bool {
+        self.allowed_fraction_difference.is_some() || self.min_agreement.is_some()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        match self.occupancy() {
+            Ok(o) => vec![
+                Indicator::new("Time within the region (expected)", o.expected_fraction),
+                Indicator::new("Time within the region (found)", o.found_fraction),
+                Indicator::new("Agreement on being within the region", o.agreement),
+            ],
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_psychrometrics() {
+        // 25°C and 50% RH at sea level are about 9.9 g/kg
+        let w = humidity_ratio(25., 50., STANDARD_PRESSURE);
+        assert!((w - 0.0099).abs() < 0.0001);
+        assert!((relative_humidity(25., w, STANDARD_PRESSURE) - 50.).abs() < 1e-9);
+
+        let region = ComfortRegion::default();
+        assert!(region.contains(23., 0.009, STANDARD_PRESSURE));
+        assert!(!region.contains(27., 0.009, STANDARD_PRESSURE));
+        // Too dry, and too humid
+        assert!(!region.contains(23., 0.002, STANDARD_PRESSURE));
+        assert!(!region.contains(25., 0.0125, STANDARD_PRESSURE));
+    }
+
+    #[test]
+    fn test_psychrometric_validator() {
+        let mut v = PsychrometricValidator {
+            expected_dry_bulb: vec![22., 23., 24., 27.],
+            expected_humidity_ratio: vec![0.008, 0.008, 0.008, 0.008],
+            found_dry_bulb: vec![22., 23., 27., 28.],
+            found_humidity_ratio: vec![0.008, 0.008, 0.008, 0.008],
+            allowed_fraction_difference: Some(0.3),
+            min_agreement: Some(0.7),
+            ..Default::default()
+        };
+        let indicators = v.indicators();
+        assert_eq!(indicators[0].value, 0.75);
+        assert_eq!(indicators[1].value, 0.5);
+        assert_eq!(indicators[2].value, 0.75);
+        match v.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("Time within the region (found): 50.0%")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        v.min_agreement = Some(0.8);
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => {
+                assert!(e.contains("agree on being within the region 75.0% of the time, which is less than the required 80.0%"))
+            }
+        }
+
+        v.found_humidity_ratio.pop();
+        assert!(v.validate().is_err());
+    }
+}
@@ -0,0 +1,293 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::numberish::Numberish;
+use crate::stats::{count_as_f64, ecdf};
+use crate::{Indicator, ReportContext, Validate, ValidationResult};
+#[cfg(feature = "charts")]
+use crate::svg::describe_series;
+#[cfg(feature = "charts")]
+use poloto::prelude::*;
+
+/// The number of quantiles compared when none is given, at most
+const DEFAULT_QUANTILES: usize = 100;
+
+/// Compares the distributions of two datasets (e.g., the outputs of two
+/// stochastic simulations) through a Quantile-Quantile plot: the quantiles
+/// of the found values against those of the expected ones. If both come
+/// from the same distribution, the points lie on the 45° line. The
+/// validation fails if any quantile deviates from it more than allowed.
+///
+/// # Example
+///
+/// ```
+/// use validate::{QQValidator, Validate};
+///
+/// let v = QQValidator {
+///     expected: vec![1., 2., 3., 4., 5., 6., 7., 8.],
+///     found: vec![8.1, 6.9, 6., 5.2, 3.9, 3.1, 1.8, 1.],
+///     allowed_max_quantile_deviation: Some(0.25),
+///     ..Default::default()
+/// };
+/// assert!(v.validate().is_ok());
+/// ```
+#[derive(Default, Clone)]
+pub struct QQValidator<T: Numberish> {
+    /// The expected values
+    pub expected: Vec<T>,
+
+    /// The values found
+    pub found: Vec<T>,
+
+    /// The number of quantiles compared, evenly spread between 0 and 1. If
+    /// not given, it is the size of the smallest dataset, up to 100.
+    pub quantiles: Option<usize>,
+
+    /// The maximum allowed difference between a quantile of the found
+    /// values and the same quantile of the expected ones
+    pub allowed_max_quantile_deviation: Option<f64>,
+
+    /// The units of the data
+    pub units: Option<&'static str>,
+
+    /// The label of the data, used in the axes of the chart
+    pub label: Option<&'static str>,
+
+    /// The name of the expected data, in the x axis of the chart
+    pub expected_legend: Option<&'static str>,
+
+    /// The name of the found data, in the y axis of the chart
+    pub found_legend: Option<&'static str>,
+
+    /// the title of the chart
+    pub chart_title: Option<&'static str>,
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"zone air temperature"`), listed in the coverage of the report
+    pub covers: Vec<&'static str>,
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"`), mapped to it in the report
+    pub requirements: Vec<&'static str>,
+}
+
+impl<T: Numberish> QQValidator<T> {
+    /// The probability of each quantile, and the expected and found
+    /// quantiles at it
+    fn quantile_pairs(&self) -> Result<Vec<(f64, f64, f64)>, String> {
+        let expected = ecdf(&self.expected).map_err(|e| format!("Invalid expected values: {}", e))?;
+        let found = ecdf(&self.found).map_err(|e| format!("Invalid found values: {}", e))?;
+        let n = self
+            .quantiles
+            .unwrap_or_else(|| self.expected.len().min(self.found.len()).min(DEFAULT_QUANTILES));
+        if n == 0 {
+            return Err("Trying to compare zero quantiles".to_string());
+        }
+        // The probabilities of the midpoints of n equal intervals
+        Ok((0..n)
+            .map(|i| {
+                let p = (count_as_f64(i) + 0.5) / count_as_f64(n);
+                (p, expected.quantile(p), found.quantile(p))
+            })
+            .collect())
+    }
+
+    /// The largest deviation from the 45° line, with its probability
+    fn max_deviation(pairs: &[(f64, f64, f64)]) -> (f64, f64) {
+        pairs
+            .iter()
+            .map(|(p, e, f)| (*p, (f - e).abs()))
+            .fold((f64::NAN, 0.), |max, (p, d)| if d > max.1 || max.0.is_nan() { (p, d) } else { max })
+    }
+}
+
+impl<T: Numberish + 'static> Validate for QQValidator<T> {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        let pairs = match self.quantile_pairs() {
+            Ok(p) => p,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+        let units = ctx.unit_system();
+        let data_units = self.units.unwrap_or("");
+        let show_diff = |v: f64| units.convert_difference(v, data_units);
+        let (p_max, max_deviation) = Self::max_deviation(&pairs);
+
+        let file_msg = format!(
+            "\n * {}: {}\n * {}: {}\n * {}: {}\n * {}: {:.4} ({} {:.1}%)",
+            ctx.tr("Expected samples"),
+            self.expected.len(),
+            ctx.tr("Found samples"),
+            self.found.len(),
+            ctx.tr("Quantiles compared"),
+            pairs.len(),
+            ctx.tr("Maximum quantile deviation"),
+            show_diff(max_deviation),
+            ctx.tr("at"),
+            p_max * 100.
+        );
+
+        let mut nchecks = 0;
+        let mut err_msg = String::new();
+        if let Some(allowed) = self.allowed_max_quantile_deviation {
+            nchecks += 1;
+            if max_deviation > allowed {
+                err_msg = format!(
+                    "{}\n * The {:.1}% quantiles differ by {:.4}, which is more than the allowed {:.4}",
+                    err_msg,
+                    p_max * 100.,
+                    show_diff(max_deviation),
+                    show_diff(allowed)
+                );
+            }
+        }
+
+        #[cfg(feature = "charts")]
+        let chart = {
+            let show = |v: f64| units.convert(v, data_units);
+            let expected_legend = ctx.tr(self.expected_legend.unwrap_or("Expected"));
+            let found_legend = ctx.tr(self.found_legend.unwrap_or("Found"));
+            let points = pairs
+                .iter()
+                .map(|(_, e, f)| [show(*e), show(*f)])
+                .buffered_plot()
+                .scatter(ctx.tr("Quantiles"));
+            let (first, last) = pairs.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (_, e, f)| {
+                (lo.min(*e).min(*f), hi.max(*e).max(*f))
+            });
+            let diagonal = [first, last]
+                .into_iter()
+                .map(|v| [show(v), show(v)])
+                .buffered_plot()
+                .line(ctx.tr("45° line"));
+
+            let axis = |legend: &str| {
+                let mut label = match self.label {
+                    Some(label) => format!("{} ({})", ctx.tr(label), legend),
+                    None => legend.to_string(),
+                };
+                if let Some(u) = self.units {
+                    label = format!("{} [{}]", label, units.units(u));
+                }
+                label
+            };
+            let (x_label, y_label) = (axis(expected_legend), axis(found_legend));
+            let p = quick_fmt!(self.chart_title.unwrap_or(""), &x_label, &y_label, points, diagonal);
+            let alt_title = match self.chart_title {
+                Some(title) if !title.is_empty() => title.to_string(),
+                _ => format!("{} vs {}", y_label, x_label),
+            };
+            let description = [
+                describe_series(&format!("{} ({})", ctx.tr("Quantiles"), expected_legend), pairs.iter().map(|(_, e, _)| show(*e))),
+                describe_series(&format!("{} ({})", ctx.tr("Quantiles"), found_legend), pairs.iter().map(|(_, _, f)| show(*f))),
+            ]
+            .join(". ");
+            ctx.chart_theme()
+                .render(poloto::disp(|w| p.simple_theme(w)).to_string(), &alt_title, &description)
+        };
+        #[cfg(not(feature = "charts"))]
+        let chart = ctx.chart_theme().omitted();
+
+        let show_err = if nchecks == 0 {
+            ctx.tr("No checks performed...")
+        } else if err_msg.is_empty() {
+            ctx.tr("No errors found")
+        } else {
+            &err_msg
+        };
+        let file = format!(
+            "{}\n\n#### {}:\n {}\n#### {}:\n\n{}",
+            file_msg,
+            ctx.tr("Errors"),
+            show_err,
+            ctx.tr("Data"),
+            chart
+        );
+        if err_msg.is_empty() {
+            ValidationResult::Ok(file)
+        } else {
+            ValidationResult::Err(file, err_msg)
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.covers.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.requirements.iter().map(|r| r.to_string()).collect()
+    }
+
+    fn defines_thresholds(&self, _ctx: &ReportContext) -> bool {
+        self.allowed_max_quantile_deviation.is_some()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        match self.quantile_pairs() {
+            Ok(pairs) => vec![Indicator::new("Maximum quantile deviation", Self::max_deviation(&pairs).1)],
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qq() {
+        // Uniform samples of different sizes, on the same range
+        let expected: Vec<f64> = (0..=10).map(|i| i as f64).collect();
+        let found: Vec<f64> = (0..=20).rev().map(|i| i as f64 / 2.).collect();
+        let mut v = QQValidator {
+            expected,
+            found,
+            allowed_max_quantile_deviation: Some(1e-9),
+            ..Default::default()
+        };
+        match v.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("Quantiles compared: 11")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        // A heavier upper tail
+        *v.found.first_mut().unwrap() = 13.;
+        v.quantiles = Some(20);
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains("The 97.5% quantiles differ by")),
+        }
+        assert_eq!(v.indicators()[0].name, "Maximum quantile deviation");
+
+        v.quantiles = Some(0);
+        assert!(v.validate().is_err());
+        v.quantiles = None;
+        v.expected.clear();
+        assert!(v.validate().is_err());
+    }
+}
@@ -0,0 +1,1369 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::sanitize::unescape;
+use crate::{escape_html, ReportContext};
+use pulldown_cmark::{html, Event, Options, Parser};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::hash_map::RandomState;
+use std::fs::File;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Write;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// The order in which the validations are written into the report
+/// (and into its summary).
+///
+/// Sorting is always stable, so validations that are considered equal
+/// keep the order in which they were pushed into the [`crate::Validator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportOrder {
+    /// The validations are reported in the order in which they were pushed
+    #[default]
+    Insertion,
+
+    /// The validations are sorted alphabetically by title
+    Alphabetical,
+
+    /// Failed validations are reported first
+    FailuresFirst,
+}
+
+/// What to do when several validations have the same title
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateTitles {
+    /// Repeated titles get a number (e.g., `Zone A (2)`), in the order
+    /// in which the validations were pushed
+    #[default]
+    Disambiguate,
+
+    /// Validations whose title was already used fail
+    Fail,
+}
+
+/// How a report is compressed when written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// The report is written as plain HTML
+    #[default]
+    None,
+
+    /// The report is compressed with Gzip, adding the `.gz` extension
+    /// to the report file. Requires the `gzip` feature: without it,
+    /// writing the report fails.
+    Gzip,
+}
+
+/// The format in which a report is written
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::{ReportFormat, Validator};
+///
+/// // E.g., for a GitHub wiki or an mdBook
+/// let mut validator = Validator::new("Markdown report", "report.md");
+/// validator.set_report_format(ReportFormat::Markdown);
+/// validator.validate().unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    /// A standalone HTML document, with the charts embedded
+    #[default]
+    Html,
+
+    /// The Markdown the HTML report is made from. The charts are written as
+    /// SVG files into the assets directory of the report, and linked as images.
+    Markdown,
+}
+
+/// Writes the charts embedded in a Markdown report into SVG files in the
+/// assets directory, replacing them with images that link to those files.
+/// The alternative text of each image is the title of its chart.
+pub(crate) fn charts_as_files(txt: &str, ctx: &ReportContext) -> Result<String, String> {
+    const CHART: &str = "<div class=\"validate_chart\">";
+    let mut ret = String::with_capacity(txt.len());
+    let mut rest = txt;
+    let mut n = 0;
+    while let Some(start) = rest.find(CHART) {
+        let svg_start = start + CHART.len();
+        let end = match rest[svg_start..].find("</div>") {
+            Some(len) => svg_start + len,
+            None => break,
+        };
+        let svg = &rest[svg_start..end];
+        let alt = match (svg.find("<title>"), svg.find("</title>")) {
+            (Some(a), Some(b)) if a < b => &svg[a + "<title>".len()..b],
+            _ => "Chart",
+        };
+        n += 1;
+        let link = ctx.write_asset(&format!("chart_{}.svg", n), svg)?;
+        ret.push_str(&rest[..start]);
+        ret.push_str(&format!("![{}]({})", alt, link.replace('\\', "/")));
+        rest = &rest[end + "</div>".len()..];
+    }
+    ret.push_str(rest);
+    Ok(ret)
+}
+
+/// A named value calculated by a validation (e.g., the Root Mean
+/// Squared Error between two series), shown in the summary of the report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Indicator {
+    /// The name of the indicator
+    pub name: String,
+
+    /// The value of the indicator
+    #[serde(deserialize_with = "number_or_nan")]
+    pub value: f64,
+}
+
+/// Reads a number from JSON, where non-finite numbers are written as `null`
+fn number_or_nan<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    Ok(Option::<f64>::deserialize(d)?.unwrap_or(f64::NAN))
+}
+
+/// Writes indicators into JSON as an object with their values by name
+/// (e.g., `{"Root Mean Squared Error": 0.12}`), and reads them back
+pub(crate) mod indicator_map {
+    use super::Indicator;
+    use serde::de::{MapAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+    use std::fmt;
+
+    pub fn serialize<S: Serializer, I: AsRef<[Indicator]>>(indicators: &I, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_map(indicators.as_ref().iter().map(|i| (&i.name, i.value)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Indicator>, D::Error> {
+        struct Indicators;
+        impl<'de> Visitor<'de> for Indicators {
+            type Value = Vec<Indicator>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an object with the values of the indicators by name")
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+                let mut ret = Vec::new();
+                while let Some((name, value)) = map.next_entry::<String, Option<f64>>()? {
+                    ret.push(Indicator::new(name, value.unwrap_or(f64::NAN)));
+                }
+                Ok(ret)
+            }
+        }
+        d.deserialize_map(Indicators)
+    }
+}
+
+/// Writes run inputs into JSON as an object with their values by name
+pub(crate) fn input_map<S: Serializer, I: AsRef<[RunInput]>>(inputs: &I, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_map(inputs.as_ref().iter().map(|i| (&i.name, &i.value)))
+}
+
+impl Indicator {
+    /// Creates a new `Indicator`
+    pub fn new<T: Into<String>>(name: T, value: f64) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
+/// A column of the run matrix of a report (see
+/// [`crate::Validator::set_matrix`]): an indicator (e.g., `"Root Mean
+/// Squared Error"`), with the values at which its cells are colored as a
+/// warning or a failure. Values are compared by their magnitude, unless
+/// higher values are better (e.g., for the Coefficient of Determination).
+///
+/// ```
+/// use validate::MatrixMetric;
+///
+/// let rmse = MatrixMetric {
+///     warning: Some(0.5),
+///     limit: Some(1.0),
+///     ..MatrixMetric::new("Root Mean Squared Error")
+/// };
+/// let r2 = MatrixMetric {
+///     limit: Some(0.8),
+///     higher_is_better: true,
+///     ..MatrixMetric::new("Coefficient of Determination (R2)")
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MatrixMetric {
+    /// The name of the indicator shown in the column
+    pub indicator: String,
+
+    /// The value from which a cell is colored as a warning
+    pub warning: Option<f64>,
+
+    /// The value from which a cell is colored as a failure
+    pub limit: Option<f64>,
+
+    /// Whether values below `warning` and `limit`—instead of above
+    /// them—are the bad ones
+    pub higher_is_better: bool,
+}
+
+impl MatrixMetric {
+    /// Creates a column for the indicator named `indicator`, without
+    /// any warning or limit
+    pub fn new<T: Into<String>>(indicator: T) -> Self {
+        Self {
+            indicator: indicator.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The state of a cell with the given value: `"pass"`, `"warn"` or
+    /// `"fail"`, or `None` if the column does not set any bound
+    fn state(&self, value: f64) -> Option<&'static str> {
+        if self.warning.is_none() && self.limit.is_none() {
+            return None;
+        }
+        let exceeds = |bound: Option<f64>| {
+            bound.is_some_and(|b| if self.higher_is_better { value < b } else { value.abs() > b })
+        };
+        if value.is_nan() || exceeds(self.limit) {
+            Some("fail")
+        } else if exceeds(self.warning) {
+            Some("warn")
+        } else {
+            Some("pass")
+        }
+    }
+}
+
+/// The place in the source code where a validation is defined (e.g., the
+/// function marked with [`crate::valid`]), so CI services can point at it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// The path of the file, relative to the root of the crate
+    pub file: String,
+
+    /// The line, counting from 1
+    pub line: u32,
+}
+
+impl SourceLocation {
+    /// Creates a new `SourceLocation`
+    pub fn new<T: Into<String>>(file: T, line: u32) -> Self {
+        Self {
+            file: file.into(),
+            line,
+        }
+    }
+}
+
+/// A setting under which a validation was run (e.g., the seed of a
+/// random number generator or the tolerance of a solver), recorded in the
+/// report so failures can be reproduced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunInput {
+    /// The name of the input
+    pub name: String,
+
+    /// The value of the input, as written in the report
+    pub value: String,
+}
+
+impl RunInput {
+    /// Creates a new `RunInput`
+    pub fn new<T: Into<String>, V: std::fmt::Display>(name: T, value: V) -> Self {
+        Self {
+            name: name.into(),
+            value: value.to_string(),
+        }
+    }
+
+    /// Records the contents of an input file (e.g., the measured data used
+    /// as the reference) by their hash, so comparing two reports tells
+    /// whether the file changed between the runs. The hash is a 64-bit
+    /// FNV-1a, which detects changes but not deliberate tampering.
+    ///
+    /// ```no_run
+    /// use validate::RunInput;
+    ///
+    /// let input = RunInput::file_hash("data/measured.csv").unwrap();
+    /// assert!(input.value.starts_with("fnv1a64:"));
+    /// ```
+    pub fn file_hash<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = std::fs::read(path)
+            .map_err(|e| format!("Cannot read input file '{}': {}", path.display(), e))?;
+        Ok(Self::new(path.display().to_string(), format!("fnv1a64:{:016x}", fnv1a64(&contents))))
+    }
+
+    /// Records the version of a tool involved in producing the found values
+    /// (e.g., `RunInput::tool_version("EnergyPlus", "23.2.0")`)
+    pub fn tool_version<T: std::fmt::Display, V: std::fmt::Display>(tool: T, version: V) -> Self {
+        Self::new(format!("{} version", tool), version)
+    }
+}
+
+/// The 64-bit FNV-1a hash of some bytes
+pub(crate) fn fnv1a64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Writes the inputs of a validation as a Markdown list
+pub(crate) fn inputs_list(inputs: &[RunInput], ctx: &ReportContext) -> String {
+    if inputs.is_empty() {
+        return String::new();
+    }
+    let mut ret = format!("\n\n#### {}:\n", ctx.tr("Inputs"));
+    for input in inputs {
+        ret.push_str(&format!(" * {}: `{}`\n", ctx.user_text(&input.name), input.value));
+    }
+    ret
+}
+
+/// The environment variable that restricts a report to the validations
+/// whose title contains its value
+pub(crate) const FILTER_VAR: &str = "VALIDATE_FILTER";
+
+/// The environment variable that restricts a report to the validation
+/// whose title is exactly its value. It takes precedence over [`FILTER_VAR`].
+pub(crate) const TITLE_VAR: &str = "VALIDATE_TITLE";
+
+/// The titles of the validations to run, as given by the environment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TitleFilter {
+    /// The titles that contain this text (see [`FILTER_VAR`])
+    Contains(String),
+    /// This very title (see [`TITLE_VAR`])
+    Exact(String),
+}
+
+impl TitleFilter {
+    /// The filter given by the environment, if any
+    pub(crate) fn from_env() -> Option<Self> {
+        match (std::env::var(TITLE_VAR), std::env::var(FILTER_VAR)) {
+            (Ok(title), _) => Some(TitleFilter::Exact(title)),
+            (_, Ok(text)) => Some(TitleFilter::Contains(text)),
+            _ => None,
+        }
+    }
+
+    /// Checks whether a title is selected
+    pub(crate) fn matches(&self, title: &str) -> bool {
+        match self {
+            TitleFilter::Contains(text) => title.contains(text.as_str()),
+            TitleFilter::Exact(t) => title == t,
+        }
+    }
+}
+
+/// Builds the command that re-runs only the validation titled `title`,
+/// quoted so that the shell does not expand any part of it.
+///
+/// When running under `cargo test`, the harness names the thread after
+/// the test being run, so the command also selects that test.
+pub(crate) fn rerun_command(title: &str) -> String {
+    let mut cmd = format!("{}='{}' cargo test", TITLE_VAR, title.replace('\'', "'\\''"));
+    if let Ok(pkg) = std::env::var("CARGO_PKG_NAME") {
+        cmd.push_str(&format!(" -p {}", pkg));
+    }
+    match std::thread::current().name() {
+        Some(test) if test != "main" => cmd.push_str(&format!(" -- --exact {}", test)),
+        _ => {}
+    }
+    cmd
+}
+
+/// The beginning of an HTML report, up to the opening of its main
+/// content (i.e., its `<main>` landmark, for assistive technologies)
+pub(crate) fn html_head(title: &str) -> String {
+    format!("<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"UTF-8\"><meta http-equiv=\"X-UA-Compatible\" content=\"IE=edge\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\"><title>{}</title></head><body><main>", escape_html(title))
+}
+
+/// The end of an HTML report, from the closing of its main content. The
+/// `footer` (e.g., the environment the report was produced in) is
+/// written as the `<footer>` landmark.
+pub(crate) fn html_tail(footer: &str) -> String {
+    format!("</main><footer>{}</footer></body></html>", footer)
+}
+
+/// The Markdown extensions enabled when transforming the reports into HTML
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::{MarkdownOptions, Validator};
+///
+/// let mut validator = Validator::new("Tables", "report.html");
+/// validator.set_markdown_options(MarkdownOptions {
+///     footnotes: true,
+///     raw_html: false,
+///     ..MarkdownOptions::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    /// Render tables (as in GitHub Flavored Markdown). Defaults to `true`.
+    pub tables: bool,
+
+    /// Render footnotes (e.g., `[^1]`). Defaults to `false`.
+    pub footnotes: bool,
+
+    /// Render `~~strikethrough~~`. Defaults to `false`.
+    pub strikethrough: bool,
+
+    /// Render task lists (e.g., `- [x] done`). Defaults to `false`.
+    pub tasklists: bool,
+
+    /// Let raw HTML written by the validations through. If `false`, it is
+    /// escaped and shown as text. The charts and tables written by this
+    /// crate are always rendered. Defaults to `true`.
+    pub raw_html: bool,
+}
+
+impl std::default::Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            footnotes: false,
+            strikethrough: false,
+            tasklists: false,
+            raw_html: true,
+        }
+    }
+}
+
+/// The beginning of the HTML blocks written by this crate, as they
+/// appear in the reports
+const PUBLISHED_HTML: &str = "<div class=\"validate_";
+
+/// The beginning of the HTML blocks written by this crate, which are
+/// rendered even if [`MarkdownOptions::raw_html`] is `false`. It carries a
+/// key unique to this process, so the texts of users cannot pass for these
+/// blocks; the key is removed when the report is written (see [`publish`]).
+pub(crate) fn own_html() -> &'static str {
+    static OWN_HTML: OnceLock<String> = OnceLock::new();
+    OWN_HTML.get_or_init(|| {
+        let key = RandomState::new().build_hasher().finish();
+        format!("<div data-validate=\"{:016x}\" class=\"validate_", key)
+    })
+}
+
+/// Removes the key of the HTML blocks written by this crate (see
+/// [`own_html`]) from the Markdown of a report
+pub(crate) fn publish(txt: &str) -> String {
+    txt.replace(own_html(), PUBLISHED_HTML)
+}
+
+/// Transforms the Markdown of a report into HTML
+pub(crate) fn markdown_to_html(txt: &str, md: &MarkdownOptions) -> String {
+    // Set up options and parser.
+    let mut options = Options::empty();
+    if md.tables {
+        options.insert(Options::ENABLE_TABLES);
+    }
+    if md.footnotes {
+        options.insert(Options::ENABLE_FOOTNOTES);
+    }
+    if md.strikethrough {
+        options.insert(Options::ENABLE_STRIKETHROUGH);
+    }
+    if md.tasklists {
+        options.insert(Options::ENABLE_TASKLISTS);
+    }
+    let parser = Parser::new_ext(txt, options);
+
+    // Each line of an HTML block is a separate event, so we
+    // need to remember whether the block was written by us
+    let mut own_block = false;
+    let mut previous_was_html = false;
+    let parser = parser.map(|event| match event {
+        Event::Html(html) => {
+            if !previous_was_html {
+                own_block = html.starts_with(own_html());
+            }
+            previous_was_html = true;
+            if own_block || md.raw_html {
+                Event::Html(publish(&html).into())
+            } else {
+                Event::Text(html)
+            }
+        }
+        event => {
+            previous_was_html = false;
+            event
+        }
+    });
+
+    // Write to String buffer.
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, parser);
+    html_output
+}
+
+/// Writes a file by writing a temporary file next to it—whose name is
+/// unique to this process and thread—and then renaming it. This way, readers
+/// never see a half-written file, and concurrent writers of the same file do
+/// not interleave their contents (the last one wins).
+pub(crate) fn write_atomically<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<(), String> {
+    let path = path.as_ref();
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // e.g., "ThreadId(3)" becomes "3"
+    let thread: String = format!("{:?}", std::thread::current().id())
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .collect();
+    let tmp = path.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), thread));
+    std::fs::write(&tmp, contents)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&tmp);
+            format!("Cannot write '{}': {}", path.display(), e)
+        })
+}
+
+/// A report that is written one section at a time, while the
+/// validations are still running
+pub(crate) struct PartialReport {
+    file: File,
+    path: String,
+    markdown: MarkdownOptions,
+}
+
+impl PartialReport {
+    /// Creates the report, writing its title
+    pub fn create(path: &str, title: &str, ctx: &ReportContext) -> Result<Self, String> {
+        let mut file = File::create(path)
+            .map_err(|e| format!("Cannot create partial report '{}': {}", path, e))?;
+        let head = format!(
+            "{}{}",
+            html_head(title),
+            markdown_to_html(
+                &format!(
+                    "# {}\n\n*Partial report: validations still running...*",
+                    ctx.user_text(title)
+                ),
+                ctx.markdown_options()
+            )
+        );
+        file.write_all(head.as_bytes())
+            .map_err(|e| format!("Cannot write partial report '{}': {}", path, e))?;
+        Ok(Self {
+            file,
+            path: path.to_string(),
+            markdown: *ctx.markdown_options(),
+        })
+    }
+
+    /// Appends a section to the report
+    pub fn append(&mut self, section: &Section) -> Result<(), String> {
+        self.file
+            .write_all(markdown_to_html(&section.body, &self.markdown).as_bytes())
+            .and_then(|_| self.file.flush())
+            .map_err(|e| format!("Cannot write partial report '{}': {}", self.path, e))
+    }
+}
+
+/// Writes the environment in which the report was produced, and how long
+/// it took to produce it (if given), as a Markdown list
+pub(crate) fn environment(ctx: &ReportContext, duration: Option<Duration>, budget: Option<Duration>) -> String {
+    let mut ret = format!(
+        "## {}\n\n * OS: `{}`\n * {}: `{}`\n * validate: `{}`\n",
+        ctx.tr("Environment"),
+        std::env::consts::OS,
+        ctx.tr("Architecture"),
+        std::env::consts::ARCH,
+        env!("CARGO_PKG_VERSION"),
+    );
+    if let Some(duration) = duration {
+        ret.push_str(&format!(
+            " * {}: `{:.2} s`\n",
+            ctx.tr("Duration"),
+            duration.as_secs_f64()
+        ));
+    }
+    if let Some(budget) = budget {
+        ret.push_str(&format!(
+            " * {}: `{:.2} s`\n",
+            ctx.tr("Time budget"),
+            budget.as_secs_f64()
+        ));
+    }
+    ret
+}
+
+/// Writes the numbers that were rounded to zero from below (e.g., `-0.0000`,
+/// which depends on the order in which the values were added up) as zeros
+pub(crate) fn without_negative_zeros(txt: &str) -> String {
+    let bytes = txt.as_bytes();
+    let mut ret = String::with_capacity(txt.len());
+    let mut last = 0;
+    for (i, b) in bytes.iter().enumerate() {
+        if *b != b'-' || (i > 0 && bytes[i - 1].is_ascii_alphanumeric()) {
+            continue;
+        }
+        let zeros = bytes[i + 1..]
+            .iter()
+            .take_while(|c| **c == b'0' || **c == b'.')
+            .count();
+        let number = &bytes[i + 1..i + 1 + zeros];
+        let next = bytes.get(i + 1 + zeros);
+        if number.first() == Some(&b'0') && number.contains(&b'.') && !next.is_some_and(|c| c.is_ascii_digit()) {
+            ret.push_str(&txt[last..i]);
+            last = i + 1;
+        }
+    }
+    ret.push_str(&txt[last..]);
+    ret
+}
+
+/// The outcome of running a single validation, ready to be
+/// written into a report
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Section {
+    /// The position of the validation that produced this section
+    pub index: usize,
+
+    /// The title of the section
+    pub title: String,
+
+    /// The Markdown to write into the report
+    pub body: String,
+
+    /// The error message, if the validation failed
+    pub error: Option<String>,
+
+    /// The indicators calculated by the validation
+    pub indicators: Vec<Indicator>,
+
+    /// The quantities checked by the validation
+    pub covers: Vec<String>,
+
+    /// The requirements verified by the validation
+    pub requirements: Vec<String>,
+
+    /// The ID of the anchor of the section in the report
+    pub anchor: String,
+
+    /// Where the validation is defined, if known
+    pub location: Option<SourceLocation>,
+
+    /// The settings and input files under which the validation was run
+    pub inputs: Vec<RunInput>,
+}
+
+impl Section {
+    /// Creates a new `Section`. The title is the first Markdown heading found
+    /// in `body` or, if there is none, a generic title built from `index`.
+    pub fn new(index: usize, body: String, error: Option<String>) -> Self {
+        let title = find_title(&body).unwrap_or_else(|| format!("Validation {}", index + 1));
+        let anchor = slug(&title);
+        Self {
+            index,
+            title,
+            body,
+            error,
+            indicators: Vec::new(),
+            covers: Vec::new(),
+            requirements: Vec::new(),
+            anchor,
+            location: None,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Changes the title of the section, and the heading from which
+    /// it was taken (if any)
+    pub fn rename(&mut self, title: String, ctx: &ReportContext) {
+        let old = self.title.clone();
+        let mut found = false;
+        let body: Vec<String> = self
+            .body
+            .lines()
+            .map(|line| {
+                let level = heading_level(line);
+                if !found && (1..=3).contains(&level) && unescape(line.trim().trim_start_matches('#').trim()) == old {
+                    found = true;
+                    format!("{} {}", "#".repeat(level), ctx.user_text(&title))
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+        let anchor = slug(&title);
+        self.body = move_anchors(&body.join("\n"), &self.anchor, &anchor);
+        self.anchor = anchor;
+        self.title = title;
+    }
+
+    /// Writes the body of the section, preceded by its anchor
+    pub fn body_with_anchor(&self) -> String {
+        format!("{}anchor\" id=\"{}\"></div>\n\n{}", own_html(), self.anchor, self.body)
+    }
+
+    /// Checks whether the validation that produced this section failed
+    pub fn failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Adds the command that re-runs this validation—selected by its
+    /// `title` before running, which might not be the one of the section
+    /// (see [`crate::Validate::title`])—to the report and error message
+    /// of a failed section
+    pub fn add_rerun_hint(&mut self, title: &str, ctx: &ReportContext) {
+        if let Some(error) = &self.error {
+            let cmd = rerun_command(title);
+            self.error = Some(format!("{}\n{}: {}", error, ctx.tr("Re-run with"), cmd));
+            self.body = format!("{}\n\n#### {}:\n\n```\n{}\n```\n", self.body, ctx.tr("Re-run with"), cmd);
+        }
+    }
+}
+
+/// The level of a Markdown heading, or 0 if `line` is not a heading
+fn heading_level(line: &str) -> usize {
+    line.trim().chars().take_while(|c| *c == '#').count()
+}
+
+/// Numbers a title if its anchor is already `used` (e.g., `Zone A (2)`)
+pub(crate) fn number_title<F: Fn(&str) -> bool>(title: String, used: F) -> String {
+    if !used(&title) {
+        return title;
+    }
+    (2..)
+        .map(|n| format!("{} ({})", title, n))
+        .find(|t| !used(t))
+        .expect("a free number")
+}
+
+/// Moves the anchors written by this crate in a text (e.g., those of the
+/// subsections of a [`crate::ValidationGroup`]) whose IDs start with `from--`
+/// to start with `to--` instead, so they stay unique when the text is nested
+/// in—or becomes—a section with another anchor
+pub(crate) fn move_anchors(txt: &str, from: &str, to: &str) -> String {
+    let marker = format!("{}anchor\" id=\"", own_html());
+    txt.replace(&format!("{}{}--", marker, from), &format!("{}{}--", marker, to))
+}
+
+/// Moves the Markdown headings of a text one level down (e.g., `###` to
+/// `####`), so it can be nested within another section
+pub(crate) fn demote_headings(txt: &str) -> String {
+    let mut in_code = false;
+    let lines: Vec<String> = txt
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") {
+                in_code = !in_code;
+            }
+            let level = heading_level(line);
+            if !in_code && (1..6).contains(&level) && trimmed[level..].starts_with(' ') {
+                format!("#{}", trimmed)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// Finds the anchors written within the body of a section (e.g., by a
+/// [`crate::ValidationGroup`]), returning the level of the heading that
+/// follows each of them, its ID and its title
+fn subsections(body: &str) -> Vec<(usize, String, String)> {
+    let marker = format!("{}anchor\" id=\"", own_html());
+    let mut ret = Vec::new();
+    for (start, _) in body.match_indices(&marker) {
+        let rest = &body[start + marker.len()..];
+        let id = rest.split('"').next().unwrap_or_default();
+        let heading = rest
+            .lines()
+            .skip(1)
+            .map(|line| line.trim())
+            .find(|line| heading_level(line) > 0);
+        if let Some(line) = heading {
+            ret.push((heading_level(line), id.to_string(), unescape(line.trim_start_matches('#').trim())));
+        }
+    }
+    ret
+}
+
+/// Writes a table of contents linking to the sections and to their
+/// subsections (e.g., the validations in a [`crate::ValidationGroup`]). It
+/// is only written if some section has subsections, as the summary table
+/// already lists the sections of a flat report.
+pub(crate) fn table_of_contents(sections: &[&Section], ctx: &ReportContext) -> String {
+    let nested: Vec<Vec<(usize, String, String)>> = sections.iter().map(|s| subsections(&s.body)).collect();
+    if nested.iter().all(|n| n.is_empty()) {
+        return String::new();
+    }
+    let entries = sections
+        .iter()
+        .zip(nested)
+        .flat_map(|(s, n)| std::iter::once((2, s.anchor.clone(), s.title.clone())).chain(n));
+
+    // The levels of the lists that are open
+    let mut open: Vec<usize> = Vec::new();
+    let mut list = String::new();
+    for (level, id, title) in entries {
+        match open.last() {
+            Some(last) if level <= *last => {
+                list.push_str("</li>");
+                while open.len() > 1 && open.last().is_some_and(|l| *l > level) {
+                    open.pop();
+                    list.push_str("</ul></li>");
+                }
+            }
+            _ => {
+                list.push_str("<ul>");
+                open.push(level);
+            }
+        }
+        list.push_str(&format!("<li><a href=\"#{}\">{}</a>", id, escape_html(&title)));
+    }
+    list.push_str("</li>");
+    for i in (0..open.len()).rev() {
+        list.push_str(if i > 0 { "</ul></li>" } else { "</ul>" });
+    }
+    format!(
+        "{}toc\"><nav aria-label=\"{}\"><h2>{}</h2>\n{}\n</nav></div>\n\n",
+        own_html(),
+        ctx.tr("Contents"),
+        ctx.tr("Contents"),
+        list
+    )
+}
+
+/// Builds an ID for an HTML anchor from a title (e.g., `zone-a-2` for
+/// `Zone A (2)`), which is always the same for the same title
+pub(crate) fn slug(title: &str) -> String {
+    let mut ret = String::with_capacity(title.len());
+    for c in title.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            ret.push(c)
+        } else if !ret.is_empty() && !ret.ends_with('-') {
+            ret.push('-')
+        }
+    }
+    let ret = ret.trim_end_matches('-');
+    if ret.is_empty() {
+        "validation".to_string()
+    } else {
+        ret.to_string()
+    }
+}
+
+/// Makes sure that every section has a different title (and therefore a
+/// different anchor), following a [`DuplicateTitles`] policy
+pub(crate) fn check_titles(sections: &mut [Section], policy: DuplicateTitles, ctx: &ReportContext) {
+    let mut used: Vec<String> = Vec::with_capacity(sections.len());
+    for s in sections.iter_mut() {
+        if used.contains(&s.anchor) {
+            match policy {
+                DuplicateTitles::Disambiguate => {
+                    let mut i = 2;
+                    while used.contains(&slug(&format!("{} ({})", s.title, i))) {
+                        i += 1;
+                    }
+                    s.rename(format!("{} ({})", s.title, i), ctx);
+                }
+                DuplicateTitles::Fail => {
+                    let e = format!("{}: '{}'", ctx.tr("Duplicate title"), s.title);
+                    s.body.push_str(&format!("\n\n**{}**\n", e));
+                    s.error = Some(match s.error.take() {
+                        Some(prev) => format!("{}\n{}", prev, e),
+                        None => e,
+                    });
+                }
+            }
+        }
+        used.push(s.anchor.clone());
+    }
+}
+
+/// Finds the first Markdown heading of level 1 to 3 in a text (deeper
+/// headings are used by the validators for their own subsections)
+fn find_title(txt: &str) -> Option<String> {
+    txt.lines()
+        .map(|line| line.trim())
+        .find(|line| (1..=3).contains(&heading_level(line)))
+        .map(|line| unescape(line.trim_start_matches('#').trim()))
+        .filter(|title| !title.is_empty())
+}
+
+/// Sorts the sections according to a [`ReportOrder`]
+pub(crate) fn sort_sections(sections: &mut [Section], order: ReportOrder) {
+    match order {
+        ReportOrder::Insertion => {}
+        ReportOrder::Alphabetical => sections.sort_by(|a, b| a.title.cmp(&b.title)),
+        ReportOrder::FailuresFirst => sections.sort_by_key(|s| !s.failed()),
+    }
+}
+
+/// The script that makes the summary table sortable and filterable
+const SUMMARY_SCRIPT: &str = r#"<script>
+function validateSortSummary(th) {
+    const table = th.closest("table");
+    const body = table.tBodies[0];
+    const col = Array.from(th.parentNode.children).indexOf(th);
+    const asc = th.dataset.order !== "asc";
+    th.dataset.order = asc ? "asc" : "desc";
+    const value = (row) => row.cells[col].dataset.value ?? row.cells[col].textContent;
+    const rows = Array.from(body.rows);
+    rows.sort((a, b) => {
+        const x = value(a), y = value(b);
+        // Empty cells always go last
+        if (x === "" || y === "") {
+            return (x === "") - (y === "");
+        }
+        const nx = parseFloat(x), ny = parseFloat(y);
+        const c = isNaN(nx) || isNaN(ny) ? x.localeCompare(y) : nx - ny;
+        return asc ? c : -c;
+    });
+    rows.forEach((row) => body.appendChild(row));
+}
+function validateFilterSummary(input) {
+    const filter = input.value.toLowerCase();
+    const table = document.getElementById("validate-summary");
+    for (const row of table.tBodies[0].rows) {
+        row.style.display = row.textContent.toLowerCase().includes(filter) ? "" : "none";
+    }
+}
+</script>"#;
+
+/// Writes an HTML table summarizing the results of the sections.
+///
+/// The table has one column per indicator reported by any of the
+/// sections, and it can be sorted (by clicking on the headers) and
+/// filtered (by typing in the box above it) when opened in a browser.
+pub(crate) fn summary_table(sections: &[&Section], ctx: &ReportContext) -> String {
+    // Gather the indicators, in order of appearance
+    let mut names: Vec<&str> = Vec::new();
+    for ind in sections.iter().flat_map(|s| s.indicators.iter()) {
+        if !names.contains(&ind.name.as_str()) {
+            names.push(&ind.name)
+        }
+    }
+
+    let th = |name: &str| format!("<th onclick=\"validateSortSummary(this)\">{}</th>", name);
+    let mut ret = format!(
+        "{}summary\">{}\n<input type=\"text\" placeholder=\"{}\" oninput=\"validateFilterSummary(this)\">\n<table id=\"validate-summary\"><thead><tr>{}{}{}",
+        own_html(),
+        SUMMARY_SCRIPT,
+        ctx.tr("Filter validations..."),
+        th("#"),
+        th(ctx.tr("Validation")),
+        th(ctx.tr("Result"))
+    );
+    for name in &names {
+        ret.push_str(&th(ctx.tr(name)));
+    }
+    ret.push_str("</tr></thead><tbody>");
+
+    for (i, s) in sections.iter().enumerate() {
+        let result = if s.failed() { "Failed" } else { "Passed" };
+        let result = ctx.tr(result);
+        ret.push_str(&format!(
+            "<tr><td>{}</td><td><a href=\"#{}\">{}</a></td><td>{}</td>",
+            i + 1,
+            s.anchor,
+            escape_html(&s.title),
+            result
+        ));
+        for name in &names {
+            match s.indicators.iter().find(|ind| &ind.name == name) {
+                Some(ind) => ret.push_str(&format!(
+                    "<td data-value=\"{}\">{:.4}</td>",
+                    ind.value, ind.value
+                )),
+                None => ret.push_str("<td></td>"),
+            }
+        }
+        ret.push_str("</tr>");
+    }
+    ret.push_str("</tbody></table></div>");
+    ret
+}
+
+/// The background of the cells of the run matrix, by state
+fn matrix_color(state: Option<&str>) -> &'static str {
+    match state {
+        Some("pass") => " style=\"background-color:#dfd\"",
+        Some("warn") => " style=\"background-color:#ffd\"",
+        Some("fail") => " style=\"background-color:#fdd\"",
+        _ => "",
+    }
+}
+
+/// Writes an HTML table with a row per validation and a column per metric,
+/// each cell colored by whether its value passes, warns or fails (see
+/// [`MatrixMetric`]). The titles are colored by the result of the
+/// validations. Returns an empty string if there are no metrics.
+pub(crate) fn run_matrix(sections: &[&Section], metrics: &[MatrixMetric], ctx: &ReportContext) -> String {
+    if metrics.is_empty() {
+        return String::new();
+    }
+    let mut ret = format!(
+        "## {}\n\n{}matrix\"><table><thead><tr><th>{}</th>",
+        ctx.tr("Run matrix"),
+        own_html(),
+        ctx.tr("Validation")
+    );
+    for metric in metrics {
+        ret.push_str(&format!("<th>{}</th>", escape_html(ctx.tr(&metric.indicator))));
+    }
+    ret.push_str("</tr></thead><tbody>");
+    for s in sections {
+        let state = if s.failed() { "fail" } else { "pass" };
+        ret.push_str(&format!(
+            "<tr><td{}><a href=\"#{}\">{}</a></td>",
+            matrix_color(Some(state)),
+            s.anchor,
+            escape_html(&s.title)
+        ));
+        for metric in metrics {
+            match s.indicators.iter().find(|ind| ind.name == metric.indicator) {
+                Some(ind) => ret.push_str(&format!(
+                    "<td{}>{:.4}</td>",
+                    matrix_color(metric.state(ind.value)),
+                    ind.value
+                )),
+                None => ret.push_str("<td></td>"),
+            }
+        }
+        ret.push_str("</tr>");
+    }
+    ret.push_str("</tbody></table></div>\n");
+    ret
+}
+
+/// Writes an HTML table listing the quantities covered by the validations
+/// (see [`crate::Validate::covers`]), with the validations that check each of
+/// them and their overall result. The quantities in `declared` that are not
+/// covered by any validation are highlighted. Returns an empty string if there
+/// is no coverage to report.
+pub(crate) fn coverage_table(sections: &[&Section], declared: &[String], ctx: &ReportContext) -> String {
+    links_table(sections, declared, |s| &s.covers, ("coverage", "Coverage", "Quantity"), ctx)
+}
+
+/// Writes an HTML table mapping the requirements verified by the validations
+/// (see [`crate::Validate::requirements`]) to those validations and their overall
+/// result. The `declared` requirements that no validation verifies are highlighted.
+/// Returns an empty string if there are no requirements at all.
+pub(crate) fn traceability_table(sections: &[&Section], declared: &[String], ctx: &ReportContext) -> String {
+    links_table(sections, declared, |s| &s.requirements, ("traceability", "Traceability", "Requirement"), ctx)
+}
+
+/// Writes a table listing some items declared by the sections (e.g., the
+/// quantities they cover), linking to the sections of each item. The items in
+/// `declared` that no section declares are highlighted. `names` holds the
+/// class of the table, its heading and the header of the items' column.
+fn links_table<F>(
+    sections: &[&Section],
+    declared: &[String],
+    items_of: F,
+    names: (&str, &str, &str),
+    ctx: &ReportContext,
+) -> String
+where
+    F: Fn(&Section) -> &[String],
+{
+    let (class, heading, column) = names;
+    // Declared items first, then the others in order of appearance
+    let mut items: Vec<&str> = declared.iter().map(|q| q.as_str()).collect();
+    for item in sections.iter().flat_map(|s| items_of(s).iter()) {
+        if !items.contains(&item.as_str()) {
+            items.push(item)
+        }
+    }
+    if items.is_empty() {
+        return String::new();
+    }
+
+    let mut ret = format!(
+        "## {}\n\n{}{}\"><table><thead><tr><th>{}</th><th>{}</th><th>{}</th></tr></thead><tbody>",
+        ctx.tr(heading),
+        own_html(),
+        class,
+        ctx.tr(column),
+        ctx.tr("Validations"),
+        ctx.tr("Result")
+    );
+    for item in items {
+        let related: Vec<&&Section> = sections.iter().filter(|s| items_of(s).iter().any(|i| i == item)).collect();
+        if related.is_empty() {
+            ret.push_str(&format!(
+                "<tr style=\"background-color:#fdd\"><td>{}</td><td></td><td><strong>{}</strong></td></tr>",
+                escape_html(item),
+                ctx.tr("Not validated")
+            ));
+            continue;
+        }
+        let links: Vec<String> = related
+            .iter()
+            .map(|s| format!("<a href=\"#{}\">{}</a>", s.anchor, escape_html(&s.title)))
+            .collect();
+        let result = if related.iter().any(|s| s.failed()) { "Failed" } else { "Passed" };
+        ret.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(item),
+            links.join("<br>"),
+            ctx.tr(result)
+        ));
+    }
+    ret.push_str("</tbody></table></div>\n");
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sections() -> Vec<Section> {
+        vec![
+            Section::new(0, "## Zone B\n\nsome text".into(), None),
+            Section::new(1, "## Zone C".into(), Some("failed".into())),
+            Section::new(2, "no heading\n#### Errors:".into(), None),
+            Section::new(3, "## Zone A".into(), Some("failed".into())),
+        ]
+    }
+
+    fn titles(sections: &[Section]) -> Vec<&str> {
+        sections.iter().map(|s| s.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_coverage_table() {
+        let ctx = ReportContext::default();
+        let mut s = sections();
+        assert_eq!(coverage_table(&s.iter().collect::<Vec<_>>(), &[], &ctx), "");
+
+        s[0].covers = vec!["air temperature".into()];
+        s[1].covers = vec!["air temperature".into(), "conduction".into()];
+        let declared = vec!["infiltration".to_string(), "conduction".to_string()];
+        let table = coverage_table(&s.iter().collect::<Vec<_>>(), &declared, &ctx);
+        let rows: Vec<&str> = table.split("<tr").skip(2).collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].contains("infiltration") && rows[0].contains("Not validated"));
+        assert!(rows[1].contains("conduction") && rows[1].contains("Failed"));
+        assert!(rows[2].contains("href=\"#zone-b\">Zone B</a><br><a href=\"#zone-c\""));
+
+        s[3].requirements = vec!["REQ-THERM-012".into()];
+        let table = traceability_table(&s.iter().collect::<Vec<_>>(), &[], &ctx);
+        assert!(table.contains("<td>REQ-THERM-012</td><td><a href=\"#zone-a\">Zone A</a></td><td>Failed</td>"));
+        let table = traceability_table(&s.iter().collect::<Vec<_>>(), &["REQ-THERM-013".to_string()], &ctx);
+        assert!(table.contains("<td>REQ-THERM-013</td><td></td><td><strong>Not validated</strong></td>"));
+    }
+
+    #[test]
+    fn test_run_matrix() {
+        let ctx = ReportContext::default();
+        let mut s = sections();
+        assert_eq!(run_matrix(&s.iter().collect::<Vec<_>>(), &[], &ctx), "");
+
+        s[0].indicators = vec![Indicator::new("RMSE", 0.3), Indicator::new("R2", 0.7)];
+        s[1].indicators = vec![Indicator::new("RMSE", 0.7)];
+        s[2].indicators = vec![Indicator::new("RMSE", -1.5)];
+        let metrics = vec![
+            MatrixMetric {
+                warning: Some(0.5),
+                limit: Some(1.0),
+                ..MatrixMetric::new("RMSE")
+            },
+            MatrixMetric {
+                limit: Some(0.8),
+                higher_is_better: true,
+                ..MatrixMetric::new("R2")
+            },
+            MatrixMetric::new("MBE"),
+        ];
+        let table = run_matrix(&s.iter().collect::<Vec<_>>(), &metrics, &ctx);
+        let rows: Vec<&str> = table.split("<tr").skip(2).collect();
+        assert_eq!(rows.len(), s.len());
+        assert!(rows[0].contains("#dfd\"><a href=\"#zone-b\">Zone B</a></td><td style=\"background-color:#dfd\">0.3000</td><td style=\"background-color:#fdd\">0.7000</td><td></td>"));
+        assert!(rows[1].contains("#ffd\">0.7000</td><td></td><td></td>"));
+        assert!(rows[2].contains("#fdd\">-1.5000</td>"));
+    }
+
+    #[test]
+    fn test_rerun_hint() {
+        let ctx = ReportContext::default();
+        let mut s = sections();
+        for section in s.iter_mut() {
+            let title = section.title.clone();
+            section.add_rerun_hint(&title, &ctx);
+        }
+        // Passed sections are left alone
+        assert_eq!(s[0].body, "## Zone B\n\nsome text");
+
+        let error = s[1].error.as_ref().unwrap();
+        assert!(error.starts_with("failed\nRe-run with: VALIDATE_TITLE='Zone C' cargo test"));
+        assert!(error.ends_with("-- --exact report::tests::test_rerun_hint"));
+        assert!(s[1].body.contains("```\nVALIDATE_TITLE='Zone C' cargo test"));
+
+        // Nothing is expanded by the shell
+        assert_eq!(
+            rerun_command("The \"best\" $zone's `id`").split(" cargo").next(),
+            Some("VALIDATE_TITLE='The \"best\" $zone'\\''s `id`'")
+        );
+
+        let exact = TitleFilter::Exact("Zone 3".into());
+        assert!(exact.matches("Zone 3") && !exact.matches("Zone 30") && !exact.matches("Zone 3 (2)"));
+        assert!(TitleFilter::Contains("Zone 3".into()).matches("Zone 30"));
+    }
+
+    #[test]
+    fn test_markdown_options() {
+        let md = format!(
+            "| a | b |\n|---|---|\n| 1 | 2 |\n\n<b>raw</b>\n\n{}chart\"><svg></svg>\n</div>",
+            own_html()
+        );
+        let html = markdown_to_html(&md, &MarkdownOptions::default());
+        assert!(html.contains("<table>"));
+        assert!(html.contains("<b>raw</b>"));
+        assert!(html.contains("<div class=\"validate_chart\"><svg></svg>\n</div>"));
+
+        let options = MarkdownOptions {
+            tables: false,
+            raw_html: false,
+            ..MarkdownOptions::default()
+        };
+        let html = markdown_to_html(&md, &options);
+        assert!(!html.contains("<table>"));
+        assert!(html.contains("&lt;b&gt;raw&lt;/b&gt;"));
+        assert!(html.contains("<div class=\"validate_chart\"><svg></svg>\n</div>"));
+        assert!(!html.contains("data-validate"));
+
+        // Users cannot write blocks that pass for those of this crate
+        let forged = "<div class=\"validate_chart\"><script>alert(1)</script></div>";
+        let html = markdown_to_html(forged, &options);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_duplicate_titles() {
+        let ctx = ReportContext::default();
+        let duplicated = || {
+            vec![
+                Section::new(0, "## Zone A\n\ntext\n\n#### Errors:".into(), None),
+                Section::new(1, "### Zone A".into(), None),
+                Section::new(2, "## Zone A (2)".into(), None),
+                Section::new(3, "## zone a!".into(), None),
+            ]
+        };
+        let mut s = duplicated();
+        check_titles(&mut s, DuplicateTitles::Disambiguate, &ctx);
+        assert_eq!(titles(&s), vec!["Zone A", "Zone A (2)", "Zone A (2) (2)", "zone a! (3)"]);
+        assert_eq!(s[1].body, "### Zone A (2)");
+        assert_eq!(s[3].anchor, "zone-a-3");
+        assert!(s.iter().all(|s| !s.failed()));
+
+        let mut s = duplicated();
+        check_titles(&mut s, DuplicateTitles::Fail, &ctx);
+        let failed: Vec<bool> = s.iter().map(|s| s.failed()).collect();
+        assert_eq!(failed, vec![false, true, false, true]);
+    }
+
+    #[test]
+    fn test_table_of_contents() {
+        let ctx = ReportContext::default();
+        let mut s = sections();
+        assert_eq!(table_of_contents(&s.iter().collect::<Vec<_>>(), &ctx), "");
+
+        let anchor = |id: &str, heading: &str| format!("{}anchor\" id=\"{}\"></div>\n\n{}\n", own_html(), id, heading);
+        s[1].body = format!(
+            "## Zone C\n{}{}{}",
+            anchor("c--air", "### Air \\& walls"),
+            anchor("c--air--east", "#### East"),
+            anchor("c--floor", "### Floor")
+        );
+        let toc = table_of_contents(&s[..2].iter().collect::<Vec<_>>(), &ctx);
+        assert!(toc.contains(concat!(
+            "<ul><li><a href=\"#zone-b\">Zone B</a></li><li><a href=\"#zone-c\">Zone C</a>",
+            "<ul><li><a href=\"#c--air\">Air &amp; walls</a><ul><li><a href=\"#c--air--east\">East</a></li></ul></li>",
+            "<li><a href=\"#c--floor\">Floor</a></li></ul></li></ul>"
+        )));
+    }
+
+    #[test]
+    fn test_demote_headings() {
+        assert_eq!(
+            demote_headings("## A\n#### Errors:\n#tag\n```\n# comment\n```\n###### F"),
+            "### A\n##### Errors:\n#tag\n```\n# comment\n```\n###### F"
+        );
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Zone A (2)"), "zone-a-2");
+        assert_eq!(slug("  Temperatura: Zona Ñ  "), "temperatura-zona-ñ");
+        assert_eq!(slug("!!"), "validation");
+    }
+
+    #[test]
+    fn test_without_negative_zeros() {
+        assert_eq!(
+            without_negative_zeros("MBE: -0.0000, RMSE: -0.0001, x-0.0, -0.50, -0.0"),
+            "MBE: 0.0000, RMSE: -0.0001, x-0.0, -0.50, 0.0"
+        );
+        assert_eq!(without_negative_zeros("-"), "-");
+    }
+
+    #[test]
+    fn test_find_title() {
+        let s = sections();
+        assert_eq!(titles(&s), vec!["Zone B", "Zone C", "Validation 3", "Zone A"]);
+    }
+
+    #[test]
+    fn test_sort_sections() {
+        let mut s = sections();
+        sort_sections(&mut s, ReportOrder::Insertion);
+        assert_eq!(titles(&s), vec!["Zone B", "Zone C", "Validation 3", "Zone A"]);
+
+        sort_sections(&mut s, ReportOrder::Alphabetical);
+        assert_eq!(titles(&s), vec!["Validation 3", "Zone A", "Zone B", "Zone C"]);
+
+        let mut s = sections();
+        sort_sections(&mut s, ReportOrder::FailuresFirst);
+        assert_eq!(titles(&s), vec!["Zone C", "Zone A", "Zone B", "Validation 3"]);
+    }
+
+    #[test]
+    fn test_summary_table_indicators() {
+        let mut s = sections();
+        s[0].indicators = vec![Indicator::new("RMSE", 1.5)];
+        s[1].indicators = vec![Indicator::new("R2", 0.5), Indicator::new("RMSE", 2.)];
+        let s: Vec<&Section> = s.iter().collect();
+        let table = summary_table(&s, &ReportContext::default());
+
+        assert!(table.contains("RMSE</th><th onclick=\"validateSortSummary(this)\">R2</th></tr>"));
+        assert!(table.contains("<td><a href=\"#zone-b\">Zone B</a></td><td>Passed</td><td data-value=\"1.5\">1.5000</td><td></td></tr>"));
+        assert!(table.contains("<td><a href=\"#zone-c\">Zone C</a></td><td>Failed</td><td data-value=\"2\">2.0000</td><td data-value=\"0.5\">0.5000</td></tr>"));
+    }
+}
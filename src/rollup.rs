@@ -0,0 +1,367 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::{escape_markdown, Criterion, Indicator, ReportContext, Validate, ValidationResult};
+
+/// A level of a [`RollupValidator`]: either a validation of a single zone or
+/// component, or a group of other levels (e.g., the zones of a floor)
+pub struct RollupNode {
+    /// The name of this level (e.g., `"Zone 3"` or `"Heating system"`)
+    pub name: String,
+
+    /// The weight of this level within its group (e.g., its floor area or
+    /// its share of the energy use). Weights are relative to those of the
+    /// other members of the group, so they do not need to add up to one.
+    pub weight: f64,
+
+    /// The validation of this level, if it is a single zone or component
+    pub validation: Option<Box<dyn Validate>>,
+
+    /// The levels grouped under this one
+    pub children: Vec<RollupNode>,
+}
+
+impl RollupNode {
+    /// Creates a level with the validation of a single zone or component
+    pub fn leaf<S: Into<String>>(name: S, weight: f64, validation: Box<dyn Validate>) -> Self {
+        Self {
+            name: name.into(),
+            weight,
+            validation: Some(validation),
+            children: Vec::new(),
+        }
+    }
+
+    /// Creates a level grouping others
+    pub fn group<S: Into<String>>(name: S, weight: f64, children: Vec<RollupNode>) -> Self {
+        Self {
+            name: name.into(),
+            weight,
+            validation: None,
+            children,
+        }
+    }
+}
+
+/// The results of a level of a [`RollupValidator`]
+struct Rolled {
+    /// Whether this level and all the levels under it passed
+    passed: bool,
+
+    /// The (weighted) indicators of this level
+    indicators: Vec<Indicator>,
+
+    /// The lines of the tree, from this level down
+    tree: String,
+}
+
+/// Rolls the indicators of per-zone or per-component validations up into
+/// building-level or system-level ones, which are the weighted means of the
+/// indicators of the members of each group (only those calculated by all of
+/// them). The report shows the levels as a tree, with the result of each:
+/// a single zone or component passes if its validation does, and a group
+/// passes if all its members do and its indicators meet the `criterion`.
+///
+/// # Example
+///
+/// ```
+/// use validate::{Criterion, RollupNode, RollupValidator, SeriesValidator, Validate};
+///
+/// let zone = |found: Vec<f64>| {
+///     Box::new(SeriesValidator {
+///         expected: vec![20., 21., 22.],
+///         found,
+///         ..Default::default()
+///     })
+/// };
+/// let v = RollupValidator {
+///     root: RollupNode::group(
+///         "Building",
+///         1.,
+///         vec![
+///             // Weighted by floor area
+///             RollupNode::leaf("Office", 300., zone(vec![20.1, 21.1, 22.1])),
+///             RollupNode::leaf("Storage", 100., zone(vec![21., 22., 23.])),
+///         ],
+///     ),
+///     criterion: Some(Criterion::parse("|mbe| < 0.5").unwrap()),
+/// };
+/// // The small storage room does not spoil the building
+/// assert!(v.validate().is_ok());
+/// assert_eq!(v.indicators()[0].name, "Mean Bias Error");
+/// assert!((v.indicators()[0].value - 0.325).abs() < 1e-9);
+/// ```
+pub struct RollupValidator {
+    /// The top level (e.g., the building)
+    pub root: RollupNode,
+
+    /// The criterion that the indicators of every group must meet
+    pub criterion: Option<Criterion>,
+}
+
+impl RollupValidator {
+    /// Validates a level, writing the sections of the single zones
+    /// or components into `sections` and their errors into `errors`
+    fn roll(
+        &self,
+        node: &RollupNode,
+        ctx: &ReportContext,
+        depth: usize,
+        sections: &mut String,
+        errors: &mut Vec<String>,
+    ) -> Rolled {
+        let indent = "    ".repeat(depth);
+        let name = escape_markdown(&node.name);
+        let (mut passed, indicators, children, mut note) = match &node.validation {
+            Some(validation) => {
+                let (txt, passed) = match validation.validate_in(ctx) {
+                    ValidationResult::Ok(txt) => (txt, true),
+                    ValidationResult::Err(txt, e) => {
+                        errors.push(format!("{}: {}", node.name, e.trim()));
+                        (txt, false)
+                    }
+                };
+                sections.push_str(&format!("\n\n#### {}\n\n{}", name, txt));
+                (passed, validation.indicators(), String::new(), String::new())
+            }
+            None => {
+                let rolled: Vec<(f64, Rolled)> = node
+                    .children
+                    .iter()
+                    .map(|c| (c.weight, self.roll(c, ctx, depth + 1, sections, errors)))
+                    .collect();
+                let passed = rolled.iter().all(|(_, r)| r.passed);
+                let children: String = rolled.iter().map(|(_, r)| r.tree.as_str()).collect();
+                let members: Vec<(f64, &[Indicator])> =
+                    rolled.iter().map(|(w, r)| (*w, r.indicators.as_slice())).collect();
+                match weighted_indicators(&members) {
+                    Ok(indicators) => (passed, indicators, children, String::new()),
+                    Err(e) => {
+                        errors.push(format!("{}: {}", node.name, e));
+                        (false, Vec::new(), children, format!(" ({})", e))
+                    }
+                }
+            }
+        };
+
+        if let (None, Some(criterion)) = (&node.validation, &self.criterion) {
+            match criterion.evaluate(&indicators) {
+                Ok(true) => {}
+                Ok(false) => {
+                    passed = false;
+                    note = format!(" ({}: `{}`)", ctx.tr("The acceptance criterion is not met"), criterion);
+                    errors.push(format!("{}: The acceptance criterion is not met: {}", node.name, criterion));
+                }
+                Err(e) => {
+                    passed = false;
+                    note = format!(" ({}: {})", ctx.tr("Cannot evaluate the acceptance criterion"), e);
+                    errors.push(format!("{}: Cannot evaluate the acceptance criterion: {}", node.name, e));
+                }
+            }
+        }
+
+        let values: Vec<String> = indicators
+            .iter()
+            .map(|i| format!("{} = {:.4}", ctx.tr(&i.name), i.value))
+            .collect();
+        let tree = format!(
+            "{} * **{}** ({} {}): {}{}{}{}\n{}",
+            indent,
+            name,
+            ctx.tr("weight"),
+            node.weight,
+            ctx.tr(if passed { "Passed" } else { "Failed" }),
+            note,
+            if values.is_empty() { "" } else { ". " },
+            values.join(", "),
+            children
+        );
+        Rolled {
+            passed,
+            indicators,
+            tree,
+        }
+    }
+}
+
+/// The weighted means of the indicators shared by the members of a group
+fn weighted_indicators(members: &[(f64, &[Indicator])]) -> Result<Vec<Indicator>, String> {
+    if members.is_empty() {
+        return Err("the group is empty".to_string());
+    }
+    if let Some((w, _)) = members.iter().find(|(w, _)| !(w.is_finite() && *w >= 0.)) {
+        return Err(format!("invalid weight {}: weights must not be negative", w));
+    }
+    let total: f64 = members.iter().map(|(w, _)| w).sum();
+    if total <= 0. {
+        return Err("the weights of the members add up to zero".to_string());
+    }
+    let value_of = |r: &[Indicator], name: &str| r.iter().find(|i| i.name == name).map(|i| i.value);
+    Ok(members[0]
+        .1
+        .iter()
+        .filter_map(|i| {
+            let values: Option<Vec<f64>> = members.iter().map(|(_, r)| value_of(r, &i.name)).collect();
+            let sum: f64 = members.iter().zip(values?).map(|((w, _), v)| w * v).sum();
+            Some(Indicator::new(i.name.clone(), sum / total))
+        })
+        .collect())
+}
+
+impl Validate for RollupValidator {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        let mut sections = String::new();
+        let mut errors = Vec::new();
+        let rolled = self.roll(&self.root, ctx, 0, &mut sections, &mut errors);
+        let criterion = match &self.criterion {
+            Some(c) => format!("\n{}: `{}`\n", ctx.tr("Acceptance criterion of each group"), c),
+            None => String::new(),
+        };
+        let file = format!("{}\n{}{}", criterion, rolled.tree, sections);
+        if rolled.passed {
+            ValidationResult::Ok(file)
+        } else {
+            let err_msg: String = errors.iter().map(|e| format!("\n * {}", e)).collect();
+            ValidationResult::Err(file, err_msg)
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        // From those of the zones or components, without validating them
+        fn of(node: &RollupNode) -> Vec<Indicator> {
+            match &node.validation {
+                Some(validation) => validation.indicators(),
+                None => {
+                    let children: Vec<(f64, Vec<Indicator>)> =
+                        node.children.iter().map(|c| (c.weight, of(c))).collect();
+                    let members: Vec<(f64, &[Indicator])> =
+                        children.iter().map(|(w, i)| (*w, i.as_slice())).collect();
+                    weighted_indicators(&members).unwrap_or_default()
+                }
+            }
+        }
+        of(&self.root)
+    }
+
+    fn defines_thresholds(&self, ctx: &ReportContext) -> bool {
+        fn any_leaf(node: &RollupNode, ctx: &ReportContext) -> bool {
+            node.validation.as_ref().is_some_and(|v| v.defines_thresholds(ctx))
+                || node.children.iter().any(|c| any_leaf(c, ctx))
+        }
+        self.criterion.is_some() || any_leaf(&self.root, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SeriesValidator;
+
+    #[test]
+    fn test_rollup_tree() {
+        let zone = |found: Vec<f64>, allowed: f64| -> Box<dyn Validate> {
+            Box::new(SeriesValidator {
+                expected: vec![1., 2., 3.],
+                found,
+                allowed_mean_bias_error: Some(allowed),
+                ..Default::default()
+            })
+        };
+        let v = RollupValidator {
+            root: RollupNode::group(
+                "Building",
+                1.,
+                vec![
+                    RollupNode::group(
+                        "Ground floor",
+                        2.,
+                        vec![
+                            RollupNode::leaf("Zone A", 1., zone(vec![1., 2., 3.], 1.)),
+                            RollupNode::leaf("Zone B", 1., zone(vec![2., 3., 4.], 0.5)),
+                        ],
+                    ),
+                    RollupNode::leaf("Roof", 1., zone(vec![1.5, 2.5, 3.5], 1.)),
+                ],
+            ),
+            criterion: None,
+        };
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(txt, e) => {
+                assert!(txt.contains(" * **Building** (weight 1): Failed. Mean Bias Error = 0.5000"));
+                assert!(txt.contains("     * **Ground floor** (weight 2): Failed. Mean Bias Error = 0.5000"));
+                assert!(txt.contains("         * **Zone A** (weight 1): Passed. Mean Bias Error = 0.0000"));
+                assert!(txt.contains("#### Zone B"));
+                assert!(e.contains("Zone B: "));
+            }
+        }
+
+        // The indicators come from those of the zones, without validating
+        // them again
+        let mbe = v.indicators().into_iter().find(|i| i.name == "Mean Bias Error").unwrap();
+        assert!((mbe.value - 0.5).abs() < 1e-9);
+
+        let empty = RollupValidator {
+            root: RollupNode::group("Nothing", 1., Vec::new()),
+            criterion: None,
+        };
+        assert!(empty.validate().is_err());
+        assert!(empty.indicators().is_empty());
+    }
+
+    #[test]
+    fn test_rollup_indicators() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static VALIDATED: AtomicUsize = AtomicUsize::new(0);
+        struct Counted;
+        impl Validate for Counted {
+            fn validate(&self) -> ValidationResult {
+                VALIDATED.fetch_add(1, Ordering::SeqCst);
+                ValidationResult::Ok(String::new())
+            }
+            fn indicators(&self) -> Vec<Indicator> {
+                vec![Indicator::new("Mean Bias Error", 1.)]
+            }
+        }
+        let v = RollupValidator {
+            root: RollupNode::group(
+                "Building",
+                1.,
+                vec![
+                    RollupNode::leaf("Zone A", 1., Box::new(Counted)),
+                    RollupNode::leaf("Zone B", 3., Box::new(Counted)),
+                ],
+            ),
+            criterion: None,
+        };
+        assert_eq!(v.indicators()[0].value, 1.);
+        assert_eq!(VALIDATED.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,153 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/// How the text provided by the user (e.g., titles, descriptions or the
+/// names of cases, which are often derived from data) is written into a report
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::{TextPolicy, Validator};
+///
+/// let mut validator = Validator::new("Cases", "report.html");
+/// validator.set_text_policy(TextPolicy::PlainText);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextPolicy {
+    /// HTML is escaped, but Markdown (e.g., `*emphasis*` or `` `code` ``) is
+    /// still rendered
+    #[default]
+    Markdown,
+
+    /// HTML and Markdown are escaped, so the text is shown exactly as written
+    PlainText,
+
+    /// The text is written unchanged, so it can contain raw HTML. Use only
+    /// with text that comes from a trusted source.
+    Trusted,
+}
+
+impl TextPolicy {
+    /// Prepares a text to be written into the Markdown of a report
+    pub fn apply(&self, txt: &str) -> String {
+        match self {
+            TextPolicy::Markdown => escape_html(txt),
+            TextPolicy::PlainText => escape_markdown(txt),
+            TextPolicy::Trusted => txt.to_string(),
+        }
+    }
+}
+
+/// Escapes the characters that have a meaning in HTML, so a text can be
+/// safely written into an HTML document (or into Markdown, where it is
+/// still rendered as Markdown).
+///
+/// ```
+/// use validate::escape_html;
+///
+/// assert_eq!(escape_html("<b>A & B</b>"), "&lt;b&gt;A &amp; B&lt;/b&gt;");
+/// ```
+pub fn escape_html(txt: &str) -> String {
+    let mut ret = String::with_capacity(txt.len());
+    for c in txt.chars() {
+        match c {
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '&' => ret.push_str("&amp;"),
+            '"' => ret.push_str("&quot;"),
+            '\'' => ret.push_str("&#39;"),
+            _ => ret.push(c),
+        }
+    }
+    ret
+}
+
+/// Escapes the characters that have a meaning in Markdown or HTML, so a
+/// text written into Markdown is shown exactly as written.
+///
+/// ```
+/// use validate::escape_markdown;
+///
+/// assert_eq!(escape_markdown("T_air < 5 *C*"), "T\\_air &lt; 5 \\*C\\*");
+/// ```
+pub fn escape_markdown(txt: &str) -> String {
+    let mut ret = String::with_capacity(txt.len());
+    for c in txt.chars() {
+        match c {
+            '<' | '>' | '&' | '"' | '\'' => ret.push_str(&escape_html(&c.to_string())),
+            c if c.is_ascii_punctuation() => {
+                ret.push('\\');
+                ret.push(c);
+            }
+            _ => ret.push(c),
+        }
+    }
+    ret
+}
+
+/// Reverts [`escape_html`] and [`escape_markdown`], getting back the
+/// text as written by the user (e.g., for using a heading as a title)
+pub(crate) fn unescape(txt: &str) -> String {
+    let mut ret = String::with_capacity(txt.len());
+    let mut chars = txt.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek().is_some_and(|n| n.is_ascii_punctuation()) => {
+                if let Some(n) = chars.next() {
+                    ret.push(n)
+                }
+            }
+            _ => ret.push(c),
+        }
+    }
+    ret.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policies() {
+        let txt = "Case <script>alert(1)</script> & *more*";
+        assert_eq!(
+            TextPolicy::Markdown.apply(txt),
+            "Case &lt;script&gt;alert(1)&lt;/script&gt; &amp; *more*"
+        );
+        assert_eq!(
+            TextPolicy::PlainText.apply(txt),
+            "Case &lt;script&gt;alert\\(1\\)&lt;\\/script&gt; &amp; \\*more\\*"
+        );
+        assert_eq!(TextPolicy::Trusted.apply(txt), txt);
+    }
+
+    #[test]
+    fn test_unescape() {
+        let txt = "Zone <A> & \"B\" [1] *C*";
+        assert_eq!(unescape(&escape_html(txt)), txt);
+        assert_eq!(unescape(&escape_markdown(txt)), txt);
+        assert_eq!(unescape("C:\\Users"), "C:\\Users");
+    }
+}
@@ -19,30 +19,34 @@ SOFTWARE.
 */
 
 use crate::numberish::Numberish;
+use crate::{Artifact, ChartData, Indicator, ReportContext, RunInput, Thresholds};
 use crate::Validate;
 use crate::ValidationResult;
+#[cfg(feature = "charts")]
+use crate::svg::describe_series;
+#[cfg(feature = "charts")]
 use poloto::prelude::*;
 
 /// Validates a time series based on Mean Bias Error and Root Mean Squared Error
 #[derive(Default, Clone)]
 pub struct ScatterValidator<T> {
     /// The units in the x and y axis of the chart (they are supposed to be the same)
-    pub units: Option<&'static str>,
+    pub units: Option<String>,
 
     /// The name of the series caled `expected`
-    pub expected_legend: Option<&'static str>,
+    pub expected_legend: Option<String>,
 
     /// The time series containing the expected values
     pub expected: Vec<T>,
 
     /// The name of the `found` time series
-    pub found_legend: Option<&'static str>,
+    pub found_legend: Option<String>,
 
     /// The time series containing the found values
     pub found: Vec<T>,
 
     /// the title of the chart
-    pub chart_title: Option<&'static str>,
+    pub chart_title: Option<String>,
 
     /// The minimum allowed R2 for the regression coefficient.
     pub allowed_r2: Option<T>,
@@ -66,10 +70,179 @@ pub struct ScatterValidator<T> {
     /// Defaults to 1.0. It is only checked if the `allowed_slope_delta`
     /// is not None.
     pub expected_slope: Option<T>,
+
+    /// The maximum allowed Standard Error of the Estimate, in the
+    /// units of the data.
+    pub allowed_standard_error: Option<T>,
+
+    /// The minimum number of points required for the regression to
+    /// be considered meaningful.
+    pub minimum_points: Option<usize>,
+
+    /// The category (e.g., the season) of each point. If not empty, it
+    /// must have the same length as `expected` and `found`; the points
+    /// are then color-coded by category and the fit of each category is
+    /// reported in a table.
+    pub categories: Vec<String>,
+
+    /// Files to attach to the report of this validation
+    pub artifacts: Vec<Artifact>,
+
+    /// The settings under which `found` was produced (e.g., random
+    /// seeds), recorded in the report
+    pub inputs: Vec<RunInput>,
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"zone air temperature"`), listed in the coverage of the report
+    pub covers: Vec<String>,
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"`), mapped to it in the report
+    pub requirements: Vec<String>,
+}
+
+
+/// The statistics calculated by a [`ScatterValidator`], as returned by
+/// [`ScatterValidator::metrics`]. Values are in the units of the data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScatterMetrics {
+    /// The number of points
+    pub points: usize,
+
+    /// The intersect of the linear fit
+    pub intersect: f64,
+
+    /// The slope of the linear fit
+    pub slope: f64,
+
+    /// The R2 of the linear fit
+    pub r2: f64,
+
+    /// The Standard Error of the Estimate of the linear fit, which
+    /// needs at least 3 points
+    pub standard_error: Option<f64>,
 }
 
-impl<T: Numberish> Validate for ScatterValidator<T> {
+impl<T: Numberish> ScatterValidator<T> {
+    /// Calculates the statistics of the linear fit of `found` against
+    /// `expected`, without checking them or writing any report.
+    ///
+    /// ```
+    /// use validate::ScatterValidator;
+    ///
+    /// let v = ScatterValidator {
+    ///     expected: vec![1., 2., 3., 4.],
+    ///     found: vec![3., 5., 7., 9.],
+    ///     ..Default::default()
+    /// };
+    /// let metrics = v.metrics().unwrap();
+    /// assert_eq!(metrics.intersect, 1.);
+    /// assert_eq!(metrics.slope, 2.);
+    /// assert_eq!(metrics.points, 4);
+    /// ```
+    pub fn metrics(&self) -> Result<ScatterMetrics, String> {
+        if self.expected.len() != self.found.len() {
+            return Err(format!(
+                "Series to compare have different lengths. expected.len() = {}, found.len() = {}",
+                self.expected.len(),
+                self.found.len()
+            ));
+        }
+        if self.expected.is_empty() {
+            return Err("Series to compare are empty".to_string());
+        }
+        if let Some(d) = crate::stats::degeneracy(&self.expected) {
+            return Err(format!(
+                "Cannot fit a line to the points, as the expected values do not vary ({})",
+                d
+            ));
+        }
+        let (intersect, slope, r2) =
+            crate::stats::linear_coefficients(self.expected.as_slice(), self.found.as_slice());
+        let points = self.expected.len();
+        let standard_error = if points > 2 {
+            Some(crate::stats::standard_error_of_estimate(
+                self.expected.as_slice(),
+                self.found.as_slice(),
+            ))
+        } else {
+            None
+        };
+        Ok(ScatterMetrics {
+            points,
+            intersect,
+            slope,
+            r2,
+            standard_error,
+        })
+    }
+
+    /// Splits the indices of the points by category, in order of appearance.
+    fn groups(&self) -> Vec<(String, Vec<usize>)> {
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        for (i, cat) in self.categories.iter().enumerate() {
+            match groups.iter_mut().find(|(c, _)| c == cat) {
+                Some((_, indices)) => indices.push(i),
+                None => groups.push((cat.clone(), vec![i])),
+            }
+        }
+        groups
+    }
+
+    /// Writes an HTML table with the fit of each category
+    fn category_table(&self, ctx: &ReportContext) -> String {
+        let units = ctx.unit_system();
+        let data_units = self.units.as_deref().unwrap_or("");
+        let show = |v: f64| units.convert(v, data_units);
+        let show_diff = |v: f64| units.convert_difference(v, data_units);
+
+        let mut ret = format!(
+            "{}categories\"><table><thead><tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr></thead><tbody>",
+            crate::report::own_html(),
+            ctx.tr("Category"),
+            ctx.tr("Points"),
+            ctx.tr("Intersect"),
+            ctx.tr("Slope"),
+            ctx.tr("R2"),
+            ctx.tr("Mean Bias Error"),
+            ctx.tr("Root Mean Squared Error"),
+        );
+        for (cat, indices) in self.groups() {
+            let expected: Vec<T> = indices.iter().map(|i| self.expected[*i]).collect();
+            let found: Vec<T> = indices.iter().map(|i| self.found[*i]).collect();
+            let mbe = crate::stats::mean_bias_error(&expected, &found);
+            let rmse = crate::stats::root_mean_squared_error(&expected, &found);
+            // A line cannot be fitted through a single point, or
+            // when all the expected values are equal
+            let fit = match crate::stats::try_linear_coefficients(&expected, &found) {
+                Ok((intersect, slope, r2)) if indices.len() > 1 => format!(
+                    "<td>{:.4}</td><td>{:.4}</td><td>{:.4}</td>",
+                    show(intersect) - slope * show(0.0),
+                    slope,
+                    r2
+                ),
+                _ => "<td>-</td><td>-</td><td>-</td>".to_string(),
+            };
+            ret.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td>{}<td>{:.4}</td><td>{:.4}</td></tr>",
+                crate::escape_html(&cat),
+                indices.len(),
+                fit,
+                show_diff(mbe),
+                show_diff(rmse)
+            ));
+        }
+        ret.push_str("</tbody></table></div>");
+        ret
+    }
+}
+
+impl<T: Numberish + 'static> Validate for ScatterValidator<T> {
     fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
         let mut err_msg = String::new();
 
         if self.expected.len() != self.found.len() {
@@ -80,19 +253,87 @@ impl<T: Numberish> Validate for ScatterValidator<T> {
             );
             return ValidationResult::Err(err_msg.clone(), err_msg);
         }
+        if !self.categories.is_empty() && self.categories.len() != self.expected.len() {
+            err_msg = format!(
+                "Categories and series have different lengths. categories.len() = {}, expected.len() = {}",
+                self.categories.len(),
+                self.expected.len()
+            );
+            return ValidationResult::Err(err_msg.clone(), err_msg);
+        }
 
-        let (intersect, slope, r2) =
-            crate::stats::linear_coefficients(self.expected.as_slice(), self.found.as_slice());
-        let fit_msg = format!(
-            " * Fit: {:.4} + {:.4}x \n * R2 = {:.4}",
-            intersect, slope, r2
+        let n = self.expected.len();
+        let minimum_points = self.minimum_points.unwrap_or(1).max(1);
+        if n < minimum_points {
+            err_msg = format!(
+                "Not enough points to compare: there are {} points, but at least {} are required",
+                n, minimum_points
+            );
+            return ValidationResult::Err(err_msg.clone(), err_msg);
+        }
+
+        let metrics = match self.metrics() {
+            Ok(m) => m,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+        let (intersect, slope, r2) = (metrics.intersect, metrics.slope, metrics.r2);
+
+        // Values are calculated in the units of the data, but
+        // displayed in the unit system of the report. The slope
+        // and R2 do not change, but the intersect does.
+        let units = ctx.unit_system();
+        let data_units = self.units.as_deref().unwrap_or("");
+        let show = |v: f64| units.convert(v, data_units);
+        let show_diff = |v: f64| units.convert_difference(v, data_units);
+        let show_intersect = |a: f64, b: f64| show(a) - b * show(0.0);
+
+        let std_error = metrics.standard_error;
+
+        let mut fit_msg = format!(
+            " * {}: {:.4} + {:.4}x \n * R2 = {:.4}\n * {}: {}",
+            ctx.tr("Fit"),
+            show_intersect(intersect, slope),
+            slope,
+            r2,
+            ctx.tr("Number of points"),
+            n
         );
+        if let Some(std_error) = std_error {
+            fit_msg = format!(
+                "{}\n * {}: {:.4}",
+                fit_msg,
+                ctx.tr("Standard Error of the Estimate"),
+                show_diff(std_error)
+            );
+        }
+        if let Some(d) = crate::stats::degeneracy(&self.found) {
+            fit_msg = format!(
+                "{}\n * {}: {} ({}), {}",
+                fit_msg,
+                ctx.tr("Warning"),
+                ctx.tr("The found values do not vary"),
+                d,
+                ctx.tr("so R2 is 0")
+            );
+        }
 
         let mut nchecks = 0;
         // Check compliance
-        if let Some(allowed_r2) = self.allowed_r2 {
+        // Thresholds not set in this validation are inherited from the report
+        let defaults = ctx.thresholds();
+        let allowed_r2: Option<f64> = self.allowed_r2.map(|v| v.into()).or(defaults.r2);
+        let allowed_intersect_delta: Option<f64> = self
+            .allowed_intersect_delta
+            .map(|v| v.into())
+            .or(defaults.intersect_delta);
+        let allowed_slope_delta: Option<f64> = self
+            .allowed_slope_delta
+            .map(|v| v.into())
+            .or(defaults.slope_delta);
+
+        if let Some(allowed_r2) = allowed_r2 {
             nchecks +=1;
-            if r2 < allowed_r2.into() {
+            if r2 < allowed_r2 {
                 err_msg = format!(
                     "{}\n *  R2 is {:.4}, which is lower than the allowed value of {:.4}",
                     err_msg, r2, allowed_r2
@@ -101,33 +342,33 @@ impl<T: Numberish> Validate for ScatterValidator<T> {
             }
         }
 
-        if let Some(allowed_intersect_delta) = self.allowed_intersect_delta {
+        if let Some(allowed_intersect_delta) = allowed_intersect_delta {
             nchecks +=1;
             let expected_intersect: f64 = match self.expected_intersect {
                 Some(v) => v.into(),
                 None => 0.0,
             };
             let delta = (intersect - expected_intersect).abs();
-            if delta > allowed_intersect_delta.into() {
+            if delta > allowed_intersect_delta {
                 err_msg = format!(
                     "{}\n *  Intersect is {:.4} when expecting {:.4}... difference ({:.4}) is higher than the allowed value of {:.4}",
                     err_msg,
-                    intersect,
-                    expected_intersect,
-                    delta,
-                    allowed_intersect_delta
+                    show_intersect(intersect, slope),
+                    show_intersect(expected_intersect, self.expected_slope.map_or(1.0, |v| v.into())),
+                    show_diff(delta),
+                    show_diff(allowed_intersect_delta)
                 );
             }
         }
 
-        if let Some(allowed_slope_delta) = self.allowed_slope_delta {
+        if let Some(allowed_slope_delta) = allowed_slope_delta {
             nchecks +=1;
             let expected_slope: f64 = match self.expected_slope {
                 Some(v) => v.into(),
                 None => 1.0,
             };
             let delta = (slope - expected_slope).abs();
-            if delta > allowed_slope_delta.into() {
+            if delta > allowed_slope_delta {
                 err_msg = format!(
                     "{}\n *  Slope is {:.4} when expecting {:.4}... difference ({:.4}) is higher than the allowed value of {:.4}",
                     err_msg,
@@ -139,60 +380,165 @@ impl<T: Numberish> Validate for ScatterValidator<T> {
             }
         }
 
-        let n = self.expected.len();
-        let data = |i: usize| [self.expected[i].into(), self.found[i].into()];
-
-        let exp_legend = self.expected_legend.unwrap_or("Expected");
-        let found_legend = self.found_legend.unwrap_or("Found");
-        let origin = poloto::build::origin();
-
-        let (.., max_x) = crate::stats::min_max(&self.expected);
-        let fit = |i: usize| {
-            if i == 0 {
-                [0., intersect]
-            } else if i == 1 {
-                [max_x.into(), (intersect + max_x.into() * slope)]
-            } else {
-                unreachable!();
+        if let Some(allowed_standard_error) = self.allowed_standard_error {
+            nchecks += 1;
+            match std_error {
+                Some(std_error) if std_error > allowed_standard_error.into() => {
+                    err_msg = format!(
+                        "{}\n *  Standard Error of the Estimate is {:.4}, which is higher than the allowed value of {:.4}",
+                        err_msg,
+                        show_diff(std_error),
+                        show_diff(allowed_standard_error.into())
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    err_msg = format!(
+                        "{}\n *  Standard Error of the Estimate requires at least 3 points, but there are only {}",
+                        err_msg, n
+                    );
+                }
             }
-        };
-        let range = (0..2).map(|x| x as usize);
-        let fit = range.clone().map(fit).buffered_plot().line("fit");
-
-        let exp_fit = |i: usize| {
-            if i == 0 {
-                [0.0, 0.0]
-            } else if i == 1 {
-                [max_x.into(), max_x.into()]
-            } else {
-                unreachable!();
+        }
+
+        #[cfg(feature = "charts")]
+        let (chart, residual_chart) = {
+            let data = |i: usize| [show(self.expected[i].into()), show(self.found[i].into())];
+
+            let mut exp_legend: String = self.expected_legend.as_deref().unwrap_or_else(|| ctx.tr("Expected")).into();
+            let mut found_legend: String = self.found_legend.as_deref().unwrap_or_else(|| ctx.tr("Found")).into();
+            if let Some(data_units) = &self.units {
+                exp_legend = format!("{} ({})", exp_legend, units.units(data_units));
+                found_legend = format!("{} ({})", found_legend, units.units(data_units));
             }
+            let origin = poloto::build::origin();
+
+            let (min_x, max_x) = crate::stats::min_max(&self.expected);
+            let min_x: f64 = min_x.into();
+            let max_x: f64 = max_x.into();
+            let fit = |i: usize| {
+                if i == 0 {
+                    [show(0.), show(intersect)]
+                } else if i == 1 {
+                    [show(max_x), show(intersect + max_x * slope)]
+                } else {
+                    unreachable!();
+                }
+            };
+            let range = (0..2).map(|x| x as usize);
+            let fit: Vec<[f64; 2]> = range.clone().map(fit).collect();
+
+            let exp_fit = |i: usize| {
+                if i == 0 {
+                    [show(0.0), show(0.0)]
+                } else if i == 1 {
+                    [show(max_x), show(max_x)]
+                } else {
+                    unreachable!();
+                }
+            };
+            let exp_fit: Vec<[f64; 2]> = range.map(exp_fit).collect();
+
+            // One scatter plot per category, so they get different colors. The
+            // fit lines go in the same list, as all the plots need to share a type.
+            let groups = if self.categories.is_empty() {
+                vec![("some name".to_string(), (0..n).collect())]
+            } else {
+                self.groups()
+            };
+            let mut plots: Vec<_> = groups
+                .iter()
+                .map(|(cat, indices)| {
+                    indices
+                        .iter()
+                        .map(|i| data(*i))
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                        .buffered_plot()
+                        .scatter(cat.as_str())
+                })
+                .collect();
+            plots.push(fit.into_iter().buffered_plot().line("fit"));
+            plots.push(exp_fit.into_iter().buffered_plot().line("expected_fit"));
+            let plots = poloto::build::plots_dyn(plots);
+
+            let chart_title = self.chart_title.as_deref().unwrap_or("");
+            let p = quick_fmt!(
+                chart_title,
+                &exp_legend,
+                &found_legend,
+                plots,
+                origin
+            );
+
+            // Residuals of the fit, plotted against the expected values
+            let residual = |i: usize| {
+                let x: f64 = self.expected[i].into();
+                let y: f64 = self.found[i].into();
+                [show(x), show_diff(y - intersect - slope * x)]
+            };
+            let residuals = (0..n).map(residual).buffered_plot().scatter("residuals");
+            let zero = [[show(min_x), 0.0], [show(max_x), 0.0]]
+                .into_iter()
+                .buffered_plot()
+                .line("zero");
+            let residual_label = match &self.units {
+                Some(data_units) => format!("{} ({})", ctx.tr("Residual"), units.units(data_units)),
+                None => ctx.tr("Residual").to_string(),
+            };
+            let r = quick_fmt!(
+                chart_title,
+                &exp_legend,
+                &residual_label,
+                residuals,
+                zero,
+                poloto::build::origin()
+            );
+            let alt_title = if chart_title.is_empty() {
+                format!("{} vs {}", found_legend, exp_legend)
+            } else {
+                chart_title.to_string()
+            };
+            let description = [
+                describe_series(&exp_legend, self.expected.iter().map(|v| show((*v).into()))),
+                describe_series(&found_legend, self.found.iter().map(|v| show((*v).into()))),
+                format!("{}: {:.4} + {:.4}x", ctx.tr("Fit"), show_intersect(intersect, slope), slope),
+                format!("R2 = {:.4}", r2),
+            ]
+            .join(". ");
+            let residual_title = format!("{} vs {}", residual_label, exp_legend);
+            let residual_description = describe_series(
+                &residual_label,
+                (0..n).map(|i| show_diff(self.found[i].into() - intersect - slope * self.expected[i].into())),
+            );
+            (
+                ctx.chart_theme()
+                    .render(poloto::disp(|w| p.simple_theme(w)).to_string(), &alt_title, &description),
+                ctx.chart_theme()
+                    .render(poloto::disp(|w| r.simple_theme(w)).to_string(), &residual_title, &residual_description),
+            )
         };
-        let exp_fit = range.map(exp_fit).buffered_plot().line("expected_fit");
-        let range = 0..n;
-        let scatter = range.map(data).buffered_plot().scatter("some name");
-
-        let chart_title = self.chart_title.unwrap_or("");
-        let p = quick_fmt!(
-            chart_title,
-            &exp_legend,
-            &found_legend,
-            scatter,
-            fit,
-            exp_fit,
-            origin
-        );
+        #[cfg(not(feature = "charts"))]
+        let (chart, residual_chart) = (ctx.chart_theme().omitted(), ctx.chart_theme().omitted());
 
         let show_err = if nchecks == 0 {
-            "No checks performed..."
+            ctx.tr("No checks performed...")
         }else if err_msg.is_empty() { 
-            "No errors found" 
+            ctx.tr("No errors found")
         } else { &err_msg };
+        if !self.categories.is_empty() {
+            fit_msg = format!("{}\n\n{}", fit_msg, self.category_table(ctx));
+        }
+
         let file = format!(
-            "{}\n#### Errors:\n {}\n\n#### Data:\n{}",
+            "{}\n#### {}:\n {}\n\n#### {}:\n{}\n#### {}:\n{}",
             fit_msg,
+            ctx.tr("Errors"),
             show_err,
-            poloto::disp(|w| p.simple_theme(w))
+            ctx.tr("Data"),
+            chart,
+            ctx.tr("Residuals"),
+            residual_chart
         );
 
         if !err_msg.is_empty() {
@@ -201,6 +547,74 @@ impl<T: Numberish> Validate for ScatterValidator<T> {
             ValidationResult::Ok(file)
         }
     }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn artifacts(&self) -> Vec<Artifact> {
+        self.artifacts.clone()
+    }
+
+    fn inputs(&self) -> Vec<RunInput> {
+        self.inputs.clone()
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.covers.clone()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.requirements.clone()
+    }
+
+    fn defines_thresholds(&self, ctx: &ReportContext) -> bool {
+        let defaults = ctx.thresholds();
+        self.allowed_r2.is_some()
+            || self.allowed_intersect_delta.is_some()
+            || self.allowed_slope_delta.is_some()
+            || self.allowed_standard_error.is_some()
+            || defaults.r2.is_some()
+            || defaults.intersect_delta.is_some()
+            || defaults.slope_delta.is_some()
+    }
+
+    fn suggested_thresholds(&self, margin: f64) -> Option<Thresholds> {
+        let m = self.metrics().ok()?;
+        let expected_intersect: f64 = self.expected_intersect.map_or(0.0, |v| v.into());
+        let expected_slope: f64 = self.expected_slope.map_or(1.0, |v| v.into());
+        Some(Thresholds {
+            r2: Thresholds::round_loosely(1.0 - margin * (1.0 - m.r2), false),
+            intersect_delta: Thresholds::round_loosely(margin * (m.intersect - expected_intersect).abs(), true),
+            slope_delta: Thresholds::round_loosely(margin * (m.slope - expected_slope).abs(), true),
+            ..Default::default()
+        })
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        let m = match self.metrics() {
+            Ok(m) => m,
+            Err(_) => return Vec::new(),
+        };
+        let mut ret = vec![
+            Indicator::new("Intersect", m.intersect),
+            Indicator::new("Slope", m.slope),
+            Indicator::new("R2", m.r2),
+            Indicator::new("Points", m.points as f64),
+        ];
+        if let Some(std_error) = m.standard_error {
+            ret.push(Indicator::new("Std Error", std_error));
+        }
+        ret
+    }
+
+    fn chart_data(&self) -> Option<ChartData> {
+        Some(ChartData {
+            x: (0..self.expected.len()).map(|i| i as f64).collect(),
+            expected: self.expected.iter().map(|v| (*v).into()).collect(),
+            found: self.found.iter().map(|v| (*v).into()).collect(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -285,4 +699,103 @@ mod testing {
 
         assert!(validator.validate().is_err());
     }
+
+    #[test]
+    fn test_scatter_fit_statistics() {
+        let expected = vec![1., 2., 3., 4.];
+        let found = vec![6., 2., 1., 0.];
+
+        let scatter = ScatterValidator {
+            expected: expected.clone(),
+            found: found.clone(),
+            allowed_standard_error: Some(1.2),
+            minimum_points: Some(4),
+            ..Default::default()
+        };
+        match scatter.validate() {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.contains("Number of points: 4"));
+                assert!(txt.contains("Standard Error of the Estimate: 1.1619"));
+                assert!(txt.contains("Residuals"));
+            }
+            ValidationResult::Err(_, e) => panic!("Unexpected error: {}", e),
+        }
+
+        let scatter = ScatterValidator {
+            expected: expected.clone(),
+            found: found.clone(),
+            allowed_standard_error: Some(1.0),
+            ..Default::default()
+        };
+        assert!(matches!(scatter.validate(), ValidationResult::Err(..)));
+
+        let scatter = ScatterValidator {
+            expected,
+            found,
+            minimum_points: Some(5),
+            ..Default::default()
+        };
+        match scatter.validate() {
+            ValidationResult::Err(_, e) => assert!(e.contains("at least 5 are required")),
+            ValidationResult::Ok(_) => panic!("Expected too few points to fail"),
+        }
+    }
+
+    #[test]
+    fn test_scatter_categories() {
+        let scatter = ScatterValidator {
+            expected: vec![1., 2., 3., 4., 5.],
+            found: vec![1., 2., 3., 5., 6.],
+            categories: ["summer", "winter", "summer", "winter", "autumn"].map(String::from).to_vec(),
+            ..Default::default()
+        };
+        assert_eq!(
+            scatter.groups(),
+            vec![
+                ("summer".to_string(), vec![0, 2]),
+                ("winter".to_string(), vec![1, 3]),
+                ("autumn".to_string(), vec![4])
+            ]
+        );
+        match scatter.validate() {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.contains("<tr><td>summer</td><td>2</td><td>0.0000</td><td>1.0000</td>"));
+                assert!(txt.contains("<tr><td>autumn</td><td>1</td><td>-</td>"));
+                assert!(txt.contains("winter"));
+            }
+            ValidationResult::Err(_, e) => panic!("Unexpected error: {}", e),
+        }
+
+        let scatter = ScatterValidator {
+            expected: vec![1., 2.],
+            found: vec![1., 2.],
+            categories: vec!["summer".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(scatter.validate(), ValidationResult::Err(..)));
+    }
+
+    #[test]
+    fn test_scatter_degenerate() {
+        let scatter = ScatterValidator {
+            expected: vec![2., 2., 2.],
+            found: vec![1., 2., 3.],
+            ..Default::default()
+        };
+        match scatter.validate() {
+            ValidationResult::Err(_, e) => assert!(e.contains("the expected values do not vary (all values are equal to 2)")),
+            ValidationResult::Ok(_) => panic!("Expected a constant expected series to fail"),
+        }
+
+        let scatter = ScatterValidator {
+            expected: vec![1., 2., 3.],
+            found: vec![0., 0., 0.],
+            ..Default::default()
+        };
+        assert_eq!(scatter.metrics().unwrap().r2, 0.);
+        match scatter.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("Warning: The found values do not vary (all values are zero)")),
+            ValidationResult::Err(_, e) => panic!("Unexpected error: {}", e),
+        }
+    }
 }
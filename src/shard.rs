@@ -0,0 +1,264 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::report::{own_html, write_atomically, Section};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The environment variable that selects the shard to run (e.g., `2/8`),
+/// overriding the one set through [`crate::Validator::set_shard`]
+pub(crate) const SHARD_VAR: &str = "VALIDATE_SHARD";
+
+/// A part of a suite of validations, so a long suite can be split across
+/// several CI runners. Shard `index` of `count` runs every `count`-th
+/// validation, starting from the `index`-th one (counting from 1).
+///
+/// Shards can also be parsed from text like `2/8` or `2 of 8`.
+///
+/// ```
+/// use validate::Shard;
+///
+/// let shard = Shard::new(2, 8).unwrap();
+/// assert_eq!("2 of 8".parse::<Shard>().unwrap(), shard);
+/// assert_eq!(shard.to_string(), "2/8");
+/// assert!(Shard::new(9, 8).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shard {
+    index: usize,
+    count: usize,
+}
+
+impl Shard {
+    /// Creates shard `index` (counting from 1) of `count`
+    pub fn new(index: usize, count: usize) -> Result<Self, String> {
+        if count == 0 || index == 0 || index > count {
+            return Err(format!(
+                "Invalid shard {} of {}: shards are counted from 1 up to their number",
+                index, count
+            ));
+        }
+        Ok(Self { index, count })
+    }
+
+    /// The position of this shard, counting from 1
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The number of shards the suite is split into
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Checks whether the validation at position `i` (counting from 0)
+    /// belongs to this shard
+    pub fn includes(&self, i: usize) -> bool {
+        i % self.count == self.index - 1
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, count) = s
+            .split_once('/')
+            .or_else(|| s.split_once(" of "))
+            .ok_or_else(|| format!("Invalid shard '{}': expected something like '2/8'", s))?;
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid shard '{}': expected something like '2/8'", s))
+        };
+        Shard::new(parse(index)?, parse(count)?)
+    }
+}
+
+/// Gets the path of the results file written by a shard next to its report
+/// (e.g., `report_shard_2_of_8.json` for `report.html`)
+pub(crate) fn results_file_for(report: &Path, shard: Shard) -> PathBuf {
+    let stem = report
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "report".to_string());
+    report.with_file_name(format!(
+        "{}_shard_{}_of_{}.json",
+        stem, shard.index, shard.count
+    ))
+}
+
+/// The results of running one shard, as written into its JSON file
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ShardResults {
+    /// The shard that was run
+    pub shard: Shard,
+
+    /// How long it took to run the shard
+    pub duration: Duration,
+
+    /// The sections produced by the validations of the shard
+    pub sections: Vec<Section>,
+
+    /// How the HTML blocks written by this crate begin in the sections,
+    /// which differs between runs (see [`crate::report::own_html`])
+    pub own_html: String,
+}
+
+/// Writes the results of running a shard into a JSON file
+pub(crate) fn write_results(path: &Path, shard: Shard, duration: Duration, sections: &[Section]) -> Result<(), String> {
+    let doc = serde_json::json!({
+        "shard": shard,
+        "duration": duration,
+        "sections": sections,
+        "own_html": own_html(),
+    });
+    write_atomically(path, doc.to_string())
+}
+
+impl ShardResults {
+    /// Reads the results written by [`write_results`]
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let txt = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read shard results '{}': {}", path.display(), e))?;
+        Self::parse(&txt).map_err(|e| format!("Invalid shard results '{}': {}", path.display(), e))
+    }
+
+    /// Reads the results from the contents of their JSON file
+    fn parse(txt: &str) -> Result<Self, String> {
+        let mut ret: Self = serde_json::from_str(txt).map_err(|e| e.to_string())?;
+        Shard::new(ret.shard.index, ret.shard.count)?;
+        // The blocks written by the shard are still rendered as such
+        for s in ret.sections.iter_mut() {
+            s.body = s.body.replace(&ret.own_html, own_html());
+        }
+        Ok(ret)
+    }
+}
+
+/// Collects the results of every shard of a suite, failing if some
+/// shard is missing or if they come from suites split differently
+pub(crate) fn collect<P: AsRef<Path>>(files: &[P]) -> Result<(Vec<Section>, Duration), String> {
+    let mut results = files
+        .iter()
+        .map(|f| ShardResults::read(f.as_ref()))
+        .collect::<Result<Vec<_>, String>>()?;
+    let count = match results.first() {
+        Some(r) => r.shard.count,
+        None => return Err("There are no shard results to merge".to_string()),
+    };
+    if let Some(r) = results.iter().find(|r| r.shard.count != count) {
+        return Err(format!(
+            "Cannot merge shard {} with shards of a suite split into {}",
+            r.shard, count
+        ));
+    }
+    let missing: Vec<String> = (1..=count)
+        .filter(|i| !results.iter().any(|r| r.shard.index == *i))
+        .map(|i| format!("{}/{}", i, count))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing results of shard(s) {}", missing.join(", ")));
+    }
+    // Shards run in parallel, so the longest one is what it took
+    let duration = results.iter().map(|r| r.duration).max().unwrap_or_default();
+    let mut sections: Vec<Section> = results.iter_mut().flat_map(|r| r.sections.drain(..)).collect();
+    sections.sort_by_key(|s| s.index);
+    sections.dedup_by_key(|s| s.index);
+    Ok((sections, duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Indicator, RunInput, SourceLocation};
+
+    #[test]
+    fn test_shard_selection() {
+        let shards: Vec<Shard> = (1..=3).map(|i| Shard::new(i, 3).unwrap()).collect();
+        for i in 0..10 {
+            assert_eq!(shards.iter().filter(|s| s.includes(i)).count(), 1);
+        }
+        assert!(shards[1].includes(1) && shards[1].includes(4));
+        assert!("0/3".parse::<Shard>().is_err());
+        assert!("two of three".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn test_results_roundtrip() {
+        let mut section = Section::new(4, "## Zone \"A\"\n\nAll good".to_string(), Some("Bad".into()));
+        section.indicators = vec![Indicator::new("RMSE", 0.5), Indicator::new("R2", f64::NAN)];
+        section.covers = vec!["zone air temperature".into()];
+        section.requirements = vec!["REQ-1".into(), "REQ-2".into()];
+        section.location = Some(SourceLocation::new("tests/zones.rs", 12));
+        section.inputs = vec![RunInput::new("data.csv", "fnv1a64:0123456789abcdef")];
+        let shard = Shard::new(2, 3).unwrap();
+        let dir = std::env::temp_dir().join(format!("validate_shard_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = results_file_for(&dir.join("report.html"), shard);
+        assert!(path.ends_with("report_shard_2_of_3.json"));
+        write_results(&path, shard, Duration::from_millis(1500), &[section]).unwrap();
+
+        let back = ShardResults::read(&path).unwrap();
+        assert_eq!(back.shard, shard);
+        assert_eq!(back.duration, Duration::from_millis(1500));
+        let s = &back.sections[0];
+        assert_eq!(s.index, 4);
+        assert_eq!(s.title, "Zone \"A\"");
+        assert_eq!(s.body, "## Zone \"A\"\n\nAll good");
+        assert_eq!(s.error.as_deref(), Some("Bad"));
+        assert_eq!(s.indicators[0], Indicator::new("RMSE", 0.5));
+        assert!(s.indicators[1].value.is_nan());
+        assert_eq!(s.covers, ["zone air temperature"]);
+        assert_eq!(s.location, Some(SourceLocation::new("tests/zones.rs", 12)));
+        assert_eq!(s.requirements, ["REQ-1", "REQ-2"]);
+        assert_eq!(s.inputs, [RunInput::new("data.csv", "fnv1a64:0123456789abcdef")]);
+
+        assert!(collect(&[&path]).err().unwrap().contains("1/3, 3/3"));
+
+        // The blocks written by another run are still those of this crate
+        let other = "<div data-validate=\"0\" class=\"validate_";
+        let txt = serde_json::json!({
+            "shard": shard,
+            "duration": Duration::ZERO,
+            "sections": [Section::new(0, format!("{}chart\"></div>", other), None)],
+            "own_html": other,
+        });
+        let back = ShardResults::parse(&txt.to_string()).unwrap();
+        assert!(back.sections[0].body.starts_with(own_html()));
+
+        std::fs::write(
+            &path,
+            r#"{"shard":{"index":4,"count":3},"duration":{"secs":1,"nanos":0},"sections":[],"own_html":""}"#,
+        )
+        .unwrap();
+        assert!(ShardResults::read(&path).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
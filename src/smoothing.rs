@@ -0,0 +1,356 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::numberish::Numberish;
+use std::fmt;
+
+/// A filter applied identically to the expected and found series before
+/// comparing them (e.g., to keep high-frequency sensor noise from dominating
+/// the errors). It is documented in the report.
+///
+/// # Example
+///
+/// ```
+/// use validate::smoothing::Smoothing;
+///
+/// let noisy = vec![1., 3., 1., 3., 1., 3., 1.];
+/// let smooth = Smoothing::MovingAverage(3).apply(&noisy).unwrap();
+/// assert!(smooth[1..6].iter().all(|v| (1.6..=2.4).contains(v)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Smoothing {
+    /// A centered moving average over a window of this many samples
+    /// (see [`moving_average`])
+    MovingAverage(usize),
+
+    /// A centered moving median over a window of this many samples
+    /// (see [`median_filter`])
+    Median(usize),
+
+    /// A zero-phase, first-order low-pass filter with this cutoff
+    /// frequency, as a fraction of the sampling frequency—strictly between
+    /// 0 and 0.5, or filtering fails (see [`low_pass`])
+    LowPass(f64),
+
+    /// A Savitzky–Golay filter fitting polynomials of degree `order` over
+    /// windows of `window` samples (see [`savitzky_golay`])
+    SavitzkyGolay {
+        /// The number of samples in each window
+        window: usize,
+        /// The degree of the fitted polynomials
+        order: usize,
+    },
+
+    /// Exponential smoothing with this smoothing factor, between 0 and 1
+    /// (see [`exponential_smoothing`])
+    Exponential(f64),
+}
+
+impl Smoothing {
+    /// Filters a series, failing if the parameters of the filter are
+    /// not valid (see [`Smoothing::LowPass`])
+    pub fn apply<T: Numberish>(&self, x: &[T]) -> Result<Vec<f64>, String> {
+        Ok(match *self {
+            Smoothing::MovingAverage(window) => moving_average(x, window),
+            Smoothing::Median(window) => median_filter(x, window),
+            Smoothing::LowPass(cutoff) => low_pass(x, cutoff)?,
+            Smoothing::SavitzkyGolay { window, order } => savitzky_golay(x, window, order),
+            Smoothing::Exponential(alpha) => exponential_smoothing(x, alpha),
+        })
+    }
+}
+
+impl fmt::Display for Smoothing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Smoothing::MovingAverage(window) => write!(f, "moving average over {} samples", window),
+            Smoothing::Median(window) => write!(f, "moving median over {} samples", window),
+            Smoothing::LowPass(cutoff) => write!(
+                f,
+                "zero-phase first-order low-pass filter with a cutoff of {} times the sampling frequency",
+                cutoff
+            ),
+            Smoothing::SavitzkyGolay { window, order } => write!(
+                f,
+                "Savitzky–Golay filter of order {} over {} samples",
+                order, window
+            ),
+            Smoothing::Exponential(alpha) => write!(f, "exponential smoothing with a factor of {}", alpha),
+        }
+    }
+}
+
+/// The range of indices of a centered window of `window` samples around
+/// `i`, shrunk at the ends of a series of `n` samples
+fn centered(i: usize, window: usize, n: usize) -> std::ops::Range<usize> {
+    let half = window.max(1) / 2;
+    i.saturating_sub(half)..(i + window.max(1) - half).min(n)
+}
+
+/// Calculates the centered moving average of a series. The window
+/// shrinks at the ends of the series, so no samples are lost.
+///
+/// # Example
+///
+/// ```
+/// use validate::smoothing::moving_average;
+///
+/// assert_eq!(moving_average(&[0., 3., 0., 3., 0.], 3), vec![1.5, 1., 2., 1., 1.5]);
+/// ```
+pub fn moving_average<T: Numberish>(x: &[T], window: usize) -> Vec<f64> {
+    (0..x.len())
+        .map(|i| {
+            let range = centered(i, window, x.len());
+            let n = crate::stats::count_as_f64(range.len());
+            x[range].iter().map(|v| (*v).into()).sum::<f64>() / n
+        })
+        .collect()
+}
+
+/// Calculates the centered moving median of a series, which removes
+/// spikes while preserving steps. The window shrinks at the ends of the
+/// series, so no samples are lost.
+///
+/// # Example
+///
+/// ```
+/// use validate::smoothing::median_filter;
+///
+/// assert_eq!(median_filter(&[1., 1., 9., 1., 5., 5.], 3), vec![1., 1., 1., 5., 5., 5.]);
+/// ```
+pub fn median_filter<T: Numberish>(x: &[T], window: usize) -> Vec<f64> {
+    (0..x.len())
+        .map(|i| {
+            let mut values: Vec<f64> = x[centered(i, window, x.len())].iter().map(|v| (*v).into()).collect();
+            values.sort_by(|a, b| a.total_cmp(b));
+            let n = values.len();
+            if n % 2 == 1 {
+                values[n / 2]
+            } else {
+                (values[n / 2 - 1] + values[n / 2]) / 2.
+            }
+        })
+        .collect()
+}
+
+/// Applies a first-order low-pass filter forwards and then backwards, so
+/// the filtered series is not delayed with respect to the original one. The
+/// `cutoff` frequency is given as a fraction of the sampling frequency, and
+/// must be strictly between 0 and 0.5 (i.e., below the Nyquist frequency):
+/// otherwise, this fails.
+///
+/// # Example
+///
+/// ```
+/// use validate::smoothing::low_pass;
+///
+/// // A constant is let through, fast oscillations are not
+/// let x: Vec<f64> = (0..100).map(|i| 2. + if i % 2 == 0 { 1. } else { -1. }).collect();
+/// let y = low_pass(&x, 0.1).unwrap();
+/// assert!(y[10..90].iter().all(|v| (v - 2.).abs() < 0.1));
+///
+/// assert!(low_pass(&x, 0.5).is_err());
+/// assert!(low_pass(&x, f64::NAN).is_err());
+/// ```
+pub fn low_pass<T: Numberish>(x: &[T], cutoff: f64) -> Result<Vec<f64>, String> {
+    if !(cutoff > 0. && cutoff < 0.5) {
+        return Err(format!(
+            "The cutoff of a low-pass filter must be between 0 and 0.5 times the sampling frequency, but it is {}",
+            cutoff
+        ));
+    }
+    let rc = 1. / (2. * std::f64::consts::PI * cutoff);
+    let alpha = 1. / (rc + 1.);
+    let pass = |x: &mut Vec<f64>| {
+        let mut prev = match x.first() {
+            Some(v) => *v,
+            None => return,
+        };
+        for v in x.iter_mut() {
+            prev += alpha * (*v - prev);
+            *v = prev;
+        }
+    };
+    let mut ret: Vec<f64> = x.iter().map(|v| (*v).into()).collect();
+    pass(&mut ret);
+    ret.reverse();
+    pass(&mut ret);
+    ret.reverse();
+    Ok(ret)
+}
+
+/// Applies a Savitzky–Golay filter, which fits a polynomial of degree
+/// `order` by least squares to the `window` samples around each one and
+/// replaces it by the value of the polynomial. Unlike a moving average, it
+/// preserves the height and width of peaks, while still removing
+/// high-frequency noise. At the ends of the series, the polynomial fitted
+/// to the first (or last) window is used.
+///
+/// The window is rounded up to an odd number of samples and shrunk to fit
+/// the series, and the order is lowered to be smaller than the window.
+///
+/// # Example
+///
+/// ```
+/// use validate::smoothing::savitzky_golay;
+///
+/// // A slow wave (50 samples per period) goes through almost untouched...
+/// let wave: Vec<f64> = (0..200).map(|i| (i as f64 * 2. * std::f64::consts::PI / 50.).sin()).collect();
+/// let y = savitzky_golay(&wave, 11, 3);
+/// assert!(wave.iter().zip(y.iter()).all(|(a, b)| (a - b).abs() < 0.01));
+///
+/// // ...while the fastest possible oscillation is mostly removed
+/// let noise: Vec<f64> = (0..200).map(|i| if i % 2 == 0 { 1. } else { -1. }).collect();
+/// let y = savitzky_golay(&noise, 11, 2);
+/// assert!(y[5..195].iter().all(|v| v.abs() < 0.15));
+///
+/// // Polynomials of the order of the filter are kept exactly
+/// let parabola: Vec<f64> = (0..20).map(|i| (i * i) as f64).collect();
+/// let y = savitzky_golay(&parabola, 7, 2);
+/// assert!(parabola.iter().zip(y.iter()).all(|(a, b)| (a - b).abs() < 1e-9));
+/// ```
+pub fn savitzky_golay<T: Numberish>(x: &[T], window: usize, order: usize) -> Vec<f64> {
+    let n = x.len();
+    let window = match (window | 1).min(n) {
+        0 => return Vec::new(),
+        w if w.is_multiple_of(2) => w - 1,
+        w => w,
+    };
+    let order = order.min(window - 1);
+    let half = window / 2;
+    let values: Vec<f64> = x.iter().map(|v| (*v).into()).collect();
+    (0..n)
+        .map(|i| {
+            // Keep the window within the series
+            let start = i.saturating_sub(half).min(n - window);
+            let at = crate::stats::count_as_f64(i) - crate::stats::count_as_f64(start + half);
+            let coefficients = fit_polynomial(&values[start..start + window], order);
+            // Evaluate the polynomial, which is centered in the window
+            coefficients.iter().rev().fold(0., |acc, c| acc * at + c)
+        })
+        .collect()
+}
+
+/// Fits a polynomial of degree `order` to values sampled at unit steps and
+/// centered around zero, by least squares. Returns its coefficients, from
+/// the constant term up.
+fn fit_polynomial(y: &[f64], order: usize) -> Vec<f64> {
+    let m = order + 1;
+    let half = crate::stats::count_as_f64(y.len() / 2);
+    // The normal equations, as an augmented matrix
+    let mut a = vec![vec![0.; m + 1]; m];
+    for (k, y) in y.iter().enumerate() {
+        let t = crate::stats::count_as_f64(k) - half;
+        let powers: Vec<f64> = (0..2 * m).scan(1., |p, _| {
+            let ret = *p;
+            *p *= t;
+            Some(ret)
+        })
+        .collect();
+        for (r, row) in a.iter_mut().enumerate() {
+            for (c, v) in row.iter_mut().take(m).enumerate() {
+                *v += powers[r + c];
+            }
+            row[m] += powers[r] * y;
+        }
+    }
+    // Gaussian elimination with partial pivoting
+    for col in 0..m {
+        let pivot = (col..m)
+            .max_by(|i, j| a[*i][col].abs().total_cmp(&a[*j][col].abs()))
+            .unwrap_or(col);
+        a.swap(col, pivot);
+        let (top, bottom) = a.split_at_mut(col + 1);
+        let pivot_row = &top[col];
+        for row in bottom {
+            let factor = row[col] / pivot_row[col];
+            for (v, p) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+                *v -= factor * p;
+            }
+        }
+    }
+    let mut ret = vec![0.; m];
+    for r in (0..m).rev() {
+        let known: f64 = (r + 1..m).map(|c| a[r][c] * ret[c]).sum();
+        ret[r] = (a[r][m] - known) / a[r][r];
+    }
+    ret
+}
+
+/// Applies exponential smoothing, in which each value is a weighted average
+/// of the current sample—with weight `alpha`—and the previous smoothed value.
+/// Smaller factors smooth more, but delay the series more too (see
+/// [`low_pass`] for a filter that does not delay it).
+///
+/// # The math
+/// ```math
+/// s_0 = x_0 \qquad s_i = \alpha x_i + (1 - \alpha) s_{i-1}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::smoothing::exponential_smoothing;
+///
+/// // Constants go through, and the fastest possible oscillation is
+/// // reduced to alpha / (2 - alpha) of its amplitude
+/// let x: Vec<f64> = (0..200).map(|i| 5. + if i % 2 == 0 { 1. } else { -1. }).collect();
+/// let y = exponential_smoothing(&x, 0.2);
+/// let amplitude = (y[199] - y[198]).abs() / 2.;
+/// assert!((amplitude - 0.2 / 1.8).abs() < 1e-6);
+/// assert!((y[199] + y[198] - 10.).abs() < 1e-6);
+/// ```
+pub fn exponential_smoothing<T: Numberish>(x: &[T], alpha: f64) -> Vec<f64> {
+    let alpha = alpha.clamp(0., 1.);
+    let mut prev: Option<f64> = None;
+    x.iter()
+        .map(|v| {
+            let v: f64 = (*v).into();
+            let s = match prev {
+                Some(p) => alpha * v + (1. - alpha) * p,
+                None => v,
+            };
+            prev = Some(s);
+            s
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows() {
+        assert_eq!(centered(0, 3, 5), 0..2);
+        assert_eq!(centered(2, 3, 5), 1..4);
+        assert_eq!(centered(4, 4, 5), 2..5);
+        assert_eq!(centered(2, 0, 5), 2..3);
+        assert!(moving_average::<f64>(&[], 3).is_empty());
+        assert!(low_pass::<f64>(&[], 0.1).unwrap().is_empty());
+        assert!(low_pass::<f64>(&[], 0.).is_err());
+        assert!(Smoothing::LowPass(-0.1).apply(&[1., 2.]).is_err());
+        assert!(savitzky_golay::<f64>(&[], 5, 2).is_empty());
+        // Windows longer than the series, and orders too high for them
+        assert_eq!(savitzky_golay(&[1., 2.], 5, 4), vec![1., 2.]);
+        let y = savitzky_golay(&[1., 2., 3., 4.], 10, 1);
+        assert!(y.iter().zip([1., 2., 3., 4.]).all(|(a, b)| (a - b).abs() < 1e-12));
+    }
+}
@@ -0,0 +1,233 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::report::Section;
+use crate::Indicator;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The longest time between checks of the stop flag while waiting
+const POLL: Duration = Duration::from_millis(100);
+
+/// When a [`crate::Validator`] re-runs its validations in soak mode (see
+/// [`crate::Validator::soak`])
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use validate::SoakSchedule;
+///
+/// // Every 15 minutes, for a day
+/// let schedule = SoakSchedule {
+///     interval: Duration::from_secs(15 * 60),
+///     max_checkpoints: Some(96),
+///     history_file: Some("history.jsonl".into()),
+/// };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SoakSchedule {
+    /// The time between the start of consecutive checkpoints. If running
+    /// the validations takes longer, the next checkpoint starts right away.
+    pub interval: Duration,
+
+    /// The number of checkpoints after which soaking stops, if any
+    pub max_checkpoints: Option<usize>,
+
+    /// The file into which the results of each checkpoint are appended,
+    /// as a JSON document per line
+    pub history_file: Option<PathBuf>,
+}
+
+/// Tolerates the failures of noisy validations (e.g., in nightly runs on a
+/// shared rig), so a validation only fails after failing on a number of
+/// consecutive runs (see [`crate::Validator::set_hysteresis`]). The results
+/// of each run are appended to a history file—in the same format as those
+/// of a [`SoakSchedule`]—from which the previous runs are read.
+///
+/// # Example
+///
+/// ```
+/// use validate::Hysteresis;
+///
+/// // Page the team only after three bad nights in a row
+/// let hysteresis = Hysteresis {
+///     history_file: "nightly.jsonl".into(),
+///     consecutive_failures: 3,
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct Hysteresis {
+    /// The file into which the results of each run are appended, and
+    /// from which those of the previous runs are read
+    pub history_file: PathBuf,
+
+    /// The number of consecutive runs on which a validation needs to fail
+    /// (counting the current one) for it to be reported as failed
+    pub consecutive_failures: usize,
+}
+
+/// The results of a checkpoint (or of a run), as a line of a history file
+/// (see [`append_history`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Checkpoint {
+    /// The number of the checkpoint, counting from 1
+    pub checkpoint: usize,
+
+    /// When the checkpoint ended, in seconds since the Unix epoch
+    pub time: f64,
+
+    /// The number of validations that passed
+    pub passed: usize,
+
+    /// The number of validations that failed
+    pub failed: usize,
+
+    /// The results of each validation
+    pub validations: Vec<CheckpointResult>,
+}
+
+/// The result of a validation at a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CheckpointResult {
+    /// The title of the validation
+    pub title: String,
+
+    /// Whether the validation passed
+    pub passed: bool,
+
+    /// The indicators calculated by the validation
+    #[serde(with = "crate::report::indicator_map")]
+    pub indicators: Vec<Indicator>,
+}
+
+/// Reads the results of the previous runs or checkpoints from a history
+/// file (see [`append_history`]). A missing file means there are none.
+pub(crate) fn read_history(path: &Path) -> Result<Vec<Checkpoint>, String> {
+    let txt = match std::fs::read_to_string(path) {
+        Ok(txt) => txt,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Cannot read history file '{}': {}", path.display(), e)),
+    };
+    txt.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| format!("Invalid history file '{}': {}", path.display(), e))
+        })
+        .collect()
+}
+
+/// Counts the last runs in a history on which the validation titled
+/// `title` failed in a row. Runs in which it did not run break the streak.
+pub(crate) fn failure_streak(history: &[Checkpoint], title: &str) -> usize {
+    history
+        .iter()
+        .rev()
+        .take_while(|run| run.validations.iter().any(|v| v.title == title && !v.passed))
+        .count()
+}
+
+/// Appends the results of a checkpoint to a history file, as a single
+/// line of JSON: its number, when it ended (in seconds since the Unix
+/// epoch), and whether each validation passed and its indicators
+pub(crate) fn append_history(path: &Path, checkpoint: usize, sections: &[Section]) -> Result<(), String> {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default();
+    let failed = sections.iter().filter(|s| s.failed()).count();
+    let line = Checkpoint {
+        checkpoint,
+        time,
+        passed: sections.len() - failed,
+        failed,
+        validations: sections
+            .iter()
+            .map(|s| CheckpointResult {
+                title: s.title.clone(),
+                passed: !s.failed(),
+                indicators: s.indicators.clone(),
+            })
+            .collect(),
+    };
+    let line = serde_json::to_string(&line).map_err(|e| format!("Cannot write history file '{}': {}", path.display(), e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Cannot open history file '{}': {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Cannot write history file '{}': {}", path.display(), e))
+}
+
+/// Waits until `deadline`, returning early if `stop` is set
+pub(crate) fn wait_until(deadline: Instant, stop: &AtomicBool) {
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return;
+        }
+        std::thread::sleep((deadline - now).min(POLL));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history() {
+        let path = std::env::temp_dir().join(format!("validate_history_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut passed = Section::new(0, "## Zone A".to_string(), None);
+        passed.indicators = vec![crate::Indicator::new("Mean Bias Error", 0.25)];
+        let failed = Section::new(1, "## Zone B".to_string(), Some("Bad".into()));
+        append_history(&path, 1, &[passed]).unwrap();
+        append_history(&path, 2, &[failed]).unwrap();
+
+        let txt = std::fs::read_to_string(&path).unwrap();
+        assert!(txt.lines().next().unwrap().contains(r#""indicators":{"Mean Bias Error":0.25}"#));
+        let lines = read_history(&path).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].checkpoint, 2);
+        assert_eq!(lines[1].failed, 1);
+        assert_eq!(lines[0].validations[0].title, "Zone A");
+        assert_eq!(lines[0].validations[0].indicators, [crate::Indicator::new("Mean Bias Error", 0.25)]);
+
+        // Zone B failed on the last run only
+        let history = read_history(&path).unwrap();
+        assert_eq!(failure_streak(&history, "Zone B"), 1);
+        assert_eq!(failure_streak(&history, "Zone A"), 0);
+        std::fs::remove_file(&path).unwrap();
+        assert!(read_history(&path).unwrap().is_empty());
+
+        // A stopped soak does not wait
+        let start = Instant::now();
+        wait_until(start + Duration::from_secs(10), &AtomicBool::new(true));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
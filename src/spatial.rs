@@ -0,0 +1,488 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::stats::count_as_f64;
+use crate::{Indicator, ReportContext, Validate, ValidationResult};
+#[cfg(feature = "charts")]
+use crate::plot::{self, Series};
+
+/// The mean radius of the Earth, in km
+const EARTH_RADIUS: f64 = 6371.0;
+
+/// The number of ranges of the size of the errors drawn in the map
+#[cfg(feature = "charts")]
+const SIZE_BINS: usize = 3;
+
+/// A place with an expected and a found value (e.g., the annual energy
+/// use of a building of a district, or the mean temperature at a weather
+/// station), checked by a [`SpatialValidator`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Site {
+    /// The name of the site
+    pub name: String,
+
+    /// The latitude, in degrees (north is positive)
+    pub latitude: f64,
+
+    /// The longitude, in degrees (east is positive)
+    pub longitude: f64,
+
+    /// The expected value at the site
+    pub expected: f64,
+
+    /// The value found at the site
+    pub found: f64,
+}
+
+impl Site {
+    /// Creates a new `Site`
+    pub fn new<S: Into<String>>(name: S, latitude: f64, longitude: f64, expected: f64, found: f64) -> Self {
+        Self {
+            name: name.into(),
+            latitude,
+            longitude,
+            expected,
+            found,
+        }
+    }
+
+    /// The error at the site (i.e., found minus expected)
+    fn error(&self) -> f64 {
+        self.found - self.expected
+    }
+
+    /// The great-circle distance to another site, in km
+    fn distance(&self, other: &Site) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.longitude - self.longitude).to_radians();
+        let a = (dlat / 2.).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.).sin().powi(2);
+        2. * EARTH_RADIUS * a.sqrt().min(1.).asin()
+    }
+}
+
+/// Validates a model against georeferenced data (e.g., a district-scale or
+/// multi-site model), with an expected and a found value per [`Site`]. It
+/// reports the errors across sites—and at each of them—and whether the
+/// errors of nearby sites are alike (i.e., Moran's I, with weights inversely
+/// proportional to distance), which points at a missing spatial effect
+/// (e.g., the microclimate of a part of the district). The errors are
+/// drawn on a map of the sites.
+///
+/// # Example
+///
+/// ```
+/// use validate::{Site, SpatialValidator, Validate};
+///
+/// let v = SpatialValidator {
+///     sites: vec![
+///         Site::new("Library", -36.85, 174.76, 120., 118.),
+///         Site::new("Pool", -36.86, 174.77, 340., 352.),
+///         Site::new("Town hall", -36.84, 174.75, 95., 96.),
+///     ],
+///     units: Some("MWh"),
+///     allowed_max_error: Some(15.),
+///     ..Default::default()
+/// };
+/// assert!(v.validate().is_ok());
+/// ```
+#[derive(Default, Clone)]
+pub struct SpatialValidator {
+    /// The sites, with their values
+    pub sites: Vec<Site>,
+
+    /// The maximum allowed Mean Bias Error across sites
+    pub allowed_mean_bias_error: Option<f64>,
+
+    /// The maximum allowed Root Mean Squared Error across sites
+    pub allowed_root_mean_squared_error: Option<f64>,
+
+    /// The maximum allowed absolute error at any site
+    pub allowed_max_error: Option<f64>,
+
+    /// The units of the values
+    pub units: Option<&'static str>,
+
+    /// the title of the chart
+    pub chart_title: Option<&'static str>,
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"district heating demand"`), listed in the coverage of the report
+    pub covers: Vec<&'static str>,
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"`), mapped to it in the report
+    pub requirements: Vec<&'static str>,
+}
+
+/// The errors across sites
+struct SpatialErrors {
+    mean_bias_error: f64,
+    root_mean_squared_error: f64,
+    /// The index of the site with the largest absolute error
+    worst: usize,
+    /// Moran's I of the errors, if it is defined
+    morans_i: Option<f64>,
+}
+
+impl SpatialValidator {
+    /// Checks the sites, and calculates the errors across them
+    fn errors(&self, ctx: &ReportContext) -> Result<SpatialErrors, String> {
+        if self.sites.is_empty() {
+            return Err("There are no sites to validate".to_string());
+        }
+        for s in &self.sites {
+            if !(-90. ..=90.).contains(&s.latitude) || !(-180. ..=180.).contains(&s.longitude) {
+                return Err(format!(
+                    "Site '{}' has invalid coordinates: latitude {}, longitude {}",
+                    ctx.user_text(&s.name),
+                    s.latitude,
+                    s.longitude
+                ));
+            }
+            if !s.expected.is_finite() || !s.found.is_finite() {
+                return Err(format!("Site '{}' has non-finite values", ctx.user_text(&s.name)));
+            }
+        }
+        let expected: Vec<f64> = self.sites.iter().map(|s| s.expected).collect();
+        let found: Vec<f64> = self.sites.iter().map(|s| s.found).collect();
+        let errors: Vec<f64> = self.sites.iter().map(|s| s.error()).collect();
+        let mut worst = 0;
+        for (i, e) in errors.iter().enumerate() {
+            if e.abs() > errors[worst].abs() {
+                worst = i;
+            }
+        }
+        Ok(SpatialErrors {
+            mean_bias_error: crate::stats::mean_bias_error(&expected, &found),
+            root_mean_squared_error: crate::stats::root_mean_squared_error(&expected, &found),
+            worst,
+            morans_i: self.morans_i(&errors),
+        })
+    }
+
+    /// Moran's I of the errors, weighting each pair of sites by the inverse
+    /// of their distance. It is not defined for fewer than three sites,
+    /// sites at the same place, or errors that are all the same.
+    fn morans_i(&self, errors: &[f64]) -> Option<f64> {
+        let n = self.sites.len();
+        if n < 3 {
+            return None;
+        }
+        let mean = errors.iter().sum::<f64>() / count_as_f64(n);
+        let z: Vec<f64> = errors.iter().map(|e| e - mean).collect();
+        let variance: f64 = z.iter().map(|v| v * v).sum();
+        if variance == 0. {
+            return None;
+        }
+        let (mut total_weight, mut sum) = (0., 0.);
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                let d = self.sites[i].distance(&self.sites[j]);
+                if d == 0. {
+                    return None;
+                }
+                total_weight += 1. / d;
+                sum += z[i] * z[j] / d;
+            }
+        }
+        Some(count_as_f64(n) / total_weight * sum / variance)
+    }
+}
+
+impl Validate for SpatialValidator {
+    fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        let errors = match self.errors(ctx) {
+            Ok(e) => e,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+        let units = ctx.unit_system();
+        let data_units = self.units.unwrap_or("");
+        let show = |v: f64| units.convert(v, data_units);
+        let show_diff = |v: f64| units.convert_difference(v, data_units);
+        let worst = &self.sites[errors.worst];
+
+        let mut file_msg = format!(
+            "\n * {}: {}\n * {}: {:.4}\n * {}: {:.4}\n * {}: {:.4} ({})",
+            ctx.tr("Number of sites"),
+            self.sites.len(),
+            ctx.tr("Mean Bias Error"),
+            show_diff(errors.mean_bias_error),
+            ctx.tr("Root Mean Squared Error"),
+            show_diff(errors.root_mean_squared_error),
+            ctx.tr("Maximum Absolute Error"),
+            show_diff(worst.error().abs()),
+            ctx.user_text(&worst.name)
+        );
+        if let Some(morans_i) = errors.morans_i {
+            let expected = -1. / count_as_f64(self.sites.len() - 1);
+            file_msg += &format!(
+                "\n * {}: {:.4} ({} {:.4})",
+                ctx.tr("Spatial autocorrelation of the errors (Moran's I)"),
+                morans_i,
+                ctx.tr("random errors would give"),
+                expected
+            );
+        }
+
+        // The errors at each site
+        let unit_label = self.units.map(|u| format!(" ({})", units.units(u))).unwrap_or_default();
+        let mut sites_table = format!("\n#### {}:\n\n", ctx.tr("Errors by site"));
+        let tables = ctx.markdown_options().tables;
+        let rows = self.sites.iter().map(|s| {
+            [
+                if tables { ctx.user_cell(&s.name) } else { ctx.user_text(&s.name) },
+                format!("{:.4}", s.latitude),
+                format!("{:.4}", s.longitude),
+                format!("{:.4}", show(s.expected)),
+                format!("{:.4}", show(s.found)),
+                format!("{:.4}", show_diff(s.error())),
+            ]
+        });
+        if tables {
+            sites_table += &format!(
+                "| {} | {} | {} | {}{} | {}{} | {}{} |\n|---|---:|---:|---:|---:|---:|\n",
+                ctx.tr("Site"),
+                ctx.tr("Latitude"),
+                ctx.tr("Longitude"),
+                ctx.tr("Expected"),
+                unit_label,
+                ctx.tr("Found"),
+                unit_label,
+                ctx.tr("Error"),
+                unit_label
+            );
+            for row in rows {
+                sites_table += &format!("| {} |\n", row.join(" | "));
+            }
+        } else {
+            for row in rows {
+                sites_table += &format!(" * {}: {} ({}, {})\n", row[0], row[5], row[1], row[2]);
+            }
+        }
+
+        let mut nchecks = 0;
+        let mut err_msg = String::new();
+        if let Some(allowed) = self.allowed_mean_bias_error {
+            nchecks += 1;
+            if errors.mean_bias_error.abs() > allowed {
+                err_msg = format!(
+                    "{}\n * Mean Bias Error across sites is {:.4}, which is greater than the allowed value of {:.4}",
+                    err_msg,
+                    show_diff(errors.mean_bias_error),
+                    show_diff(allowed)
+                );
+            }
+        }
+        if let Some(allowed) = self.allowed_root_mean_squared_error {
+            nchecks += 1;
+            if errors.root_mean_squared_error > allowed {
+                err_msg = format!(
+                    "{}\n * Root Mean Squared Error across sites is {:.4}, which is greater than the allowed value of {:.4}",
+                    err_msg,
+                    show_diff(errors.root_mean_squared_error),
+                    show_diff(allowed)
+                );
+            }
+        }
+        if let Some(allowed) = self.allowed_max_error {
+            nchecks += 1;
+            for s in self.sites.iter().filter(|s| s.error().abs() > allowed) {
+                err_msg = format!(
+                    "{}\n * The error at site '{}' is {:.4}, which is beyond the allowed {:.4}",
+                    err_msg,
+                    ctx.user_text(&s.name),
+                    show_diff(s.error()),
+                    show_diff(allowed)
+                );
+            }
+        }
+
+        #[cfg(feature = "charts")]
+        let chart = {
+            // A map of the sites (longitude as x, latitude as y), split by
+            // the sign of their errors and by their size, in equal ranges
+            // up to the largest one
+            let at = |s: &Site| [s.longitude, s.latitude];
+            let largest = worst.error().abs();
+            let mut series = Vec::new();
+            let exact: Vec<[f64; 2]> = self.sites.iter().filter(|s| s.error() == 0.).map(at).collect();
+            if !exact.is_empty() {
+                series.push(Series::scatter(ctx.tr("No error"), exact));
+            }
+            // The range of sizes of a non-zero error, from 0 to SIZE_BINS - 1
+            let bin_of = |s: &Site| {
+                let bin = (s.error().abs() / largest * count_as_f64(SIZE_BINS)).ceil() as usize;
+                bin.clamp(1, SIZE_BINS) - 1
+            };
+            for bin in 0..SIZE_BINS {
+                let from = largest * count_as_f64(bin) / count_as_f64(SIZE_BINS);
+                let to = largest * count_as_f64(bin + 1) / count_as_f64(SIZE_BINS);
+                for (label, sign) in [("Over-predicted by", 1.), ("Under-predicted by", -1.)] {
+                    let points: Vec<[f64; 2]> = self
+                        .sites
+                        .iter()
+                        .filter(|s| s.error() * sign > 0. && bin_of(s) == bin)
+                        .map(at)
+                        .collect();
+                    if !points.is_empty() {
+                        let name = format!("{} {:.2}–{:.2}{}", ctx.tr(label), show_diff(from), show_diff(to), unit_label);
+                        series.push(Series::scatter(name, points));
+                    }
+                }
+            }
+            series.push(Series::scatter(ctx.tr("Maximum Absolute Error"), [at(worst)]));
+            plot::chart(
+                self.chart_title.unwrap_or(""),
+                ctx.tr("Longitude (degrees)"),
+                ctx.tr("Latitude (degrees)"),
+                series,
+                ctx,
+            )
+            .to_string()
+        };
+        #[cfg(not(feature = "charts"))]
+        let chart = ctx.chart_theme().omitted();
+
+        let show_err = if nchecks == 0 {
+            ctx.tr("No checks performed...")
+        } else if err_msg.is_empty() {
+            ctx.tr("No errors found")
+        } else {
+            &err_msg
+        };
+        let file = format!(
+            "{}\n{}\n#### {}:\n {}\n#### {}:\n\n{}",
+            file_msg,
+            sites_table,
+            ctx.tr("Errors"),
+            show_err,
+            ctx.tr("Data"),
+            chart
+        );
+        if err_msg.is_empty() {
+            ValidationResult::Ok(file)
+        } else {
+            ValidationResult::Err(file, err_msg)
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.covers.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.requirements.iter().map(|r| r.to_string()).collect()
+    }
+
+    fn defines_thresholds(&self, _ctx: &ReportContext) -> bool {
+        self.allowed_mean_bias_error.is_some()
+            || self.allowed_root_mean_squared_error.is_some()
+            || self.allowed_max_error.is_some()
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        let errors = match self.errors(&ReportContext::default()) {
+            Ok(e) => e,
+            Err(_) => return Vec::new(),
+        };
+        let mut ret = vec![
+            Indicator::new("Mean Bias Error", errors.mean_bias_error),
+            Indicator::new("Root Mean Squared Error", errors.root_mean_squared_error),
+            Indicator::new("Maximum Absolute Error", self.sites[errors.worst].error().abs()),
+        ];
+        if let Some(morans_i) = errors.morans_i {
+            ret.push(Indicator::new("Moran's I", morans_i));
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spatial() {
+        // A degree of longitude on the equator
+        let a = Site::new("A", 0., 0., 1., 1.);
+        let b = Site::new("B", 0., 1., 1., 1.);
+        assert!((a.distance(&b) - 111.19).abs() < 0.01);
+
+        // The sites to the east are over-predicted, and those to the west under-predicted
+        let mut v = SpatialValidator {
+            sites: vec![
+                Site::new("West 1", 0., 0., 10., 9.),
+                Site::new("West 2", 0.1, 0., 10., 9.),
+                Site::new("East 1", 0., 2., 10., 11.),
+                Site::new("East 2", 0.1, 2., 10., 11.5),
+            ],
+            allowed_root_mean_squared_error: Some(2.),
+            ..Default::default()
+        };
+        let indicators = v.indicators();
+        assert!(indicators[3].value > 0.5);
+        match v.validate() {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.contains("Maximum Absolute Error: 1.5000 (East 2)"));
+                assert!(txt.contains("| East 2 | 0.1000 | 2.0000 | 10.0000 | 11.5000 | 1.5000 |"));
+                #[cfg(feature = "charts")]
+                assert!(txt.contains("Over-predicted by 1.00–1.50") && txt.contains("Under-predicted by 0.50–1.00"));
+            }
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        v.allowed_max_error = Some(1.2);
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains("The error at site 'East 2' is 1.5000, which is beyond the allowed 1.2000")),
+        }
+
+        // Site names are user text
+        v.sites[3].name = "<img src=x onerror=alert(1)>".into();
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(txt, e) => {
+                assert!(!txt.contains("<img") && !e.contains("<img"));
+                assert!(e.contains("The error at site '&lt;img src=x onerror=alert(1)&gt;' is 1.5000"));
+            }
+        }
+        v.sites[3].found = f64::NAN;
+        match v.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.starts_with("Site '&lt;img")),
+        }
+        v.sites[3].found = 11.5;
+
+        v.sites[0].latitude = 91.;
+        assert!(v.validate().is_err());
+        v.sites.clear();
+        assert!(v.validate().is_err());
+    }
+}
@@ -19,14 +19,150 @@ SOFTWARE.
 */
 
 use crate::numberish::Numberish;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
-/// Attempts transform a `usize` into a generic parameter `T`.
-/// Panics if the usize is too large
-pub(crate) fn try_into_t(n_usize: usize) -> f64 {    
-    if n_usize > i32::MAX as usize {
-        panic!("Too many samples... the limit is {}", i32::MAX)
+/// Parallel implementations of some of these statistics, for very large datasets
+#[cfg(feature = "rayon")]
+pub mod parallel;
+
+/// Norms and errors of matrices
+pub mod linalg;
+
+#[cfg(feature = "std")]
+mod changepoint;
+#[cfg(feature = "std")]
+mod circular;
+#[cfg(feature = "std")]
+mod distribution;
+#[cfg(feature = "std")]
+mod gaps;
+mod guard;
+#[cfg(feature = "std")]
+mod hypothesis;
+mod integration;
+#[cfg(feature = "rand")]
+mod permutation;
+#[cfg(feature = "rand")]
+mod sampling;
+#[cfg(feature = "fft")]
+mod spectrum;
+#[cfg(feature = "std")]
+mod special;
+#[cfg(feature = "std")]
+pub use changepoint::{changepoints, Changepoint};
+#[cfg(feature = "std")]
+pub use circular::{circular_correlation, circular_mean, circular_std};
+#[cfg(feature = "std")]
+pub use distribution::{chi_square_two_sample, cramer_von_mises, ecdf, histogram, wasserstein_distance, Ecdf};
+#[cfg(feature = "std")]
+pub use gaps::{sampling, Gap, Sampling};
+pub use guard::{degeneracy, Degeneracy};
+#[cfg(feature = "std")]
+pub use hypothesis::{
+    cliffs_delta, cohens_d, hedges_g, mann_whitney_u, trend_test, welch_t_test, MannWhitney, TTest, TrendTest,
+};
+pub use integration::{simpson, trapz};
+#[cfg(feature = "rand")]
+pub use permutation::{permutation_test, sign_flip_test, PermutationTest};
+#[cfg(feature = "rand")]
+pub use sampling::Sampler;
+#[cfg(feature = "fft")]
+pub use spectrum::{fft_magnitude, psd_welch, Window};
+
+/// The square root of `x`, taken from [libm](https://docs.rs/libm) in
+/// `no_std` builds, which have no floating point functions of their own
+#[cfg(feature = "std")]
+pub(crate) fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+/// The square root of `x`, taken from [libm](https://docs.rs/libm) in
+/// `no_std` builds, which have no floating point functions of their own
+#[cfg(not(feature = "std"))]
+pub(crate) fn sqrt(x: f64) -> f64 {
+    libm::sqrt(x)
+}
+
+/// Transforms the number of samples in a dataset into a `f64`.
+///
+/// This is exact for any dataset smaller than $`2^{53}`$ samples, which is
+/// well beyond what fits in memory.
+pub(crate) fn count_as_f64(n: usize) -> f64 {
+    n as f64
+}
+
+/// A sum that keeps track of the rounding errors of its additions
+/// (i.e., Kahan-Babuška-Neumaier summation), so precision is not lost
+/// when adding up a very large number of values.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct CompensatedSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl CompensatedSum {
+    /// Adds a value to the sum
+    pub fn add(&mut self, v: f64) {
+        let t = self.sum + v;
+        if self.sum.abs() >= v.abs() {
+            self.compensation += (self.sum - t) + v;
+        } else {
+            self.compensation += (v - t) + self.sum;
+        }
+        self.sum = t;
+    }
+
+    /// Adds up two partial sums
+    #[cfg(feature = "rayon")]
+    pub fn merge(mut self, other: Self) -> Self {
+        self.add(other.sum);
+        self.compensation += other.compensation;
+        self
+    }
+
+    /// The value of the sum
+    pub fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+/// Adds up a number of values with [`CompensatedSum`]
+fn compensated_sum<I: Iterator<Item = f64>>(values: I) -> f64 {
+    let mut s = CompensatedSum::default();
+    values.for_each(|v| s.add(v));
+    s.value()
+}
+
+/// Checks that two datasets can be compared: they have the same
+/// length and are not empty. `what` is the name of the indicator
+/// being calculated, used in the error messages.
+fn check_pair<T>(x: &[T], y: &[T], what: &str) -> Result<(), String> {
+    if x.len() != y.len() {
+        return Err(format!(
+            "Calculating {} of two datasets of different length. x.len() = {}, y.len = {}",
+            what,
+            x.len(),
+            y.len()
+        ));
+    }
+    if x.is_empty() {
+        return Err(format!("Trying to calculate {} of empty datasets", what));
+    }
+    Ok(())
+}
+
+/// Checks that the values of a dataset are finite. `what` is the name
+/// of the indicator being calculated, used in the error messages.
+fn check_finite<T: Numberish>(x: &[T], what: &str) -> Result<(), String> {
+    match x.iter().position(|v| !(*v).into().is_finite()) {
+        Some(i) => Err(format!(
+            "Trying to calculate {} of a dataset with a non-finite value at index {}",
+            what, i
+        )),
+        None => Ok(()),
     }
-    (n_usize as i32).into()
 }
 
 /// Calculates the maximum and minimum in a series.
@@ -47,19 +183,31 @@ pub(crate) fn try_into_t(n_usize: usize) -> f64 {
 /// * if the dataset is empty
 /// * If there are any `NaN` in the dataset
 pub fn min_max<T: Numberish>(x: &[T]) -> (T, T) {
-    assert_ne!(
-        x.len(),
-        0,
-        "Trying to calculate Max and Min of empty dataset"
-    );
+    try_min_max(x).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Calculates the maximum and minimum in a series, like [`min_max`], but
+/// returning an error instead of panicking.
+///
+/// # Example
+/// ```
+/// use validate::stats::try_min_max;
+///
+/// assert_eq!(try_min_max(&[1., 3., 2.]), Ok((1., 3.)));
+/// assert!(try_min_max::<f64>(&[]).is_err());
+/// assert!(try_min_max(&[1., f64::NAN]).is_err());
+/// ```
+pub fn try_min_max<T: Numberish>(x: &[T]) -> Result<(T, T), String> {
+    if x.is_empty() {
+        return Err("Trying to calculate Max and Min of empty dataset".to_string());
+    }
 
     let mut max: T = f32::MIN.into();
     let mut min: T = f32::MAX.into();
     for v in x {
-        assert!(
-            !v.is_it_nan(),
-            "Found NaN when calculating min and max of dataset"
-        );
+        if v.is_it_nan() {
+            return Err("Found NaN when calculating min and max of dataset".to_string());
+        }
         if *v < min {
             min = *v;
         }
@@ -67,7 +215,7 @@ pub fn min_max<T: Numberish>(x: &[T]) -> (T, T) {
             max = *v;
         }
     }
-    (min, max)
+    Ok((min, max))
 }
 
 /// Calculates the mean of a dataset
@@ -94,18 +242,45 @@ pub fn min_max<T: Numberish>(x: &[T]) -> (T, T) {
 ///
 /// * If the dataset is empty
 pub fn mean<T: Numberish>(x: &[T]) -> f64 {
-    assert_ne!(x.len(), 0, "Trying to calculate mean of empty dataset");
-    let n  = try_into_t(x.len());
+    nan_propagating_mean(x).unwrap_or_else(|e| panic!("{}", e))
+}
 
-    
+/// Calculates the mean of a dataset, like [`mean`], but returning an
+/// error instead of panicking, or of returning `NaN` if any value is
+/// not finite.
+///
+/// # Example
+/// ```
+/// use validate::stats::try_mean;
+///
+/// assert_eq!(try_mean(&[1., 3.]), Ok(2.));
+/// assert!(try_mean::<f64>(&[]).is_err());
+/// assert!(try_mean(&[1., f64::NAN]).is_err());
+/// ```
+pub fn try_mean<T: Numberish>(x: &[T]) -> Result<f64, String> {
+    check_finite(x, "mean")?;
+    nan_propagating_mean(x)
+}
 
-    let s: f64 = x.iter().fold(0.0, |acc, item| acc + (*item).into());
-    s / n
+/// The mean of a dataset, propagating any `NaN` in it
+fn nan_propagating_mean<T: Numberish>(x: &[T]) -> Result<f64, String> {
+    if x.is_empty() {
+        return Err("Trying to calculate mean of empty dataset".to_string());
+    }
+    let n = count_as_f64(x.len());
+    let s = compensated_sum(x.iter().map(|v| (*v).into()));
+    Ok(s / n)
 }
 
 /// Calculates the coefficients $`a`$ and $`b`$ that best fit the model $`y = a + b\times x`$.
 /// Returns $`a`$, $`b`$ and the $`R^2`$ of the fit.
 ///
+/// If $`y`$ is constant, the fit is the flat line through it and, as there is no
+/// variation in $`y`$ for $`x`$ to explain, its $`R^2`$ is $`0`$. If $`x`$ is
+/// constant, the slope is undefined: the fit is the flat line through the mean
+/// of $`y`$, with an $`R^2`$ of $`0`$ (see [`degeneracy`] to detect this case,
+/// or [`try_linear_coefficients`] to get an error instead).
+///
 /// # Example
 /// ```
 /// use validate::assert_close;
@@ -125,48 +300,78 @@ pub fn mean<T: Numberish>(x: &[T]) -> f64 {
 /// assert_close!(a, 7.);
 /// assert_close!(b, -1.9);
 /// assert_close!(rsquared, 0.8699, 1e-3);
+///
+/// // Constant y
+/// assert_eq!(linear_coefficients(&x, &[2., 2., 2., 2.]), (2., 0., 0.));
+///
+/// // Constant x
+/// assert_eq!(linear_coefficients(&[3., 3., 3.], &[1., 2., 3.]), (2., 0., 0.));
 /// ```
 /// # Panics
 ///
 /// * Panics if the datasets `x` and `y` are of different lengths
 /// * If the datasets are empty
 pub fn linear_coefficients<T: Numberish>(x: &[T], y: &[T]) -> (f64, f64, f64) {
-    assert_eq!(x.len(), y.len(), "Calculating linear coefficients of two datasets of different length. x.len() = {}, y.len = {}", x.len(), y.len());
-    assert_ne!(
-        x.len(),
-        0,
-        "Trying to calculate linear coefficients of empty datasets"
-    );
+    nan_propagating_linear_coefficients(x, y).unwrap_or_else(|e| panic!("{}", e))
+}
 
-    let n  = try_into_t(x.len());    
+/// Calculates the coefficients of the linear model $`y = a + b\times x`$, like
+/// [`linear_coefficients`], but returning an error instead of panicking,
+/// or of returning `NaN` if any value is not finite. All the values of `x`
+/// being equal is an error too, reporting the [`Degeneracy`].
+///
+/// # Example
+/// ```
+/// use validate::stats::try_linear_coefficients;
+///
+/// let x = vec![1., 2., 3., 4.];
+/// assert_eq!(try_linear_coefficients(&x, &x), Ok((0., 1., 1.)));
+/// assert!(try_linear_coefficients(&x, &x[1..]).is_err());
+/// assert!(try_linear_coefficients(&[3., 3., 3.], &[1., 2., 3.]).is_err());
+/// assert!(try_linear_coefficients(&[f64::NAN, 2., 3.], &[1., 2., 3.]).is_err());
+/// ```
+pub fn try_linear_coefficients<T: Numberish>(x: &[T], y: &[T]) -> Result<(f64, f64, f64), String> {
+    check_pair(x, y, "linear coefficients")?;
+    check_finite(x, "linear coefficients")?;
+    check_finite(y, "linear coefficients")?;
+    if let Some(d @ (Degeneracy::AllZero | Degeneracy::Constant(_))) = degeneracy(x) {
+        return Err(format!("Cannot fit a line against x, as {}", d));
+    }
+    nan_propagating_linear_coefficients(x, y)
+}
 
-    let ss_x: f64 = x.iter().fold(0.0, |acc, &item| acc + item.into());
-    let ss_xx: f64 = x
-        .iter()
-        .map(|x| *x * *x)
-        .fold(0.0, |acc, item| acc + item.into());
+/// The coefficients of the linear model, propagating any `NaN` in the datasets
+fn nan_propagating_linear_coefficients<T: Numberish>(x: &[T], y: &[T]) -> Result<(f64, f64, f64), String> {
+    check_pair(x, y, "linear coefficients")?;
 
-    let ss_y: f64 = y
-        .iter()
-        .fold(0.0, |acc, &item| acc + item.into());
+    let n = count_as_f64(x.len());
 
-    let ss_yy: f64 = y
-        .iter()
-        .map(|y| *y * *y)
-        .fold(0.0, |acc, item| acc + item.into());
+    let (xs, ys) = (x, y);
+    let x = || xs.iter().map(|v| -> f64 { (*v).into() });
+    let y = || ys.iter().map(|v| -> f64 { (*v).into() });
+    let ss_x = compensated_sum(x());
+    let ss_xx = compensated_sum(x().map(|x| x * x));
+    let ss_y = compensated_sum(y());
+    let ss_yy = compensated_sum(y().map(|y| y * y));
+    let ss_xy = compensated_sum(x().zip(y()).map(|(x, y)| x * y));
 
-    let ss_xy: f64 = x
-        .iter()
-        .zip(y.iter())
-        .map(|(x, y)| *x * *y)
-        .fold(0.0, |acc, item| acc + item.into());
+    Ok(fit_line(xs, ys, n, [ss_x, ss_xx, ss_y, ss_yy, ss_xy]))
+}
 
+/// Fits the model $`y = a + b\times x`$ (see [`linear_coefficients`]) given the
+/// sums of $`x`$, $`x^2`$, $`y`$, $`y^2`$ and $`xy`$ over the `n` pairs.
+pub(crate) fn fit_line<T: Numberish>(x: &[T], y: &[T], n: f64, sums: [f64; 5]) -> (f64, f64, f64) {
+    let [ss_x, ss_xx, ss_y, ss_yy, ss_xy] = sums;
+    if let Some(Degeneracy::AllZero | Degeneracy::Constant(_)) = degeneracy(y) {
+        return (y[0].into(), 0., 0.);
+    }
+    if let Some(Degeneracy::AllZero | Degeneracy::Constant(_)) = degeneracy(x) {
+        return (ss_y / n, 0., 0.);
+    }
     let b = (ss_xy - ss_x * ss_y / n) / (ss_xx - ss_x * ss_x / n);
     let a = (ss_y - b * ss_x) / n;
     let rsquared = (n * ss_xy - ss_x * ss_y) * (n * ss_xy - ss_x * ss_y)
         / ((n * ss_xx - ss_x * ss_x) * (n * ss_yy - ss_y * ss_y));
-
-    // let rsquared = 1.;
     (a, b, rsquared)
 }
 
@@ -211,16 +416,82 @@ pub fn linear_coefficients<T: Numberish>(x: &[T], y: &[T]) -> (f64, f64, f64) {
 /// * Panics if the datasets `x` and `y` are of different lengths
 /// * If the datasets are empty
 pub fn root_mean_squared_error<T: Numberish>(x: &[T], y: &[T]) -> f64 {
-    assert_eq!(x.len(), y.len(), "Calculating Root Mean Squared Error of two datasets of different length. x.len() = {}, y.len = {}", x.len(), y.len());
-    assert_ne!(
-        x.len(),
-        0,
-        "Trying to calculate Root Mean Squared Error of empty datasets"
-    );
-    let n  = try_into_t(x.len());    
+    nan_propagating_root_mean_squared_error(x, y).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Calculates the Root Mean Squared Error between two datasets, like
+/// [`root_mean_squared_error`], but returning an error instead of panicking,
+/// or of returning `NaN` if any value is not finite.
+///
+/// # Example
+/// ```
+/// use validate::stats::try_root_mean_squared_error;
+///
+/// assert_eq!(try_root_mean_squared_error(&[0., 0.], &[1., 1.]), Ok(1.));
+/// assert!(try_root_mean_squared_error::<f64>(&[], &[]).is_err());
+/// assert!(try_root_mean_squared_error(&[0., f64::NAN], &[1., 1.]).is_err());
+/// ```
+pub fn try_root_mean_squared_error<T: Numberish>(x: &[T], y: &[T]) -> Result<f64, String> {
+    check_pair(x, y, "Root Mean Squared Error")?;
+    check_finite(x, "Root Mean Squared Error")?;
+    check_finite(y, "Root Mean Squared Error")?;
+    nan_propagating_root_mean_squared_error(x, y)
+}
+
+/// The Root Mean Squared Error, propagating any `NaN` in the datasets
+fn nan_propagating_root_mean_squared_error<T: Numberish>(x: &[T], y: &[T]) -> Result<f64, String> {
+    check_pair(x, y, "Root Mean Squared Error")?;
+    let n = count_as_f64(x.len());
 
-    let squared_error: f64 = x.iter().zip(y.iter()).map(|(x, y)| (*y - *x)*(*y - *x)).fold(0.0, |acc, item| acc + item.into());
-    squared_error / n
+    let squared_error = compensated_sum(x.iter().zip(y.iter()).map(|(x, y)| {
+        let e = (*y).into() - (*x).into();
+        e * e
+    }));
+    Ok(squared_error / n)
+}
+
+/// Calculates the weighted mean of the squared errors between two datasets,
+/// weighting each pair (e.g., by the period of time it represents, for
+/// irregularly sampled data). No square root is taken, so equal weights give
+/// the same result as [`root_mean_squared_error`], whose implementation does
+/// not take it either.
+///
+/// # The math
+/// ```math
+/// MSE_w = \frac{\sum_{i=0}^{n} w_i (y_i - x_i)^2}{\sum_{i=0}^{n} w_i}
+/// ```
+///
+/// # Example
+/// ```
+/// use validate::stats::{root_mean_squared_error, weighted_mean_squared_error};
+///
+/// let x = vec![0., 0., 0.];
+/// let y = vec![2., 2., 4.];
+/// assert_eq!(weighted_mean_squared_error(&x, &y, &[1., 1., 0.]), Ok(4.));
+/// assert_eq!(weighted_mean_squared_error(&x, &y, &[1., 1., 1.]), Ok(root_mean_squared_error(&x, &y)));
+/// assert!(weighted_mean_squared_error(&x, &y, &[1., 1.]).is_err());
+/// ```
+pub fn weighted_mean_squared_error<T: Numberish>(x: &[T], y: &[T], weights: &[f64]) -> Result<f64, String> {
+    check_pair(x, y, "Mean Squared Error")?;
+    if weights.len() != x.len() {
+        return Err(format!(
+            "There are {} weights for datasets of {} values",
+            weights.len(),
+            x.len()
+        ));
+    }
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.) {
+        return Err("Weights must be finite and non-negative".to_string());
+    }
+    let total = compensated_sum(weights.iter().copied());
+    if total == 0. {
+        return Err("Trying to calculate a weighted Mean Squared Error with null weights".to_string());
+    }
+    let squared_error = compensated_sum(x.iter().zip(y.iter()).zip(weights.iter()).map(|((x, y), w)| {
+        let e = (*y).into() - (*x).into();
+        w * e * e
+    }));
+    Ok(squared_error / total)
 }
 
 /// Calculates the Mean Bias Error between to datasets, indicating whether
@@ -262,20 +533,496 @@ pub fn root_mean_squared_error<T: Numberish>(x: &[T], y: &[T]) -> f64 {
 /// * Panics if the datasets `x` and `y` are of different lengths
 /// * If the datasets are empty
 pub fn mean_bias_error<T: Numberish>(x: &[T], y: &[T]) -> f64 {
+    nan_propagating_mean_bias_error(x, y).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Calculates the Mean Bias Error between two datasets, like
+/// [`mean_bias_error`], but returning an error instead of panicking,
+/// or of returning `NaN` if any value is not finite.
+///
+/// # Example
+/// ```
+/// use validate::stats::try_mean_bias_error;
+///
+/// assert_eq!(try_mean_bias_error(&[0., 0.], &[1., 1.]), Ok(1.));
+/// assert!(try_mean_bias_error(&[0., 0.], &[1.]).is_err());
+/// assert!(try_mean_bias_error(&[0., 0.], &[f64::NAN, 1.]).is_err());
+/// ```
+pub fn try_mean_bias_error<T: Numberish>(x: &[T], y: &[T]) -> Result<f64, String> {
+    check_pair(x, y, "Mean Bias Error")?;
+    check_finite(x, "Mean Bias Error")?;
+    check_finite(y, "Mean Bias Error")?;
+    nan_propagating_mean_bias_error(x, y)
+}
+
+/// The Mean Bias Error, propagating any `NaN` in the datasets
+fn nan_propagating_mean_bias_error<T: Numberish>(x: &[T], y: &[T]) -> Result<f64, String> {
+    check_pair(x, y, "Mean Bias Error")?;
+    let n = count_as_f64(x.len());
+    let bias_error = compensated_sum(x.iter().zip(y.iter()).map(|(x, y)| (*y).into() - (*x).into()));
+    Ok(bias_error / n)
+}
+
+/// Finds the largest absolute difference between the elements of two datasets,
+/// returning its index and value. If several pairs share the largest difference,
+/// the first one is returned.
+///
+/// Contrary to the [`root_mean_squared_error`] and the [`mean_bias_error`], this
+/// indicator is not an aggregate, so short but large discrepancies between
+/// the datasets are not averaged away.
+///
+/// # The math
+/// ```math
+/// E_{max} = \max_{i} |y_i - x_i|
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::max_absolute_error;
+/// use validate::assert_close;
+///
+/// let x = vec![0., 0., 0., 0.];
+/// let y = vec![0.1, -3., 2., 3.];
+/// let (i, e) = max_absolute_error(&x, &y);
+/// assert_eq!(i, 1);
+/// assert_close!(e, 3.);
+/// ```
+///
+/// # Panics
+///
+/// * Panics if the datasets `x` and `y` are of different lengths
+/// * If the datasets are empty
+pub fn max_absolute_error<T: Numberish>(x: &[T], y: &[T]) -> (usize, f64) {
     assert_eq!(
         x.len(),
         y.len(),
-        "Calculating Mean Bias Error of two datasets of different length. x.len() = {}, y.len = {}",
+        "Calculating Maximum Absolute Error of two datasets of different length. x.len() = {}, y.len = {}",
         x.len(),
         y.len()
     );
     assert_ne!(
         x.len(),
         0,
-        "Trying to calculate Mean Bias Error of empty datasets"
+        "Trying to calculate Maximum Absolute Error of empty datasets"
+    );
+
+    let mut worst = (0, 0.0);
+    for (i, (x, y)) in x.iter().zip(y.iter()).enumerate() {
+        let e: f64 = (*y).into() - (*x).into();
+        if e.abs() > worst.1 {
+            worst = (i, e.abs());
+        }
+    }
+    worst
+}
+
+/// Finds the lag—within `max_lag` steps in either direction—at which `y` best
+/// matches `x`, by maximizing the Pearson correlation of their overlapping values.
+/// A positive lag `k` means that `y[i + k]` corresponds to `x[i]` (i.e., `y` is
+/// delayed). Returns the lag and its correlation.
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::best_lag;
+///
+/// let x = vec![0., 1., 4., 2., 0., 0., 3., 1.];
+/// let y = vec![0., 0., 0., 1., 4., 2., 0., 0.];
+/// let (lag, correlation) = best_lag(&x, &y, 3).unwrap();
+/// assert_eq!(lag, 2);
+/// assert!(correlation > 0.99);
+/// assert!(best_lag(&[1., 1., 1.], &[1., 2., 3.], 1).is_err());
+/// ```
+pub fn best_lag<T: Numberish>(x: &[T], y: &[T], max_lag: usize) -> Result<(isize, f64), String> {
+    check_pair(x, y, "the lag")?;
+    let n = x.len();
+    // Keep at least two overlapping values
+    let max_lag = max_lag.min(n.saturating_sub(2)) as isize;
+    let mut best: Option<(isize, f64)> = None;
+    for lag in -max_lag..=max_lag {
+        let (x, y) = overlap(x, y, lag);
+        let r = correlation(x, y);
+        if r.is_finite() && best.is_none_or(|(_, b)| r > b) {
+            best = Some((lag, r))
+        }
+    }
+    best.ok_or_else(|| "Cannot calculate the lag of series that do not vary".to_string())
+}
+
+/// The overlapping parts of `x` and `y` once `y` is shifted back by `lag` steps
+/// (see [`best_lag`])
+pub(crate) fn overlap<'a, T>(x: &'a [T], y: &'a [T], lag: isize) -> (&'a [T], &'a [T]) {
+    let n = x.len().min(y.len());
+    let k = lag.unsigned_abs().min(n);
+    if lag >= 0 {
+        (&x[..n - k], &y[k..n])
+    } else {
+        (&x[k..n], &y[..n - k])
+    }
+}
+
+/// The Pearson correlation coefficient of two datasets of the same
+/// length, which is `NaN` if either of them does not vary
+fn correlation<T: Numberish>(x: &[T], y: &[T]) -> f64 {
+    let (mx, my) = (mean(x), mean(y));
+    let (mut sxy, mut sxx, mut syy) = (0., 0., 0.);
+    for (x, y) in x.iter().zip(y.iter()) {
+        let (dx, dy) = ((*x).into() - mx, (*y).into() - my);
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    sxy / sqrt(sxx * syy)
+}
+
+/// Checks whether `found` is close enough to `expected`, which happens if their
+/// absolute difference is within `abs_tol` OR their relative difference (i.e.,
+/// divided by the magnitude of `expected`) is within `rel_tol`. The absolute
+/// tolerance keeps relative checks meaningful when `expected` is (close to) zero.
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::within_tolerance;
+///
+/// // A purely relative check would fail, as the expected value is zero
+/// assert!(within_tolerance(0., 1e-9, 1e-6, 0.01));
+/// assert!(within_tolerance(1000., 1005., 1e-6, 0.01));
+/// assert!(!within_tolerance(1000., 1020., 1e-6, 0.01));
+/// ```
+pub fn within_tolerance(expected: f64, found: f64, abs_tol: f64, rel_tol: f64) -> bool {
+    let diff = (found - expected).abs();
+    diff <= abs_tol || diff <= rel_tol * expected.abs()
+}
+
+/// Calculates the Standard Error of the Estimate of the linear model
+/// $`y = a + b\times x`$ fitted by [`linear_coefficients`]. It indicates how far,
+/// on average, the points in $`y`$ fall from the regression line.
+///
+/// # The math
+/// ```math
+/// S_e = \sqrt{ \frac{\sum_{i=0}^{n}(y_i - a - b x_i)^2}{n - 2} }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::standard_error_of_estimate;
+/// use validate::assert_close;
+///
+/// // Perfect fit... no error
+/// let x = vec![1., 2., 3., 4.];
+/// assert_close!(0., standard_error_of_estimate(&x, &x));
+///
+/// // The fit is y = 7 - 1.9x
+/// let y = vec![6., 2., 1., 0.];
+/// assert_close!(1.1619, standard_error_of_estimate(&x, &y), 1e-3);
+///
+/// // A constant x explains nothing: the fit is the mean of y
+/// assert_close!(2f64.sqrt(), standard_error_of_estimate(&[2., 2., 2.], &[1., 2., 3.]));
+/// ```
+///
+/// As in [`linear_coefficients`], if all the values of `x` are equal the fit
+/// is the flat line through the mean of `y`.
+///
+/// # Panics
+///
+/// * Panics if the datasets `x` and `y` are of different lengths
+/// * If the datasets have fewer than 3 elements
+pub fn standard_error_of_estimate<T: Numberish>(x: &[T], y: &[T]) -> f64 {
+    assert!(
+        x.len() > 2,
+        "Trying to calculate Standard Error of the Estimate of datasets with fewer than 3 elements"
     );
+    let (a, b, _) = linear_coefficients(x, y);
+    let n = count_as_f64(x.len());
+    let squared_error = compensated_sum(x.iter().zip(y.iter()).map(|(x, y)| {
+        let e = (*y).into() - a - b * (*x).into();
+        e * e
+    }));
+    sqrt(squared_error / (n - 2.))
+}
+
+/// The fractions of the Mean Squared Error between two datasets that are
+/// due to their bias, their amplitude and their phase, as calculated by
+/// [`theil_decomposition`]. They add up to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TheilDecomposition {
+    /// The fraction due to the difference between the means (i.e., a
+    /// systematic offset)
+    pub bias: f64,
+
+    /// The fraction due to the difference between the standard deviations
+    /// (i.e., one dataset varies more than the other)
+    pub amplitude: f64,
+
+    /// The fraction due to the imperfect correlation of the datasets (i.e.,
+    /// they do not rise and fall together, as when one lags the other)
+    pub phase: f64,
+}
+
+impl TheilDecomposition {
+    /// The name of the largest component (`"bias"`, `"amplitude"` or
+    /// `"phase"`) and its fraction of the Mean Squared Error
+    pub fn dominant(&self) -> (&'static str, f64) {
+        let mut ret = ("bias", self.bias);
+        for (name, v) in [("amplitude", self.amplitude), ("phase", self.phase)] {
+            if v > ret.1 {
+                ret = (name, v);
+            }
+        }
+        ret
+    }
+}
 
-    let n  = try_into_t(x.len());    
-    let bias_error: f64 = x.iter().zip(y.iter()).map(|(x, y)| *y - *x).fold(0.0, |acc, item| acc + item.into());
-    bias_error / n
+/// Decomposes the Mean Squared Error between two datasets into the
+/// fractions due to bias, amplitude and phase (Theil, 1966), which tell
+/// what is wrong with `y` and not just how much.
+///
+/// # The math
+/// ```math
+/// MSE = (\bar{y} - \bar{x})^2 + (s_y - s_x)^2 + 2 (1 - r) s_y s_x
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::theil_decomposition;
+/// use validate::assert_close;
+///
+/// // y is x, shifted up
+/// let x = vec![1., 2., 3., 4.];
+/// let y = vec![2., 3., 4., 5.];
+/// let d = theil_decomposition(&x, &y).unwrap();
+/// assert_close!(1., d.bias);
+/// assert_eq!(d.dominant().0, "bias");
+///
+/// // Identical datasets have no error to decompose
+/// assert!(theil_decomposition(&x, &x).is_err());
+/// ```
+pub fn theil_decomposition<T: Numberish>(x: &[T], y: &[T]) -> Result<TheilDecomposition, String> {
+    check_pair(x, y, "the Theil decomposition")?;
+    if x.iter().zip(y.iter()).all(|(x, y)| (*x).into() == (*y).into()) {
+        return Err("Cannot decompose the error of identical datasets".to_string());
+    }
+    let n = count_as_f64(x.len());
+    let mean_x = try_mean(x)?;
+    let mean_y = try_mean(y)?;
+    let pairs = || x.iter().zip(y.iter()).map(|(x, y)| ((*x).into() - mean_x, (*y).into() - mean_y));
+    let var_x = compensated_sum(pairs().map(|(dx, _)| dx * dx)) / n;
+    let var_y = compensated_sum(pairs().map(|(_, dy)| dy * dy)) / n;
+    let cov = compensated_sum(pairs().map(|(dx, dy)| dx * dy)) / n;
+    let (s_x, s_y) = (sqrt(var_x), sqrt(var_y));
+
+    let bias = (mean_y - mean_x) * (mean_y - mean_x);
+    let amplitude = (s_y - s_x) * (s_y - s_x);
+    // Rounding can make a perfect correlation slightly greater than 1
+    let phase = (2. * (s_x * s_y - cov)).max(0.);
+    let mse = bias + amplitude + phase;
+    if mse.is_nan() || mse <= 0. {
+        return Err("Cannot decompose the error of the datasets (do they contain NaN?)".to_string());
+    }
+    Ok(TheilDecomposition { bias: bias / mse, amplitude: amplitude / mse, phase: phase / mse })
+}
+
+/// What the `*_nan_aware` functions do with `NaN` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// Samples containing `NaN` are excluded from the calculation. When
+    /// comparing two datasets, the whole pair is excluded.
+    Skip,
+
+    /// Any `NaN` makes the result `NaN`
+    Propagate,
+}
+
+/// The result of a `*_nan_aware` function
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NanAware<V> {
+    /// The calculated value
+    pub value: V,
+
+    /// The number of samples (or pairs of samples) that were excluded
+    /// because they contained `NaN`. It is always 0 under [`NanPolicy::Propagate`].
+    pub excluded: usize,
+}
+
+/// Removes the `NaN` from a dataset, returning the remaining values and
+/// how many were removed
+fn without_nan<T: Numberish>(x: &[T]) -> (Vec<T>, usize) {
+    let clean: Vec<T> = x.iter().copied().filter(|v| !v.is_it_nan()).collect();
+    let excluded = x.len() - clean.len();
+    (clean, excluded)
+}
+
+/// Removes the pairs in which either value is `NaN` from two datasets,
+/// returning the remaining values and how many pairs were removed
+fn pairs_without_nan<T: Numberish>(x: &[T], y: &[T]) -> (Vec<T>, Vec<T>, usize) {
+    let (x_clean, y_clean): (Vec<T>, Vec<T>) = x
+        .iter()
+        .zip(y.iter())
+        .filter(|(a, b)| !a.is_it_nan() && !b.is_it_nan())
+        .unzip();
+    let excluded = x.len().min(y.len()) - x_clean.len();
+    (x_clean, y_clean, excluded)
+}
+
+/// Calculates the mean of a dataset following a [`NanPolicy`]
+///
+/// # Example
+/// ```
+/// use validate::stats::{mean_nan_aware, NanPolicy};
+///
+/// let x = vec![1., f64::NAN, 3.];
+/// let skipped = mean_nan_aware(&x, NanPolicy::Skip).unwrap();
+/// assert_eq!(skipped.value, 2.);
+/// assert_eq!(skipped.excluded, 1);
+///
+/// let propagated = mean_nan_aware(&x, NanPolicy::Propagate).unwrap();
+/// assert!(propagated.value.is_nan());
+/// ```
+pub fn mean_nan_aware<T: Numberish>(x: &[T], policy: NanPolicy) -> Result<NanAware<f64>, String> {
+    match policy {
+        NanPolicy::Skip => {
+            let (x, excluded) = without_nan(x);
+            Ok(NanAware { value: nan_propagating_mean(&x)?, excluded })
+        }
+        NanPolicy::Propagate => Ok(NanAware { value: nan_propagating_mean(x)?, excluded: 0 }),
+    }
+}
+
+/// Calculates the minimum and maximum of a dataset following a [`NanPolicy`]
+///
+/// # Example
+/// ```
+/// use validate::stats::{min_max_nan_aware, NanPolicy};
+///
+/// let x = vec![1., f64::NAN, 3.];
+/// let skipped = min_max_nan_aware(&x, NanPolicy::Skip).unwrap();
+/// assert_eq!(skipped.value, (1., 3.));
+/// assert_eq!(skipped.excluded, 1);
+///
+/// let propagated = min_max_nan_aware(&x, NanPolicy::Propagate).unwrap();
+/// assert!(propagated.value.0.is_nan() && propagated.value.1.is_nan());
+/// ```
+pub fn min_max_nan_aware<T: Numberish>(x: &[T], policy: NanPolicy) -> Result<NanAware<(T, T)>, String> {
+    let (clean, excluded) = without_nan(x);
+    match policy {
+        NanPolicy::Skip => Ok(NanAware { value: try_min_max(&clean)?, excluded }),
+        NanPolicy::Propagate if excluded > 0 => {
+            let nan: T = f32::NAN.into();
+            Ok(NanAware { value: (nan, nan), excluded: 0 })
+        }
+        NanPolicy::Propagate => Ok(NanAware { value: try_min_max(x)?, excluded: 0 }),
+    }
+}
+
+/// Calculates the coefficients of the linear model $`y = a + b\times x`$ (see
+/// [`linear_coefficients`]) following a [`NanPolicy`]
+///
+/// # Example
+/// ```
+/// use validate::stats::{linear_coefficients_nan_aware, NanPolicy};
+///
+/// let x = vec![1., 2., f64::NAN, 4.];
+/// let y = vec![1., 2., 3., 4.];
+/// let skipped = linear_coefficients_nan_aware(&x, &y, NanPolicy::Skip).unwrap();
+/// assert_eq!(skipped.value, (0., 1., 1.));
+/// assert_eq!(skipped.excluded, 1);
+/// ```
+pub fn linear_coefficients_nan_aware<T: Numberish>(
+    x: &[T],
+    y: &[T],
+    policy: NanPolicy,
+) -> Result<NanAware<(f64, f64, f64)>, String> {
+    check_pair(x, y, "linear coefficients")?;
+    match policy {
+        NanPolicy::Skip => {
+            let (x, y, excluded) = pairs_without_nan(x, y);
+            Ok(NanAware { value: nan_propagating_linear_coefficients(&x, &y)?, excluded })
+        }
+        NanPolicy::Propagate => Ok(NanAware { value: nan_propagating_linear_coefficients(x, y)?, excluded: 0 }),
+    }
+}
+
+/// Calculates the Root Mean Squared Error (see [`root_mean_squared_error`])
+/// following a [`NanPolicy`]
+///
+/// # Example
+/// ```
+/// use validate::stats::{root_mean_squared_error_nan_aware, NanPolicy};
+///
+/// let x = vec![0., 0., 0.];
+/// let y = vec![1., f64::NAN, 1.];
+/// let skipped = root_mean_squared_error_nan_aware(&x, &y, NanPolicy::Skip).unwrap();
+/// assert_eq!(skipped.value, 1.);
+/// assert_eq!(skipped.excluded, 1);
+/// ```
+pub fn root_mean_squared_error_nan_aware<T: Numberish>(
+    x: &[T],
+    y: &[T],
+    policy: NanPolicy,
+) -> Result<NanAware<f64>, String> {
+    check_pair(x, y, "Root Mean Squared Error")?;
+    match policy {
+        NanPolicy::Skip => {
+            let (x, y, excluded) = pairs_without_nan(x, y);
+            Ok(NanAware { value: nan_propagating_root_mean_squared_error(&x, &y)?, excluded })
+        }
+        NanPolicy::Propagate => Ok(NanAware { value: nan_propagating_root_mean_squared_error(x, y)?, excluded: 0 }),
+    }
+}
+
+/// Calculates the Mean Bias Error (see [`mean_bias_error`]) following
+/// a [`NanPolicy`]
+///
+/// # Example
+/// ```
+/// use validate::stats::{mean_bias_error_nan_aware, NanPolicy};
+///
+/// let x = vec![0., 0., 0.];
+/// let y = vec![1., f64::NAN, 1.];
+/// let skipped = mean_bias_error_nan_aware(&x, &y, NanPolicy::Skip).unwrap();
+/// assert_eq!(skipped.value, 1.);
+/// assert_eq!(skipped.excluded, 1);
+///
+/// let propagated = mean_bias_error_nan_aware(&x, &y, NanPolicy::Propagate).unwrap();
+/// assert!(propagated.value.is_nan());
+/// ```
+pub fn mean_bias_error_nan_aware<T: Numberish>(
+    x: &[T],
+    y: &[T],
+    policy: NanPolicy,
+) -> Result<NanAware<f64>, String> {
+    check_pair(x, y, "Mean Bias Error")?;
+    match policy {
+        NanPolicy::Skip => {
+            let (x, y, excluded) = pairs_without_nan(x, y);
+            Ok(NanAware { value: nan_propagating_mean_bias_error(&x, &y)?, excluded })
+        }
+        NanPolicy::Propagate => Ok(NanAware { value: nan_propagating_mean_bias_error(x, y)?, excluded: 0 }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compensated_sum() {
+        // Naive summation loses the small values
+        let mut s = CompensatedSum::default();
+        for v in [1e16, 1., 1., -1e16] {
+            s.add(v);
+        }
+        assert_close!(s.value(), 2.);
+    }
+
+    #[test]
+    fn test_count_as_f64() {
+        // Larger than the old i32 limit
+        let n = i32::MAX as usize * 3;
+        assert_eq!(count_as_f64(n) as usize, n);
+    }
 }
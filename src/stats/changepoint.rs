@@ -0,0 +1,171 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Detection of sudden shifts in the mean of a series (e.g., in the errors
+//! of a model after a sensor was recalibrated).
+
+use super::count_as_f64;
+use crate::numberish::Numberish;
+
+/// The smallest number of samples between changepoints
+const MIN_SEGMENT: usize = 2;
+
+/// The cumulative sums of a series and of its squares, for calculating
+/// the cost of any segment in constant time
+struct Prefix {
+    sum: Vec<f64>,
+    squares: Vec<f64>,
+}
+
+impl Prefix {
+    fn new(x: &[f64]) -> Self {
+        let mut sum = vec![0.; x.len() + 1];
+        let mut squares = vec![0.; x.len() + 1];
+        for (i, v) in x.iter().enumerate() {
+            sum[i + 1] = sum[i] + v;
+            squares[i + 1] = squares[i] + v * v;
+        }
+        Self { sum, squares }
+    }
+
+    /// The sum of the squared deviations from the mean of `x[a..b]`
+    fn cost(&self, a: usize, b: usize) -> f64 {
+        let n = count_as_f64(b - a);
+        let s = self.sum[b] - self.sum[a];
+        (self.squares[b] - self.squares[a] - s * s / n).max(0.)
+    }
+
+    /// The mean of `x[a..b]`
+    fn mean(&self, a: usize, b: usize) -> f64 {
+        (self.sum[b] - self.sum[a]) / count_as_f64(b - a)
+    }
+}
+
+/// Estimates the variance of the noise of a series from its first
+/// differences, which are barely affected by a few shifts in its mean
+/// (the differences of white noise have twice its variance)
+fn noise_variance(x: &[f64]) -> f64 {
+    if x.len() < 2 {
+        return 0.;
+    }
+    let squares: f64 = x.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum();
+    squares / (2. * count_as_f64(x.len() - 1))
+}
+
+/// Splits `x[a..b]` where it reduces the cost the most, as long as that
+/// reduction exceeds the penalty, and does the same with both halves
+fn split(prefix: &Prefix, a: usize, b: usize, penalty: f64, found: &mut Vec<usize>) {
+    if b - a < 2 * MIN_SEGMENT {
+        return;
+    }
+    let whole = prefix.cost(a, b);
+    let best = (a + MIN_SEGMENT..=b - MIN_SEGMENT)
+        .map(|k| (k, whole - prefix.cost(a, k) - prefix.cost(k, b)))
+        .max_by(|x, y| x.1.total_cmp(&y.1));
+    if let Some((k, gain)) = best {
+        if gain > penalty {
+            found.push(k);
+            split(prefix, a, k, penalty, found);
+            split(prefix, k, b, penalty, found);
+        }
+    }
+}
+
+/// A sudden shift in the mean of a series, as detected by [`changepoints`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Changepoint {
+    /// The index of the first sample after the shift
+    pub index: usize,
+
+    /// The mean of the series between the previous changepoint and this one
+    pub mean_before: f64,
+
+    /// The mean of the series between this changepoint and the next one
+    pub mean_after: f64,
+}
+
+/// Detects sudden shifts in the mean of a series by binary segmentation:
+/// the series is split where that reduces its squared deviations from the
+/// mean the most, as long as the reduction exceeds a `penalty`, and then the
+/// same is done with each part. Without a penalty, $`3\sigma^2\ln n`$ is used,
+/// estimating the variance $`\sigma^2`$ of the noise from the first
+/// differences of the series. Higher penalties detect fewer changepoints.
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::changepoints;
+///
+/// // A sensor that gets recalibrated at sample 30
+/// let x: Vec<f64> = (0..60)
+///     .map(|i| if i < 30 { 0. } else { 2. } + 0.1 * ((i * 7) % 5) as f64)
+///     .collect();
+/// let found = changepoints(&x, None);
+/// assert_eq!(found.len(), 1);
+/// assert_eq!(found[0].index, 30);
+/// assert!((found[0].mean_after - found[0].mean_before - 2.).abs() < 0.1);
+/// ```
+pub fn changepoints<T: Numberish>(x: &[T], penalty: Option<f64>) -> Vec<Changepoint> {
+    let x: Vec<f64> = x.iter().map(|v| (*v).into()).collect();
+    if x.iter().any(|v| !v.is_finite()) {
+        return Vec::new();
+    }
+    let n = x.len();
+    let prefix = Prefix::new(&x);
+    let penalty = penalty
+        .unwrap_or_else(|| 3. * noise_variance(&x) * count_as_f64(n.max(1)).ln())
+        // Keep rounding errors from passing as shifts
+        .max(1e-12 * prefix.cost(0, n));
+    let mut found = Vec::new();
+    split(&prefix, 0, n, penalty, &mut found);
+    found.sort_unstable();
+
+    let mut bounds = vec![0];
+    bounds.extend(found.iter().copied());
+    bounds.push(n);
+    bounds
+        .windows(3)
+        .map(|w| Changepoint {
+            index: w[1],
+            mean_before: prefix.mean(w[0], w[1]),
+            mean_after: prefix.mean(w[1], w[2]),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changepoints() {
+        assert!(changepoints::<f64>(&[], None).is_empty());
+        assert!(changepoints(&[1., 2., 3.], None).is_empty());
+        assert!(changepoints(&[1.; 20], None).is_empty());
+
+        // Two exact steps
+        let x: Vec<f64> = (0..30).map(|i| (i / 10) as f64).collect();
+        let found: Vec<usize> = changepoints(&x, None).iter().map(|c| c.index).collect();
+        assert_eq!(found, vec![10, 20]);
+
+        // A large penalty hides them
+        assert!(changepoints(&x, Some(100.)).is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Statistics of angles (e.g., wind directions or solar azimuths), which
+//! wrap around: the mean of 350° and 10° is 0°, not 180°. Angles are
+//! given in radians; use [`f64::to_radians`] to convert degrees.
+
+use super::count_as_f64;
+use crate::numberish::Numberish;
+
+/// The mean direction of a set of angles and the length of their
+/// mean resultant vector, which is 1 when all angles are equal and
+/// close to 0 when they are spread around the circle
+fn resultant<T: Numberish>(x: &[T], what: &str) -> Result<(f64, f64), String> {
+    if x.is_empty() {
+        return Err(format!("Trying to calculate {} of an empty dataset", what));
+    }
+    let (mut s, mut c) = (0., 0.);
+    for v in x {
+        let v: f64 = (*v).into();
+        s += v.sin();
+        c += v.cos();
+    }
+    let n = count_as_f64(x.len());
+    Ok((s.atan2(c), (s * s + c * c).sqrt() / n))
+}
+
+/// Calculates the circular mean of a set of angles (in radians), which
+/// is the direction of the sum of their unit vectors. The result lies
+/// between $`-\pi`$ and $`\pi`$.
+///
+/// # The math
+/// ```math
+/// \bar{\theta} = \operatorname{atan2}\left(\sum_{i} \sin\theta_i, \sum_{i} \cos\theta_i\right)
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::circular_mean;
+///
+/// let x: Vec<f64> = vec![350., 10.].iter().map(|d: &f64| d.to_radians()).collect();
+/// assert_close!(circular_mean(&x).unwrap(), 0.);
+///
+/// // Opposite directions have no mean
+/// assert!(circular_mean(&[0., std::f64::consts::PI]).is_err());
+/// ```
+///
+/// # Errors
+///
+/// * if the dataset is empty
+/// * if the angles cancel each other out, so there is no mean direction
+pub fn circular_mean<T: Numberish>(x: &[T]) -> Result<f64, String> {
+    let (mean, r) = resultant(x, "the circular mean")?;
+    if r < 1e-12 {
+        return Err("The circular mean is undefined, as the angles cancel each other out".to_string());
+    }
+    Ok(mean)
+}
+
+/// Calculates the circular standard deviation of a set of angles (in
+/// radians). It is close to the usual standard deviation when the angles
+/// are concentrated, and grows without bound as they spread around the circle.
+///
+/// # The math
+/// ```math
+/// \sigma = \sqrt{-2 \ln \bar{R}}
+/// ```
+///
+/// where $`\bar{R}`$ is the length of the mean of the unit vectors of the angles.
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::circular_std;
+///
+/// let x: Vec<f64> = vec![350., 10.].iter().map(|d: &f64| d.to_radians()).collect();
+/// assert_close!(circular_std(&x).unwrap().to_degrees(), 10.025, 1e-3);
+/// ```
+///
+/// # Errors
+///
+/// * if the dataset is empty
+pub fn circular_std<T: Numberish>(x: &[T]) -> Result<f64, String> {
+    let (_, r) = resultant(x, "the circular standard deviation")?;
+    Ok((-2. * r.min(1.).ln()).sqrt())
+}
+
+/// Calculates the circular correlation coefficient between two sets of
+/// angles (in radians), as proposed by Jammalamadaka and Sarma. Like the
+/// Pearson coefficient, it lies between -1 and 1, and it is 1 when one
+/// set is the other one rotated by a constant angle.
+///
+/// # The math
+/// ```math
+/// r = \frac{\sum_{i} \sin(x_i - \bar{x})\sin(y_i - \bar{y})}{\sqrt{\sum_{i} \sin^2(x_i - \bar{x}) \sum_{i} \sin^2(y_i - \bar{y})}}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::circular_correlation;
+///
+/// let x = vec![0.1, 0.5, 1.2, 2.0, 2.5];
+/// // Rotated by 3 radians, which wraps around for most of them
+/// let y: Vec<f64> = x.iter().map(|v| v + 3.).collect();
+/// assert_close!(circular_correlation(&x, &y).unwrap(), 1.);
+/// ```
+///
+/// # Errors
+///
+/// * if the datasets have different lengths or are empty
+/// * if either dataset has no mean direction, or all of its angles are
+///   equal (or opposite)
+pub fn circular_correlation<T: Numberish>(x: &[T], y: &[T]) -> Result<f64, String> {
+    super::check_pair(x, y, "the circular correlation")?;
+    let (mx, my) = (circular_mean(x)?, circular_mean(y)?);
+    let (mut sxy, mut sxx, mut syy) = (0., 0., 0.);
+    for (x, y) in x.iter().zip(y.iter()) {
+        let (dx, dy) = (((*x).into() - mx).sin(), ((*y).into() - my).sin());
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    if sxx <= 0. || syy <= 0. {
+        return Err("Cannot calculate the circular correlation of angles that do not vary".to_string());
+    }
+    Ok(sxy / (sxx * syy).sqrt())
+}
@@ -0,0 +1,353 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Comparisons between the distributions of two samples, regardless of the
+//! order of their values. They are built on the empirical cumulative
+//! distribution function (see [`ecdf`]).
+
+use super::count_as_f64;
+use crate::numberish::Numberish;
+
+/// The empirical cumulative distribution function of a sample, as
+/// returned by [`ecdf`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ecdf {
+    /// The values of the sample, sorted
+    sorted: Vec<f64>,
+}
+
+impl Ecdf {
+    /// The fraction of the sample that is smaller than or equal to `v`
+    pub fn eval(&self, v: f64) -> f64 {
+        let below = self.sorted.partition_point(|x| *x <= v);
+        count_as_f64(below) / count_as_f64(self.sorted.len())
+    }
+
+    /// The points at which the function steps up, as pairs of (value,
+    /// fraction of the sample smaller than or equal to it). Repeated
+    /// values produce a single, taller step.
+    pub fn steps(&self) -> Vec<(f64, f64)> {
+        let n = count_as_f64(self.sorted.len());
+        let mut ret: Vec<(f64, f64)> = Vec::new();
+        for (i, v) in self.sorted.iter().enumerate() {
+            let fraction = count_as_f64(i + 1) / n;
+            match ret.last_mut() {
+                Some(last) if last.0 == *v => last.1 = fraction,
+                _ => ret.push((*v, fraction)),
+            }
+        }
+        ret
+    }
+
+    /// The quantile of the sample at probability `p` (between 0 and 1),
+    /// interpolating linearly between the sorted values, so the quantiles
+    /// at 0 and 1 are the minimum and the maximum of the sample. Returns
+    /// `NaN` if `p` is out of range.
+    ///
+    /// ```
+    /// use validate::stats::ecdf;
+    ///
+    /// let f = ecdf(&[4., 1., 3., 2.]).unwrap();
+    /// assert_eq!(f.quantile(0.), 1.);
+    /// assert_eq!(f.quantile(0.5), 2.5);
+    /// assert_eq!(f.quantile(1.), 4.);
+    /// ```
+    pub fn quantile(&self, p: f64) -> f64 {
+        if !(0. ..=1.).contains(&p) {
+            return f64::NAN;
+        }
+        let position = p * count_as_f64(self.sorted.len() - 1);
+        let below = position.floor() as usize;
+        match self.sorted.get(below + 1) {
+            Some(next) => {
+                let v = self.sorted[below];
+                v + (next - v) * (position - position.floor())
+            }
+            None => self.sorted[below],
+        }
+    }
+
+    /// The sorted values of the sample
+    pub fn values(&self) -> &[f64] {
+        &self.sorted
+    }
+}
+
+/// Calculates the empirical cumulative distribution function of a sample:
+/// the fraction of its values that are smaller than or equal to each value.
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::ecdf;
+///
+/// let f = ecdf(&[3., 1., 2., 2.]).unwrap();
+/// assert_eq!(f.eval(0.), 0.);
+/// assert_eq!(f.eval(2.), 0.75);
+/// assert_eq!(f.eval(10.), 1.);
+/// assert_eq!(f.steps(), vec![(1., 0.25), (2., 0.75), (3., 1.)]);
+/// ```
+///
+/// # Errors
+///
+/// * if the sample is empty
+/// * if the sample contains `NaN`
+pub fn ecdf<T: Numberish>(x: &[T]) -> Result<Ecdf, String> {
+    if x.is_empty() {
+        return Err("Trying to calculate the ECDF of an empty sample".to_string());
+    }
+    let mut sorted: Vec<f64> = x.iter().map(|v| (*v).into()).collect();
+    if sorted.iter().any(|v| v.is_nan()) {
+        return Err("Trying to calculate the ECDF of a sample containing NaN".to_string());
+    }
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    Ok(Ecdf { sorted })
+}
+
+/// Walks through the distinct values of both samples in increasing order,
+/// calling `f(value, next_value, F_x, F_y, repetitions)` at each of them, where
+/// `F_x` and `F_y` are the ECDFs at `value`, `next_value` is the following
+/// distinct value (if any) and `repetitions` is how many times `value` appears
+/// in both samples together.
+fn walk<T: Numberish, F: FnMut(f64, Option<f64>, f64, f64, usize)>(
+    x: &[T],
+    y: &[T],
+    what: &str,
+    mut f: F,
+) -> Result<(), String> {
+    let ecdf_of = |v: &[T]| ecdf(v).map_err(|e| format!("Cannot calculate {}: {}", what, e));
+    let (x, y) = (ecdf_of(x)?, ecdf_of(y)?);
+    let (x, y) = (x.values(), y.values());
+    let (nx, ny) = (count_as_f64(x.len()), count_as_f64(y.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < x.len() || j < y.len() {
+        let v = match (x.get(i), y.get(j)) {
+            (Some(a), Some(b)) => a.min(*b),
+            (Some(a), None) => *a,
+            (None, Some(b)) => *b,
+            (None, None) => unreachable!(),
+        };
+        let (i0, j0) = (i, j);
+        while x.get(i) == Some(&v) {
+            i += 1;
+        }
+        while y.get(j) == Some(&v) {
+            j += 1;
+        }
+        let next = match (x.get(i), y.get(j)) {
+            (Some(a), Some(b)) => Some(a.min(*b)),
+            (a, b) => a.or(b).copied(),
+        };
+        f(v, next, count_as_f64(i) / nx, count_as_f64(j) / ny, i - i0 + j - j0);
+    }
+    Ok(())
+}
+
+/// Calculates the Wasserstein-1 (or Earth Mover's) distance between the
+/// distributions of two samples: the area between their ECDFs. It is in the
+/// units of the samples, and it is how far (on average) the values of one
+/// sample need to be moved to turn it into the other one. Unlike the
+/// Kolmogorov-Smirnov statistic, it grows with the size of the differences,
+/// including those in the tails.
+///
+/// # The math
+/// ```math
+/// W_1 = \int_{-\infty}^{\infty} \left|F_x(t) - F_y(t)\right| dt
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::wasserstein_distance;
+///
+/// // Shifting every value by 1.5
+/// let x = vec![0., 1., 2., 5.];
+/// let y = vec![1.5, 2.5, 3.5, 6.5];
+/// assert_close!(wasserstein_distance(&x, &y).unwrap(), 1.5);
+/// assert_close!(wasserstein_distance(&x, &x).unwrap(), 0.);
+/// ```
+///
+/// # Errors
+///
+/// * if either sample is empty or contains `NaN`
+pub fn wasserstein_distance<T: Numberish>(x: &[T], y: &[T]) -> Result<f64, String> {
+    let mut area = 0.;
+    walk(x, y, "the Wasserstein distance", |v, next, fx, fy, _| {
+        if let Some(next) = next {
+            area += (fx - fy).abs() * (next - v);
+        }
+    })?;
+    Ok(area)
+}
+
+/// Calculates the two-sample Cramér–von Mises statistic, which adds up the
+/// squared differences between the ECDFs of the samples at every value of
+/// both of them. It considers the whole distributions rather than their
+/// largest difference (like Kolmogorov-Smirnov does), so it is less
+/// sensitive to ties and to a single jump in the ECDFs. Identical samples
+/// score 0.
+///
+/// # The math
+/// ```math
+/// T = \frac{n_x n_y}{(n_x + n_y)^2} \sum_{z \in x \cup y} \left(F_x(z) - F_y(z)\right)^2
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::cramer_von_mises;
+///
+/// let x = vec![1., 2., 3.];
+/// let y = vec![4., 5., 6.];
+/// assert_close!(cramer_von_mises(&x, &y).unwrap(), 0.52778, 1e-4);
+/// assert_close!(cramer_von_mises(&x, &x).unwrap(), 0.);
+/// ```
+///
+/// # Errors
+///
+/// * if either sample is empty or contains `NaN`
+pub fn cramer_von_mises<T: Numberish>(x: &[T], y: &[T]) -> Result<f64, String> {
+    let mut sum = 0.;
+    walk(x, y, "the Cramér-von Mises statistic", |_, _, fx, fy, repetitions| {
+        sum += (fx - fy).powi(2) * count_as_f64(repetitions);
+    })?;
+    let (nx, ny) = (count_as_f64(x.len()), count_as_f64(y.len()));
+    Ok(nx * ny / (nx + ny).powi(2) * sum)
+}
+
+/// Counts the values of a sample that fall into each of `bins` bins of
+/// equal width, between `min` and `max`. Each bin includes its lower edge,
+/// and the last one also its upper edge. Values out of range are not counted.
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::histogram;
+///
+/// let x = vec![0., 0.5, 1., 1.5, 2., 3.];
+/// assert_eq!(histogram(&x, 0., 2., 4).unwrap(), vec![1, 1, 1, 2]);
+/// ```
+///
+/// # Errors
+///
+/// * if there are no bins
+/// * if `min` is not smaller than `max`, or either of them is not finite
+pub fn histogram<T: Numberish>(x: &[T], min: f64, max: f64, bins: usize) -> Result<Vec<usize>, String> {
+    if bins == 0 {
+        return Err("Trying to calculate a histogram with no bins".to_string());
+    }
+    if !(min.is_finite() && max.is_finite() && min < max) {
+        return Err(format!("Invalid range of a histogram: from {} to {}", min, max));
+    }
+    let width = (max - min) / count_as_f64(bins);
+    let mut ret = vec![0; bins];
+    for v in x.iter().map(|v| (*v).into()) {
+        if (min..=max).contains(&v) {
+            let bin = ((v - min) / width).floor() as usize;
+            ret[bin.min(bins - 1)] += 1;
+        }
+    }
+    Ok(ret)
+}
+
+/// Calculates the two-sample chi-square statistic, which tells whether two
+/// histograms with the same bins (see [`histogram`]) come from the same
+/// distribution. The samples may have different sizes. Bins that are empty
+/// in both histograms are ignored. Identical histograms score 0.
+///
+/// # The math
+/// ```math
+/// \chi^2 = \sum_i \frac{\left(\sqrt{N_y/N_x}\,x_i - \sqrt{N_x/N_y}\,y_i\right)^2}{x_i + y_i}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::chi_square_two_sample;
+///
+/// assert_close!(chi_square_two_sample(&[2, 4, 0], &[1, 2, 0]).unwrap(), 0.);
+/// assert_close!(chi_square_two_sample(&[4, 0], &[0, 4]).unwrap(), 8.);
+/// ```
+///
+/// # Errors
+///
+/// * if the histograms have a different number of bins
+/// * if either histogram is empty (i.e., counts nothing)
+pub fn chi_square_two_sample(x: &[usize], y: &[usize]) -> Result<f64, String> {
+    if x.len() != y.len() {
+        return Err(format!(
+            "Trying to compare histograms with {} and {} bins",
+            x.len(),
+            y.len()
+        ));
+    }
+    let (nx, ny) = (count_as_f64(x.iter().sum()), count_as_f64(y.iter().sum()));
+    if nx == 0. || ny == 0. {
+        return Err("Trying to calculate the chi-square statistic of an empty histogram".to_string());
+    }
+    let (kx, ky) = ((ny / nx).sqrt(), (nx / ny).sqrt());
+    Ok(x.iter()
+        .zip(y.iter())
+        .filter(|(a, b)| **a + **b > 0)
+        .map(|(a, b)| {
+            let (a, b) = (count_as_f64(*a), count_as_f64(*b));
+            (kx * a - ky * b).powi(2) / (a + b)
+        })
+        .sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances_with_ties() {
+        // Interleaved samples with repeated values, of different sizes
+        let x = vec![1., 1., 2., 4.];
+        let y = vec![1., 3., 3.];
+        // F_x - F_y = 0.5 - 1/3 on [1, 2), 0.75 - 1/3 on [2, 3), 0.75 - 1 on [3, 4)
+        let w = wasserstein_distance(&x, &y).unwrap();
+        assert_close!(w, 1. / 6. + 5. / 12. + 0.25);
+        assert_close!(wasserstein_distance(&y, &x).unwrap(), w);
+        // The value 1 appears three times
+        let sum = 3. * (0.5f64 - 1. / 3.).powi(2) + (0.75f64 - 1. / 3.).powi(2) + 2. * 0.25f64.powi(2);
+        assert_close!(cramer_von_mises(&x, &y).unwrap(), 12. / 49. * sum);
+
+        assert!(ecdf::<f64>(&[]).is_err());
+        assert!(wasserstein_distance(&[1., f64::NAN], &[1.]).is_err());
+    }
+
+    #[test]
+    fn test_histograms() {
+        let x = vec![-1., 0., 0.99, 1., 10.];
+        assert_eq!(histogram(&x, 0., 10., 10).unwrap(), vec![2, 1, 0, 0, 0, 0, 0, 0, 0, 1]);
+        assert!(histogram(&x, 1., 1., 3).is_err());
+        assert!(histogram(&x, 0., 1., 0).is_err());
+
+        // Twice as many samples, distributed the same way
+        assert_close!(chi_square_two_sample(&[1, 3, 2], &[2, 6, 4]).unwrap(), 0.);
+        assert!(chi_square_two_sample(&[1, 3, 2], &[3, 1, 2]).unwrap() > 1.);
+        assert!(chi_square_two_sample(&[1, 3], &[1, 3, 0]).is_err());
+        assert!(chi_square_two_sample(&[0, 0], &[1, 3]).is_err());
+    }
+}
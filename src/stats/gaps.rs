@@ -0,0 +1,191 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+//! Checks of the timestamps of a series, whose gaps and duplicates would
+//! otherwise silently bias anything calculated over periods of time.
+
+use super::count_as_f64;
+use crate::numberish::Numberish;
+
+/// Intervals that differ from the sampling step by more than this fraction
+/// of it are irregular
+const IRREGULAR_TOLERANCE: f64 = 0.01;
+
+/// Intervals longer than this many sampling steps are gaps
+const GAP_STEPS: f64 = 1.5;
+
+/// An interval between timestamps in which samples are missing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gap {
+    /// The index of the first sample after the gap
+    pub index: usize,
+
+    /// The timestamp of the last sample before the gap
+    pub from: f64,
+
+    /// The timestamp of the first sample after the gap
+    pub to: f64,
+
+    /// The number of samples that would fit in the gap
+    pub missing: usize,
+}
+
+/// How a series was sampled, as found by [`sampling`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sampling {
+    /// The nominal interval between samples
+    pub step: f64,
+
+    /// The intervals in which samples are missing
+    pub gaps: Vec<Gap>,
+
+    /// The indices of the samples whose timestamp repeats the previous one
+    pub duplicates: Vec<usize>,
+
+    /// The number of intervals that are neither the sampling step
+    /// (within 1%) nor gaps
+    pub irregular: usize,
+}
+
+impl Sampling {
+    /// The total number of samples missing in the gaps
+    pub fn missing(&self) -> usize {
+        self.gaps.iter().map(|g| g.missing).sum()
+    }
+
+    /// The fraction of the period covered by the series that falls
+    /// in gaps, measured in samples
+    pub fn gap_fraction(&self, samples: usize) -> f64 {
+        let missing = self.missing();
+        if missing == 0 {
+            return 0.;
+        }
+        count_as_f64(missing) / count_as_f64(samples + missing)
+    }
+}
+
+/// Checks the timestamps `t` of a series for gaps, duplicates and irregular
+/// intervals. The sampling `step` is, unless given, the median of the
+/// intervals between different timestamps. Intervals longer than 1.5 steps
+/// are gaps, in which `round(interval / step) - 1` samples are missing.
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::sampling;
+///
+/// // Hourly readings, with a repeated one and three missing
+/// let t = vec![0., 1., 2., 2., 3., 7., 8.];
+/// let s = sampling(&t, None).unwrap();
+/// assert_eq!(s.step, 1.);
+/// assert_eq!(s.duplicates, vec![3]);
+/// assert_eq!(s.gaps.len(), 1);
+/// assert_eq!((s.gaps[0].index, s.gaps[0].missing), (5, 3));
+/// assert_eq!(s.gap_fraction(t.len()), 0.3);
+/// ```
+///
+/// # Errors
+///
+/// * if there are fewer than two timestamps, or any of them is not finite
+/// * if the timestamps go backwards
+/// * if the step is not positive
+pub fn sampling<T: Numberish>(t: &[T], step: Option<f64>) -> Result<Sampling, String> {
+    let t: Vec<f64> = t.iter().map(|v| (*v).into()).collect();
+    if t.len() < 2 {
+        return Err(format!(
+            "Checking the sampling of a series requires at least 2 timestamps, but there are {}",
+            t.len()
+        ));
+    }
+    if let Some(i) = t.iter().position(|v| !v.is_finite()) {
+        return Err(format!("Timestamp {} is not a number ({})", i, t[i]));
+    }
+    if let Some(i) = t.windows(2).position(|w| w[1] < w[0]) {
+        return Err(format!(
+            "Timestamps must not go backwards, but t[{}] = {} and t[{}] = {}",
+            i,
+            t[i],
+            i + 1,
+            t[i + 1]
+        ));
+    }
+    let step = match step {
+        Some(s) => s,
+        None => {
+            let mut intervals: Vec<f64> = t.windows(2).map(|w| w[1] - w[0]).filter(|d| *d > 0.).collect();
+            if intervals.is_empty() {
+                return Err("All the timestamps are the same".to_string());
+            }
+            intervals.sort_by(|a, b| a.total_cmp(b));
+            intervals[(intervals.len() - 1) / 2]
+        }
+    };
+    if !(step.is_finite() && step > 0.) {
+        return Err(format!("Invalid sampling step {}: it must be positive", step));
+    }
+
+    let mut ret = Sampling {
+        step,
+        gaps: Vec::new(),
+        duplicates: Vec::new(),
+        irregular: 0,
+    };
+    for (i, w) in t.windows(2).enumerate() {
+        let d = w[1] - w[0];
+        if d == 0. {
+            ret.duplicates.push(i + 1);
+        } else if d > GAP_STEPS * step {
+            ret.gaps.push(Gap {
+                index: i + 1,
+                from: w[0],
+                to: w[1],
+                missing: ((d / step).round() as usize).saturating_sub(1),
+            });
+        } else if (d - step).abs() > IRREGULAR_TOLERANCE * step {
+            ret.irregular += 1;
+        }
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampling() {
+        assert!(sampling(&[1.], None).is_err());
+        assert!(sampling(&[1., 1.], None).is_err());
+        assert!(sampling(&[1., 0.], None).is_err());
+        assert!(sampling(&[0., 1.], Some(0.)).is_err());
+
+        let regular = sampling(&[0., 0.5, 1., 1.5], None).unwrap();
+        assert_eq!(regular.step, 0.5);
+        assert!(regular.gaps.is_empty() && regular.duplicates.is_empty());
+        assert_eq!(regular.irregular, 0);
+        assert_eq!(regular.gap_fraction(4), 0.);
+
+        // A given step, and a jittery interval
+        let s = sampling(&[0., 10., 21., 30., 60.], Some(10.)).unwrap();
+        assert_eq!(s.irregular, 2);
+        assert_eq!(s.missing(), 2);
+    }
+}
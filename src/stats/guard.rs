@@ -0,0 +1,78 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Detection of datasets for which some statistics are undefined (e.g.,
+//! the slope of a line fitted against a constant), so they can be reported
+//! clearly instead of as `NaN`.
+
+use crate::numberish::Numberish;
+use core::fmt;
+
+/// A reason why a dataset cannot be meaningfully compared, as
+/// detected by [`degeneracy`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Degeneracy {
+    /// The dataset has no values
+    Empty,
+
+    /// Every value is zero, so errors relative to it are undefined
+    AllZero,
+
+    /// Every value is equal to this one, so its variance is zero and
+    /// correlations with it are undefined
+    Constant(f64),
+}
+
+impl fmt::Display for Degeneracy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Degeneracy::Empty => write!(f, "there are no values"),
+            Degeneracy::AllZero => write!(f, "all values are zero"),
+            Degeneracy::Constant(v) => write!(f, "all values are equal to {}", v),
+        }
+    }
+}
+
+/// Checks whether a dataset is degenerate: empty, all zeros or
+/// constant. `NaN` values are never considered equal to each other.
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::{degeneracy, Degeneracy};
+///
+/// assert_eq!(degeneracy(&[0., 0., 0.]), Some(Degeneracy::AllZero));
+/// assert_eq!(degeneracy(&[2., 2.]), Some(Degeneracy::Constant(2.)));
+/// assert_eq!(degeneracy::<f64>(&[]), Some(Degeneracy::Empty));
+/// assert_eq!(degeneracy(&[1., 2.]), None);
+/// ```
+pub fn degeneracy<T: Numberish>(x: &[T]) -> Option<Degeneracy> {
+    let first: f64 = match x.first() {
+        Some(v) => (*v).into(),
+        None => return Some(Degeneracy::Empty),
+    };
+    if !x.iter().all(|v| (*v).into() == first) {
+        None
+    } else if first == 0. {
+        Some(Degeneracy::AllZero)
+    } else {
+        Some(Degeneracy::Constant(first))
+    }
+}
@@ -0,0 +1,440 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Tests of whether two independent groups of samples (e.g., measurements
+//! taken in two different experiments) come from the same population. Unlike
+//! the errors between series, these do not pair the samples up, so the groups
+//! may have different sizes.
+//!
+//! With enough samples, even negligible differences are "statistically
+//! significant", so each test also reports an effect size telling how large
+//! the difference is.
+
+use super::special::{normal_two_sided, student_t_two_sided};
+use super::{count_as_f64, mean};
+use crate::numberish::Numberish;
+
+/// The result of [`welch_t_test`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TTest {
+    /// The t statistic, which is negative when the mean of the
+    /// first group is smaller than that of the second one
+    pub t: f64,
+
+    /// The (Welch–Satterthwaite) degrees of freedom
+    pub degrees_of_freedom: f64,
+
+    /// The two-sided p-value: the probability of a difference between the
+    /// means at least this large if both groups had the same mean
+    pub p_value: f64,
+
+    /// The size of the difference between the means, in pooled standard
+    /// deviations (see [`cohens_d`]). Unlike the p-value, it does not shrink
+    /// as the groups grow, so it tells whether the difference matters.
+    pub cohens_d: f64,
+}
+
+/// The result of [`mann_whitney_u`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MannWhitney {
+    /// The U statistic of the first group: the number of pairs in which
+    /// its sample is larger than that of the second group (ties count as half)
+    pub u: f64,
+
+    /// The standardized U statistic, corrected for ties and continuity
+    pub z: f64,
+
+    /// The two-sided p-value, from the normal approximation of U
+    pub p_value: f64,
+
+    /// How much the samples of the first group tend to be larger than those
+    /// of the second one, between -1 and 1 (see [`cliffs_delta`])
+    pub cliffs_delta: f64,
+}
+
+/// The mean and the (sample) variance of a group
+fn mean_and_variance<T: Numberish>(x: &[T]) -> (f64, f64) {
+    let m = mean(x);
+    let ss: f64 = x
+        .iter()
+        .map(|v| {
+            let d = (*v).into() - m;
+            d * d
+        })
+        .sum();
+    (m, ss / count_as_f64(x.len() - 1))
+}
+
+/// Checks that two groups have at least `min` samples each. `what` is
+/// the name of what is being calculated, used in the error messages.
+fn check_groups<T>(x: &[T], y: &[T], min: usize, what: &str) -> Result<(), String> {
+    if x.len() < min || y.len() < min {
+        return Err(format!(
+            "{} needs at least {} sample(s) per group. x.len() = {}, y.len() = {}",
+            what,
+            min,
+            x.len(),
+            y.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Calculates the U statistic of the first group (i.e., the number of pairs
+/// in which its sample is larger, with ties counting as half), and the sum
+/// of $`t^3 - t`$ over the groups of $`t`$ tied samples
+fn u_statistic<T: Numberish>(x: &[T], y: &[T]) -> (f64, f64) {
+    let mut all: Vec<(f64, bool)> = x
+        .iter()
+        .map(|v| ((*v).into(), true))
+        .chain(y.iter().map(|v| ((*v).into(), false)))
+        .collect();
+    all.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Average the ranks of ties, and keep track of them for the variance
+    let mut rank_sum = 0.;
+    let mut ties = 0.;
+    let mut i = 0;
+    while i < all.len() {
+        let mut j = i + 1;
+        while j < all.len() && all[j].0 == all[i].0 {
+            j += 1;
+        }
+        let rank = count_as_f64(i + j + 1) / 2.;
+        rank_sum += rank * count_as_f64(all[i..j].iter().filter(|(_, in_x)| *in_x).count());
+        let t = count_as_f64(j - i);
+        ties += t * t * t - t;
+        i = j;
+    }
+    let nx = count_as_f64(x.len());
+    (rank_sum - nx * (nx + 1.) / 2., ties)
+}
+
+/// Calculates Cohen's d, the difference between the means of two independent
+/// groups in units of their pooled standard deviation. As a rule of thumb,
+/// magnitudes around 0.2 are small, 0.5 medium and 0.8 large.
+///
+/// # The math
+/// ```math
+/// d = \frac{\bar{x} - \bar{y}}{\sqrt{\frac{(n_x-1)s_x^2 + (n_y-1)s_y^2}{n_x + n_y - 2}}}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::cohens_d;
+///
+/// let x = vec![1., 2., 3., 4., 5.];
+/// let y = vec![6., 7., 8., 9., 10.];
+/// assert_close!(cohens_d(&x, &y).unwrap(), -3.16228, 1e-4);
+/// ```
+///
+/// # Errors
+///
+/// * if either group has fewer than two samples
+/// * if neither group varies
+pub fn cohens_d<T: Numberish>(x: &[T], y: &[T]) -> Result<f64, String> {
+    check_groups(x, y, 2, "Cohen's d")?;
+    let (mx, vx) = mean_and_variance(x);
+    let (my, vy) = mean_and_variance(y);
+    let (nx, ny) = (count_as_f64(x.len()), count_as_f64(y.len()));
+    let pooled = ((nx - 1.) * vx + (ny - 1.) * vy) / (nx + ny - 2.);
+    if pooled <= 0. {
+        return Err("Cannot calculate Cohen's d of groups that do not vary".to_string());
+    }
+    Ok((mx - my) / pooled.sqrt())
+}
+
+/// Calculates Hedges' g, which is [`cohens_d`] corrected for the
+/// bias it has when the groups are small.
+///
+/// # The math
+/// ```math
+/// g = d \left(1 - \frac{3}{4(n_x + n_y) - 9}\right)
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::hedges_g;
+///
+/// let x = vec![1., 2., 3., 4., 5.];
+/// let y = vec![6., 7., 8., 9., 10.];
+/// assert_close!(hedges_g(&x, &y).unwrap(), -2.85626, 1e-4);
+/// ```
+///
+/// # Errors
+///
+/// * if either group has fewer than two samples
+/// * if neither group varies
+pub fn hedges_g<T: Numberish>(x: &[T], y: &[T]) -> Result<f64, String> {
+    let d = cohens_d(x, y)?;
+    let n = count_as_f64(x.len() + y.len());
+    Ok(d * (1. - 3. / (4. * n - 9.)))
+}
+
+/// Calculates Cliff's delta: the probability that a sample of the first group
+/// is larger than one of the second group, minus the probability that it is
+/// smaller. It ranges from -1 (every sample of `x` is smaller) to 1 (every
+/// one is larger), and needs no assumption about the distributions. It is
+/// the effect size that accompanies [`mann_whitney_u`].
+///
+/// # The math
+/// ```math
+/// \delta = \frac{\#(x_i > y_j) - \#(x_i < y_j)}{n_x n_y} = \frac{2U_x}{n_x n_y} - 1
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::cliffs_delta;
+///
+/// assert_close!(cliffs_delta(&[1., 2., 3.], &[4., 5.]).unwrap(), -1.);
+/// assert_close!(cliffs_delta(&[1., 3.], &[2., 2.]).unwrap(), 0.);
+/// ```
+///
+/// # Errors
+///
+/// * if either group is empty
+pub fn cliffs_delta<T: Numberish>(x: &[T], y: &[T]) -> Result<f64, String> {
+    check_groups(x, y, 1, "Cliff's delta")?;
+    let (u, _) = u_statistic(x, y);
+    Ok(2. * u / count_as_f64(x.len() * y.len()) - 1.)
+}
+
+/// Performs Welch's t-test, which checks whether two independent groups of
+/// samples have the same mean without assuming they have the same variance.
+///
+/// # The math
+/// ```math
+/// t = \frac{\bar{x} - \bar{y}}{\sqrt{s_x^2/n_x + s_y^2/n_y}}
+/// \qquad
+/// \nu = \frac{\left(s_x^2/n_x + s_y^2/n_y\right)^2}{\frac{(s_x^2/n_x)^2}{n_x - 1} + \frac{(s_y^2/n_y)^2}{n_y - 1}}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::welch_t_test;
+///
+/// let x = vec![1., 2., 3., 4., 5.];
+/// let y = vec![6., 7., 8., 9., 10.];
+/// let test = welch_t_test(&x, &y).unwrap();
+/// assert_close!(test.t, -5.);
+/// assert_close!(test.degrees_of_freedom, 8.);
+/// assert!(test.p_value < 0.01);
+/// assert!(test.cohens_d < -0.8);
+///
+/// // Groups that cannot be told apart
+/// let test = welch_t_test(&[1., 3., 2., 4.], &[2., 4., 1., 3., 2.5]).unwrap();
+/// assert!(test.p_value > 0.5);
+/// ```
+///
+/// # Errors
+///
+/// * if either group has fewer than two samples
+/// * if neither group varies
+pub fn welch_t_test<T: Numberish>(x: &[T], y: &[T]) -> Result<TTest, String> {
+    check_groups(x, y, 2, "Welch's t-test")?;
+    let (mx, vx) = mean_and_variance(x);
+    let (my, vy) = mean_and_variance(y);
+    let (nx, ny) = (count_as_f64(x.len()), count_as_f64(y.len()));
+    let (sx, sy) = (vx / nx, vy / ny);
+    let se2 = sx + sy;
+    if se2 <= 0. {
+        return Err("Welch's t-test cannot compare groups that do not vary".to_string());
+    }
+    let t = (mx - my) / se2.sqrt();
+    let degrees_of_freedom = se2 * se2 / (sx * sx / (nx - 1.) + sy * sy / (ny - 1.));
+    Ok(TTest {
+        t,
+        degrees_of_freedom,
+        p_value: student_t_two_sided(t, degrees_of_freedom),
+        cohens_d: cohens_d(x, y)?,
+    })
+}
+
+/// Performs the Mann–Whitney U test (also known as the Wilcoxon rank-sum
+/// test), which checks whether samples from one group tend to be larger than
+/// those from another one. It does not assume the samples are normally
+/// distributed, so it suits skewed data or data with outliers.
+///
+/// Tied samples get the average of their ranks, and the p-value comes from
+/// the normal approximation of U, corrected for ties and continuity, so it
+/// is reliable when each group has more than (about) eight samples.
+///
+/// # The math
+/// ```math
+/// U_x = R_x - \frac{n_x(n_x+1)}{2}
+/// \qquad
+/// z = \frac{U_x - n_x n_y / 2}{\sigma_U}
+/// ```
+///
+/// where $`R_x`$ is the sum of the ranks of the first group within both groups.
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::mann_whitney_u;
+///
+/// let x = vec![1., 2., 3., 4., 5.];
+/// let y = vec![6., 7., 8., 9., 10.];
+/// let test = mann_whitney_u(&x, &y).unwrap();
+/// assert_close!(test.u, 0.);
+/// assert_close!(test.p_value, 0.01219, 1e-4);
+/// assert_close!(test.cliffs_delta, -1.);
+/// ```
+///
+/// # Errors
+///
+/// * if either group is empty
+/// * if all the samples are equal
+pub fn mann_whitney_u<T: Numberish>(x: &[T], y: &[T]) -> Result<MannWhitney, String> {
+    check_groups(x, y, 1, "The Mann-Whitney U test")?;
+    let (u, ties) = u_statistic(x, y);
+    let (nx, ny) = (count_as_f64(x.len()), count_as_f64(y.len()));
+    let n = nx + ny;
+    let variance = nx * ny / 12. * ((n + 1.) - ties / (n * (n - 1.)));
+    if variance <= 0. || variance.is_nan() {
+        return Err("The Mann-Whitney U test cannot compare groups in which all samples are equal".to_string());
+    }
+    let diff = u - nx * ny / 2.;
+    let z = diff.signum() * (diff.abs() - 0.5).max(0.) / variance.sqrt();
+    Ok(MannWhitney {
+        u,
+        z,
+        p_value: normal_two_sided(z),
+        cliffs_delta: 2. * u / (nx * ny) - 1.,
+    })
+}
+
+/// The result of [`trend_test`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrendTest {
+    /// The slope of the line fitted to the data
+    pub slope: f64,
+
+    /// The intersect of the line fitted to the data
+    pub intersect: f64,
+
+    /// The t statistic of the slope
+    pub t: f64,
+
+    /// The two-sided p-value: the probability of a slope at least this
+    /// steep if `y` did not depend on `x`
+    pub p_value: f64,
+}
+
+/// Fits the line $`y = a + b\times x`$ (see [`crate::stats::linear_coefficients`]) and
+/// tests whether its slope differs from zero. Regressing the errors of a
+/// model against time or against the expected values reveals whether the
+/// model drifts or gets worse under some conditions.
+///
+/// # The math
+/// ```math
+/// t = \frac{b}{\sqrt{\frac{\sum_i (y_i - a - b x_i)^2}{(n - 2)\sum_i (x_i - \bar{x})^2}}}
+/// ```
+///
+/// with $`n - 2`$ degrees of freedom.
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::trend_test;
+///
+/// let x = vec![1., 2., 3., 4., 5., 6.];
+/// let y = vec![0.1, 0.3, 0.2, 0.5, 0.4, 0.6];
+/// let test = trend_test(&x, &y).unwrap();
+/// assert_close!(test.slope, 0.0886, 1e-3);
+/// assert!(test.p_value < 0.05);
+///
+/// // No trend at all
+/// let test = trend_test(&x, &[1., -1., 1., -1., -1., 1.]).unwrap();
+/// assert!(test.p_value > 0.5);
+/// ```
+///
+/// # Errors
+///
+/// * if the datasets have different lengths or fewer than three values
+/// * if all the values of `x` are equal
+pub fn trend_test<T: Numberish>(x: &[T], y: &[T]) -> Result<TrendTest, String> {
+    if x.len() < 3 {
+        return Err(format!(
+            "Testing a trend needs at least three values, but there are {}",
+            x.len()
+        ));
+    }
+    let (intersect, slope, _) = super::try_linear_coefficients(x, y)?;
+    let mx = mean(x);
+    let (mut sxx, mut ssr) = (0., 0.);
+    for (x, y) in x.iter().zip(y.iter()) {
+        let (x, y): (f64, f64) = ((*x).into(), (*y).into());
+        sxx += (x - mx) * (x - mx);
+        let e = y - intersect - slope * x;
+        ssr += e * e;
+    }
+    let df = count_as_f64(x.len() - 2);
+    let se = (ssr / df / sxx).sqrt();
+    let (t, p_value) = if se > 0. {
+        let t = slope / se;
+        (t, student_t_two_sided(t, df))
+    } else if slope == 0. {
+        // The points lie exactly on a flat line
+        (0., 1.)
+    } else {
+        // The points lie exactly on a sloped line
+        (slope.signum() * f64::INFINITY, 0.)
+    };
+    Ok(TrendTest {
+        slope,
+        intersect,
+        t,
+        p_value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hypothesis_errors() {
+        assert!(welch_t_test(&[1.], &[1., 2.]).is_err());
+        assert!(welch_t_test(&[1., 1.], &[2., 2.]).is_err());
+        assert!(mann_whitney_u::<f64>(&[], &[1.]).is_err());
+        assert!(mann_whitney_u(&[1., 1.], &[1.]).is_err());
+
+        // Ties: U counts them as half
+        let test = mann_whitney_u(&[1., 2., 3.], &[2., 3., 4.]).unwrap();
+        assert_close!(test.u, 2.);
+        assert!(test.z < 0.);
+
+        let test = trend_test(&[1., 2., 3.], &[2., 4., 6.]).unwrap();
+        assert_eq!((test.slope, test.p_value), (2., 0.));
+        assert_eq!(trend_test(&[1., 2., 3.], &[5., 5., 5.]).unwrap().p_value, 1.);
+        assert!(trend_test(&[1., 2.], &[1., 2.]).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Numerical integration of sampled data (e.g., to turn a power series into
+//! the energy it adds up to). The samples do not need to be evenly spaced.
+
+use crate::numberish::Numberish;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Checks that `y` is sampled at `x`, which must be strictly increasing.
+/// `what` is the name of the integration rule, used in the error messages.
+fn check_samples<X: Numberish, Y>(x: &[X], y: &[Y], what: &str) -> Result<(), String> {
+    if x.len() != y.len() {
+        return Err(format!(
+            "Integrating with {} over x and y of different length. x.len() = {}, y.len = {}",
+            what,
+            x.len(),
+            y.len()
+        ));
+    }
+    if x.is_empty() {
+        return Err(format!("Trying to integrate empty datasets with {}", what));
+    }
+    if let Some(i) = x
+        .windows(2)
+        .position(|w| w[1].into().partial_cmp(&w[0].into()) != Some(core::cmp::Ordering::Greater))
+    {
+        return Err(format!(
+            "Integrating with {} requires strictly increasing x values, but x[{}] = {} and x[{}] = {}",
+            what,
+            i,
+            x[i],
+            i + 1,
+            x[i + 1]
+        ));
+    }
+    Ok(())
+}
+
+/// Integrates `y` over `x` by the trapezoidal rule, which is exact for
+/// piecewise linear data. A single sample integrates to zero.
+///
+/// # The math
+/// ```math
+/// \int y\,dx \approx \sum_{i=1}^{n-1} \frac{(x_i - x_{i-1})(y_i + y_{i-1})}{2}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::trapz;
+///
+/// // Hourly readings, with a missing one
+/// let hours = vec![0., 1., 3., 4.];
+/// let power = vec![0., 2., 2., 0.];
+/// assert_close!(trapz(&hours, &power).unwrap(), 6.);
+/// assert!(trapz(&[0., 2., 1.], &[1., 1., 1.]).is_err());
+/// ```
+///
+/// # Errors
+///
+/// * if `x` and `y` have different lengths or are empty
+/// * if `x` is not strictly increasing
+pub fn trapz<X: Numberish, Y: Numberish>(x: &[X], y: &[Y]) -> Result<f64, String> {
+    check_samples(x, y, "the trapezoidal rule")?;
+    Ok(x.windows(2)
+        .zip(y.windows(2))
+        .map(|(x, y)| (x[1].into() - x[0].into()) * (y[0].into() + y[1].into()) / 2.)
+        .sum())
+}
+
+/// Integrates `y` over `x` by the composite Simpson's rule for unevenly
+/// spaced samples, which is exact for quadratic (and, with evenly spaced
+/// samples, cubic) data. When there is an odd number of intervals, the last
+/// one is integrated with a quadratic through the last three samples. Two
+/// samples are integrated by the trapezoidal rule, and a single sample
+/// integrates to zero.
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::simpson;
+///
+/// // The integral of x² between 0 and 3 is 9
+/// let x = vec![0., 0.5, 1.5, 2., 3.];
+/// let y: Vec<f64> = x.iter().map(|x| x * x).collect();
+/// assert_close!(simpson(&x, &y).unwrap(), 9.);
+///
+/// // ... also with an odd number of intervals
+/// assert_close!(simpson(&x[..4], &y[..4]).unwrap(), 8. / 3.);
+/// ```
+///
+/// # Errors
+///
+/// * if `x` and `y` have different lengths or are empty
+/// * if `x` is not strictly increasing
+pub fn simpson<X: Numberish, Y: Numberish>(x: &[X], y: &[Y]) -> Result<f64, String> {
+    check_samples(x, y, "Simpson's rule")?;
+    if x.len() < 3 {
+        return trapz(x, y);
+    }
+    let x: Vec<f64> = x.iter().map(|v| (*v).into()).collect();
+    let y: Vec<f64> = y.iter().map(|v| (*v).into()).collect();
+    let n = x.len();
+    let mut ret = 0.;
+    let mut i = 0;
+    while i + 2 < n {
+        let (h0, h1) = (x[i + 1] - x[i], x[i + 2] - x[i + 1]);
+        let h = h0 + h1;
+        ret += h / 6. * ((2. - h1 / h0) * y[i] + h * h / (h0 * h1) * y[i + 1] + (2. - h0 / h1) * y[i + 2]);
+        i += 2;
+    }
+    if i + 2 == n {
+        // The last interval, from the quadratic through the last three samples
+        let (h0, h1) = (x[n - 2] - x[n - 3], x[n - 1] - x[n - 2]);
+        let alpha = (2. * h1 * h1 + 3. * h0 * h1) / (6. * (h0 + h1));
+        let beta = (h1 * h1 + 3. * h0 * h1) / (6. * h0);
+        let eta = h1 * h1 * h1 / (6. * h0 * (h0 + h1));
+        ret += alpha * y[n - 1] + beta * y[n - 2] - eta * y[n - 3];
+    }
+    Ok(ret)
+}
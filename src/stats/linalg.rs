@@ -0,0 +1,269 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! These functions compare matrices (e.g., the Jacobian or the solution of
+//! a linear system calculated by a simulation against a reference). Matrices
+//! are given as slices of rows, so both `&[Vec<f64>]` and `&[[f64; 3]]` work.
+
+use super::{compensated_sum, count_as_f64, sqrt};
+use crate::numberish::Numberish;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Checks that a matrix is not empty and that all its rows have the
+/// same length, returning its number of rows and columns. `what` is the
+/// name of what is being calculated, used in the error messages.
+fn shape<T, R: AsRef<[T]>>(a: &[R], what: &str) -> Result<(usize, usize), String> {
+    let cols = match a.first() {
+        Some(row) if !row.as_ref().is_empty() => row.as_ref().len(),
+        _ => return Err(format!("Trying to calculate {} of an empty matrix", what)),
+    };
+    if let Some(i) = a.iter().position(|row| row.as_ref().len() != cols) {
+        return Err(format!(
+            "Calculating {} of a matrix whose rows have different lengths. row[0].len() = {}, row[{}].len() = {}",
+            what,
+            cols,
+            i,
+            a[i].as_ref().len()
+        ));
+    }
+    Ok((a.len(), cols))
+}
+
+/// Calculates the Frobenius norm of a matrix: the square root of the
+/// sum of its squared elements.
+///
+/// # The math
+/// ```math
+/// \|A\|_F = \sqrt{\sum_{i}\sum_{j} a_{ij}^2}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::linalg::frobenius_norm;
+///
+/// let a = vec![vec![1., -2.], vec![2., 4.]];
+/// assert_close!(frobenius_norm(&a), 5.);
+/// ```
+pub fn frobenius_norm<T: Numberish, R: AsRef<[T]>>(a: &[R]) -> f64 {
+    sqrt(compensated_sum(a.iter().flat_map(|row| row.as_ref().iter()).map(|v| {
+        let v: f64 = (*v).into();
+        v * v
+    })))
+}
+
+/// Calculates the infinity norm of a matrix: the largest sum of the
+/// absolute values of the elements of a row.
+///
+/// # The math
+/// ```math
+/// \|A\|_\infty = \max_{i} \sum_{j} |a_{ij}|
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::linalg::inf_norm;
+///
+/// let a = vec![vec![1., -2.], vec![2., 4.]];
+/// assert_close!(inf_norm(&a), 6.);
+/// ```
+pub fn inf_norm<T: Numberish, R: AsRef<[T]>>(a: &[R]) -> f64 {
+    a.iter()
+        .map(|row| compensated_sum(row.as_ref().iter().map(|v| (*v).into().abs())))
+        .fold(0., f64::max)
+}
+
+/// Calculates the 1-norm of a matrix: the largest sum of the absolute
+/// values of the elements of a column.
+///
+/// # The math
+/// ```math
+/// \|A\|_1 = \max_{j} \sum_{i} |a_{ij}|
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::linalg::one_norm;
+///
+/// let a = vec![vec![1., -2.], vec![2., 4.]];
+/// assert_close!(one_norm(&a).unwrap(), 6.);
+/// ```
+///
+/// # Errors
+///
+/// * if the matrix is empty or its rows have different lengths
+pub fn one_norm<T: Numberish, R: AsRef<[T]>>(a: &[R]) -> Result<f64, String> {
+    let (_, cols) = shape(a, "the 1-norm")?;
+    Ok((0..cols)
+        .map(|j| compensated_sum(a.iter().map(|row| row.as_ref()[j].into().abs())))
+        .fold(0., f64::max))
+}
+
+/// Calculates the error of a matrix relative to the expected one, as the
+/// Frobenius norm of their difference divided by that of the expected matrix.
+///
+/// # The math
+/// ```math
+/// \epsilon = \frac{\|F - E\|_F}{\|E\|_F}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::linalg::relative_matrix_error;
+///
+/// let expected = vec![[3., 0.], [0., 4.]];
+/// let found = vec![[3., 0.5], [0., 4.]];
+/// assert_close!(relative_matrix_error(&expected, &found).unwrap(), 0.1);
+/// ```
+///
+/// # Errors
+///
+/// * if the matrices are empty, their rows have different lengths or
+///   they have different shapes
+/// * if every element of the expected matrix is zero
+pub fn relative_matrix_error<T: Numberish, R: AsRef<[T]>>(expected: &[R], found: &[R]) -> Result<f64, String> {
+    let what = "the relative matrix error";
+    let (e_shape, f_shape) = (shape(expected, what)?, shape(found, what)?);
+    if e_shape != f_shape {
+        return Err(format!(
+            "Calculating {} of matrices of different shapes. expected is {}x{}, found is {}x{}",
+            what, e_shape.0, e_shape.1, f_shape.0, f_shape.1
+        ));
+    }
+    let norm = frobenius_norm(expected);
+    if norm == 0. {
+        return Err(format!("Cannot calculate {} against a matrix of zeros", what));
+    }
+    let diff = compensated_sum(expected.iter().zip(found.iter()).flat_map(|(e, f)| {
+        e.as_ref().iter().zip(f.as_ref().iter()).map(|(e, f)| {
+            let d = (*f).into() - (*e).into();
+            d * d
+        })
+    }));
+    Ok(sqrt(diff) / norm)
+}
+
+/// Calculates the condition number of a square matrix in the 1-norm, which
+/// bounds how much relative errors in the data of a linear system $`Ax = b`$
+/// can be amplified in its solution. Large values (e.g., above $`10^{10}`$)
+/// mean that small discrepancies in a solver's results are to be expected.
+///
+/// The inverse is calculated by Gauss-Jordan elimination with partial pivoting,
+/// so this is meant for the small and moderate matrices that are compared
+/// in validations.
+///
+/// # The math
+/// ```math
+/// \kappa_1(A) = \|A\|_1 \|A^{-1}\|_1
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::linalg::condition_number;
+///
+/// let a = vec![vec![1., 2.], vec![3., 4.]];
+/// assert_close!(condition_number(&a).unwrap(), 21.);
+///
+/// // Singular matrices have no condition number
+/// assert!(condition_number(&[[1., 2.], [2., 4.]]).is_err());
+/// ```
+///
+/// # Errors
+///
+/// * if the matrix is empty, its rows have different lengths or it is not square
+/// * if the matrix is singular
+pub fn condition_number<T: Numberish, R: AsRef<[T]>>(a: &[R]) -> Result<f64, String> {
+    let inverse = inverse(a)?;
+    Ok(one_norm(a)? * one_norm(&inverse)?)
+}
+
+/// Inverts a square matrix by Gauss-Jordan elimination with partial pivoting
+fn inverse<T: Numberish, R: AsRef<[T]>>(a: &[R]) -> Result<Vec<Vec<f64>>, String> {
+    let what = "the condition number";
+    let (rows, cols) = shape(a, what)?;
+    if rows != cols {
+        return Err(format!("Calculating {} of a non-square ({}x{}) matrix", what, rows, cols));
+    }
+    let n = rows;
+    let mut m: Vec<Vec<f64>> = a
+        .iter()
+        .map(|row| row.as_ref().iter().map(|v| (*v).into()).collect())
+        .collect();
+    let mut inv: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1. } else { 0. }).collect())
+        .collect();
+    let scale = inf_norm(&m);
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|i, j| m[*i][col].abs().total_cmp(&m[*j][col].abs()))
+            .unwrap_or(col);
+        let p = m[pivot][col].abs();
+        if p.is_nan() || p <= scale * f64::EPSILON * count_as_f64(n) {
+            return Err(format!("Cannot calculate {} of a singular matrix", what));
+        }
+        m.swap(col, pivot);
+        inv.swap(col, pivot);
+        let p = m[col][col];
+        for j in 0..n {
+            m[col][j] /= p;
+            inv[col][j] /= p;
+        }
+        for i in (0..n).filter(|i| *i != col) {
+            let factor = m[i][col];
+            if factor != 0. {
+                for j in 0..n {
+                    m[i][j] -= factor * m[col][j];
+                    inv[i][j] -= factor * inv[col][j];
+                }
+            }
+        }
+    }
+    Ok(inv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_errors() {
+        let ragged = vec![vec![1., 2.], vec![3.]];
+        assert!(one_norm(&ragged).unwrap_err().contains("row[1].len() = 1"));
+        assert!(one_norm::<f64, Vec<f64>>(&[]).is_err());
+        assert!(condition_number(&[[1., 2., 3.], [4., 5., 6.]]).is_err());
+        assert!(relative_matrix_error(&[[0., 0.]], &[[1., 0.]]).is_err());
+        assert!(relative_matrix_error(&[vec![1., 0.]], &[vec![1.]]).is_err());
+
+        // The identity is perfectly conditioned
+        let identity = vec![[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        assert_close!(condition_number(&identity).unwrap(), 1.);
+    }
+}
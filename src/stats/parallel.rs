@@ -0,0 +1,170 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! These functions split the datasets into chunks that are processed in
+//! parallel (using [rayon](https://docs.rs/rayon)), and add up the results
+//! with compensated (i.e., Kahan-Babuška-Neumaier) summation so that
+//! precision is not lost when adding up hundreds of millions of values.
+//! They return the same results as their sequential counterparts in
+//! [`crate::stats`], up to rounding.
+
+use super::{check_pair, count_as_f64, fit_line, CompensatedSum};
+use crate::numberish::Numberish;
+use rayon::prelude::*;
+
+/// The number of elements processed by each task
+const CHUNK: usize = 1 << 16;
+
+/// Adds up `f(x_i, y_i)` over two datasets, in parallel
+fn sum_pairs<T: Numberish, F>(x: &[T], y: &[T], f: F) -> f64
+where
+    F: Fn(f64, f64) -> f64 + Sync,
+{
+    x.par_chunks(CHUNK)
+        .zip(y.par_chunks(CHUNK))
+        .map(|(x, y)| {
+            let mut s = CompensatedSum::default();
+            for (a, b) in x.iter().zip(y.iter()) {
+                s.add(f((*a).into(), (*b).into()));
+            }
+            s
+        })
+        .reduce(CompensatedSum::default, CompensatedSum::merge)
+        .value()
+}
+
+/// Calculates the Root Mean Squared Error between two datasets, in parallel.
+/// See [`crate::stats::root_mean_squared_error`].
+///
+/// # Example
+/// ```
+/// use validate::stats::parallel::root_mean_squared_error;
+/// use validate::assert_close;
+///
+/// let x = vec![0.; 100_000];
+/// let y = vec![1.; 100_000];
+/// assert_close!(1., root_mean_squared_error(&x, &y));
+/// ```
+///
+/// # Panics
+///
+/// * Panics if the datasets `x` and `y` are of different lengths
+/// * If the datasets are empty
+pub fn root_mean_squared_error<T: Numberish>(x: &[T], y: &[T]) -> f64 {
+    check_pair(x, y, "Root Mean Squared Error").unwrap_or_else(|e| panic!("{}", e));
+    let n = count_as_f64(x.len());
+    sum_pairs(x, y, |a, b| (b - a) * (b - a)) / n
+}
+
+/// Calculates the Mean Bias Error between two datasets, in parallel.
+/// See [`crate::stats::mean_bias_error`].
+///
+/// # Example
+/// ```
+/// use validate::stats::parallel::mean_bias_error;
+/// use validate::assert_close;
+///
+/// let x = vec![0.; 100_000];
+/// let y = vec![1.; 100_000];
+/// assert_close!(1., mean_bias_error(&x, &y));
+/// ```
+///
+/// # Panics
+///
+/// * Panics if the datasets `x` and `y` are of different lengths
+/// * If the datasets are empty
+pub fn mean_bias_error<T: Numberish>(x: &[T], y: &[T]) -> f64 {
+    check_pair(x, y, "Mean Bias Error").unwrap_or_else(|e| panic!("{}", e));
+    let n = count_as_f64(x.len());
+    sum_pairs(x, y, |a, b| b - a) / n
+}
+
+/// Calculates the coefficients $`a`$ and $`b`$ that best fit the model
+/// $`y = a + b\times x`$, and the $`R^2`$ of the fit, in parallel.
+/// See [`crate::stats::linear_coefficients`], which also describes the fit
+/// when all the values of `x` are equal.
+///
+/// # Example
+/// ```
+/// use validate::stats::parallel::linear_coefficients;
+/// use validate::assert_close;
+///
+/// let x: Vec<f64> = (0..100_000).map(|i| i as f64).collect();
+/// let y: Vec<f64> = x.iter().map(|x| 2. + 3. * x).collect();
+/// let (a, b, rsquared) = linear_coefficients(&x, &y);
+/// assert_close!(a, 2., 1e-6);
+/// assert_close!(b, 3.);
+/// assert_close!(rsquared, 1.);
+/// ```
+///
+/// # Panics
+///
+/// * Panics if the datasets `x` and `y` are of different lengths
+/// * If the datasets are empty
+pub fn linear_coefficients<T: Numberish>(x: &[T], y: &[T]) -> (f64, f64, f64) {
+    check_pair(x, y, "linear coefficients").unwrap_or_else(|e| panic!("{}", e));
+    let n = count_as_f64(x.len());
+
+    // Sums of x, x*x, y, y*y and x*y
+    let sums = x
+        .par_chunks(CHUNK)
+        .zip(y.par_chunks(CHUNK))
+        .map(|(x, y)| {
+            let mut s = [CompensatedSum::default(); 5];
+            for (a, b) in x.iter().zip(y.iter()) {
+                let (a, b): (f64, f64) = ((*a).into(), (*b).into());
+                s[0].add(a);
+                s[1].add(a * a);
+                s[2].add(b);
+                s[3].add(b * b);
+                s[4].add(a * b);
+            }
+            s
+        })
+        .reduce(
+            || [CompensatedSum::default(); 5],
+            |mut a, b| {
+                for (a, b) in a.iter_mut().zip(b.iter()) {
+                    *a = a.merge(*b);
+                }
+                a
+            },
+        );
+    fit_line(x, y, n, sums.map(|s| s.value()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_sequential() {
+        let x: Vec<f64> = (0..200_000).map(|i| (i as f64 * 0.37).sin()).collect();
+        let y: Vec<f64> = x.iter().enumerate().map(|(i, x)| 0.5 + 1.2 * x + (i % 7) as f64 * 0.01).collect();
+
+        assert_close!(root_mean_squared_error(&x, &y), crate::stats::root_mean_squared_error(&x, &y), 1e-9);
+        assert_close!(mean_bias_error(&x, &y), crate::stats::mean_bias_error(&x, &y), 1e-9);
+        let (a, b, r2) = linear_coefficients(&x, &y);
+        let (sa, sb, sr2) = crate::stats::linear_coefficients(&x, &y);
+        assert_close!(a, sa, 1e-9);
+        assert_close!(b, sb, 1e-9);
+        assert_close!(r2, sr2, 1e-9);
+    }
+}
@@ -0,0 +1,167 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Tests of whether a metric comparing two paired series could plausibly
+//! arise by chance, estimated by recalculating it on random rearrangements
+//! of the data. They make no assumption about the distribution of the data,
+//! which suits short and noisy experimental datasets.
+
+use super::{check_pair, count_as_f64, Sampler};
+
+/// The result of [`permutation_test`] or [`sign_flip_test`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PermutationTest {
+    /// The value of the metric on the data as given
+    pub observed: f64,
+
+    /// The estimated probability of a value as extreme as the observed
+    /// one arising by chance
+    pub p_value: f64,
+
+    /// The number of random rearrangements that were evaluated
+    pub permutations: usize,
+
+    /// The seed of the random rearrangements
+    pub seed: u64,
+}
+
+/// Checks the inputs of the tests
+fn check<T>(x: &[T], y: &[T], n: usize) -> Result<(), String> {
+    check_pair(x, y, "a permutation test")?;
+    if n == 0 {
+        return Err("A permutation test needs at least one permutation".to_string());
+    }
+    Ok(())
+}
+
+/// Tests whether the pairing of `x` and `y` matters for an error `metric`
+/// (e.g., [`crate::stats::root_mean_squared_error`]) by shuffling `y` `n`
+/// times. The p-value is the (estimated) probability that pairing the values
+/// at random gives an error at least as small—in magnitude—as the observed
+/// one. A large p-value means that `y` follows `x` no better than a shuffled
+/// version of itself would.
+///
+/// # The math
+/// ```math
+/// p = \frac{1 + \#\left\{|m(x, \pi(y))| \le |m(x, y)|\right\}}{1 + n}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::{permutation_test, root_mean_squared_error};
+///
+/// let x: Vec<f64> = (0..20).map(|i| i as f64).collect();
+/// let y: Vec<f64> = x.iter().map(|v| v + if *v as usize % 2 == 0 { 0.5 } else { -0.5 }).collect();
+/// let test = permutation_test(&x, &y, root_mean_squared_error, 999, 42).unwrap();
+/// assert!(test.p_value < 0.01);
+///
+/// // The same seed gives the same result
+/// assert_eq!(test, permutation_test(&x, &y, root_mean_squared_error, 999, 42).unwrap());
+/// ```
+///
+/// # Errors
+///
+/// * if the series have different lengths or are empty
+/// * if `n` is zero
+pub fn permutation_test<T: Copy, F: Fn(&[T], &[T]) -> f64>(
+    x: &[T],
+    y: &[T],
+    metric: F,
+    n: usize,
+    seed: u64,
+) -> Result<PermutationTest, String> {
+    check(x, y, n)?;
+    let observed = metric(x, y);
+    let mut sampler = Sampler::new(seed);
+    let as_good = (0..n)
+        .filter(|_| metric(x, &sampler.permute(y)).abs() <= observed.abs())
+        .count();
+    Ok(PermutationTest {
+        observed,
+        p_value: count_as_f64(1 + as_good) / count_as_f64(1 + n),
+        permutations: n,
+        seed,
+    })
+}
+
+/// Tests whether a signed `metric` (e.g., [`crate::stats::mean_bias_error`])
+/// differs from what noise alone would produce, by swapping the values of
+/// each pair of `x` and `y` at random, `n` times. If `x` and `y` were
+/// interchangeable (i.e., there was no systematic difference), the observed
+/// value would be typical of the swapped ones. The p-value is the (estimated)
+/// probability of a value at least as large—in magnitude—as the observed one.
+///
+/// # The math
+/// ```math
+/// p = \frac{1 + \#\left\{|m(x', y')| \ge |m(x, y)|\right\}}{1 + n}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::{mean_bias_error, sign_flip_test};
+///
+/// // A consistent bias of 1, with some noise
+/// let x = vec![1., 2., 3., 4., 5., 6., 7., 8., 9., 10.];
+/// let y = vec![2.2, 2.9, 4.1, 5.0, 5.8, 7.1, 8.0, 9.2, 9.9, 11.1];
+/// let test = sign_flip_test(&x, &y, mean_bias_error, 999, 7).unwrap();
+/// assert!(test.p_value < 0.01);
+///
+/// // Noise alone
+/// let y = vec![1.2, 1.9, 3.1, 3.8, 5.1, 5.9, 7.2, 7.9, 9.0, 10.1];
+/// let test = sign_flip_test(&x, &y, mean_bias_error, 999, 7).unwrap();
+/// assert!(test.p_value > 0.1);
+/// ```
+///
+/// # Errors
+///
+/// * if the series have different lengths or are empty
+/// * if `n` is zero
+pub fn sign_flip_test<T: Copy, F: Fn(&[T], &[T]) -> f64>(
+    x: &[T],
+    y: &[T],
+    metric: F,
+    n: usize,
+    seed: u64,
+) -> Result<PermutationTest, String> {
+    check(x, y, n)?;
+    let observed = metric(x, y);
+    let mut sampler = Sampler::new(seed);
+    let (mut a, mut b) = (x.to_vec(), y.to_vec());
+    let as_large = (0..n)
+        .filter(|_| {
+            for i in 0..x.len() {
+                if sampler.coin() {
+                    (a[i], b[i]) = (y[i], x[i]);
+                } else {
+                    (a[i], b[i]) = (x[i], y[i]);
+                }
+            }
+            metric(&a, &b).abs() >= observed.abs()
+        })
+        .count();
+    Ok(PermutationTest {
+        observed,
+        p_value: count_as_f64(1 + as_large) / count_as_f64(1 + n),
+        permutations: n,
+        seed,
+    })
+}
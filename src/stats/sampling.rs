@@ -0,0 +1,111 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Random resampling of datasets (using [rand](https://docs.rs/rand)), for
+//! statistics whose distribution is estimated by simulation. The generator is
+//! seeded explicitly and does not depend on the platform, so the same seed
+//! always produces the same samples and a report can be reproduced.
+
+use crate::RunInput;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// A seeded source of random samples of a dataset
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::Sampler;
+///
+/// let x: Vec<f64> = vec![1., 2., 3., 4., 5.];
+/// let mut a = Sampler::new(42);
+/// let mut b = Sampler::new(42);
+/// assert_eq!(a.bootstrap(&x), b.bootstrap(&x));
+///
+/// // A permutation keeps every value once
+/// let mut p = a.permute(&x);
+/// p.sort_by(|a, b| a.total_cmp(b));
+/// assert_eq!(p, x);
+///
+/// assert_eq!(a.input().value, "42");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl Sampler {
+    /// Creates a sampler whose samples are determined by `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// The seed this sampler was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The seed as an input of a validation, so it is recorded
+    /// in the report (see [`crate::RunInput`])
+    pub fn input(&self) -> RunInput {
+        RunInput::new("seed", self.seed)
+    }
+
+    /// Draws `n` indices between `0` and `n - 1` with replacement, as for a
+    /// bootstrap sample. Using the same indices for several datasets keeps
+    /// their values paired.
+    pub fn bootstrap_indices(&mut self, n: usize) -> Vec<usize> {
+        if n == 0 {
+            return Vec::new();
+        }
+        (0..n).map(|_| self.rng.gen_range(0..n)).collect()
+    }
+
+    /// Draws a bootstrap sample of a dataset: as many values as it has,
+    /// with replacement
+    pub fn bootstrap<T: Copy>(&mut self, x: &[T]) -> Vec<T> {
+        self.bootstrap_indices(x.len()).into_iter().map(|i| x[i]).collect()
+    }
+
+    /// Draws a random permutation of the indices between `0` and `n - 1`
+    pub fn permutation(&mut self, n: usize) -> Vec<usize> {
+        let mut ret: Vec<usize> = (0..n).collect();
+        ret.shuffle(&mut self.rng);
+        ret
+    }
+
+    /// Returns the values of a dataset in a random order
+    pub fn permute<T: Copy>(&mut self, x: &[T]) -> Vec<T> {
+        let mut ret = x.to_vec();
+        ret.shuffle(&mut self.rng);
+        ret
+    }
+
+    /// Draws `true` or `false` with the same probability (e.g., to decide
+    /// whether to swap the values of a pair)
+    pub fn coin(&mut self) -> bool {
+        self.rng.gen()
+    }
+}
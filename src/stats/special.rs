@@ -0,0 +1,147 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Special functions behind the distributions used by the statistical tests
+
+/// The natural logarithm of the Gamma function, by the Lanczos approximation
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    const G: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut ser = 1.000_000_000_190_015;
+    for (j, g) in G.iter().enumerate() {
+        ser += g / (x + 1. + j as f64);
+    }
+    -tmp + (2.506_628_274_631_000_5 * ser / x).ln()
+}
+
+/// The continued fraction of the incomplete Beta function
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let (qab, qap, qam) = (a + b, a + 1., a - 1.);
+    let mut c = 1.;
+    let mut d = 1. - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1. / d;
+    let mut h = d;
+    for m in 1..300 {
+        let m = m as f64;
+        let m2 = 2. * m;
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1. + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1. + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1. / d;
+        h *= d * c;
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1. + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1. + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1. / d;
+        let del = d * c;
+        h *= del;
+        if (del - 1.).abs() < 1e-15 {
+            break;
+        }
+    }
+    h
+}
+
+/// The regularized incomplete Beta function $`I_x(a, b)`$
+pub(crate) fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0. {
+        return 0.;
+    }
+    if x >= 1. {
+        return 1.;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1. - x).ln()).exp();
+    if x < (a + 1.) / (a + b + 2.) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1. - front * beta_continued_fraction(b, a, 1. - x) / b
+    }
+}
+
+/// The probability that the absolute value of a Student's t variable with
+/// `df` degrees of freedom exceeds `|t|` (i.e., a two-sided p-value)
+pub(crate) fn student_t_two_sided(t: f64, df: f64) -> f64 {
+    incomplete_beta(df / 2., 0.5, df / (df + t * t))
+}
+
+/// The complementary error function, with a relative error below 1.2e-7
+pub(crate) fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1. / (1. + 0.5 * z);
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98 + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let ret = t * poly.exp();
+    if x >= 0. {
+        ret
+    } else {
+        2. - ret
+    }
+}
+
+/// The probability that the absolute value of a standard normal
+/// variable exceeds `|z|` (i.e., a two-sided p-value)
+pub(crate) fn normal_two_sided(z: f64) -> f64 {
+    erfc(z.abs() / std::f64::consts::SQRT_2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_special_functions() {
+        // Gamma(5) = 24
+        assert!((ln_gamma(5.) - 24f64.ln()).abs() < 1e-10);
+        assert!((incomplete_beta(2., 3., 0.4) - 0.5248).abs() < 1e-10);
+        assert!((erfc(0.5) - 0.479_500_122).abs() < 1e-7);
+        assert!((normal_two_sided(1.959_964) - 0.05).abs() < 1e-6);
+        // Critical value of a t distribution with 10 degrees of freedom
+        assert!((student_t_two_sided(2.228_139, 10.) - 0.05).abs() < 1e-6);
+    }
+}
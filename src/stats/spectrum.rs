@@ -0,0 +1,202 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Frequency-domain analysis of series (using [rustfft](https://docs.rs/rustfft)),
+//! for comparing how much of their variation happens at each frequency.
+
+use super::count_as_f64;
+use crate::numberish::Numberish;
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+/// The number of samples in each segment of [`psd_welch`], unless
+/// the series is shorter
+const WELCH_SEGMENT: usize = 256;
+
+/// A window applied to a segment of a series before transforming it, to
+/// reduce the leakage of each frequency into its neighbours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Window {
+    /// No window at all
+    Rectangular,
+
+    /// The Hann (raised cosine) window, which goes down to zero at the ends
+    #[default]
+    Hann,
+
+    /// The Hamming window, which does not quite reach zero at the ends
+    Hamming,
+}
+
+impl Window {
+    /// The coefficients of a (periodic) window of `n` samples
+    pub fn coefficients(&self, n: usize) -> Vec<f64> {
+        let len = count_as_f64(n);
+        (0..n)
+            .map(|i| {
+                let c = (2. * std::f64::consts::PI * count_as_f64(i) / len).cos();
+                match self {
+                    Window::Rectangular => 1.,
+                    Window::Hann => 0.5 - 0.5 * c,
+                    Window::Hamming => 0.54 - 0.46 * c,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Transforms a (complex) buffer in place
+fn fft(buffer: &mut [Complex<f64>]) {
+    FftPlanner::new().plan_fft_forward(buffer.len()).process(buffer);
+}
+
+/// Calculates the magnitudes of the discrete Fourier transform of a real
+/// series, for frequencies $`k/n`$ times the sampling frequency, with $`k`$
+/// from $`0`$ to $`n/2`$ (the higher ones mirror these). They are not
+/// normalized, so a cosine of amplitude $`A`$ spanning a whole number of
+/// periods shows up as $`A n/2`$.
+///
+/// # Example
+///
+/// ```
+/// use validate::assert_close;
+/// use validate::stats::fft_magnitude;
+///
+/// // Two periods over 8 samples
+/// let x: Vec<f64> = (0..8).map(|i| (std::f64::consts::PI * i as f64 / 2.).cos()).collect();
+/// let m = fft_magnitude(&x);
+/// assert_eq!(m.len(), 5);
+/// assert_close!(m[2], 4.);
+/// assert!(m[0].abs() < 1e-9 && m[1].abs() < 1e-9);
+/// ```
+pub fn fft_magnitude<T: Numberish>(x: &[T]) -> Vec<f64> {
+    if x.is_empty() {
+        return Vec::new();
+    }
+    let mut buffer: Vec<Complex<f64>> = x.iter().map(|v| Complex::new((*v).into(), 0.)).collect();
+    fft(&mut buffer);
+    buffer.iter().take(x.len() / 2 + 1).map(|c| c.norm()).collect()
+}
+
+/// Estimates the one-sided power spectral density of a series sampled at
+/// `fs` by Welch's method: the series is split into segments of 256 samples
+/// (or fewer, for shorter series) overlapping by half, whose mean is removed
+/// before applying the `window` and transforming them. Their periodograms
+/// are then averaged, which reduces the noise of the estimate.
+///
+/// Returns the frequencies (in the units of `fs`) and the density at each
+/// of them (in the squared units of `x` per unit of `fs`), so that adding
+/// up the density times the frequency step approximates the variance of `x`.
+///
+/// # Example
+///
+/// ```
+/// use validate::stats::{psd_welch, Window};
+///
+/// // A 10 Hz wave sampled at 100 Hz
+/// let fs = 100.;
+/// let x: Vec<f64> = (0..2048).map(|i| (2. * std::f64::consts::PI * 10. * i as f64 / fs).sin()).collect();
+/// let (frequencies, density) = psd_welch(&x, fs, Window::Hann).unwrap();
+///
+/// let peak = (0..density.len()).max_by(|a, b| density[*a].total_cmp(&density[*b])).unwrap();
+/// assert!((frequencies[peak] - 10.).abs() < 0.5);
+///
+/// // The variance of a sine wave of unit amplitude is 0.5
+/// let df = frequencies[1] - frequencies[0];
+/// let variance: f64 = density.iter().sum::<f64>() * df;
+/// assert!((variance - 0.5).abs() < 0.01);
+/// ```
+///
+/// # Errors
+///
+/// * if the series has fewer than two samples
+/// * if the sampling frequency is not positive
+pub fn psd_welch<T: Numberish>(x: &[T], fs: f64, window: Window) -> Result<(Vec<f64>, Vec<f64>), String> {
+    if x.len() < 2 {
+        return Err(format!(
+            "Estimating a power spectral density requires at least 2 samples, but there are {}",
+            x.len()
+        ));
+    }
+    if !(fs.is_finite() && fs > 0.) {
+        return Err(format!("Invalid sampling frequency {}: it must be positive", fs));
+    }
+    let n = WELCH_SEGMENT.min(x.len());
+    let step = (n / 2).max(1);
+    let w = window.coefficients(n);
+    let scale = fs * w.iter().map(|w| w * w).sum::<f64>();
+    let bins = n / 2 + 1;
+    let plan = FftPlanner::new().plan_fft_forward(n);
+
+    let values: Vec<f64> = x.iter().map(|v| (*v).into()).collect();
+    let mut density = vec![0.; bins];
+    let mut segments = 0;
+    for start in (0..=values.len() - n).step_by(step) {
+        let segment = &values[start..start + n];
+        let mean = segment.iter().sum::<f64>() / count_as_f64(n);
+        let mut buffer: Vec<Complex<f64>> = segment
+            .iter()
+            .zip(w.iter())
+            .map(|(v, w)| Complex::new((v - mean) * w, 0.))
+            .collect();
+        plan.process(&mut buffer);
+        for (d, c) in density.iter_mut().zip(buffer.iter()) {
+            *d += c.norm_sqr() / scale;
+        }
+        segments += 1;
+    }
+    let segments = count_as_f64(segments);
+    for (k, d) in density.iter_mut().enumerate() {
+        *d /= segments;
+        // Fold the negative frequencies onto the positive ones, except for
+        // the zero frequency and (for even segments) the Nyquist one
+        if k != 0 && !(n.is_multiple_of(2) && k == n / 2) {
+            *d *= 2.;
+        }
+    }
+    let frequencies = (0..bins).map(|k| count_as_f64(k) * fs / count_as_f64(n)).collect();
+    Ok((frequencies, density))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_and_errors() {
+        let hann = Window::Hann.coefficients(4);
+        assert_close!(hann[0], 0.);
+        assert_close!(hann[2], 1.);
+        assert_close!(Window::Hamming.coefficients(4)[0], 0.08);
+        assert!(fft_magnitude::<f64>(&[]).is_empty());
+        assert!(psd_welch(&[1.], 1., Window::Hann).is_err());
+        assert!(psd_welch(&[1., 2.], 0., Window::Hann).is_err());
+
+        // Without a window, the density adds up to the variance
+        let x: Vec<f64> = (0..4096).map(|i| if (i * 7919) % 13 < 6 { 1. } else { -1. }).collect();
+        let (f, d) = psd_welch(&x, 10., Window::Rectangular).unwrap();
+        assert_eq!(f.len(), 129);
+        assert_close!(f[128], 5.);
+        let mean = crate::stats::mean(&x);
+        let expected = x.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / x.len() as f64;
+        let variance: f64 = d.iter().sum::<f64>() * (f[1] - f[0]);
+        assert!((variance - expected).abs() < 0.05 * expected);
+    }
+}
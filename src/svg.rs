@@ -0,0 +1,381 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+#[cfg(feature = "charts")]
+use crate::escape_html;
+
+/// The style of the charts in the report
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::{ChartTheme, Validator};
+///
+/// let mut validator = Validator::new("Styled report", "report.html");
+/// validator.set_chart_theme(ChartTheme {
+///     font_family: "Georgia,serif".into(),
+///     line_width: 1.,
+///     ..ChartTheme::default()
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartTheme {
+    /// The font family (as in CSS) of the texts in the chart
+    pub font_family: String,
+
+    /// The size of the texts (e.g., ticks), in pixels
+    pub font_size: f64,
+
+    /// The size of the title and the axis labels, in pixels
+    pub title_font_size: f64,
+
+    /// The size of the legends, in pixels
+    pub legend_font_size: f64,
+
+    /// The width of the lines in line plots
+    pub line_width: f64,
+
+    /// The size of the markers in scatter plots
+    pub marker_size: f64,
+
+    /// The color of the background of the charts
+    pub background: String,
+
+    /// The colors assigned to each series, in order. Only
+    /// the first eight are used.
+    pub colors: Vec<String>,
+}
+
+impl std::default::Default for ChartTheme {
+    fn default() -> Self {
+        Self {
+            font_family: "Roboto,sans-serif".into(),
+            font_size: 16.,
+            title_font_size: 24.,
+            legend_font_size: 20.,
+            line_width: 2.,
+            marker_size: 7.,
+            background: "AliceBlue".into(),
+            colors: ["blue", "red", "green", "gold", "aqua", "lime", "orange", "chocolate"]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl ChartTheme {
+    /// Writes the CSS that styles the charts
+    #[cfg(feature = "charts")]
+    fn css(&self) -> String {
+        let mut ret = format!(
+            ".poloto{{stroke-linecap:round;stroke-linejoin:round;font-family:{font};font-size:{size}px;}}\
+            .poloto_background{{fill:{background};}}\
+            .poloto_scatter{{stroke-width:{marker}}}\
+            .poloto_tick_line{{stroke:gray;stroke-width:0.5}}\
+            .poloto_line{{stroke-width:{line}}}\
+            .poloto_text{{fill: black;}}\
+            .poloto_axis_lines{{stroke: black;stroke-width:3;fill:none;stroke-dasharray:none}}\
+            .poloto_title{{font-size:{title}px;dominant-baseline:start;text-anchor:middle;}}\
+            .poloto_xname{{font-size:{title}px;dominant-baseline:start;text-anchor:middle;}}\
+            .poloto_yname{{font-size:{title}px;dominant-baseline:start;text-anchor:middle;}}\
+            .poloto_legend_text{{font-size:{legend}px;dominant-baseline:middle;text-anchor:start;}}",
+            font = self.font_family,
+            size = self.font_size,
+            background = self.background,
+            marker = self.marker_size,
+            line = self.line_width,
+            title = self.title_font_size,
+            legend = self.legend_font_size,
+        );
+        for (i, color) in self.colors.iter().take(8).enumerate() {
+            ret.push_str(&format!(".poloto{}stroke{{stroke:{};}}", i, color));
+        }
+        for (i, color) in self.colors.iter().take(8).enumerate() {
+            ret.push_str(&format!(".poloto{}fill{{fill:{};}}", i, color));
+        }
+        ret
+    }
+
+    /// What is written in place of each chart when the crate is built
+    /// without the `charts` feature
+    #[cfg(not(feature = "charts"))]
+    pub(crate) fn omitted(&self) -> String {
+        "\n\n*Charts are not drawn without the `charts` feature*\n\n".to_string()
+    }
+
+    /// Applies this theme to a chart drawn with poloto's simple theme,
+    /// and wraps it so that it is written verbatim into the report (i.e., so
+    /// that its texts are not interpreted as Markdown).
+    ///
+    /// The `title` and `description` are the alternative text of the chart,
+    /// read by screen readers instead of the drawing (see [`describe_series`]).
+    #[cfg(feature = "charts")]
+    pub(crate) fn render(&self, svg: String, title: &str, description: &str) -> String {
+        let svg = if *self == Self::default() {
+            svg
+        } else {
+            svg.replacen(
+                poloto::simple_theme::STYLE_CONFIG_LIGHT_DEFAULT,
+                &self.css(),
+                1,
+            )
+        };
+        let svg = with_alt_text(&svg, title, description);
+        format!("\n\n{}chart\">{}</div>\n\n", crate::report::own_html(), svg)
+    }
+}
+
+/// Marks an SVG as an image and adds `<title>` and `<desc>` elements
+/// to it, so it is announced by screen readers
+#[cfg(feature = "charts")]
+fn with_alt_text(svg: &str, title: &str, description: &str) -> String {
+    let end = match svg.find('>') {
+        Some(end) if svg.starts_with("<svg") => end,
+        _ => return svg.to_string(),
+    };
+    let title = if title.trim().is_empty() { "Chart" } else { title };
+    format!(
+        "{} role=\"img\"><title>{}</title><desc>{}</desc>{}",
+        &svg[..end],
+        escape_html(title),
+        escape_html(description),
+        &svg[end + 1..]
+    )
+}
+
+/// Describes a series drawn in a chart, for its alternative text
+/// (e.g., `Expected: 24 values from 1.2 to 5.3`)
+#[cfg(feature = "charts")]
+pub(crate) fn describe_series<I: IntoIterator<Item = f64>>(name: &str, values: I) -> String {
+    let (mut n, mut min, mut max) = (0, f64::INFINITY, f64::NEG_INFINITY);
+    for v in values.into_iter().filter(|v| v.is_finite()) {
+        n += 1;
+        min = min.min(v);
+        max = max.max(v);
+    }
+    match n {
+        0 => format!("{}: no values", name),
+        1 => format!("{}: 1 value, {:.4}", name, min),
+        _ => format!("{}: {} values from {:.4} to {:.4}", name, n, min, max),
+    }
+}
+
+/// Rounds a number in an SVG to one decimal (i.e., a tenth of a
+/// pixel), which is more than enough for the charts in a report.
+/// Tokens that are not numbers are returned unchanged.
+fn round_token(t: &str) -> String {
+    match t.parse::<f64>() {
+        Ok(v) => {
+            let r = format!("{:.1}", v);
+            match r.strip_suffix(".0") {
+                Some("-0") => "0".to_string(),
+                Some(r) => r.to_string(),
+                None => r,
+            }
+        }
+        Err(_) => t.to_string(),
+    }
+}
+
+/// Simplifies the `d` attribute of an SVG path by rounding its
+/// coordinates and removing the points that, once rounded,
+/// are equal to the previous one.
+fn simplify_path(d: &str) -> String {
+    let tokens: Vec<&str> = d.split_whitespace().collect();
+    let mut ret: Vec<String> = Vec::with_capacity(tokens.len());
+    let mut last: Option<(String, String)> = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let t = tokens[i];
+        let is_point = (t == "M" || t == "L")
+            && i + 2 < tokens.len()
+            && tokens[i + 1].parse::<f64>().is_ok()
+            && tokens[i + 2].parse::<f64>().is_ok();
+        if is_point {
+            let point = (round_token(tokens[i + 1]), round_token(tokens[i + 2]));
+            if t == "M" || last.as_ref() != Some(&point) {
+                ret.push(t.to_string());
+                ret.push(point.0.clone());
+                ret.push(point.1.clone());
+                last = Some(point);
+            }
+            i += 3;
+        } else {
+            ret.push(round_token(t));
+            i += 1;
+        }
+    }
+    ret.join(" ")
+}
+
+/// Optimizes a single SVG chart. Style blocks that have already been
+/// found in a previous chart are removed—they apply to the whole
+/// document anyway—and paths are simplified.
+fn optimize_svg<'b>(svg: &'b str, styles: &mut Vec<&'b str>) -> String {
+    let mut ret = String::with_capacity(svg.len());
+    let mut rest = svg;
+
+    // Remove repeated styles
+    if let Some(start) = rest.find("<style>") {
+        if let Some(len) = rest[start..].find("</style>") {
+            let end = start + len + "</style>".len();
+            let style = &rest[start..end];
+            ret.push_str(&rest[..start]);
+            if !styles.contains(&style) {
+                ret.push_str(style);
+                styles.push(style);
+            }
+            rest = &rest[end..];
+        }
+    }
+
+    // Simplify paths
+    while let Some(start) = rest.find(" d=\"") {
+        let start = start + " d=\"".len();
+        let end = match rest[start..].find('"') {
+            Some(len) => start + len,
+            None => break,
+        };
+        ret.push_str(&rest[..start]);
+        ret.push_str(&simplify_path(&rest[start..end]));
+        rest = &rest[end..];
+    }
+    ret.push_str(rest);
+    ret
+}
+
+/// Replaces the `id` attributes of the charts—and the references to them—by
+/// sequential ones (`chart-id-1`, `chart-id-2`...), so that they do not
+/// change between runs. Other IDs (e.g., the anchors of the sections) are kept.
+pub(crate) fn stable_ids(txt: &str) -> String {
+    let mut ids: Vec<String> = Vec::new();
+    let mut charts = txt;
+    while let Some(start) = charts.find("<svg") {
+        let end = match charts[start..].find("</svg>") {
+            Some(len) => start + len,
+            None => break,
+        };
+        let mut rest = &charts[start..end];
+        while let Some(id_start) = rest.find(" id=\"") {
+            let value = &rest[id_start + 5..];
+            match value.find('"') {
+                Some(id_end) => {
+                    let id = value[..id_end].to_string();
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                    rest = &value[id_end..];
+                }
+                None => break,
+            }
+        }
+        charts = &charts[end..];
+    }
+    let mut ret = txt.to_string();
+    // Longer IDs first, so IDs that contain others are replaced whole
+    let mut order: Vec<usize> = (0..ids.len()).collect();
+    order.sort_by_key(|i| std::cmp::Reverse(ids[*i].len()));
+    for i in order {
+        let id = &ids[i];
+        let new = format!("chart-id-{}", i + 1);
+        for (from, to) in [("id=\"{}\"", "id=\"{}\""), ("#{}\"", "#{}\""), ("#{})", "#{})")] {
+            ret = ret.replace(&from.replace("{}", id), &to.replace("{}", &new));
+        }
+    }
+    ret
+}
+
+/// Reduces the size of the SVG charts embedded in an HTML document
+/// without visibly changing them.
+pub(crate) fn optimize_svgs(html: &str) -> String {
+    let mut ret = String::with_capacity(html.len());
+    let mut styles = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<svg") {
+        let end = match rest[start..].find("</svg>") {
+            Some(len) => start + len + "</svg>".len(),
+            None => break,
+        };
+        ret.push_str(&rest[..start]);
+        ret.push_str(&optimize_svg(&rest[start..end], &mut styles));
+        rest = &rest[end..];
+    }
+    ret.push_str(rest);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_default_theme_css() {
+        // The default theme should reproduce poloto's
+        assert_eq!(
+            ChartTheme::default().css(),
+            poloto::simple_theme::STYLE_CONFIG_LIGHT_DEFAULT
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_alt_text() {
+        let svg = "<svg class=\"poloto\" width=\"800\"><g></g></svg>";
+        let found = with_alt_text(svg, "Temperature", "Expected: 2 values from 1 to <2");
+        assert_eq!(
+            found,
+            "<svg class=\"poloto\" width=\"800\" role=\"img\"><title>Temperature</title><desc>Expected: 2 values from 1 to &lt;2</desc><g></g></svg>"
+        );
+        assert!(with_alt_text(svg, " ", "").contains("<title>Chart</title>"));
+        assert_eq!(with_alt_text("<div>", "a", "b"), "<div>");
+
+        assert_eq!(describe_series("Found", [1., f64::NAN, 3.]), "Found: 2 values from 1.0000 to 3.0000");
+        assert_eq!(describe_series("Found", []), "Found: no values");
+    }
+
+    #[test]
+    fn test_round_token() {
+        assert_eq!(round_token("150.00"), "150");
+        assert_eq!(round_token("316.67"), "316.7");
+        assert_eq!(round_token("-0.01"), "0");
+        assert_eq!(round_token("L"), "L");
+    }
+
+    #[test]
+    fn test_simplify_path() {
+        assert_eq!(
+            simplify_path(" M 150.00 340.00 L 150.01 340.02 L 316.67 280.00 L 316.66 280.00"),
+            "M 150 340 L 316.7 280"
+        );
+    }
+
+    #[test]
+    fn test_optimize_svgs() {
+        let chart = "<svg><style>.a{}</style><path d=\" M 1.00 2.00 L 1.00 2.00\" /></svg>";
+        let html = format!("<p>{}</p><p>{}</p>", chart, chart);
+        assert_eq!(
+            optimize_svgs(&html),
+            "<p><svg><style>.a{}</style><path d=\"M 1 2\" /></svg></p><p><svg><path d=\"M 1 2\" /></svg></p>"
+        );
+    }
+}
@@ -0,0 +1,297 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+
+use crate::svg::stable_ids;
+use crate::{ReportContext, Validate, ValidationResult};
+use std::path::Path;
+use std::time::Duration;
+
+/// The environment variable that, if set, makes [`assert_report_matches`]
+/// write the golden files instead of comparing against them
+pub const BLESS_VAR: &str = "VALIDATE_BLESS";
+
+/// The number of unchanged lines shown around each difference
+const DIFF_CONTEXT: usize = 2;
+
+/// Beyond this many pairs of lines, the differences are not aligned and
+/// only the first differing line is shown
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Renders the section that a validation would write into a report—its
+/// title, description, results and (if it failed) its error—with the default
+/// settings and stable chart IDs, so that the same validation always renders
+/// the same text.
+///
+/// # Example
+///
+/// ```
+/// use validate::testing::render;
+/// use validate::SeriesValidator;
+///
+/// let v = SeriesValidator {
+///     expected: vec![1., 2.],
+///     found: vec![1., 2.],
+///     ..Default::default()
+/// };
+/// assert!(render(&v).contains("Mean Bias Error: 0.0000"));
+/// assert_eq!(render(&v), render(&v));
+/// ```
+pub fn render<V: Validate + ?Sized>(v: &V) -> String {
+    let ctx = ReportContext::default();
+    let (txt, error) = match v.validate_in(&ctx) {
+        ValidationResult::Ok(txt) => (txt, None),
+        ValidationResult::Err(txt, e) => (txt, Some(e)),
+    };
+    let mut ret = String::new();
+    if let Some(title) = v.title() {
+        ret.push_str(&format!("## {}\n\n", title));
+    }
+    if let Some(description) = v.description() {
+        ret.push_str(&format!("{}\n\n", description));
+    }
+    ret.push_str(&txt);
+    if let Some(error) = error {
+        ret.push_str(&format!("\n\n**Error**: {}\n", error.trim()));
+    }
+    stable_ids(&ret.replace("\r\n", "\n"))
+}
+
+/// Writes the differences between two texts, line by line, as in a unified
+/// diff: lines only in `expected` start with `-`, and lines only in `found`
+/// with `+`. Long lines (e.g., charts) are truncated.
+fn diff(expected: &str, found: &str) -> String {
+    let a: Vec<&str> = expected.lines().collect();
+    let b: Vec<&str> = found.lines().collect();
+    let show = |line: &str| -> String {
+        const MAX: usize = 160;
+        match line.char_indices().nth(MAX) {
+            Some((i, _)) => format!("{}...", &line[..i]),
+            None => line.to_string(),
+        }
+    };
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELLS {
+        let i = a.iter().zip(b.iter()).position(|(x, y)| x != y).unwrap_or(a.len().min(b.len()));
+        return format!(
+            "first difference at line {}:\n-{}\n+{}\n",
+            i + 1,
+            a.get(i).map_or(String::new(), |l| show(l)),
+            b.get(i).map_or(String::new(), |l| show(l))
+        );
+    }
+
+    // Longest common subsequence of lines, from the end
+    let (n, m) = (a.len(), b.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    // Each line of the diff, marked with ' ', '-' or '+'
+    let mut lines: Vec<(char, usize, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            lines.push((' ', i, a[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', i, a[i]));
+            i += 1;
+        } else {
+            lines.push(('+', j, b[j]));
+            j += 1;
+        }
+    }
+    let changed: Vec<usize> = (0..lines.len()).filter(|k| lines[*k].0 != ' ').collect();
+    let mut ret = String::new();
+    let mut last_shown: Option<usize> = None;
+    for (k, (mark, line_number, line)) in lines.iter().enumerate() {
+        let near = changed
+            .iter()
+            .any(|c| k + DIFF_CONTEXT >= *c && k <= c + DIFF_CONTEXT);
+        if !near {
+            continue;
+        }
+        if last_shown.is_none_or(|l| l + 1 != k) {
+            ret.push_str(&format!("@@ line {} @@\n", line_number + 1));
+        }
+        ret.push_str(&format!("{}{}\n", mark, show(line)));
+        last_shown = Some(k);
+    }
+    ret
+}
+
+/// Checks that a validation renders (see [`render`]) exactly as recorded in a
+/// golden file, panicking with the differences otherwise. This lets crates that
+/// write their own validators test that their reports do not change by accident.
+///
+/// If the `VALIDATE_BLESS` environment variable is set, the golden file is
+/// written instead (creating its directory if needed), so it can be reviewed
+/// and committed.
+///
+/// # Example
+///
+/// ```no_run
+/// use validate::testing::assert_report_matches;
+/// use validate::SeriesValidator;
+///
+/// let v = SeriesValidator {
+///     expected: vec![1., 2., 3.],
+///     found: vec![1.1, 2., 3.],
+///     ..Default::default()
+/// };
+/// // Run with VALIDATE_BLESS=1 to write it the first time
+/// assert_report_matches(&v, "tests/golden/series.md");
+/// ```
+///
+/// # Panics
+///
+/// * if the rendered report differs from the golden file
+/// * if the golden file cannot be read (or written)
+pub fn assert_report_matches<V: Validate + ?Sized, P: AsRef<Path>>(v: &V, golden: P) {
+    let golden = golden.as_ref();
+    let found = render(v);
+    if std::env::var_os(BLESS_VAR).is_some() {
+        if let Some(dir) = golden.parent().filter(|d| !d.as_os_str().is_empty()) {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                panic!("Cannot create directory '{}': {}", dir.display(), e);
+            }
+        }
+        if let Err(e) = std::fs::write(golden, &found) {
+            panic!("Cannot write golden file '{}': {}", golden.display(), e);
+        }
+        return;
+    }
+    let expected = match std::fs::read_to_string(golden) {
+        Ok(txt) => txt.replace("\r\n", "\n"),
+        Err(e) => panic!(
+            "Cannot read golden file '{}' ({}). Set {}=1 to write it.",
+            golden.display(),
+            e,
+            BLESS_VAR
+        ),
+    };
+    if expected != found {
+        panic!(
+            "The report does not match the golden file '{}' (set {}=1 to update it):\n{}",
+            golden.display(),
+            BLESS_VAR,
+            diff(&expected, &found)
+        );
+    }
+}
+
+/// A validation that always passes, for testing how a suite is run
+/// (e.g., its filters or summaries) without real data
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::testing::{AlwaysFail, AlwaysPass, FailsWith, Sleeps};
+/// use validate::Validator;
+///
+/// let mut validator = Validator::new("Mock suite", "report.html");
+/// validator.push(AlwaysPass);
+/// validator.push(Sleeps(std::time::Duration::from_millis(1)));
+/// assert!(validator.validate().is_ok());
+///
+/// validator.push(AlwaysFail);
+/// validator.push(FailsWith("The rig is offline".to_string()));
+/// assert!(validator.validate().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlwaysPass;
+
+impl Validate for AlwaysPass {
+    fn validate(&self) -> ValidationResult {
+        ValidationResult::Ok("This validation always passes".to_string())
+    }
+}
+
+/// A validation that always fails (see [`AlwaysPass`])
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlwaysFail;
+
+impl Validate for AlwaysFail {
+    fn validate(&self) -> ValidationResult {
+        let e = "This validation always fails".to_string();
+        ValidationResult::Err(e.clone(), e)
+    }
+}
+
+/// A validation that fails with the given error message (see [`AlwaysPass`])
+#[derive(Debug, Clone, Default)]
+pub struct FailsWith(pub String);
+
+impl Validate for FailsWith {
+    fn validate(&self) -> ValidationResult {
+        ValidationResult::Err(self.0.clone(), self.0.clone())
+    }
+}
+
+/// A validation that takes the given time and then passes, for testing
+/// time budgets and timeouts (see [`AlwaysPass`])
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sleeps(pub Duration);
+
+impl Validate for Sleeps {
+    fn validate(&self) -> ValidationResult {
+        std::thread::sleep(self.0);
+        ValidationResult::Ok(format!("This validation took {} ms", self.0.as_millis()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_ids_and_diff() {
+        let svg = "<svg id=\"a1b2\"><use href=\"#a1b2\"/><g id=\"x\" fill=\"url(#x)\"/></svg>";
+        assert_eq!(
+            stable_ids(svg),
+            "<svg id=\"chart-id-1\"><use href=\"#chart-id-1\"/><g id=\"chart-id-2\" fill=\"url(#chart-id-2)\"/></svg>"
+        );
+
+        let d = diff("a\nb\nc\nd\ne\nf\ng\nh", "a\nb\nc\nD\ne\nf\ng\nh");
+        assert_eq!(d, "@@ line 2 @@\n b\n c\n-d\n+D\n e\n f\n");
+        assert_eq!(diff("same", "same"), "");
+    }
+
+    #[test]
+    fn test_mocks() {
+        assert!(AlwaysPass.validate().is_ok());
+        assert!(AlwaysFail.validate().is_err());
+        match FailsWith("Broken".to_string()).validate() {
+            ValidationResult::Err(_, e) => assert_eq!(e, "Broken"),
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+        }
+        let start = std::time::Instant::now();
+        assert!(Sleeps(Duration::from_millis(20)).validate().is_ok());
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,257 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/// Default thresholds for the validations in a report.
+///
+/// These are inherited by every validation that does not set the
+/// corresponding threshold itself, so the policy of a whole suite can
+/// be changed in one place. Thresholds on errors are given as a fraction
+/// of the range (i.e., max - min) of the expected values, so they can
+/// be shared by validations of different magnitudes.
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::{Thresholds, Validator};
+///
+/// let mut validator = Validator::new("Suite", "report.html");
+/// validator.set_default_thresholds(Thresholds {
+///     r2: Some(0.9),
+///     root_mean_squared_error_fraction: Some(0.05),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Thresholds {
+    /// The minimum allowed R2 of a regression
+    pub r2: Option<f64>,
+
+    /// The maximum allowed difference between the found and the
+    /// expected intersect of a regression
+    pub intersect_delta: Option<f64>,
+
+    /// The maximum allowed difference between the found and the
+    /// expected slope of a regression
+    pub slope_delta: Option<f64>,
+
+    /// The maximum allowed Mean Bias Error, as a fraction of the range of
+    /// the expected values
+    pub mean_bias_error_fraction: Option<f64>,
+
+    /// The maximum allowed Root Mean Squared Error, as a fraction of the
+    /// range of the expected values
+    pub root_mean_squared_error_fraction: Option<f64>,
+
+    /// The maximum allowed absolute difference between any pair of values,
+    /// as a fraction of the range of the expected values
+    pub max_error_fraction: Option<f64>,
+}
+
+impl Thresholds {
+    /// Scales a threshold given as a fraction by the range of `values`
+    pub(crate) fn of_range<T: crate::numberish::Numberish>(
+        fraction: Option<f64>,
+        values: &[T],
+    ) -> Option<f64> {
+        let fraction = fraction?;
+        if values.is_empty() {
+            return None;
+        }
+        let (min, max) = crate::stats::min_max(values);
+        Some(fraction * (max.into() - min.into()))
+    }
+
+    /// Combines two sets of thresholds keeping the loosest of each (i.e.,
+    /// the lowest minimum and the highest maximums), so every validation
+    /// that meets either of them meets the result
+    pub fn loosest(self, other: Self) -> Self {
+        fn pick(a: Option<f64>, b: Option<f64>, f: fn(f64, f64) -> f64) -> Option<f64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(f(a, b)),
+                (a, b) => a.or(b),
+            }
+        }
+        Self {
+            r2: pick(self.r2, other.r2, f64::min),
+            intersect_delta: pick(self.intersect_delta, other.intersect_delta, f64::max),
+            slope_delta: pick(self.slope_delta, other.slope_delta, f64::max),
+            mean_bias_error_fraction: pick(
+                self.mean_bias_error_fraction,
+                other.mean_bias_error_fraction,
+                f64::max,
+            ),
+            root_mean_squared_error_fraction: pick(
+                self.root_mean_squared_error_fraction,
+                other.root_mean_squared_error_fraction,
+                f64::max,
+            ),
+            max_error_fraction: pick(self.max_error_fraction, other.max_error_fraction, f64::max),
+        }
+    }
+
+    /// Writes these thresholds as a table named `profile`, in the format
+    /// read by `ThresholdProfiles` (which needs the `toml` feature)
+    pub fn to_toml(&self, profile: &str) -> String {
+        let mut ret = format!("[{}]\n", profile);
+        let fields = [
+            ("r2", self.r2),
+            ("intersect_delta", self.intersect_delta),
+            ("slope_delta", self.slope_delta),
+            ("mean_bias_error_fraction", self.mean_bias_error_fraction),
+            ("root_mean_squared_error_fraction", self.root_mean_squared_error_fraction),
+            ("max_error_fraction", self.max_error_fraction),
+        ];
+        for (name, value) in fields {
+            if let Some(v) = value.filter(|v| v.is_finite()) {
+                // Debug keeps the decimal point of integral values
+                ret += &format!("{} = {:?}\n", name, v);
+            }
+        }
+        ret
+    }
+
+    /// Rounds a suggested threshold to three significant digits towards the
+    /// loose side—`up` for maximums and down for minimums—so suggestions
+    /// read well in a TOML file
+    pub(crate) fn round_loosely(value: f64, up: bool) -> Option<f64> {
+        if !value.is_finite() {
+            return None;
+        }
+        if value == 0.0 {
+            return Some(0.0);
+        }
+        // Dividing by an exact power of ten gives the closest value to the
+        // rounded decimal, which is then printed without noise
+        let digits = 2 - value.abs().log10().floor() as i32;
+        let scaled = if digits >= 0 {
+            value * 10f64.powi(digits)
+        } else {
+            value / 10f64.powi(-digits)
+        };
+        // Tolerate the noise of the arithmetic that produced the value
+        let rounded = if up { (scaled - 1e-9).ceil() } else { (scaled + 1e-9).floor() };
+        Some(if digits >= 0 {
+            rounded / 10f64.powi(digits)
+        } else {
+            rounded * 10f64.powi(-digits)
+        })
+    }
+}
+
+/// A set of named [`Thresholds`] (e.g., "strict", "publication", "smoke")
+/// loaded from a TOML file in which each table is a profile:
+///
+/// ```toml
+/// [strict]
+/// r2 = 0.95
+/// root_mean_squared_error_fraction = 0.02
+///
+/// [smoke]
+/// r2 = 0.5
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # std::env::set_current_dir(std::env::temp_dir()).unwrap();
+/// use validate::{ThresholdProfiles, Validator};
+///
+/// let profiles = ThresholdProfiles::parse("[strict]\nr2 = 0.95\n\n[smoke]\nr2 = 0.5").unwrap();
+/// assert_eq!(profiles.get("smoke").unwrap().r2, Some(0.5));
+///
+/// let mut validator = Validator::new("Suite", "report.html");
+/// validator.set_threshold_profile(&profiles, "strict").unwrap();
+/// ```
+#[cfg(feature = "toml")]
+#[derive(Debug, Clone, Default)]
+pub struct ThresholdProfiles {
+    profiles: std::collections::HashMap<String, Thresholds>,
+}
+
+#[cfg(feature = "toml")]
+impl ThresholdProfiles {
+    /// Parses the profiles from the contents of a TOML file
+    pub fn parse(txt: &str) -> Result<Self, String> {
+        let profiles = toml::from_str(txt)
+            .map_err(|e| format!("Cannot parse threshold profiles: {}", e))?;
+        Ok(Self { profiles })
+    }
+
+    /// Reads the profiles from a TOML file
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let txt = std::fs::read_to_string(path).map_err(|e| {
+            format!("Cannot read threshold profiles from '{}': {}", path.display(), e)
+        })?;
+        Self::parse(&txt)
+    }
+
+    /// Gets a profile by name
+    pub fn get(&self, name: &str) -> Option<&Thresholds> {
+        self.profiles.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn test_parse_profiles() {
+        let profiles = ThresholdProfiles::parse(
+            "[strict]\nr2 = 0.95\nmax_error_fraction = 0.1\n\n[smoke]\n",
+        )
+        .unwrap();
+        let strict = profiles.get("strict").unwrap();
+        assert_eq!(strict.r2, Some(0.95));
+        assert_eq!(strict.max_error_fraction, Some(0.1));
+        assert_eq!(strict.slope_delta, None);
+        assert_eq!(profiles.get("smoke"), Some(&Thresholds::default()));
+        assert!(profiles.get("publication").is_none());
+
+        // Typos should not go unnoticed
+        assert!(ThresholdProfiles::parse("[strict]\nr_2 = 0.95").is_err());
+    }
+
+    #[test]
+    fn test_suggestions() {
+        assert_eq!(Thresholds::round_loosely(0.043219, true), Some(0.0433));
+        assert_eq!(Thresholds::round_loosely(0.9377, false), Some(0.937));
+        assert_eq!(Thresholds::round_loosely(1234.5, true), Some(1240.0));
+        assert_eq!(Thresholds::round_loosely(0.06, true), Some(0.06));
+        assert_eq!(Thresholds::round_loosely(f64::NAN, true), None);
+
+        let a = Thresholds { r2: Some(0.9), slope_delta: Some(0.1), ..Default::default() };
+        let b = Thresholds { r2: Some(0.8), slope_delta: Some(0.05), max_error_fraction: Some(0.2), ..Default::default() };
+        let loosest = a.loosest(b);
+        assert_eq!(loosest.r2, Some(0.8));
+        assert_eq!(loosest.slope_delta, Some(0.1));
+        assert_eq!(loosest.max_error_fraction, Some(0.2));
+        assert_eq!(loosest.intersect_delta, None);
+
+        let txt = loosest.to_toml("suggested");
+        assert_eq!(txt, "[suggested]\nr2 = 0.8\nslope_delta = 0.1\nmax_error_fraction = 0.2\n");
+        #[cfg(feature = "toml")]
+        assert_eq!(ThresholdProfiles::parse(&txt).unwrap().get("suggested"), Some(&loosest));
+    }
+}
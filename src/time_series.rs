@@ -19,11 +19,64 @@ SOFTWARE.
 */
 
 use crate::numberish::Numberish;
-use crate::stats::try_into_t;
+use crate::stats::count_as_f64;
+use crate::smoothing::Smoothing;
+use crate::normalize::Normalization;
+use crate::{Artifact, ChartData, Indicator, ReportContext, RunInput, Thresholds};
 use crate::Validate;
 use crate::ValidationResult;
+#[cfg(feature = "charts")]
+use crate::svg::describe_series;
+#[cfg(feature = "charts")]
 use poloto::prelude::*;
 
+/// The p-value below which the residual diagnostics flag a trend
+const SIGNIFICANCE: f64 = 0.05;
+
+/// The number of gaps in the timestamps that are listed in the report
+const MAX_LISTED_GAPS: usize = 10;
+
+/// The uncertainty of the values of a series (e.g., the accuracy of the
+/// sensor that measured them), as the half-width of the interval around
+/// each value in which the true value lies
+#[derive(Debug, Clone, PartialEq)]
+pub enum Uncertainty {
+    /// The same uncertainty for every value (e.g., `0.5` for a ±0.5 °C
+    /// thermocouple)
+    Constant(f64),
+
+    /// An uncertainty for each value (e.g., for sensors whose accuracy is
+    /// a percentage of the reading)
+    PerPoint(Vec<f64>),
+}
+
+impl Uncertainty {
+    /// The uncertainty of the value at index `i`
+    pub fn at(&self, i: usize) -> f64 {
+        match self {
+            Uncertainty::Constant(u) => *u,
+            Uncertainty::PerPoint(u) => u[i],
+        }
+    }
+
+    /// Checks that this is a valid uncertainty for a series of `n` values
+    fn check(&self, n: usize) -> Result<(), String> {
+        if let Uncertainty::PerPoint(u) = self {
+            if u.len() != n {
+                return Err(format!(
+                    "There are {} uncertainties for series of {} samples",
+                    u.len(),
+                    n
+                ));
+            }
+        }
+        if let Some(u) = (0..n).map(|i| self.at(i)).find(|u| u.is_nan() || *u < 0.) {
+            return Err(format!("Invalid uncertainty {}: it must not be negative", u));
+        }
+        Ok(())
+    }
+}
+
 /// Validates a time series based on Mean Bias Error and Root Mean Squared Error
 #[derive(Default, Clone)]
 pub struct SeriesValidator<T: Numberish> {
@@ -33,36 +86,580 @@ pub struct SeriesValidator<T: Numberish> {
     /// The maximum allowed Root Mean Squared Error
     pub allowed_root_mean_squared_error: Option<f64>,
 
+    /// The minimum number of samples that the series need to have. This
+    /// guards against comparing series that have been truncated upstream
+    /// (e.g., a few days instead of a whole year).
+    pub minimum_samples: Option<usize>,
+
+    /// The maximum allowed absolute difference between any
+    /// pair of `expected` and `found` values. The worst pair is
+    /// marked on the chart.
+    pub allowed_max_error: Option<f64>,
+
+    /// The maximum allowed difference between any pair of `expected` and
+    /// `found` values, relative to the expected value (e.g., `0.05` for 5%).
+    /// If `allowed_max_error` is also given, each pair passes if it meets
+    /// either of them, so expected values crossing zero do not make this
+    /// check explode.
+    pub allowed_relative_error: Option<f64>,
+
+    /// The maximum allowed difference between the totals of `found` and
+    /// `expected`—i.e., their integrals over the `timestamps` (or over x, if
+    /// there are none), by the trapezoidal rule—relative
+    /// to the total of `expected` (e.g., `0.05` for 5%). This is useful when
+    /// totals (e.g., the annual energy use) are an acceptance criterion of their own.
+    pub allowed_total_relative_error: Option<f64>,
+
+    /// The maximum allowed Root Mean Squared Error between the first
+    /// differences (i.e., `y[i + 1] - y[i]`) of the series. This flags
+    /// series that match in level but not in their rate of change (e.g.,
+    /// ramp rates of a controller).
+    pub allowed_derivative_root_mean_squared_error: Option<f64>,
+
+    /// If given, the lag (of up to this many samples) that best aligns
+    /// `found` with `expected` is estimated by cross-correlation, and the
+    /// errors of the aligned series are reported next to the unaligned ones.
+    /// This separates timing errors from magnitude errors. The thresholds
+    /// are still checked on the unaligned series.
+    pub max_lag: Option<usize>,
+
+    /// If given, the pairs of values are split into this many bins of
+    /// (roughly) the same size according to the expected value (e.g., `10`
+    /// for deciles), and the errors within each bin are tabulated. This
+    /// reveals errors that depend on the magnitude (e.g., biases at the extremes).
+    pub error_bins: Option<usize>,
+
+    /// The maximum allowed Mean Bias Error within each of the `error_bins`
+    pub allowed_bin_mean_bias_error: Option<f64>,
+
+    /// The maximum allowed Root Mean Squared Error within each of the `error_bins`
+    pub allowed_bin_root_mean_squared_error: Option<f64>,
+
+    /// A normalization of the expected values for the weather under which
+    /// the found values were produced (e.g., by the ratio of their degree-days),
+    /// which is documented in the report. It is applied before the `smoothing`.
+    pub normalization: Option<Normalization>,
+
+    /// A filter applied to both series before comparing them (e.g., to
+    /// keep sensor noise from dominating the errors), which is documented
+    /// in the report. The chart shows the filtered series.
+    pub smoothing: Option<Smoothing>,
+
+    /// Whether to check the residuals (i.e., `found - expected`) for
+    /// patterns: a trend of their magnitude with the expected value (e.g.,
+    /// the model gets worse at high loads) and a trend of the residuals over
+    /// time (e.g., the model drifts over the season). Significant trends are
+    /// flagged in the report, but do not make the validation fail.
+    pub residual_diagnostics: bool,
+
+    /// Whether to draw a second chart with the residuals (i.e., `found -
+    /// expected`) over x, so errors that build up along the series (e.g.,
+    /// a drifting model) can be seen rather than hidden in the Root Mean
+    /// Squared Error. The `allowed_max_error`, if any, is drawn around zero.
+    pub residual_chart: bool,
+
+    /// Whether to look for sudden shifts in the mean of the residuals (see
+    /// [`crate::stats::changepoints`]), which usually come from a sensor being
+    /// recalibrated or a wrong input of the model. They are listed in the
+    /// report and marked on the chart, but do not make the validation fail.
+    pub detect_changepoints: bool,
+
+    /// The time of each sample (e.g., hours since the start of the
+    /// experiment), if known. The timestamps are checked for gaps,
+    /// duplicates and irregular intervals (see [`crate::stats::sampling`]),
+    /// which are reported because they bias anything calculated over
+    /// periods of time (e.g., monthly totals). If given, they are the
+    /// x-values of the chart, instead of the index of each sample.
+    pub timestamps: Option<Vec<f64>>,
+
+    /// The maximum allowed fraction of the period covered by the
+    /// `timestamps` that falls in gaps (e.g., `0.05` for 5%)
+    pub allowed_gap_fraction: Option<f64>,
+
+    /// Whether to weight each sample by the period of time it represents
+    /// (i.e., half the interval to each of its neighbours, according to the
+    /// `timestamps`) when calculating the Root Mean Squared Error, so that
+    /// densely sampled periods of irregularly sampled data do not dominate it
+    /// (see [`crate::stats::weighted_mean_squared_error`])
+    pub time_weighted: bool,
+
+    /// If given, the probability of errors as small as the Root Mean Squared
+    /// Error and as large as the Mean Bias Error arising by chance is estimated
+    /// with this many random rearrangements of the data (see
+    /// [`crate::stats::permutation_test`] and [`crate::stats::sign_flip_test`]).
+    /// This tells whether short, noisy datasets support the conclusions.
+    #[cfg(feature = "rand")]
+    pub permutations: Option<usize>,
+
+    /// The seed of the random rearrangements used for `permutations`,
+    /// which is recorded in the report
+    #[cfg(feature = "rand")]
+    pub seed: u64,
+
+    /// The uncertainty of the `expected` values (e.g., of the measurements)
+    pub expected_uncertainty: Option<Uncertainty>,
+
+    /// The uncertainty of the `found` values (e.g., propagated from the
+    /// uncertain inputs of a model)
+    pub found_uncertainty: Option<Uncertainty>,
+
+    /// The minimum fraction of the samples (e.g., `0.95` for 95%) in which
+    /// the difference between the series must be within their combined
+    /// uncertainty (i.e., the square root of the sum of their squares). If
+    /// given, this is the pass criterion of the validation, so the thresholds
+    /// of the report are not inherited: demanding a Root Mean Squared Error
+    /// smaller than the accuracy of the sensors makes no sense. The band of
+    /// uncertainty around the expected values is drawn on the chart.
+    pub required_within_uncertainty: Option<f64>,
+
     /// The units in the y axis of the chart
-    pub y_units: Option<&'static str>,
+    pub y_units: Option<String>,
 
     /// The label in the y axis of the chart
-    pub y_label: Option<&'static str>,
+    pub y_label: Option<String>,
 
     /// The units in the x axis of the chart
-    pub x_units: Option<&'static str>,
+    pub x_units: Option<String>,
 
     /// The label in the x axis of the chart
-    pub x_label: Option<&'static str>,
+    pub x_label: Option<String>,
 
     /// The name of the series caled `expected`
-    pub expected_legend: Option<&'static str>,
+    pub expected_legend: Option<String>,
 
     /// The time series containing the expected values
     pub expected: Vec<T>,
 
     /// The name of the `found` time series
-    pub found_legend: Option<&'static str>,
+    pub found_legend: Option<String>,
 
     /// The time series containing the found values
     pub found: Vec<T>,
 
     /// the title of the chart
-    pub chart_title: Option<&'static str>,
+    pub chart_title: Option<String>,
+
+    /// Files to attach to the report of this validation
+    pub artifacts: Vec<Artifact>,
+
+    /// The settings under which `found` was produced (e.g., random
+    /// seeds), recorded in the report
+    pub inputs: Vec<RunInput>,
+
+    /// The physical quantities or subsystems checked by this validation
+    /// (e.g., `"zone air temperature"`), listed in the coverage of the report
+    pub covers: Vec<String>,
+
+    /// The IDs of the requirements verified by this validation (e.g.,
+    /// `"REQ-THERM-012"`), mapped to it in the report
+    pub requirements: Vec<String>,
 }
 
-impl<T: Numberish> Validate for SeriesValidator<T> {
+/// The statistics calculated by a [`SeriesValidator`], as returned by
+/// [`SeriesValidator::metrics`]. Values are in the units of the data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeriesMetrics {
+    /// The number of samples in each series
+    pub samples: usize,
+
+    /// The Mean Bias Error between the series
+    pub mean_bias_error: f64,
+
+    /// The Root Mean Squared Error between the series
+    pub root_mean_squared_error: f64,
+
+    /// The Coefficient of Variation of the Root Mean Squared Error, as in
+    /// ASHRAE Guideline 14: the square root of the mean squared error divided
+    /// by the absolute value of the mean of the expected values. It is
+    /// dimensionless, and `NaN` if that mean is zero.
+    ///
+    /// Note that [`SeriesMetrics::root_mean_squared_error`] is not rooted
+    /// (see [`crate::stats::root_mean_squared_error`]), while this is.
+    pub cv_root_mean_squared_error: f64,
+
+    /// The largest absolute difference between the series
+    pub max_absolute_error: f64,
+
+    /// The index at which the largest absolute difference is found
+    pub max_absolute_error_index: usize,
+
+    /// The Root Mean Squared Error between the first differences of the
+    /// series, or `NaN` if they have a single sample
+    pub derivative_root_mean_squared_error: f64,
+
+    /// The integral of the expected values over the timestamps (or over x,
+    /// if there are none), by the trapezoidal rule
+    pub expected_total: f64,
+
+    /// The integral of the found values over the timestamps (or over x,
+    /// if there are none), by the trapezoidal rule
+    pub found_total: f64,
+
+    /// The errors once `found` is aligned with `expected`, if a
+    /// `max_lag` was given and a lag could be estimated
+    pub alignment: Option<SeriesAlignment>,
+}
+
+/// The errors of a [`SeriesValidator`] once the found series is
+/// shifted to best match the expected one (see `SeriesValidator::max_lag`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeriesAlignment {
+    /// The number of samples by which the found series is delayed
+    /// (or advanced, if negative)
+    pub lag: isize,
+
+    /// The correlation between the aligned series
+    pub correlation: f64,
+
+    /// The Mean Bias Error between the aligned series
+    pub mean_bias_error: f64,
+
+    /// The Root Mean Squared Error between the aligned series
+    pub root_mean_squared_error: f64,
+}
+
+/// Integrates values sampled at `timestamps`—or, if there are none, at
+/// unit steps of x—by the trapezoidal rule
+fn trapezoid<T: Numberish>(timestamps: Option<&[f64]>, y: &[T]) -> f64 {
+    match timestamps {
+        Some(x) => crate::stats::trapz(x, y).unwrap_or(0.),
+        None => {
+            let x: Vec<f64> = (0..y.len()).map(crate::stats::count_as_f64).collect();
+            crate::stats::trapz(&x, y).unwrap_or(0.)
+        }
+    }
+}
+
+/// The period of time represented by each sample (i.e., half the interval
+/// to each of its neighbours)
+fn time_weights(timestamps: &[f64]) -> Result<Vec<f64>, String> {
+    if timestamps.iter().any(|t| !t.is_finite()) || timestamps.windows(2).any(|w| w[1] < w[0]) {
+        return Err("Time-weighting needs timestamps in increasing order".to_string());
+    }
+    if timestamps.len() < 2 {
+        return Ok(vec![1.; timestamps.len()]);
+    }
+    let last = timestamps.len() - 1;
+    Ok((0..=last)
+        .map(|i| (timestamps[(i + 1).min(last)] - timestamps[i.saturating_sub(1)]) / 2.)
+        .collect())
+}
+
+/// The differences between consecutive values
+fn first_differences<T: Numberish>(y: &[T]) -> Vec<f64> {
+    y.windows(2).map(|w| w[1].into() - w[0].into()).collect()
+}
+
+/// The errors of the pairs of values whose expected value falls within a
+/// range (see `SeriesValidator::error_bins`)
+struct ErrorBin {
+    /// The smallest expected value in the bin
+    from: f64,
+
+    /// The largest expected value in the bin
+    to: f64,
+
+    /// The number of pairs in the bin
+    points: usize,
+
+    /// The Mean Bias Error of the pairs in the bin
+    mean_bias_error: f64,
+
+    /// The Root Mean Squared Error of the pairs in the bin
+    root_mean_squared_error: f64,
+}
+
+/// The CV(RMSE) of ASHRAE Guideline 14 from the (un-rooted) `mean_squared_error`
+/// of a series, or `NaN` if the mean of the `expected` values is zero
+fn cv_root_mean_squared_error<T: Numberish>(mean_squared_error: f64, expected: &[T]) -> f64 {
+    let mean = crate::stats::mean(expected).abs();
+    if mean == 0. {
+        return f64::NAN;
+    }
+    mean_squared_error.sqrt() / mean
+}
+
+impl<T: Numberish> SeriesValidator<T> {
+    /// This same validation, but comparing the series once normalized
+    /// by its `normalization` and filtered by its `smoothing` (if any)
+    fn transformed(&self) -> Option<Result<SeriesValidator<f64>, String>> {
+        if self.normalization.is_none() && self.smoothing.is_none() {
+            return None;
+        }
+        let mut expected: Vec<f64> = self.expected.iter().map(|v| (*v).into()).collect();
+        let mut found: Vec<f64> = self.found.iter().map(|v| (*v).into()).collect();
+        if let Some(normalization) = &self.normalization {
+            match normalization.scale(expected.len()) {
+                Ok(scale) => expected.iter_mut().for_each(|v| *v *= scale),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        if let Some(smoothing) = self.smoothing {
+            match (smoothing.apply(&expected), smoothing.apply(&found)) {
+                (Ok(e), Ok(f)) => (expected, found) = (e, f),
+                (Err(e), _) | (_, Err(e)) => return Some(Err(e)),
+            }
+        }
+        Some(Ok(SeriesValidator {
+            allowed_mean_bias_error: self.allowed_mean_bias_error,
+            allowed_root_mean_squared_error: self.allowed_root_mean_squared_error,
+            minimum_samples: self.minimum_samples,
+            allowed_max_error: self.allowed_max_error,
+            allowed_relative_error: self.allowed_relative_error,
+            allowed_total_relative_error: self.allowed_total_relative_error,
+            allowed_derivative_root_mean_squared_error: self.allowed_derivative_root_mean_squared_error,
+            max_lag: self.max_lag,
+            error_bins: self.error_bins,
+            allowed_bin_mean_bias_error: self.allowed_bin_mean_bias_error,
+            allowed_bin_root_mean_squared_error: self.allowed_bin_root_mean_squared_error,
+            normalization: None,
+            smoothing: None,
+            residual_diagnostics: self.residual_diagnostics,
+            residual_chart: self.residual_chart,
+            detect_changepoints: self.detect_changepoints,
+            timestamps: self.timestamps.clone(),
+            allowed_gap_fraction: self.allowed_gap_fraction,
+            time_weighted: self.time_weighted,
+            expected_uncertainty: self.expected_uncertainty.clone(),
+            found_uncertainty: self.found_uncertainty.clone(),
+            required_within_uncertainty: self.required_within_uncertainty,
+            #[cfg(feature = "rand")]
+            permutations: self.permutations,
+            #[cfg(feature = "rand")]
+            seed: self.seed,
+            y_units: self.y_units.clone(),
+            y_label: self.y_label.clone(),
+            x_units: self.x_units.clone(),
+            x_label: self.x_label.clone(),
+            expected_legend: self.expected_legend.clone(),
+            expected,
+            found_legend: self.found_legend.clone(),
+            found,
+            chart_title: self.chart_title.clone(),
+            artifacts: self.artifacts.clone(),
+            inputs: self.inputs.clone(),
+            covers: self.covers.clone(),
+            requirements: self.requirements.clone(),
+        }))
+    }
+
+    /// The combined uncertainty of both series at each sample, if
+    /// any of them is uncertain
+    fn combined_uncertainty(&self) -> Result<Option<Vec<f64>>, String> {
+        let n = self.expected.len();
+        if self.expected_uncertainty.is_none() && self.found_uncertainty.is_none() {
+            return Ok(None);
+        }
+        for u in [&self.expected_uncertainty, &self.found_uncertainty].into_iter().flatten() {
+            u.check(n)?;
+        }
+        let at = |u: &Option<Uncertainty>, i: usize| u.as_ref().map(|u| u.at(i)).unwrap_or(0.);
+        Ok(Some(
+            (0..n)
+                .map(|i| at(&self.expected_uncertainty, i).hypot(at(&self.found_uncertainty, i)))
+                .collect(),
+        ))
+    }
+
+    /// Writes the trends of the residuals with the expected value and
+    /// over time (see `residual_diagnostics`)
+    fn residual_diagnostics(&self, ctx: &ReportContext) -> String {
+        let units = ctx.unit_system();
+        let y_units = self.y_units.as_deref().unwrap_or("");
+        let residuals: Vec<f64> = self
+            .expected
+            .iter()
+            .zip(self.found.iter())
+            .map(|(e, f)| (*f).into() - (*e).into())
+            .collect();
+        let magnitudes: Vec<f64> = residuals.iter().map(|r| r.abs()).collect();
+        let expected: Vec<f64> = self.expected.iter().map(|v| (*v).into()).collect();
+        let time: Vec<f64> = (0..residuals.len()).map(count_as_f64).collect();
+
+        let mut ret = String::new();
+        let tests = [
+            (
+                crate::stats::trend_test(&expected, &magnitudes),
+                "Trend of the absolute error with the expected value",
+                "per unit",
+                "the error grows with the expected value",
+                "the error shrinks as the expected value grows",
+            ),
+            (
+                crate::stats::trend_test(&time, &residuals),
+                "Trend of the error over time",
+                "per sample",
+                "the error drifts upwards over time",
+                "the error drifts downwards over time",
+            ),
+        ];
+        for (i, (test, title, per, grows, shrinks)) in tests.into_iter().enumerate() {
+            let test = match test {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+            // The drift is in the units of the data
+            let slope = if i == 1 {
+                units.convert_difference(test.slope, y_units)
+            } else {
+                test.slope
+            };
+            ret.push_str(&format!(
+                "\n * {}: {:.4} {} (p = {:.4})",
+                ctx.tr(title),
+                slope,
+                ctx.tr(per),
+                test.p_value
+            ));
+            if test.p_value < SIGNIFICANCE {
+                let pattern = if test.slope > 0. { grows } else { shrinks };
+                ret.push_str(&format!(". **{}: {}**", ctx.tr("Warning"), ctx.tr(pattern)));
+            }
+        }
+        ret
+    }
+
+    /// Splits the pairs of values into `n` bins of (roughly) the same size,
+    /// sorted by their expected value
+    fn error_bins(&self, n: usize) -> Vec<ErrorBin> {
+        let mut indices: Vec<usize> = (0..self.expected.len()).collect();
+        indices.sort_by(|a, b| {
+            let (a, b): (f64, f64) = (self.expected[*a].into(), self.expected[*b].into());
+            a.total_cmp(&b)
+        });
+        let n = n.clamp(1, indices.len().max(1));
+        (0..n)
+            .map(|b| &indices[b * indices.len() / n..(b + 1) * indices.len() / n])
+            .filter(|bin| !bin.is_empty())
+            .map(|bin| {
+                let expected: Vec<T> = bin.iter().map(|i| self.expected[*i]).collect();
+                let found: Vec<T> = bin.iter().map(|i| self.found[*i]).collect();
+                ErrorBin {
+                    from: expected[0].into(),
+                    to: expected[expected.len() - 1].into(),
+                    points: bin.len(),
+                    mean_bias_error: crate::stats::mean_bias_error(&expected, &found),
+                    root_mean_squared_error: crate::stats::root_mean_squared_error(&expected, &found),
+                }
+            })
+            .collect()
+    }
+
+    /// Calculates the statistics comparing `expected` and `found`,
+    /// without checking them or writing any report.
+    ///
+    /// ```
+    /// use validate::SeriesValidator;
+    ///
+    /// let v = SeriesValidator {
+    ///     expected: vec![1., 2., 3.],
+    ///     found: vec![1., 2., 5.],
+    ///     ..Default::default()
+    /// };
+    /// let metrics = v.metrics().unwrap();
+    /// assert_eq!(metrics.max_absolute_error, 2.);
+    /// assert_eq!(metrics.max_absolute_error_index, 2);
+    /// ```
+    pub fn metrics(&self) -> Result<SeriesMetrics, String> {
+        if let Some(transformed) = self.transformed() {
+            return transformed?.metrics();
+        }
+        if self.expected.len() != self.found.len() {
+            return Err(format!(
+                "Series to compare have different lengths. expected.len() = {}, found.len() = {}",
+                self.expected.len(),
+                self.found.len()
+            ));
+        }
+        if self.expected.is_empty() {
+            return Err("Series to compare are empty".to_string());
+        }
+        let (max_absolute_error_index, max_absolute_error) =
+            crate::stats::max_absolute_error(&self.expected, &self.found);
+        let timestamps = self.timestamps.as_deref();
+        if let Some(timestamps) = timestamps {
+            if timestamps.len() != self.expected.len() {
+                return Err(format!(
+                    "There are {} timestamps for series of {} samples",
+                    timestamps.len(),
+                    self.expected.len()
+                ));
+            }
+        }
+        let root_mean_squared_error = if self.time_weighted {
+            let timestamps =
+                timestamps.ok_or_else(|| "Time-weighting needs the timestamps of the samples".to_string())?;
+            crate::stats::weighted_mean_squared_error(&self.expected, &self.found, &time_weights(timestamps)?)?
+        } else {
+            crate::stats::root_mean_squared_error(&self.expected, &self.found)
+        };
+        Ok(SeriesMetrics {
+            samples: self.expected.len(),
+            mean_bias_error: crate::stats::mean_bias_error(&self.expected, &self.found),
+            root_mean_squared_error,
+            cv_root_mean_squared_error: cv_root_mean_squared_error(root_mean_squared_error, &self.expected),
+            max_absolute_error,
+            max_absolute_error_index,
+            derivative_root_mean_squared_error: if self.expected.len() > 1 {
+                crate::stats::root_mean_squared_error(
+                    &first_differences(&self.expected),
+                    &first_differences(&self.found),
+                )
+            } else {
+                f64::NAN
+            },
+            expected_total: trapezoid(timestamps, &self.expected),
+            found_total: trapezoid(timestamps, &self.found),
+            alignment: self.max_lag.and_then(|max_lag| {
+                let (lag, correlation) = crate::stats::best_lag(&self.expected, &self.found, max_lag).ok()?;
+                let (expected, found) = crate::stats::overlap(&self.expected, &self.found, lag);
+                Some(SeriesAlignment {
+                    lag,
+                    correlation,
+                    mean_bias_error: crate::stats::mean_bias_error(expected, found),
+                    root_mean_squared_error: crate::stats::root_mean_squared_error(expected, found),
+                })
+            }),
+        })
+    }
+}
+
+impl<T: Numberish + 'static> Validate for SeriesValidator<T> {
     fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
+        if let Some(transformed) = self.transformed() {
+            let transformed = match transformed {
+                Ok(t) => t,
+                Err(e) => return ValidationResult::Err(e.clone(), e),
+            };
+            let mut note = String::new();
+            if let Some(normalization) = &self.normalization {
+                // The scale was already calculated, so this cannot fail
+                let (expected, found) = normalization.totals(self.expected.len()).unwrap_or_default();
+                note = format!(
+                    "{} {}: {:.1} / {:.1}\n",
+                    ctx.tr("Expected values normalized to the weather of the found ones by the ratio of their"),
+                    normalization,
+                    found,
+                    expected
+                );
+            }
+            if let Some(smoothing) = self.smoothing {
+                note = format!(
+                    "{}{}: {}\n",
+                    note,
+                    ctx.tr("Filter applied to both series before comparing them"),
+                    smoothing
+                );
+            }
+            return match transformed.validate_in(ctx) {
+                ValidationResult::Ok(txt) => ValidationResult::Ok(format!("{}{}", note, txt)),
+                ValidationResult::Err(txt, e) => ValidationResult::Err(format!("{}{}", note, txt), e),
+            };
+        }
         let mut err_msg = String::new();
         let mut file_msg = String::new();
 
@@ -75,88 +672,628 @@ impl<T: Numberish> Validate for SeriesValidator<T> {
             return ValidationResult::Err(err_msg.clone(), err_msg);
         }
 
-        let n = try_into_t(self.expected.len());
+        if let Some(timestamps) = &self.timestamps {
+            if timestamps.len() != self.expected.len() {
+                err_msg = format!(
+                    "There are {} timestamps for series of {} samples",
+                    timestamps.len(),
+                    self.expected.len()
+                );
+                return ValidationResult::Err(err_msg.clone(), err_msg);
+            }
+        }
+
+        let uncertainty = match self.combined_uncertainty() {
+            Ok(u) => u,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+
+        if let Some(minimum_samples) = self.minimum_samples {
+            if self.expected.len() < minimum_samples {
+                err_msg = format!(
+                    "Series to compare are too short: they have {} samples, but at least {} are required",
+                    self.expected.len(),
+                    minimum_samples
+                );
+                return ValidationResult::Err(err_msg.clone(), err_msg);
+            }
+        }
+        let metrics = match self.metrics() {
+            Ok(m) => m,
+            Err(e) => return ValidationResult::Err(e.clone(), e),
+        };
+
+        let n = count_as_f64(self.expected.len());
 
         let num = self.expected.len();
 
-        let mean_bias_error = crate::stats::mean_bias_error(&self.expected, &self.found);
-        file_msg = format!("{}\n * Mean Bias Error: {:.4}", file_msg, mean_bias_error);
+        // Values are calculated in the units of the data, but
+        // displayed in the unit system of the report
+        let units = ctx.unit_system();
+        let y_units = self.y_units.as_deref().unwrap_or("");
+        let show = |v: T| units.convert(v.into(), y_units);
+        let show_diff = |v: f64| units.convert_difference(v, y_units);
+
+        let mean_bias_error = metrics.mean_bias_error;
+        file_msg = format!(
+            "{}\n * {}: {:.4}",
+            file_msg,
+            ctx.tr("Mean Bias Error"),
+            show_diff(mean_bias_error)
+        );
 
         // Process Root Mean Squared Error
-        let root_mean_squared_error =
-            crate::stats::root_mean_squared_error(&self.expected, &self.found);
+        let root_mean_squared_error = metrics.root_mean_squared_error;
         file_msg = format!(
-            "{}\n * Root Mean Squared Error: {:.4}",
-            file_msg, root_mean_squared_error
+            "{}\n * {}: {:.4}",
+            file_msg,
+            if self.time_weighted {
+                ctx.tr("Root Mean Squared Error (time-weighted)")
+            } else {
+                ctx.tr("Root Mean Squared Error")
+            },
+            show_diff(root_mean_squared_error)
         );
 
+        // Process the Maximum Absolute Error
+        let (worst_index, max_error) = (metrics.max_absolute_error_index, metrics.max_absolute_error);
+        file_msg = format!(
+            "{}\n * {}: {:.4} ({} {})",
+            file_msg,
+            ctx.tr("Maximum Absolute Error"),
+            show_diff(max_error),
+            ctx.tr("at index"),
+            worst_index
+        );
+
+        // Some indicators are undefined for series that do not vary
+        for (name, series) in [("The expected series", &self.expected), ("The found series", &self.found)] {
+            if let Some(d) = crate::stats::degeneracy(series) {
+                file_msg = format!(
+                    "{}\n * {}: {} {} ({})",
+                    file_msg,
+                    ctx.tr("Warning"),
+                    ctx.tr(name),
+                    ctx.tr("does not vary"),
+                    d
+                );
+            }
+        }
+
+        if self.max_lag.is_some() {
+            match &metrics.alignment {
+                Some(a) => {
+                    file_msg = format!(
+                        "{}\n * {}: {} ({}: {:.4})\n * {} ({}): {:.4}\n * {} ({}): {:.4}",
+                        file_msg,
+                        ctx.tr("Lag"),
+                        a.lag,
+                        ctx.tr("correlation"),
+                        a.correlation,
+                        ctx.tr("Mean Bias Error"),
+                        ctx.tr("aligned"),
+                        show_diff(a.mean_bias_error),
+                        ctx.tr("Root Mean Squared Error"),
+                        ctx.tr("aligned"),
+                        show_diff(a.root_mean_squared_error)
+                    );
+                }
+                None => {
+                    file_msg = format!(
+                        "{}\n * {}",
+                        file_msg,
+                        ctx.tr("The lag between the series cannot be estimated")
+                    );
+                }
+            }
+        }
+
+        if self.residual_diagnostics {
+            file_msg = format!("{}{}", file_msg, self.residual_diagnostics(ctx));
+        }
+
+        let changepoints = if self.detect_changepoints {
+            let residuals: Vec<f64> = self
+                .expected
+                .iter()
+                .zip(self.found.iter())
+                .map(|(e, f)| (*f).into() - (*e).into())
+                .collect();
+            crate::stats::changepoints(&residuals, None)
+        } else {
+            Vec::new()
+        };
+        if self.detect_changepoints {
+            if changepoints.is_empty() {
+                file_msg = format!("{}\n * {}", file_msg, ctx.tr("No sudden shifts in the error were detected"));
+            }
+            for c in changepoints.iter() {
+                file_msg = format!(
+                    "{}\n * {} {}: {} {:.4} {} {:.4}",
+                    file_msg,
+                    ctx.tr("Sudden shift in the error at sample"),
+                    c.index,
+                    ctx.tr("the Mean Bias Error goes from"),
+                    show_diff(c.mean_before),
+                    ctx.tr("to"),
+                    show_diff(c.mean_after)
+                );
+            }
+        }
+
+        let sampling = self.timestamps.as_ref().map(|t| crate::stats::sampling(t, None));
+        match &sampling {
+            Some(Ok(s)) => {
+                file_msg = format!(
+                    "{}\n * {}: {:.4}. {} {} ({} {}, {:.2}% {}), {} {}, {} {}",
+                    file_msg,
+                    ctx.tr("Sampling step"),
+                    s.step,
+                    s.gaps.len(),
+                    ctx.tr("gaps"),
+                    s.missing(),
+                    ctx.tr("missing samples"),
+                    s.gap_fraction(num) * 100.,
+                    ctx.tr("of the period"),
+                    s.duplicates.len(),
+                    ctx.tr("duplicated timestamps"),
+                    s.irregular,
+                    ctx.tr("irregular intervals")
+                );
+                for gap in s.gaps.iter().take(MAX_LISTED_GAPS) {
+                    file_msg = format!(
+                        "{}\n     * {} {} {} {} ({} {})",
+                        file_msg,
+                        ctx.tr("Gap from"),
+                        gap.from,
+                        ctx.tr("to"),
+                        gap.to,
+                        gap.missing,
+                        ctx.tr("missing samples")
+                    );
+                }
+                if s.gaps.len() > MAX_LISTED_GAPS {
+                    file_msg = format!("{}\n     * ...", file_msg);
+                }
+            }
+            Some(Err(e)) => {
+                file_msg = format!("{}\n * {}: {}", file_msg, ctx.tr("The sampling cannot be checked"), e);
+            }
+            None => {}
+        }
+
+        let within_uncertainty = uncertainty.as_ref().map(|u| {
+            let within = (0..num)
+                .filter(|i| (self.found[*i].into() - self.expected[*i].into()).abs() <= u[*i])
+                .count();
+            count_as_f64(within) / n
+        });
+        if let Some(within) = within_uncertainty {
+            file_msg = format!(
+                "{}\n * {}: {:.2}%",
+                file_msg,
+                ctx.tr("Samples within the combined uncertainty"),
+                within * 100.
+            );
+        }
+
+        #[cfg(feature = "rand")]
+        if let Some(permutations) = self.permutations {
+            let rmse = crate::stats::permutation_test(
+                &self.expected,
+                &self.found,
+                crate::stats::root_mean_squared_error,
+                permutations,
+                self.seed,
+            );
+            let mbe = crate::stats::sign_flip_test(
+                &self.expected,
+                &self.found,
+                crate::stats::mean_bias_error,
+                permutations,
+                self.seed,
+            );
+            if let (Ok(rmse), Ok(mbe)) = (rmse, mbe) {
+                file_msg = format!(
+                    "{}\n * {} ({} {}): {:.4}\n * {} ({} {}): {:.4}",
+                    file_msg,
+                    ctx.tr("Probability of a Root Mean Squared Error this small by chance"),
+                    permutations,
+                    ctx.tr("permutations"),
+                    rmse.p_value,
+                    ctx.tr("Probability of a Mean Bias Error this large by chance"),
+                    permutations,
+                    ctx.tr("permutations"),
+                    mbe.p_value
+                );
+            }
+        }
+
+        // Thresholds not set in this validation are inherited from the report,
+        // unless the uncertainty of the data is the pass criterion
+        let defaults = if self.required_within_uncertainty.is_some() {
+            Thresholds::default()
+        } else {
+            *ctx.thresholds()
+        };
+        let allowed_mean_bias_error = self.allowed_mean_bias_error.or_else(|| {
+            Thresholds::of_range(defaults.mean_bias_error_fraction, &self.expected)
+        });
+        let allowed_root_mean_squared_error = self.allowed_root_mean_squared_error.or_else(|| {
+            Thresholds::of_range(defaults.root_mean_squared_error_fraction, &self.expected)
+        });
+        let allowed_max_error = self
+            .allowed_max_error
+            .or_else(|| Thresholds::of_range(defaults.max_error_fraction, &self.expected));
+
         let mut nchecks = 0;
 
         // Check compliance
-        if let Some(allowed_mean_bias_error) = self.allowed_mean_bias_error {
+        if let Some(allowed_mean_bias_error) = allowed_mean_bias_error {
             nchecks += 1;
             if mean_bias_error.abs() > allowed_mean_bias_error {
                 err_msg = format!(
                     "{} * Mean Bias Error is {:.4}, which is greater than the allowed value of {:.4}",
                     err_msg,
-                    mean_bias_error.abs(),
-                    allowed_mean_bias_error
+                    show_diff(mean_bias_error.abs()),
+                    show_diff(allowed_mean_bias_error)
                 );
             }
         }
-        if let Some(allowed_root_mean_squared_error) = self.allowed_root_mean_squared_error {
+        if let Some(allowed_root_mean_squared_error) = allowed_root_mean_squared_error {
             nchecks += 1;
             // this is always positive... but just in case
             if root_mean_squared_error.abs() > allowed_root_mean_squared_error {
                 err_msg = format!(
                     "{}\n * Mean Root Squared Error is {:.4}, which is greater than the allowed value of {:.4}",
-                    err_msg,  root_mean_squared_error, allowed_root_mean_squared_error
-                );                
-            }
-        }
-
-        let exp_legend = self.expected_legend.unwrap_or("Expected");
-        let line_expected = poloto::range_iter([0.0, n], num)
-            .zip_output(|i| self.expected[i as usize].into())
-            .buffered_plot()
-            .line(exp_legend);
-        let found_legend = self.found_legend.unwrap_or("Found");
-        let line_found = poloto::range_iter([0.0, n], num)
-            .zip_output(|i| self.found[i as usize].into())
-            .buffered_plot()
-            .line(found_legend);
-        let origin = poloto::build::origin();
-        // let data = plots!(line_expected, line_found, m);
-
-        let mut x_label: String = self.x_label.unwrap_or("x").into();
-        if let Some(units) = self.x_units {
-            x_label = format!("{} ({})", x_label, units);
-        }
-        let mut y_label: String = self.y_label.unwrap_or("y").into();
-        if let Some(units) = self.y_units {
-            y_label = format!("{} ({})", y_label, units);
-        }
-        let chart_title = self.chart_title.unwrap_or("");
-        let p = quick_fmt!(
-            chart_title,
-            &x_label,
-            &y_label,
-            line_expected,
-            line_found,
-            origin
-        );
+                    err_msg,
+                    show_diff(root_mean_squared_error),
+                    show_diff(allowed_root_mean_squared_error)
+                );
+            }
+        }
+
+        if let Some(required) = self.required_within_uncertainty {
+            nchecks += 1;
+            match within_uncertainty {
+                Some(within) if within < required => {
+                    err_msg = format!(
+                        "{}\n * Only {:.2}% of the samples are within the combined uncertainty of the series, but at least {:.2}% are required",
+                        err_msg,
+                        within * 100.,
+                        required * 100.
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    err_msg = format!(
+                        "{}\n * A fraction of samples within the uncertainty was required, but no uncertainty was given",
+                        err_msg
+                    );
+                }
+            }
+        }
+
+        if let Some(allowed_gap_fraction) = self.allowed_gap_fraction {
+            nchecks += 1;
+            match &sampling {
+                Some(Ok(s)) if s.gap_fraction(num) > allowed_gap_fraction => {
+                    err_msg = format!(
+                        "{}\n * The gaps in the timestamps cover {:.2}% of the period, which is greater than the allowed {:.2}%",
+                        err_msg,
+                        s.gap_fraction(num) * 100.,
+                        allowed_gap_fraction * 100.
+                    );
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    err_msg = format!("{}\n * The gaps in the timestamps cannot be checked: {}", err_msg, e);
+                }
+                None => {
+                    err_msg = format!(
+                        "{}\n * An allowed gap fraction was given, but there are no timestamps to check",
+                        err_msg
+                    );
+                }
+            }
+        }
+
+        if let Some(allowed_relative_error) = self.allowed_relative_error {
+            nchecks += 1;
+            // Pairs within the absolute tolerance (if any) pass as well
+            let abs_tol = allowed_max_error.unwrap_or(0.0);
+            let failing: Vec<usize> = (0..num)
+                .filter(|i| {
+                    let (e, f) = (self.expected[*i].into(), self.found[*i].into());
+                    !crate::stats::within_tolerance(e, f, abs_tol, allowed_relative_error)
+                })
+                .collect();
+            if let Some(first) = failing.first() {
+                let tolerance = match allowed_max_error {
+                    Some(a) => format!(
+                        "both the allowed absolute error of {:.4} and the allowed relative error of {:.2}%",
+                        show_diff(a),
+                        allowed_relative_error * 100.
+                    ),
+                    None => format!("the allowed relative error of {:.2}%", allowed_relative_error * 100.),
+                };
+                err_msg = format!(
+                    "{}\n * {} values (e.g., at index {}: expected {:.4}, found {:.4}) exceed {}",
+                    err_msg,
+                    failing.len(),
+                    first,
+                    show(self.expected[*first]),
+                    show(self.found[*first]),
+                    tolerance
+                );
+            }
+        } else if let Some(allowed_max_error) = allowed_max_error {
+            nchecks += 1;
+            if max_error > allowed_max_error {
+                err_msg = format!(
+                    "{}\n * Maximum Absolute Error is {:.4} (at index {}: expected {:.4}, found {:.4}), which is greater than the allowed value of {:.4}",
+                    err_msg,
+                    show_diff(max_error),
+                    worst_index,
+                    show(self.expected[worst_index]),
+                    show(self.found[worst_index]),
+                    show_diff(allowed_max_error)
+                );
+            }
+        }
+
+        if let Some(allowed) = self.allowed_derivative_root_mean_squared_error {
+            nchecks += 1;
+            let derivative_rmse = metrics.derivative_root_mean_squared_error;
+            if derivative_rmse.is_nan() {
+                err_msg = format!(
+                    "{}\n * The first differences of the series cannot be compared, as they have a single sample",
+                    err_msg
+                );
+            } else {
+                file_msg = format!(
+                    "{}\n * {}: {:.4}",
+                    file_msg,
+                    ctx.tr("Root Mean Squared Error of the first differences"),
+                    show_diff(derivative_rmse)
+                );
+                if derivative_rmse > allowed {
+                    err_msg = format!(
+                        "{}\n * Root Mean Squared Error of the first differences is {:.4}, which is greater than the allowed value of {:.4}",
+                        err_msg,
+                        show_diff(derivative_rmse),
+                        show_diff(allowed)
+                    );
+                }
+            }
+        }
+
+        if let Some(allowed_total_relative_error) = self.allowed_total_relative_error {
+            nchecks += 1;
+            let (expected_total, found_total) = (metrics.expected_total, metrics.found_total);
+            // Totals are written in the units of the data times those of x
+            file_msg = format!(
+                "{}\n * {}: {:.4}\n * {}: {:.4}",
+                file_msg,
+                ctx.tr("Expected total"),
+                expected_total,
+                ctx.tr("Found total"),
+                found_total
+            );
+            let diff = (found_total - expected_total).abs();
+            if diff > allowed_total_relative_error * expected_total.abs() {
+                err_msg = format!(
+                    "{}\n * The found total is {:.4} and the expected total is {:.4}, a relative difference of {:.2}%, which is greater than the allowed {:.2}%",
+                    err_msg,
+                    found_total,
+                    expected_total,
+                    100. * diff / expected_total.abs(),
+                    100. * allowed_total_relative_error
+                );
+            }
+        }
+
+        let mut bins_table = String::new();
+        if let Some(n) = self.error_bins {
+            let bins = self.error_bins(n);
+            if self.allowed_bin_mean_bias_error.is_some() || self.allowed_bin_root_mean_squared_error.is_some() {
+                nchecks += 1;
+            }
+            bins_table = format!(
+                "{}error_bins\"><table><thead><tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr></thead><tbody>",
+                crate::report::own_html(),
+                ctx.tr("Bin"),
+                ctx.tr("Expected values"),
+                ctx.tr("Points"),
+                ctx.tr("Mean Bias Error"),
+                ctx.tr("Root Mean Squared Error"),
+            );
+            for (i, bin) in bins.iter().enumerate() {
+                // Errors beyond the thresholds are highlighted
+                let cell = |v: f64, allowed: Option<f64>| {
+                    if allowed.is_some_and(|a| v.abs() > a) {
+                        format!("<td><strong>{:.4}</strong></td>", show_diff(v))
+                    } else {
+                        format!("<td>{:.4}</td>", show_diff(v))
+                    }
+                };
+                bins_table.push_str(&format!(
+                    "<tr><td>{}</td><td>{:.4} - {:.4}</td><td>{}</td>{}{}</tr>",
+                    i + 1,
+                    units.convert(bin.from, y_units),
+                    units.convert(bin.to, y_units),
+                    bin.points,
+                    cell(bin.mean_bias_error, self.allowed_bin_mean_bias_error),
+                    cell(bin.root_mean_squared_error, self.allowed_bin_root_mean_squared_error)
+                ));
+                let checks = [
+                    ("Mean Bias Error", bin.mean_bias_error, self.allowed_bin_mean_bias_error),
+                    ("Root Mean Squared Error", bin.root_mean_squared_error, self.allowed_bin_root_mean_squared_error),
+                ];
+                for (name, value, allowed) in checks {
+                    if let Some(allowed) = allowed.filter(|a| value.abs() > *a) {
+                        err_msg = format!(
+                            "{}\n * {} of bin {} (expected values from {:.4} to {:.4}) is {:.4}, which is greater than the allowed value of {:.4}",
+                            err_msg,
+                            name,
+                            i + 1,
+                            units.convert(bin.from, y_units),
+                            units.convert(bin.to, y_units),
+                            show_diff(value.abs()),
+                            show_diff(allowed)
+                        );
+                    }
+                }
+            }
+            bins_table = format!(
+                "\n#### {}:\n\n{}</tbody></table></div>\n",
+                ctx.tr("Errors by magnitude"),
+                bins_table
+            );
+        }
+
+        // Tell what kind of error makes the validation fail, so it can be acted upon
+        let mut diagnosis = String::new();
+        if !err_msg.is_empty() {
+            if let Ok(d) = crate::stats::theil_decomposition(&self.expected, &self.found) {
+                let (dominant, fraction) = d.dominant();
+                let hint = match dominant {
+                    "bias" => "The found values are offset from the expected ones (e.g., a calibration offset or a wrong boundary condition)",
+                    "amplitude" => "The found values vary more or less than the expected ones (e.g., a wrong thermal mass or gain)",
+                    _ => "The found values do not rise and fall with the expected ones (e.g., a time lag or a missing dynamic)",
+                };
+                diagnosis = format!(
+                    "#### {}:\n\n * {}: {:.1}%\n * {}: {:.1}%\n * {}: {:.1}%\n\n{}\n",
+                    ctx.tr("Diagnosis (fractions of the Mean Squared Error)"),
+                    ctx.tr("Bias"),
+                    d.bias * 100.,
+                    ctx.tr("Amplitude"),
+                    d.amplitude * 100.,
+                    ctx.tr("Phase"),
+                    d.phase * 100.,
+                    ctx.tr(hint)
+                );
+                err_msg = format!(
+                    "{}\n * Hint: {:.0}% of the Mean Squared Error is due to {}. {}",
+                    err_msg,
+                    fraction * 100.,
+                    dominant,
+                    hint
+                );
+            }
+        }
+
+        #[cfg(feature = "charts")]
+        let chart = {
+            // The samples are drawn at their timestamps, if known
+            let x = |i: usize| self.timestamps.as_ref().map_or(i as f64, |t| t[i]);
+            let exp_legend = self.expected_legend.as_deref().unwrap_or_else(|| ctx.tr("Expected"));
+            let line_expected = (0..num)
+                .map(|i| [x(i), show(self.expected[i])])
+                .buffered_plot()
+                .line(exp_legend);
+            let found_legend = self.found_legend.as_deref().unwrap_or_else(|| ctx.tr("Found"));
+            let line_found = (0..num)
+                .map(|i| [x(i), show(self.found[i])])
+                .buffered_plot()
+                .line(found_legend);
+            // Draw the band of uncertainty around the expected values, if any
+            let band = |sign: f64| {
+                let u = uncertainty.as_deref().unwrap_or(&[]);
+                (0..u.len())
+                    .map(|i| [x(i), units.convert(self.expected[i].into() + sign * u[i], y_units)])
+                    .collect::<Vec<[f64; 2]>>()
+            };
+            let band_legend = if uncertainty.is_some() { ctx.tr("Uncertainty") } else { "" };
+            let upper = band(1.).into_iter().buffered_plot().line(band_legend);
+            let lower = band(-1.).into_iter().buffered_plot().line("");
+            // Mark the worst point, if it is being checked
+            let n_marks = if allowed_max_error.is_some() { 1 } else { 0 };
+            let worst_legend = if n_marks > 0 { ctx.tr("Maximum Absolute Error") } else { "" };
+            let worst = (0..n_marks)
+                .map(|_| [x(worst_index), show(self.found[worst_index])])
+                .buffered_plot()
+                .scatter(worst_legend);
+            // Mark the shifts in the error, if any
+            let shifts_legend = if changepoints.is_empty() { "" } else { ctx.tr("Shift in the error") };
+            let shifts = changepoints
+                .iter()
+                .map(|c| [x(c.index), show(self.found[c.index])])
+                .buffered_plot()
+                .scatter(shifts_legend);
+            let origin = poloto::build::origin();
+
+            let mut x_label: String = self.x_label.as_deref().unwrap_or("x").into();
+            if let Some(units) = &self.x_units {
+                x_label = format!("{} ({})", x_label, units);
+            }
+            let mut y_label: String = self.y_label.as_deref().unwrap_or("y").into();
+            if let Some(y_units) = &self.y_units {
+                y_label = format!("{} ({})", y_label, units.units(y_units));
+            }
+            let chart_title = self.chart_title.as_deref().unwrap_or("");
+            let p = quick_fmt!(
+                chart_title,
+                &x_label,
+                &y_label,
+                line_expected,
+                line_found,
+                upper,
+                lower,
+                worst,
+                shifts,
+                origin
+            );
+            let alt_title = if chart_title.is_empty() {
+                format!("{} vs {}", y_label, x_label)
+            } else {
+                chart_title.to_string()
+            };
+            let description = [
+                describe_series(exp_legend, self.expected.iter().map(|v| show(*v))),
+                describe_series(found_legend, self.found.iter().map(|v| show(*v))),
+                format!("{}: {:.4}", ctx.tr("Mean Bias Error"), show_diff(metrics.mean_bias_error)),
+                format!("{}: {:.4}", ctx.tr("Root Mean Squared Error"), show_diff(metrics.root_mean_squared_error)),
+            ]
+            .join(". ");
+            let mut chart = ctx
+                .chart_theme()
+                .render(poloto::disp(|w| p.simple_theme(w)).to_string(), &alt_title, &description);
+
+            if self.residual_chart {
+                let residuals = (0..num).map(|i| [x(i), show_diff(self.found[i].into() - self.expected[i].into())]);
+                let mut series = vec![crate::plot::Series::line(ctx.tr("Residual"), residuals)];
+                if let Some(allowed) = allowed_max_error {
+                    let level = |y: f64| [[x(0), show_diff(y)], [x(num.saturating_sub(1)), show_diff(y)]];
+                    series.push(crate::plot::Series::line(ctx.tr("Maximum allowed error"), level(allowed)));
+                    series.push(crate::plot::Series::line("", level(-allowed)));
+                }
+                let mut residual_label = format!("{} ({})", ctx.tr("Residual"), ctx.tr("found minus expected"));
+                if let Some(y_units) = &self.y_units {
+                    residual_label = format!("{} ({})", residual_label, units.units(y_units));
+                }
+                chart += crate::plot::chart(chart_title, &x_label, &residual_label, series, ctx).as_str();
+            }
+            chart
+        };
+        #[cfg(not(feature = "charts"))]
+        let chart = ctx.chart_theme().omitted();
 
         let show_err = if nchecks == 0 {
-            "No checks performed..."
+            ctx.tr("No checks performed...")
         }else if err_msg.is_empty() { 
-            "No errors found" 
+            ctx.tr("No errors found")
         } else { &err_msg };
 
         let file = format!(
-            "{}\n#### Errors:\n {}\n#### Data:\n\n{}",
+            "{}\n{}\n#### {}:\n {}\n{}#### {}:\n\n{}",
             file_msg,
+            bins_table,
+            ctx.tr("Errors"),
             show_err,
-            poloto::disp(|w| p.simple_theme(w))
+            diagnosis,
+            ctx.tr("Data"),
+            chart
         );
 
         if !err_msg.is_empty() {
@@ -165,6 +1302,99 @@ impl<T: Numberish> Validate for SeriesValidator<T> {
             ValidationResult::Ok(file)
         }
     }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn artifacts(&self) -> Vec<Artifact> {
+        self.artifacts.clone()
+    }
+
+    fn inputs(&self) -> Vec<RunInput> {
+        #[allow(unused_mut)]
+        let mut inputs = self.inputs.clone();
+        #[cfg(feature = "rand")]
+        if self.permutations.is_some() {
+            inputs.push(crate::stats::Sampler::new(self.seed).input());
+        }
+        inputs
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.covers.clone()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.requirements.clone()
+    }
+
+    fn defines_thresholds(&self, ctx: &ReportContext) -> bool {
+        let defaults = ctx.thresholds();
+        self.allowed_mean_bias_error.is_some()
+            || self.allowed_root_mean_squared_error.is_some()
+            || self.allowed_max_error.is_some()
+            || self.allowed_relative_error.is_some()
+            || self.allowed_total_relative_error.is_some()
+            || self.allowed_derivative_root_mean_squared_error.is_some()
+            || self.allowed_bin_mean_bias_error.is_some()
+            || self.allowed_bin_root_mean_squared_error.is_some()
+            || self.allowed_gap_fraction.is_some()
+            || self.required_within_uncertainty.is_some()
+            || defaults.mean_bias_error_fraction.is_some()
+            || defaults.root_mean_squared_error_fraction.is_some()
+            || defaults.max_error_fraction.is_some()
+    }
+
+    fn suggested_thresholds(&self, margin: f64) -> Option<Thresholds> {
+        let m = self.metrics().ok()?;
+        let (min, max) = crate::stats::try_min_max(&self.expected).ok()?;
+        let range = max.into() - min.into();
+        if range <= 0.0 {
+            return None;
+        }
+        let fraction = |error: f64| Thresholds::round_loosely(margin * error.abs() / range, true);
+        Some(Thresholds {
+            mean_bias_error_fraction: fraction(m.mean_bias_error),
+            root_mean_squared_error_fraction: fraction(m.root_mean_squared_error),
+            max_error_fraction: fraction(m.max_absolute_error),
+            ..Default::default()
+        })
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        match self.metrics() {
+            Ok(m) => vec![
+                Indicator::new("Mean Bias Error", m.mean_bias_error),
+                Indicator::new("Root Mean Squared Error", m.root_mean_squared_error),
+                Indicator::new("CV(RMSE)", m.cv_root_mean_squared_error),
+                Indicator::new("Maximum Absolute Error", m.max_absolute_error),
+            ]
+            .into_iter()
+            .chain(m.alignment.into_iter().flat_map(|a| {
+                [
+                    Indicator::new("Lag", a.lag as f64),
+                    Indicator::new("Aligned Root Mean Squared Error", a.root_mean_squared_error),
+                ]
+            }))
+            .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn chart_data(&self) -> Option<ChartData> {
+        if let Some(transformed) = self.transformed() {
+            return transformed.ok()?.chart_data();
+        }
+        Some(ChartData {
+            x: match &self.timestamps {
+                Some(t) if t.len() == self.expected.len() => t.clone(),
+                _ => (0..self.expected.len()).map(|i| i as f64).collect(),
+            },
+            expected: self.expected.iter().map(|v| (*v).into()).collect(),
+            found: self.found.iter().map(|v| (*v).into()).collect(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -201,6 +1431,617 @@ mod testing {
         validator.validate().unwrap()
     }
 
+    #[test]
+    #[cfg(feature = "csv")]
+    fn test_series_export_chart_data() {
+        use crate::Validator;
+
+        let dir = std::env::temp_dir().join("validate_test_series_export");
+        let report = dir.join("report.html");
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Time series test", &report);
+        validator.set_export_chart_data(true);
+
+        let series = SeriesValidator {
+            expected: vec![1., 2., 3., 4.],
+            found: vec![1., 2., 3., 5.],
+            ..Default::default()
+        };
+        validator.push(Box::new(series));
+        validator.validate().unwrap();
+
+        let csv = dir.join("report_assets/1_chart_data.csv");
+        assert!(csv.exists());
+        let html = std::fs::read_to_string(dir.join("report.html")).unwrap();
+        assert!(html.contains("report_assets/1_chart_data.csv"));
+    }
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_series_in_ip_units() {
+        let mut locale = crate::Locale::new();
+        locale.insert("Mean Bias Error", "Error Medio");
+        let ctx = ReportContext {
+            locale,
+            unit_system: crate::UnitSystem::IP,
+            ..ReportContext::default()
+        };
+
+        let series = SeriesValidator {
+            expected: vec![1., 2., 3., 4.],
+            found: vec![2., 3., 4., 5.],
+            y_units: Some("C".into()),
+            ..Default::default()
+        };
+
+        match series.validate_in(&ctx) {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.contains("Error Medio: 1.8000"));
+                assert!(txt.contains("°F"));
+            }
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_series_labels_are_not_markdown() {
+        use crate::Validator;
+
+        let report = std::env::temp_dir().join("validate_test_series_labels.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("Labels", &report);
+        validator.push(Box::new(SeriesValidator {
+            expected: vec![1., 2.],
+            found: vec![1., 2.],
+            y_label: Some("T_air_ < 5 & *more*".into()),
+            ..Default::default()
+        }));
+        validator.validate().unwrap();
+
+        let html = std::fs::read_to_string(report.as_ref()).unwrap();
+        assert!(html.contains("T_air_ &lt; 5 &amp; *more*"));
+        assert!(!html.contains("<em>"));
+    }
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_series_without_raw_html() {
+        use crate::{MarkdownOptions, Validator};
+
+        let report = std::env::temp_dir().join("validate_test_series_no_html.html");
+        let report = report.to_string_lossy();
+        let mut validator = Validator::new("No HTML", &report);
+        validator.set_markdown_options(MarkdownOptions {
+            raw_html: false,
+            ..Default::default()
+        });
+        validator.push(Box::new(SeriesValidator {
+            expected: vec![1., 2.],
+            found: vec![1., 2.],
+            ..Default::default()
+        }));
+        validator.validate().unwrap();
+
+        // The chart and the summary are still rendered
+        let html = std::fs::read_to_string(report.as_ref()).unwrap();
+        assert!(html.contains("<svg"));
+        assert!(!html.contains("&lt;svg"));
+        assert!(html.contains("<table id=\"validate-summary\">"));
+    }
+
+    #[test]
+    fn test_series_max_error() {
+        let mut series = SeriesValidator {
+            expected: vec![1., 2., 3., 4., 5.],
+            found: vec![1., 2., 9., 4., 5.],
+            allowed_root_mean_squared_error: Some(10.),
+            allowed_max_error: Some(7.),
+            ..Default::default()
+        };
+        assert!(series.validate().is_ok());
+
+        series.allowed_max_error = Some(5.);
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => {
+                assert!(e.contains("Maximum Absolute Error is 6.0000 (at index 2: expected 3.0000, found 9.0000)"))
+            }
+        }
+    }
+
+    #[test]
+    fn test_series_relative_error() {
+        let mut series = SeriesValidator {
+            expected: vec![-1., 0., 1., 100.],
+            found: vec![-1.01, 0.001, 1.01, 104.],
+            allowed_relative_error: Some(0.05),
+            allowed_max_error: Some(0.01),
+            ..Default::default()
+        };
+        assert!(series.validate().is_ok());
+
+        // Without the absolute tolerance, the value crossing zero fails
+        series.allowed_max_error = None;
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => {
+                assert!(e.contains("1 values (e.g., at index 1: expected 0.0000, found 0.0010) exceed the allowed relative error of 5.00%"))
+            }
+        }
+
+        series.allowed_relative_error = Some(0.01);
+        series.allowed_max_error = Some(0.5);
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains("1 values (e.g., at index 3")),
+        }
+    }
+
+    #[test]
+    fn test_series_total() {
+        let mut series = SeriesValidator {
+            expected: vec![0., 2., 4., 2.],
+            found: vec![0., 3., 3., 2.],
+            allowed_total_relative_error: Some(0.05),
+            ..Default::default()
+        };
+        let metrics = series.metrics().unwrap();
+        assert_eq!(metrics.expected_total, 7.);
+        assert_eq!(metrics.found_total, 7.);
+        match series.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("Expected total: 7.0000")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        series.found[2] = 5.;
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains(
+                "The found total is 9.0000 and the expected total is 7.0000, a relative difference of 28.57%"
+            )),
+        }
+    }
+
+    #[test]
+    fn test_series_cv_rmse() {
+        let series = SeriesValidator {
+            expected: vec![-10., -10., -10., -10.],
+            found: vec![-12., -8., -12., -8.],
+            ..Default::default()
+        };
+        // The errors are all 2 units, over a mean of -10
+        assert!((series.metrics().unwrap().cv_root_mean_squared_error - 0.2).abs() < 1e-12);
+        assert!(cv_root_mean_squared_error(4., &[1., -1.]).is_nan());
+    }
+
+    #[test]
+    fn test_series_derivative() {
+        // Same level on average, but the found series ramps too fast
+        let mut series = SeriesValidator {
+            expected: vec![0., 1., 2., 3., 4.],
+            found: vec![0., 2., 2., 2., 4.],
+            allowed_root_mean_squared_error: Some(1.),
+            allowed_derivative_root_mean_squared_error: Some(1.),
+            ..Default::default()
+        };
+        assert!(series.validate().is_ok());
+        assert_eq!(series.metrics().unwrap().derivative_root_mean_squared_error, 1.);
+
+        series.allowed_derivative_root_mean_squared_error = Some(0.5);
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains(
+                "Root Mean Squared Error of the first differences is 1.0000, which is greater than the allowed value of 0.5000"
+            )),
+        }
+
+        let single = SeriesValidator {
+            expected: vec![1.],
+            found: vec![1.],
+            allowed_derivative_root_mean_squared_error: Some(0.5),
+            ..Default::default()
+        };
+        assert!(single.validate().is_err());
+    }
+
+    #[test]
+    fn test_series_alignment() {
+        let expected: Vec<f64> = (0..50).map(|i| (i as f64 / 5.).sin()).collect();
+        let mut found = vec![0.; 3];
+        found.extend_from_slice(&expected[..47]);
+        let mut series = SeriesValidator {
+            expected,
+            found,
+            max_lag: Some(5),
+            ..Default::default()
+        };
+        let a = series.metrics().unwrap().alignment.unwrap();
+        assert_eq!(a.lag, 3);
+        assert!(a.root_mean_squared_error < 1e-12);
+        assert!(series.metrics().unwrap().root_mean_squared_error > 0.1);
+        match series.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("Lag: 3 (correlation: 1.0000)")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+        assert_eq!(series.indicators().len(), 6);
+
+        series.found = vec![1.; 50];
+        assert!(series.metrics().unwrap().alignment.is_none());
+    }
+
+    #[test]
+    fn test_series_error_bins() {
+        // Fine at mid-range, biased at the top
+        let expected: Vec<f64> = (0..10).map(|i| i as f64).collect();
+        let mut found = expected.clone();
+        found[8] += 2.;
+        found[9] += 2.;
+        let mut series = SeriesValidator {
+            expected,
+            found,
+            error_bins: Some(5),
+            ..Default::default()
+        };
+        let bins = series.error_bins(5);
+        assert_eq!(bins.len(), 5);
+        assert_eq!((bins[4].from, bins[4].to, bins[4].points), (8., 9., 2));
+        assert_eq!(bins[0].mean_bias_error, 0.);
+        assert_eq!(bins[4].mean_bias_error, 2.);
+        match series.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("<tr><td>5</td><td>8.0000 - 9.0000</td><td>2</td>")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        series.allowed_bin_mean_bias_error = Some(1.);
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(txt, e) => {
+                assert!(txt.contains("<td><strong>2.0000</strong></td>"));
+                assert!(e.contains("Mean Bias Error of bin 5 (expected values from 8.0000 to 9.0000) is 2.0000"));
+                assert!(!e.contains("bin 4"));
+            }
+        }
+        // More bins than values
+        assert_eq!(series.error_bins(50).len(), 10);
+    }
+
+    #[test]
+    fn test_series_smoothing() {
+        let expected = vec![2.; 20];
+        let found: Vec<f64> = (0..20).map(|i| if i % 2 == 0 { 1. } else { 3. }).collect();
+        let mut series = SeriesValidator {
+            expected,
+            found,
+            allowed_root_mean_squared_error: Some(0.3),
+            ..Default::default()
+        };
+        assert!(series.validate().is_err());
+
+        series.smoothing = Some(Smoothing::MovingAverage(4));
+        assert!(series.metrics().unwrap().root_mean_squared_error < 0.3);
+        match series.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.starts_with(
+                "Filter applied to both series before comparing them: moving average over 4 samples"
+            )),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+        assert_eq!(series.chart_data().unwrap().found[5], 2.);
+
+        series.smoothing = Some(Smoothing::LowPass(f64::NAN));
+        assert!(series.validate().is_err());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_series_permutations() {
+        let expected: Vec<f64> = (0..30).map(|i| (i as f64 / 3.).sin()).collect();
+        let found: Vec<f64> = expected.iter().map(|v| v + 0.5).collect();
+        let series = SeriesValidator {
+            expected,
+            found,
+            permutations: Some(199),
+            seed: 3,
+            ..Default::default()
+        };
+        match series.validate() {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.contains("Probability of a Root Mean Squared Error this small by chance (199 permutations): 0.005"));
+                assert!(txt.contains("Probability of a Mean Bias Error this large by chance (199 permutations): 0.005"));
+            }
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+        assert_eq!(series.inputs(), vec![RunInput::new("seed", 3)]);
+    }
+
+    #[test]
+    fn test_series_residual_diagnostics() {
+        // The error grows with the load, but does not drift
+        let expected: Vec<f64> = (0..40).map(|i| if i % 2 == 0 { 10. } else { 100. }).collect();
+        let found: Vec<f64> = expected.iter().enumerate().map(|(i, e)| e * if i % 4 < 2 { 1.1 } else { 0.9 }).collect();
+        let series = SeriesValidator {
+            expected,
+            found,
+            residual_diagnostics: true,
+            ..Default::default()
+        };
+        match series.validate() {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.contains("Trend of the absolute error with the expected value: 0.1000 per unit (p = 0.0000). **Warning: the error grows with the expected value**"));
+                assert!(txt.contains("Trend of the error over time: "));
+                assert!(!txt.contains("drifts"));
+            }
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+    }
+
+    #[cfg(feature = "charts")]
+    #[test]
+    fn test_series_residual_chart() {
+        let expected: Vec<f64> = (0..20).map(|i| i as f64).collect();
+        let found: Vec<f64> = expected.iter().map(|e| e * 1.01).collect();
+        let mut series = SeriesValidator {
+            expected,
+            found,
+            allowed_max_error: Some(0.5),
+            ..Default::default()
+        };
+        let charts = |v: &SeriesValidator<f64>| match v.validate() {
+            ValidationResult::Ok(txt) => txt.matches("validate_chart\">").count(),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        };
+        assert_eq!(charts(&series), 1);
+        series.residual_chart = true;
+        assert_eq!(charts(&series), 2);
+        match series.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("<title>Residual (found minus expected) vs x</title>")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+    }
+
+    #[cfg(feature = "charts")]
+    #[test]
+    fn test_series_runtime_labels() {
+        // Labels built at runtime (e.g., one validation per zone)
+        let series: Vec<SeriesValidator<f64>> = (1..=2)
+            .map(|i| SeriesValidator {
+                expected: vec![20., 21., 22.],
+                found: vec![20., 21., 22.],
+                y_label: Some(format!("Zone {} temperature", i)),
+                y_units: Some("C".into()),
+                ..Default::default()
+            })
+            .collect();
+        match series[1].validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("<title>Zone 2 temperature (C) vs x</title>")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn test_series_time_weighted() {
+        // Two samples ten minutes apart, then one an hour later
+        let mut series = SeriesValidator {
+            expected: vec![0., 0., 0.],
+            found: vec![1., 1., 2.],
+            timestamps: Some(vec![0., 10., 70.]),
+            ..Default::default()
+        };
+        assert_eq!(series.metrics().unwrap().root_mean_squared_error, 2.);
+        // The totals are integrated over the timestamps, unevenly spaced
+        series.expected = vec![1., 1., 1.];
+        let metrics = series.metrics().unwrap();
+        assert_eq!(metrics.expected_total, 70.);
+        assert_eq!(metrics.found_total, 1. * 10. + 1.5 * 60.);
+        series.expected = vec![0., 0., 0.];
+        series.time_weighted = true;
+        // Weights of 5, 35 and 30 minutes
+        assert_eq!(series.metrics().unwrap().root_mean_squared_error, (5. + 35. + 4. * 30.) / 70.);
+        match series.validate() {
+            ValidationResult::Ok(txt) | ValidationResult::Err(txt, _) => {
+                assert!(txt.contains("Root Mean Squared Error (time-weighted): 2.2857"));
+                // The samples are drawn at their timestamps
+                #[cfg(feature = "charts")]
+                assert!(txt.contains(">70<"));
+            }
+        }
+        assert_eq!(series.chart_data().unwrap().x, vec![0., 10., 70.]);
+
+        series.timestamps = Some(vec![0., 70., 10.]);
+        assert!(series.metrics().is_err());
+        series.timestamps = None;
+        assert!(series.validate().is_err());
+    }
+
+    #[test]
+    fn test_series_failure_diagnosis() {
+        // The found values lag the expected ones by a sample
+        let expected: Vec<f64> = (0..48).map(|i| (i as f64 * 0.5).sin()).collect();
+        let found: Vec<f64> = (0..48).map(|i| ((i as f64 - 1.) * 0.5).sin()).collect();
+        let mut series = SeriesValidator {
+            expected,
+            found,
+            allowed_root_mean_squared_error: Some(0.01),
+            ..Default::default()
+        };
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(txt, e) => {
+                assert!(txt.contains("#### Diagnosis (fractions of the Mean Squared Error):"));
+                assert!(e.contains("of the Mean Squared Error is due to phase. The found values do not rise and fall with the expected ones"));
+            }
+        }
+
+        // A constant offset
+        series.found = series.expected.iter().map(|v| v + 1.).collect();
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains("Hint: 100% of the Mean Squared Error is due to bias")),
+        }
+
+        // Passing validations need no diagnosis
+        series.allowed_root_mean_squared_error = None;
+        match series.validate() {
+            ValidationResult::Ok(txt) => assert!(!txt.contains("Diagnosis")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_series_changepoints() {
+        // The sensor is recalibrated halfway through
+        let expected: Vec<f64> = (0..40).map(|i| (i % 5) as f64).collect();
+        let found: Vec<f64> = expected.iter().enumerate().map(|(i, e)| e + if i < 25 { 0. } else { 1. }).collect();
+        let series = SeriesValidator {
+            expected,
+            found,
+            detect_changepoints: true,
+            ..Default::default()
+        };
+        match series.validate() {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.contains("Sudden shift in the error at sample 25: the Mean Bias Error goes from 0.0000 to 1.0000"));
+                assert!(txt.contains("Shift in the error"));
+            }
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn test_series_gaps() {
+        let mut series = SeriesValidator {
+            expected: vec![1., 2., 3., 4., 5., 6.],
+            found: vec![1., 2., 3., 4., 5., 6.],
+            timestamps: Some(vec![0., 1., 1., 2., 6., 7.]),
+            allowed_gap_fraction: Some(0.5),
+            ..Default::default()
+        };
+        match series.validate() {
+            ValidationResult::Ok(txt) => {
+                assert!(txt.contains("Sampling step: 1.0000. 1 gaps (3 missing samples, 33.33% of the period), 1 duplicated timestamps, 0 irregular intervals"));
+                assert!(txt.contains("Gap from 2 to 6 (3 missing samples)"));
+            }
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        series.allowed_gap_fraction = Some(0.1);
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains("cover 33.33% of the period, which is greater than the allowed 10.00%")),
+        }
+
+        series.timestamps = Some(vec![0., 1.]);
+        assert!(series.validate().is_err());
+    }
+
+    #[test]
+    fn test_series_uncertainty() {
+        let mut series = SeriesValidator {
+            expected: vec![20., 21., 22., 23.],
+            found: vec![20.3, 21.4, 22.2, 24.],
+            expected_uncertainty: Some(Uncertainty::Constant(0.3)),
+            found_uncertainty: Some(Uncertainty::PerPoint(vec![0.4, 0.4, 0.4, 0.4])),
+            required_within_uncertainty: Some(0.75),
+            ..Default::default()
+        };
+        match series.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("Samples within the combined uncertainty: 75.00%")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+
+        series.required_within_uncertainty = Some(0.9);
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains("Only 75.00% of the samples are within the combined uncertainty")),
+        }
+
+        series.found_uncertainty = Some(Uncertainty::PerPoint(vec![0.4]));
+        assert!(series.validate().is_err());
+        series.found_uncertainty = Some(Uncertainty::Constant(-1.));
+        assert!(series.validate().is_err());
+    }
+
+    #[test]
+    fn test_series_normalization() {
+        use crate::normalize::{DegreeDays, Normalization};
+        // Heating loads from a winter twice as cold
+        let mut series = SeriesValidator {
+            expected: vec![20., 40., 60.],
+            found: vec![10., 20., 30.],
+            normalization: Some(Normalization::DegreeDays {
+                kind: DegreeDays::Heating(18.),
+                samples_per_day: 1.,
+                expected_temperature: vec![8., -2., -12.],
+                found_temperature: vec![13., 8., 3.],
+            }),
+            allowed_mean_bias_error: Some(1e-9),
+            ..Default::default()
+        };
+        match series.validate() {
+            ValidationResult::Ok(txt) => assert!(txt.contains("ratio of their heating degree-days (base 18 C): 30.0 / 60.0")),
+            ValidationResult::Err(_, e) => panic!("{}", e),
+        }
+        assert_eq!(series.metrics().unwrap().mean_bias_error, 0.);
+
+        series.normalization = Some(Normalization::Radiation {
+            expected_radiation: vec![1.],
+            found_radiation: vec![1.],
+        });
+        assert!(series.validate().is_err());
+        assert!(series.metrics().is_err());
+    }
+
+    #[test]
+    fn test_series_minimum_samples() {
+        let mut series = SeriesValidator {
+            expected: vec![1., 2., 3.],
+            found: vec![1., 2., 3.],
+            minimum_samples: Some(3),
+            ..Default::default()
+        };
+        assert!(series.validate().is_ok());
+
+        series.minimum_samples = Some(8760);
+        match series.validate() {
+            ValidationResult::Ok(_) => panic!("Expecting an error"),
+            ValidationResult::Err(_, e) => assert!(e.contains("they have 3 samples, but at least 8760")),
+        }
+
+        let empty = SeriesValidator::<f64>::default();
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_series_default_thresholds() {
+        let ctx = ReportContext {
+            thresholds: Thresholds {
+                root_mean_squared_error_fraction: Some(0.1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // The range of expected is 10, so the inherited threshold is 1
+        let mut series = SeriesValidator {
+            expected: vec![0., 10.],
+            found: vec![2., 12.],
+            ..Default::default()
+        };
+        assert!(series.validate().is_ok());
+        assert!(series.validate_in(&ctx).is_err());
+
+        // Thresholds set in the validator override the inherited ones
+        series.allowed_root_mean_squared_error = Some(5.0);
+        assert!(series.validate_in(&ctx).is_ok());
+
+        // Inherited thresholds count as thresholds in strict mode
+        series.allowed_root_mean_squared_error = None;
+        assert!(series.defines_thresholds(&ctx));
+        assert!(!series.defines_thresholds(&ReportContext::default()));
+    }
+
     #[test]
     fn test_series_perfect_fail() {
         use crate::Validator;
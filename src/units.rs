@@ -0,0 +1,126 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/// The system of units in which the values are displayed in the report.
+///
+/// Validations always calculate in the units of their data—which are expected to
+/// be SI—and the conversion is only applied when writing the report. Units that
+/// are not recognized are displayed unchanged.
+///
+/// # Example
+///
+/// ```
+/// use validate::{assert_close, UnitSystem};
+///
+/// let ip = UnitSystem::IP;
+/// assert_close!(ip.convert(100., "C"), 212.);
+/// assert_close!(ip.convert_difference(1., "C"), 1.8);
+/// assert_eq!(ip.units("C"), "°F");
+/// assert_eq!(ip.units("furlongs"), "furlongs");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitSystem {
+    /// The International System of Units
+    #[default]
+    SI,
+
+    /// The Inch-Pound system
+    IP,
+}
+
+impl UnitSystem {
+    /// Gets the `(scale, offset, units)` needed for transforming a value
+    /// in SI `units` into this unit system
+    fn conversion(&self, units: &str) -> Option<(f64, f64, &'static str)> {
+        if let UnitSystem::SI = self {
+            return None;
+        }
+        let c = match units.trim() {
+            "C" | "°C" => (1.8, 32., "°F"),
+            "K" => (1.8, 0., "°R"),
+            "W" => (3.412142, 0., "Btu/h"),
+            "kW" => (3412.142, 0., "Btu/h"),
+            "W/m2" => (0.316998, 0., "Btu/h.ft2"),
+            "W/m2K" => (0.176110, 0., "Btu/h.ft2.°F"),
+            "Wh" => (3.412142, 0., "Btu"),
+            "kWh" => (3.412142, 0., "kBtu"),
+            "J" => (1. / 1055.056, 0., "Btu"),
+            "m" => (3.280840, 0., "ft"),
+            "m2" => (10.76391, 0., "ft2"),
+            "m3" => (35.31467, 0., "ft3"),
+            "m/s" => (196.8504, 0., "ft/min"),
+            "m3/s" => (2118.880, 0., "cfm"),
+            "kg" => (2.204623, 0., "lb"),
+            "kg/kg" => (7000., 0., "gr/lb"),
+            "Pa" => (0.000145038, 0., "psi"),
+            _ => return None,
+        };
+        Some(c)
+    }
+
+    /// Converts a value in SI `units` into this unit system
+    pub fn convert(&self, value: f64, units: &str) -> f64 {
+        match self.conversion(units) {
+            Some((scale, offset, _)) => value * scale + offset,
+            None => value,
+        }
+    }
+
+    /// Converts a difference between two values in SI `units`
+    /// (e.g., a Mean Bias Error) into this unit system. This is
+    /// different from [`UnitSystem::convert`] for units with an
+    /// offset, such as degrees Celsius.
+    pub fn convert_difference(&self, value: f64, units: &str) -> f64 {
+        match self.conversion(units) {
+            Some((scale, _, _)) => value * scale,
+            None => value,
+        }
+    }
+
+    /// Gets the name of the units in this unit system that
+    /// correspond to SI `units`
+    pub fn units(&self, units: &str) -> String {
+        match self.conversion(units) {
+            Some((_, _, u)) => u.to_string(),
+            None => units.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_si_is_unchanged() {
+        let si = UnitSystem::SI;
+        assert_eq!(si.convert(21., "C"), 21.);
+        assert_eq!(si.convert_difference(2., "C"), 2.);
+        assert_eq!(si.units("C"), "C");
+    }
+
+    #[test]
+    fn test_ip() {
+        let ip = UnitSystem::IP;
+        assert!((ip.convert(0., "C") - 32.).abs() < 1e-9);
+        assert!((ip.convert(1000., "W") - 3412.142).abs() < 1e-6);
+        assert_eq!(ip.units("W/m2"), "Btu/h.ft2");
+    }
+}
@@ -18,7 +18,8 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-use crate::{Validate, ValidationResult};
+use crate::{Artifact, ChartData, Indicator, ReportContext, RunInput, SourceLocation, Thresholds, Validate, ValidationResult};
+use std::sync::{Mutex, MutexGuard};
 
 /// The type that represents the output of a valid 
 /// validation function
@@ -36,37 +37,161 @@ pub struct ValidatorWrapper {
 
     /// The Validator
     pub val: ValidationFn,
+
+    /// The IDs of the requirements verified by the test, on top of
+    /// those declared by the validator built by `val`
+    pub requirements: Vec<String>,
+
+    /// Where the test is defined
+    pub location: Option<SourceLocation>,
+
+    /// What the validator built by `val` reported when validating, kept
+    /// so it can be queried afterwards (see [`ValidatorWrapper::new`])
+    outcome: Mutex<Option<Outcome>>,
+}
+
+/// What a validator reported when validating. It is kept instead of the
+/// validator itself, which might be neither `Send` nor `Sync`, so that
+/// wrappers can run concurrently (see [`crate::Validator::validate_par`]).
+struct Outcome {
+    artifacts: Vec<Artifact>,
+    chart_data: Option<ChartData>,
+    indicators: Vec<Indicator>,
+    inputs: Vec<RunInput>,
+    covers: Vec<String>,
+    requirements: Vec<String>,
+    defines_thresholds: bool,
+}
+
+impl Outcome {
+    /// Queries a validator after it has validated in `ctx`
+    fn of(v: &dyn Validate, ctx: &ReportContext) -> Self {
+        Self {
+            artifacts: v.artifacts(),
+            chart_data: v.chart_data(),
+            indicators: v.indicators(),
+            inputs: v.inputs(),
+            covers: v.covers(),
+            requirements: v.requirements(),
+            defines_thresholds: v.defines_thresholds(ctx),
+        }
+    }
 }
 
 impl ValidatorWrapper {
-    /// Format the description of a Validator
-    fn format_description(&self, txt: String) -> String {
-        format!(
-            "## {}\n\n{}\n\n #### Indicators \n\n{}\n",
-            self.title, self.description, txt
-        )
+    /// Creates a new `ValidatorWrapper`. It is `Send` and `Sync`, whatever
+    /// the validator built by `val`: that validator only lives while
+    /// validating, and what it reports (e.g., its indicators) is kept.
+    pub fn new(title: String, description: String, val: ValidationFn) -> Self {
+        Self {
+            title,
+            description,
+            val,
+            requirements: Vec::new(),
+            location: None,
+            outcome: Mutex::new(None),
+        }
+    }
+
+    /// What the validator reported when it last validated, if it has
+    fn outcome(&self) -> MutexGuard<'_, Option<Outcome>> {
+        self.outcome.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Format the description of a Validator. The title and the description
+    /// are written by the [`crate::Validator`] (see [`Validate::title`]).
+    fn format_description(&self, txt: String, ctx: &ReportContext) -> String {
+        format!("#### {} \n\n{}\n", ctx.tr("Indicators"), txt)
     }
 }
 
 impl Validate for ValidatorWrapper {
     /// Validates a Wrapper
     fn validate(&self) -> ValidationResult {
+        self.validate_in(&ReportContext::default())
+    }
+
+    fn validate_in(&self, ctx: &ReportContext) -> ValidationResult {
         let v = &self.val;
         let validator = match v() {
             Ok(v) => v,
-            Err(e) => return ValidationResult::Err(e.clone(), e),
+            Err(e) => {
+                // Nothing to report from a validator that was not built
+                *self.outcome() = None;
+                return ValidationResult::Err(e.clone(), e);
+            }
         };
 
-        match validator.validate() {
+        let ret = match validator.validate_in(ctx) {
             ValidationResult::Ok(txt) => {
-                let ret = self.format_description(txt);
+                let ret = self.format_description(txt, ctx);
                 ValidationResult::Ok(ret)
             }
             ValidationResult::Err(txt, err) => {
-                let ret = self.format_description(txt);
+                let ret = self.format_description(txt, ctx);
                 ValidationResult::Err(ret, err)
             }
+        };
+        *self.outcome() = Some(Outcome::of(validator.as_ref(), ctx));
+        ret
+    }
+
+    fn title(&self) -> Option<String> {
+        Some(self.title.clone())
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn artifacts(&self) -> Vec<Artifact> {
+        self.outcome().as_ref().map(|o| o.artifacts.clone()).unwrap_or_default()
+    }
+
+    fn chart_data(&self) -> Option<ChartData> {
+        self.outcome().as_ref().and_then(|o| o.chart_data.clone())
+    }
+
+    fn indicators(&self) -> Vec<Indicator> {
+        self.outcome().as_ref().map(|o| o.indicators.clone()).unwrap_or_default()
+    }
+
+    fn inputs(&self) -> Vec<RunInput> {
+        self.outcome().as_ref().map(|o| o.inputs.clone()).unwrap_or_default()
+    }
+
+    fn covers(&self) -> Vec<String> {
+        self.outcome().as_ref().map(|o| o.covers.clone()).unwrap_or_default()
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        let mut ret = self.requirements.clone();
+        if let Some(o) = self.outcome().as_ref() {
+            for r in &o.requirements {
+                if !ret.contains(r) {
+                    ret.push(r.clone())
+                }
+            }
         }
+        ret
+    }
+
+    fn defines_thresholds(&self, _ctx: &ReportContext) -> bool {
+        self.outcome().as_ref().is_none_or(|o| o.defines_thresholds)
+    }
+
+    fn suggested_thresholds(&self, margin: f64) -> Option<Thresholds> {
+        // Thresholds depend on the margin, so the validator is built again
+        self.outcome().as_ref()?;
+        (self.val)().ok()?.suggested_thresholds(margin)
+    }
+
+    fn location(&self) -> Option<SourceLocation> {
+        self.location.clone()
     }
 }
 
@@ -84,9 +209,10 @@ mod tests {
             let v = SeriesValidator {
                 x_label: Some("time step".into()),
                 y_label: Some("Zone Temperature".into()),
-                y_units: Some("C"),
+                y_units: Some("C".into()),
                 expected,
                 found,
+                artifacts: vec![Artifact::new("Data", "./tests/test_data/data.csv")],
                 ..SeriesValidator::default()
             };
             Ok(Box::new(v))
@@ -94,11 +220,7 @@ mod tests {
 
         let t = aux;
 
-        let wrapper = ValidatorWrapper {
-            title: "Some Title".into(),
-            description: "The Description".into(),
-            val: t,
-        };
+        let wrapper = ValidatorWrapper::new("Some Title".into(), "The Description".into(), t);
 
         match wrapper.validate() {
             ValidationResult::Ok(txt) => {
@@ -108,7 +230,40 @@ mod tests {
                 panic!("{}", err)
             }
         };
+        assert_eq!(wrapper.artifacts().len(), 1);
+        assert_eq!(wrapper.title().as_deref(), Some("Some Title"));
+        assert!(!wrapper.indicators().is_empty());
+        assert!(wrapper.suggested_thresholds(1.5).is_some());
+
+        // Wrappers can be shared across threads
+        fn is_send_sync<T: Send + Sync>(_: &T) {}
+        is_send_sync(&wrapper);
 
         Ok(())
     }
+
+    #[test]
+    fn test_wrapper_failed_build() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        // Builds the validator once, and then fails
+        static BUILT: AtomicBool = AtomicBool::new(false);
+        fn aux() -> Result<Box<dyn Validate>, String> {
+            if BUILT.swap(true, Ordering::SeqCst) {
+                return Err("Could not read the results".to_string());
+            }
+            Ok(Box::new(SeriesValidator {
+                expected: vec![1., 2., 3.],
+                found: vec![1., 2., 3.],
+                ..SeriesValidator::default()
+            }))
+        }
+
+        let wrapper = ValidatorWrapper::new("Some Title".into(), "The Description".into(), aux);
+        assert!(wrapper.validate().is_ok());
+        assert!(!wrapper.indicators().is_empty());
+        assert!(wrapper.validate().is_err());
+        assert!(wrapper.indicators().is_empty());
+        assert!(wrapper.chart_data().is_none());
+    }
 }
@@ -29,7 +29,7 @@ impl Validate for CustomValidator {
                 ret, self.title, self.expected, self.found
             );
         };
-        if err.len() > 0 {
+        if !err.is_empty() {
             ValidationResult::Err(err, ret)
         } else {
             ValidationResult::Ok(ret)
@@ -56,3 +56,36 @@ fn test_custom_validator() {
     validator.push(check_if_equal());
     validator.validate().unwrap()
 }
+
+#[test]
+fn test_failures_report() {
+    let dir = std::env::temp_dir();
+    let full_file = dir.join("validate_full.html").to_string_lossy().to_string();
+    let failures_file = dir.join("validate_failures.html").to_string_lossy().to_string();
+
+    let mut validator = Validator::new("Full report", &full_file);
+    validator.set_failures_file(&failures_file);
+    validator.push(Box::new(CustomValidator {
+        expected: 2,
+        found: 2,
+        title: "Check that 2 and 2 are equal",
+    }));
+    validator.push(Box::new(CustomValidator {
+        expected: 2,
+        found: 3,
+        title: "Check that 2 and 3 are equal",
+    }));
+    assert!(validator.validate().is_err());
+
+    let full = std::fs::read_to_string(&full_file).unwrap();
+    let failures = std::fs::read_to_string(&failures_file).unwrap();
+    assert!(full.contains("2 and 2 are equal"));
+    assert!(full.contains("2 and 3 aren"));
+    assert!(!failures.contains("2 and 2 are equal"));
+    assert!(failures.contains("2 and 3 aren"));
+}
+
+#[test]
+fn test_custom_validator_report() {
+    validate::testing::assert_report_matches(&check_if_equal(), "tests/golden/custom_validator.md");
+}
@@ -0,0 +1,64 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use validate::{Validate, ValidationResult, Validator};
+
+// Records the validations that were run
+struct Recorded {
+    title: &'static str,
+    run: Rc<RefCell<Vec<&'static str>>>,
+}
+
+impl Validate for Recorded {
+    fn validate(&self) -> ValidationResult {
+        self.run.borrow_mut().push(self.title);
+        ValidationResult::Ok("Done".to_string())
+    }
+
+    fn title(&self) -> Option<String> {
+        // Untitled validations are reported as `Validation N`
+        (!self.title.is_empty()).then(|| self.title.to_string())
+    }
+}
+
+// The only test of this file, as it sets an environment variable
+#[test]
+fn test_rerun_filter() {
+    std::env::set_var("VALIDATE_FILTER", "Zone B");
+    let report = std::env::temp_dir().join(format!("validate_test_rerun_filter_{}.html", std::process::id()));
+    let report = report.to_string_lossy().to_string();
+    let run = Rc::new(RefCell::new(Vec::new()));
+    let mut validator = Validator::new("Filtered", &report);
+    for title in ["Zone A", "Zone B", "Zone C"] {
+        validator.push(Recorded { title, run: run.clone() });
+    }
+    validator.validate().unwrap();
+    assert_eq!(*run.borrow(), ["Zone B"]);
+    let html = std::fs::read_to_string(&report).unwrap();
+    assert!(html.contains("Zone B") && !html.contains("Zone A"));
+
+    // Repeated titles are numbered when pushed, so they can be selected
+    std::env::set_var("VALIDATE_FILTER", "Zone B (2)");
+    run.borrow_mut().clear();
+    validator.push(Recorded { title: "Zone B", run: run.clone() });
+    validator.validate().unwrap();
+    assert_eq!(*run.borrow(), ["Zone B"]);
+    let html = std::fs::read_to_string(&report).unwrap();
+    assert!(html.contains("Zone B (2)"));
+
+    // The exact title takes precedence, and selects untitled validations
+    // before running them
+    validator.push(Recorded { title: "", run: run.clone() });
+    validator.push(Recorded { title: "", run: run.clone() });
+    std::env::set_var("VALIDATE_TITLE", "Zone B");
+    run.borrow_mut().clear();
+    validator.validate().unwrap();
+    assert_eq!(*run.borrow(), ["Zone B"]);
+    std::env::set_var("VALIDATE_TITLE", "Validation 6");
+    run.borrow_mut().clear();
+    validator.validate().unwrap();
+    assert_eq!(*run.borrow(), [""]);
+    let html = std::fs::read_to_string(&report).unwrap();
+    assert!(html.contains("Validation 6") && !html.contains("Validation 5"));
+    std::env::remove_var("VALIDATE_TITLE");
+    std::fs::remove_file(&report).unwrap();
+}
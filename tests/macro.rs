@@ -11,10 +11,41 @@ fn aux() -> Box<dyn Validate> {
     let v = SeriesValidator {
         x_label: Some("time step".into()),
         y_label: Some("Zone Temperature".into()),
-        y_units: Some("C"),
+        y_units: Some("C".into()),
         expected,
         found,
         ..SeriesValidator::default()
     };
     Box::new(v)
 }
+
+/// Checks a requirement
+#[valid(Traced Test, requirement = "REQ-THERM-012", requirement = r#"ISO 52016-1 "6.5""#)]
+fn traced() -> Box<dyn Validate> {
+    Box::new(SeriesValidator {
+        expected: vec![1., 2., 3.],
+        found: vec![1., 2., 3.],
+        requirements: vec!["REQ-THERM-001".to_string()],
+        ..SeriesValidator::default()
+    })
+}
+
+#[test]
+fn test_requirements() {
+    let v = traced();
+    assert_eq!(v.title().as_deref(), Some("Traced Test"));
+    assert_eq!(v.requirements(), ["REQ-THERM-012", "ISO 52016-1 \"6.5\""]);
+    assert!(v.validate().is_ok());
+    assert_eq!(
+        v.requirements(),
+        ["REQ-THERM-012", "ISO 52016-1 \"6.5\"", "REQ-THERM-001"]
+    );
+    assert_eq!(aux().title().as_deref(), Some("Wonderful Test"));
+}
+
+#[test]
+fn test_location() {
+    let location = traced().location().unwrap();
+    assert_eq!(location.file, "tests/macro.rs");
+    assert_eq!(location.line, 23);
+}
@@ -11,7 +11,7 @@ fn time_series_test() -> Result<ValidFunc, String> {
     let v = SeriesValidator {
         x_label: Some("time step".into()),
         y_label: Some("Zone Temperature".into()),
-        y_units: Some("C"),
+        y_units: Some("C".into()),
         expected,
         found,
         ..validate::SeriesValidator::default()